@@ -1,19 +1,53 @@
-//! This little command line program illustrates how to use this
-//! library. It extracts all Markdown and RestructuredText
-//! hyperlinks found in the input stream `stdin` and
-//! prints the list as HTML.
+//! This command line program illustrates how to use this library. It reads
+//! files, directories or `stdin` and prints the input with its Markdown,
+//! RestructuredText, Asciidoc, HTML, Wikitext and Org-mode hyperlinks
+//! rendered as HTML. `--format` restricts the parsed markup languages. Alternatively,
+//! it prints only the links (`--only-links`, `--json`, `--csv`), reports
+//! broken links (`--check`), counts links (`--stats`) or rewrites link
+//! destinations in place (`--rewrite`). The output goes to `stdout`, to a
+//! file (`--output`) or to one file per input (`--out-dir`). Default
+//! options are read from `atext2html.toml`.
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use html_escape::encode_double_quoted_attribute;
+use html_escape::encode_text;
 use lazy_static::lazy_static;
-use parse_hyperlinks::renderer::links2html_writer;
-use parse_hyperlinks::renderer::text_links2html_writer;
-use parse_hyperlinks::renderer::text_rawlinks2html_writer;
+use parse_hyperlinks::analysis::LinkStats;
+use parse_hyperlinks::check::LinkChecker;
+use parse_hyperlinks::parser::parse::detect_markup;
+use parse_hyperlinks::parser::parse::ParserOptions;
+use parse_hyperlinks::parser::MarkupLang;
+use parse_hyperlinks::parser::MarkupLangs;
+use parse_hyperlinks::renderer::link_rows;
+use parse_hyperlinks::renderer::links2html_writer_with;
+use parse_hyperlinks::renderer::table_cell;
+use parse_hyperlinks::renderer::text_links2html_stream_with;
+use parse_hyperlinks::renderer::text_links2html_writer_with;
+use parse_hyperlinks::renderer::text_rawlinks2html_stream_with;
+use parse_hyperlinks::renderer::text_rawlinks2html_writer_with;
+use parse_hyperlinks::renderer::DestFilter;
+use parse_hyperlinks::renderer::LinkRow;
+use parse_hyperlinks::renderer::RendererOptions;
+use parse_hyperlinks::rewrite::rewrite_writer;
+use parse_hyperlinks::url::scheme;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs;
 use std::fs::File;
 use std::io;
+use std::io::BufReader;
 use std::io::Read;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process;
+use std::str::FromStr;
 use structopt::StructOpt;
+use walkdir::WalkDir;
 
 #[derive(Debug, PartialEq, StructOpt)]
 #[structopt(
@@ -30,71 +64,862 @@ pub struct Args {
     /// print only links (one per line)
     pub only_links: bool,
 
+    #[structopt(long, short = "j", conflicts_with_all = &["render-links", "only-links", "csv"])]
+    /// print links as JSON Lines with the fields `text`, `dest`, `title`,
+    /// `kind`, `file` and `line`
+    pub json: bool,
+
+    #[structopt(long, short = "c", conflicts_with_all = &["render-links", "only-links"])]
+    /// print links as CSV table with the columns `text`, `dest`, `title`,
+    /// `kind`, `file` and `line`
+    pub csv: bool,
+
     #[structopt(name = "FILE", parse(from_os_str))]
-    /// paths to files to render (or `-` for stdin)
+    /// paths to files or directories to render (or `-` for stdin)
     pub inputs: Vec<PathBuf>,
 
+    #[structopt(long, short = "g", number_of_values = 1)]
+    /// render only those files in directories, whose path relative to the
+    /// directory matches this glob pattern, e.g. `**/*.md` (repeatable).
+    /// Default: files with a markup file extension
+    pub glob: Vec<String>,
+
+    #[structopt(long, conflicts_with_all = &["render-links", "only-links", "json", "csv", "out-dir"])]
+    /// print the broken links instead of rendering and exit with status 1,
+    /// if there are any. Relative destinations are resolved against the
+    /// directory of the input file
+    pub check: bool,
+
+    #[structopt(long, conflicts_with_all = &["render-links", "only-links", "json", "csv", "out-dir", "check"])]
+    /// print the number of links per kind for every input file, followed
+    /// by the numbers of links per kind, URL scheme and markup language of
+    /// all inputs
+    pub stats: bool,
+
+    #[structopt(long)]
+    /// with `--check`: request `http:` and `https:` URLs too
+    pub online: bool,
+
+    #[structopt(long, short = "s", use_delimiter = true, number_of_values = 1)]
+    /// keep only links, whose destination has one of these comma separated
+    /// URL schemes, e.g. `https,mailto`. `relative` stands for destinations
+    /// without scheme. Not applied with `--stats`
+    pub scheme: Vec<String>,
+
+    #[structopt(long = "match", short = "m")]
+    /// keep only links, whose destination matches this regular expression.
+    /// Not applied with `--stats`
+    pub matches: Option<String>,
+
+    #[structopt(long, short = "v", overrides_with = "no-invert-match")]
+    /// with `--scheme` or `--match`: keep the other links instead
+    pub invert_match: bool,
+
+    #[structopt(long, overrides_with = "invert-match")]
+    /// keep the links selected by `--scheme` or `--match`. Overrides
+    /// `invert-match` in the configuration file
+    pub no_invert_match: bool,
+
+    #[structopt(long, short = "w", number_of_values = 1, conflicts_with_all = &["render-links", "only-links", "json", "csv", "check", "stats"])]
+    /// print the input with the link destinations replaced according to
+    /// this substitution, e.g. `s|^/old/|/new/|` (repeatable, applied in
+    /// order). The pattern is a regular expression, `$1` in the replacement
+    /// refers to its first group. The flag `g` replaces all matches. With
+    /// `--scheme` or `--match`, only the selected destinations are replaced
+    pub rewrite: Vec<String>,
+
+    #[structopt(long, short = "C", parse(from_os_str))]
+    /// read default options from this TOML file instead of
+    /// `atext2html.toml` in the current directory, if it exists. Keys:
+    /// `format`, `scheme`, `match`, `invert-match`, `output` (`raw`,
+    /// `render`, `links`, `json`, `csv`, `check` or `stats`) and `rewrite`.
+    /// Command-line options take precedence
+    pub config: Option<PathBuf>,
+
+    #[structopt(long, short = "S", conflicts_with_all = &["json", "csv", "check", "stats", "rewrite"])]
+    /// print a complete HTML page instead of an HTML fragment
+    pub standalone: bool,
+
+    #[structopt(long, number_of_values = 1, conflicts_with_all = &["json", "csv", "check", "stats", "rewrite"])]
+    /// link this style sheet into the HTML page (repeatable). Implies
+    /// `--standalone`
+    pub css: Vec<String>,
+
+    #[structopt(long, parse(from_os_str), conflicts_with_all = &["json", "csv", "check", "stats", "rewrite"])]
+    /// wrap the HTML output in this template: `{{content}}` stands for the
+    /// rendered input, `{{title}}` for the input's file name and `{{css}}`
+    /// for the style sheet links of `--css`. Implies `--standalone`
+    pub template: Option<PathBuf>,
+
+    #[structopt(long, short = "t", default_value = "0")]
+    /// number of threads processing the input files in parallel, `0` for
+    /// one per CPU. The output order is always the input order
+    pub threads: usize,
+
+    #[structopt(long, conflicts_with_all = &["only-links", "json", "csv", "check", "stats", "rewrite", "out-dir"])]
+    /// read and render the inputs in chunks of about this many bytes,
+    /// each printed as soon as it is rendered, instead of reading every
+    /// input as a whole. This keeps memory usage bounded for large inputs,
+    /// e.g. logs piped through stdin. Link references are resolved only
+    /// within the same chunk
+    pub chunk_size: Option<usize>,
+
     #[structopt(long, short = "o", parse(from_os_str))]
     /// print not to stdout but in file
     pub output: Option<PathBuf>,
 
+    #[structopt(long, short = "d", parse(from_os_str), conflicts_with = "output")]
+    /// print not to stdout but in one file per input in this directory
+    pub out_dir: Option<PathBuf>,
+
+    #[structopt(long, short = "f")]
+    /// markup languages to parse: `all` (but `org`), `auto` (by file
+    /// extension or, if unknown, by content) or a comma separated list of
+    /// `md`, `rst`, `adoc`, `html`, `wikitext` and `org`. Default: `all`
+    pub format: Option<Format>,
+
     /// print version and exit
     #[structopt(long, short = "V")]
     pub version: bool,
 }
 
+/// The markup languages to parse, see `Args::format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Parse the markup languages of `MarkupLangs::default()`, i.e. all
+    /// but Org-mode.
+    All,
+    /// Derive the markup language from the file extension or, if unknown,
+    /// from the content.
+    Auto,
+    /// Parse the given markup languages.
+    Langs(MarkupLangs),
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => return Ok(Format::All),
+            "auto" => return Ok(Format::Auto),
+            _ => {}
+        }
+        s.split(',')
+            .map(|name| match name.trim() {
+                "md" | "markdown" => Ok(MarkupLang::Markdown),
+                "rst" => Ok(MarkupLang::Rst),
+                "adoc" | "asciidoc" => Ok(MarkupLang::Adoc),
+                "html" => Ok(MarkupLang::Html),
+                "wikitext" | "wiki" => Ok(MarkupLang::Wikitext),
+                "org" => Ok(MarkupLang::Org),
+                name => Err(format!("unknown markup language `{}`", name)),
+            })
+            .collect::<Result<MarkupLangs, _>>()
+            .map(Format::Langs)
+    }
+}
+
+impl Format {
+    /// The markup languages to parse in `input`, the content of the file
+    /// `path`. `None` stands for `stdin`.
+    fn markup_langs(&self, path: Option<&Path>, input: &str) -> MarkupLangs {
+        match self {
+            Format::All => MarkupLangs::default(),
+            Format::Langs(langs) => *langs,
+            Format::Auto => {
+                let lang = path
+                    .and_then(markup_lang)
+                    .unwrap_or_else(|| detect_markup(input));
+                MarkupLangs::empty().with(lang)
+            }
+        }
+    }
+}
+
+/// The markup language of the file `path` according to its extension.
+fn markup_lang(path: &Path) -> Option<MarkupLang> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "md" | "markdown" => Some(MarkupLang::Markdown),
+        "rst" | "rest" => Some(MarkupLang::Rst),
+        "adoc" | "asciidoc" => Some(MarkupLang::Adoc),
+        "html" | "htm" => Some(MarkupLang::Html),
+        "wiki" | "mediawiki" => Some(MarkupLang::Wikitext),
+        "org" => Some(MarkupLang::Org),
+        _ => None,
+    }
+}
+
+/// Expands the `inputs` and returns for every file to render its path and
+/// its name relative to the given input. Directories are walked recursively,
+/// their files are filtered with `globs` or, if `None`, by their file
+/// extension. `-` stands for `stdin`.
+fn input_files(inputs: &[PathBuf], globs: Option<&GlobSet>) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    if inputs.is_empty() {
+        return Ok(vec![(PathBuf::from("-"), PathBuf::from("stdin"))]);
+    }
+    let mut files = Vec::new();
+    for input in inputs {
+        if input == Path::new("-") {
+            files.push((input.clone(), PathBuf::from("stdin")));
+        } else if input.is_dir() {
+            for entry in WalkDir::new(input).sort_by_file_name() {
+                let entry = entry?;
+                let name = entry.path().strip_prefix(input).unwrap_or(entry.path());
+                let selected = match globs {
+                    Some(globs) => globs.is_match(name),
+                    None => markup_lang(name).is_some(),
+                };
+                if entry.file_type().is_file() && selected {
+                    files.push((entry.path().to_path_buf(), name.to_path_buf()));
+                }
+            }
+        } else {
+            let name = input.file_name().map_or(input.as_path(), Path::new);
+            files.push((input.clone(), name.to_path_buf()));
+        }
+    }
+    Ok(files)
+}
+
+/// The filter selecting the links to print, see `Args::scheme`,
+/// `Args::matches` and `Args::invert_match`. `matches` is the compiled
+/// regular expression of `Args::matches`. `None` keeps all links.
+fn dest_filter(matches: Option<Regex>) -> Option<DestFilter> {
+    if ARGS.scheme.is_empty() && matches.is_none() {
+        return None;
+    }
+    Some(DestFilter::new(move |dest| {
+        let scheme_ok = ARGS.scheme.is_empty()
+            || ARGS.scheme.iter().any(|s| {
+                let s = s.trim();
+                match scheme(dest) {
+                    Some(scheme) => scheme.eq_ignore_ascii_case(s),
+                    None => s == "relative",
+                }
+            });
+        let match_ok = matches.as_ref().is_none_or(|re| re.is_match(dest));
+        (scheme_ok && match_ok) != ARGS.invert_match
+    }))
+}
+
+/// A substitution `s/pattern/replacement/flags` as given with
+/// `Args::rewrite`.
+#[derive(Debug)]
+struct Substitution {
+    regex: Regex,
+    replacement: String,
+    /// If true, all matches are replaced, otherwise only the first.
+    global: bool,
+}
+
+impl FromStr for Substitution {
+    type Err = String;
+
+    /// Parses the substitution. Any character can serve as delimiter.
+    /// Escaped delimiters, e.g. `\|`, are taken literally.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid substitution `{}`", s);
+        let mut chars = s.strip_prefix('s').ok_or_else(err)?.chars();
+        let delimiter = chars.next().ok_or_else(err)?;
+        let mut parts = vec![String::new()];
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    // A literal delimiter in the pattern.
+                    Some(c) if c == delimiter && parts.len() == 1 => {
+                        parts[0].push_str(&regex::escape(&c.to_string()))
+                    }
+                    Some(c) if c == delimiter => parts.last_mut().unwrap().push(c),
+                    Some(c) => parts.last_mut().unwrap().extend(['\\', c]),
+                    None => return Err(err()),
+                },
+                c if c == delimiter => parts.push(String::new()),
+                c => parts.last_mut().unwrap().push(c),
+            }
+        }
+        let (pattern, replacement, global) = match parts.as_slice() {
+            [pattern, replacement, flags] if flags.is_empty() || flags == "g" => {
+                (pattern, replacement, flags == "g")
+            }
+            _ => return Err(err()),
+        };
+        Ok(Substitution {
+            regex: Regex::new(pattern).map_err(|e| e.to_string())?,
+            replacement: replacement.clone(),
+            global,
+        })
+    }
+}
+
+impl Substitution {
+    /// Applies the substitution to `dest`. Returns `None` if nothing
+    /// matches.
+    fn apply(&self, dest: &str) -> Option<String> {
+        if !self.regex.is_match(dest) {
+            return None;
+        }
+        let res = if self.global {
+            self.regex.replace_all(dest, self.replacement.as_str())
+        } else {
+            self.regex.replace(dest, self.replacement.as_str())
+        };
+        Some(res.into_owned())
+    }
+}
+
+/// A link as printed with `--json` and `--csv`.
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    text: &'a str,
+    dest: &'a str,
+    title: &'a str,
+    kind: &'a str,
+    /// The input file, `-` for `stdin`.
+    file: &'a str,
+    line: usize,
+}
+
+impl<'a> Record<'a> {
+    fn new(row: &'a LinkRow, file: &'a str) -> Self {
+        Record {
+            text: &row.text,
+            dest: &row.dest,
+            title: &row.title,
+            kind: row.kind,
+            file,
+            line: row.line,
+        }
+    }
+
+    /// Writes the record as a CSV row.
+    fn write_csv(&self, output: &mut dyn Write) -> Result<(), io::Error> {
+        let line = self.line.to_string();
+        let row = [
+            self.text, self.dest, self.title, self.kind, self.file, &line,
+        ]
+        .map(|cell| table_cell(cell, ','));
+        writeln!(output, "{}", row.join(","))
+    }
+}
+
+/// Prints the links of `inbuf` as JSON Lines or CSV rows, see `Args::json`
+/// and `Args::csv`. `file` is the name of the input.
+fn write_records(
+    inbuf: &str,
+    options: &RendererOptions,
+    file: &str,
+    output: &mut dyn Write,
+) -> Result<(), io::Error> {
+    for row in link_rows(inbuf, &options.parser_options) {
+        if !options
+            .dest_filter
+            .as_ref()
+            .is_none_or(|f| f.matches(&row.dest))
+        {
+            continue;
+        }
+        let record = Record::new(&row, file);
+        if ARGS.json {
+            serde_json::to_writer(&mut *output, &record)?;
+            writeln!(output)?;
+        } else {
+            record.write_csv(output)?;
+        }
+    }
+    Ok(())
+}
+
+/// The template of the HTML page printed with `--standalone`.
+const PAGE_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{{title}}</title>
+{{css}}</head>
+<body>
+{{content}}
+</body>
+</html>
+";
+
+/// The HTML before and after the rendered input, if the output is an HTML
+/// page, see `Args::standalone`.
+#[derive(Debug)]
+struct Page {
+    template: String,
+}
+
+impl Page {
+    /// Reads the page template, if any. Returns `None` if no page is to be
+    /// printed.
+    fn new() -> Result<Option<Self>, String> {
+        let template = match &ARGS.template {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("can not read `{}`: {}", path.display(), e))?,
+            None if ARGS.standalone || !ARGS.css.is_empty() => PAGE_TEMPLATE.to_string(),
+            None => return Ok(None),
+        };
+        if !template.contains("{{content}}") {
+            return Err("the template lacks `{{content}}`".to_string());
+        }
+        Ok(Some(Page { template }))
+    }
+
+    /// Returns the HTML before and after the content of the page with the
+    /// title `title`.
+    fn wrapper(&self, title: &str) -> (String, String) {
+        let css: String = ARGS
+            .css
+            .iter()
+            .map(|href| {
+                format!(
+                    "<link rel=\"stylesheet\" href=\"{}\">\n",
+                    encode_double_quoted_attribute(href)
+                )
+            })
+            .collect();
+        let page = self
+            .template
+            .replace("{{title}}", &encode_text(title))
+            .replace("{{css}}", &css);
+        let (head, tail) = page.split_once("{{content}}").unwrap_or((&page, ""));
+        (head.to_string(), tail.to_string())
+    }
+}
+
+/// Default options read from the configuration file, see `Args::config`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Config {
+    format: Option<String>,
+    scheme: Vec<String>,
+    #[serde(rename = "match")]
+    matches: Option<String>,
+    invert_match: bool,
+    /// The output mode, if none is given on the command line.
+    output: Option<String>,
+    /// Applied, if no other output mode is selected.
+    rewrite: Vec<String>,
+}
+
+/// Name of the configuration file looked up in the current directory.
+const CONFIG_FILE: &str = "atext2html.toml";
+
+/// Completes `args` with the defaults of the configuration file, see
+/// `Args::config`.
+fn with_config(mut args: Args) -> Result<Args, String> {
+    let path = match &args.config {
+        Some(path) => path.clone(),
+        None if Path::new(CONFIG_FILE).is_file() => PathBuf::from(CONFIG_FILE),
+        None => return Ok(args),
+    };
+    let config = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str::<Config>(&s).map_err(|e| e.to_string()))
+        .map_err(|e| format!("can not read `{}`: {}", path.display(), e))?;
+
+    if args.format.is_none() {
+        args.format = config.format.map(|f| f.parse()).transpose()?;
+    }
+    if args.scheme.is_empty() {
+        args.scheme = config.scheme;
+    }
+    if args.matches.is_none() {
+        args.matches = config.matches;
+    }
+    if !args.invert_match && !args.no_invert_match {
+        args.invert_match = config.invert_match;
+    }
+
+    let has_mode = args.render_links
+        || args.only_links
+        || args.json
+        || args.csv
+        || args.check
+        || args.stats
+        || !args.rewrite.is_empty();
+    if !has_mode {
+        let mode = match config.output.as_deref() {
+            None => {
+                args.rewrite = config.rewrite;
+                "rewrite"
+            }
+            Some("raw") => "raw",
+            Some("render") => {
+                args.render_links = true;
+                "render"
+            }
+            Some("links") => {
+                args.only_links = true;
+                "links"
+            }
+            Some("json") => {
+                args.json = true;
+                "json"
+            }
+            Some("csv") => {
+                args.csv = true;
+                "csv"
+            }
+            Some("check") => {
+                args.check = true;
+                "check"
+            }
+            Some("stats") => {
+                args.stats = true;
+                "stats"
+            }
+            Some(output) => return Err(format!("unknown output mode `{}`", output)),
+        };
+        // The command line parser checks these conflicts only for the
+        // output modes given on the command line.
+        let page = args.standalone || !args.css.is_empty() || args.template.is_some();
+        let conflict = match mode {
+            "rewrite" if args.rewrite.is_empty() => None,
+            "links" => args.chunk_size.map(|_| "`--chunk-size`"),
+            "json" | "csv" | "check" | "stats" | "rewrite" if args.chunk_size.is_some() => {
+                Some("`--chunk-size`")
+            }
+            "json" | "csv" | "check" | "stats" | "rewrite" if page => {
+                Some("`--standalone`, `--css` or `--template`")
+            }
+            "check" | "stats" if args.out_dir.is_some() => Some("`--out-dir`"),
+            _ => None,
+        };
+        if let Some(option) = conflict {
+            return Err(format!(
+                "the output mode `{}` of `{}` can not be used with {}",
+                mode,
+                path.display(),
+                option
+            ));
+        }
+    }
+    Ok(args)
+}
+
+/// Checks the constraints of `args`, that depend on the configuration
+/// file, see `with_config()`.
+fn check_args(args: Args) -> Result<Args, String> {
+    if args.online && !args.check {
+        return Err("`--online` requires `--check` or the output mode `check`".to_string());
+    }
+    Ok(args)
+}
+
 lazy_static! {
-    /// Structure to hold the parsed command-line arguments.
-    pub static ref ARGS : Args = Args::from_args();
+    /// Structure to hold the parsed command-line arguments, completed with
+    /// the configuration file.
+    pub static ref ARGS : Args = with_config(Args::from_args()).and_then(check_args).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    });
+}
+
+/// Renders `input` chunk by chunk, see `Args::chunk_size`.
+fn stream<R: io::BufRead>(
+    input: R,
+    options: &RendererOptions,
+    chunk_size: usize,
+    mut output: &mut dyn Write,
+) -> Result<(), io::Error> {
+    if ARGS.render_links {
+        text_links2html_stream_with(input, options, chunk_size, &mut output)
+    } else {
+        text_rawlinks2html_stream_with(input, options, chunk_size, &mut output)
+    }
 }
 
 /// Uses the version-number defined in `../Cargo.toml`.
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 /// (c) Jens Getreu
 const AUTHOR: &str = "(c) Jens Getreu, 2020-2023";
+/// Number of input files processed in parallel before their output is
+/// printed.
+const CHUNK_SIZE: usize = 256;
 
-/// Minimal application that prints all Markdown and
-/// RestructuredText links in `stdin`as HTML to `stdout`.
+/// Processes the inputs of `ARGS` and prints the result, see the module
+/// documentation.
 fn main() -> Result<(), ::std::io::Error> {
     if ARGS.version {
         println!("Version {}, {}", VERSION.unwrap_or("unknown"), AUTHOR);
         process::exit(0);
     };
 
-    let renderer = match (ARGS.render_links, ARGS.only_links) {
-        (false, false) => |(inbuf, mut output): (&str, &mut dyn Write)| -> Result<_, _> {
-            text_rawlinks2html_writer(&inbuf, &mut output)
-        },
-        (true, false) => |(inbuf, mut output): (&str, &mut dyn Write)| -> Result<_, _> {
-            text_links2html_writer(&inbuf, &mut output)
-        },
-        (_, true) => |(inbuf, mut output): (&str, &mut dyn Write)| -> Result<_, _> {
-            links2html_writer(&inbuf, &mut output)
+    let renderer =
+        match (ARGS.render_links, ARGS.only_links) {
+            (false, false) => |(inbuf, options, mut output): (
+                &str,
+                &RendererOptions,
+                &mut dyn Write,
+            )|
+             -> Result<_, _> {
+                text_rawlinks2html_writer_with(inbuf, options, &mut output)
+            },
+            (true, false) => |(inbuf, options, mut output): (
+                &str,
+                &RendererOptions,
+                &mut dyn Write,
+            )|
+             -> Result<_, _> {
+                text_links2html_writer_with(inbuf, options, &mut output)
+            },
+            (_, true) => |(inbuf, options, mut output): (
+                &str,
+                &RendererOptions,
+                &mut dyn Write,
+            )|
+             -> Result<_, _> {
+                links2html_writer_with(inbuf, options, &mut output)
+            },
+        };
+    let matches = ARGS.matches.as_ref().map(|re| {
+        Regex::new(re).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        })
+    });
+    let dest_filter = dest_filter(matches);
+    let substitutions: Vec<_> = ARGS
+        .rewrite
+        .iter()
+        .map(|s| {
+            Substitution::from_str(s).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            })
+        })
+        .collect();
+    let options = |path: Option<&Path>, input: &str| RendererOptions {
+        parser_options: ParserOptions {
+            markup_langs: ARGS.format.unwrap_or(Format::All).markup_langs(path, input),
+            ..ParserOptions::default()
         },
+        dest_filter: dest_filter.clone(),
+        ..RendererOptions::default()
     };
 
-    // Where to print the output.
-    let mut output = if let Some(outname) = &ARGS.output {
-        let file = File::create(&Path::new(&outname))?;
-        Box::new(file) as Box<dyn Write>
+    let globs = if ARGS.glob.is_empty() {
+        None
     } else {
-        Box::new(io::stdout()) as Box<dyn Write>
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &ARGS.glob {
+            match Glob::new(pattern) {
+                Ok(glob) => builder.add(glob),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            };
+        }
+        Some(builder.build().map_err(io::Error::other)?)
+    };
+    let page = Page::new().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    });
+    let suffix = match (ARGS.json, ARGS.csv) {
+        (true, _) => "jsonl",
+        (_, true) => "csv",
+        _ => "html",
+    };
+
+    // Renders `inbuf`, the content of the input `file`.
+    let render = |inbuf: &str, file: Option<&Path>, output: &mut dyn Write| {
+        let options = options(file, inbuf);
+        if !substitutions.is_empty() {
+            let rewriter = |dest: &str| {
+                if !dest_filter.as_ref().is_none_or(|f| f.matches(dest)) {
+                    return None;
+                }
+                let mut res: Option<String> = None;
+                for substitution in &substitutions {
+                    if let Some(new) = substitution.apply(res.as_deref().unwrap_or(dest)) {
+                        res = Some(new);
+                    }
+                }
+                res
+            };
+            rewrite_writer(inbuf, rewriter, &mut &mut *output)
+        } else if ARGS.json || ARGS.csv {
+            let file = file.map_or(Cow::Borrowed("-"), |f| f.to_string_lossy());
+            write_records(inbuf, &options, &file, output)
+        } else {
+            renderer((inbuf, &options, output))
+        }
     };
 
-    if (ARGS.inputs.is_empty()) || ((ARGS.inputs.len() == 1) && ARGS.inputs[0] == Path::new("-")) {
+    // Processes the input `path` and returns what to print (nothing with
+    // `--out-dir`), the number of broken links and the link counts.
+    // `checker` is created on first use with `--check`.
+    let process = |checker: &mut Option<LinkChecker>,
+                   path: &Path,
+                   name: &Path|
+     -> Result<(Vec<u8>, usize, LinkStats), io::Error> {
         let mut inbuf = String::new();
-        Read::read_to_string(&mut io::stdin(), &mut inbuf)?;
+        let file = if path == Path::new("-") {
+            Read::read_to_string(&mut io::stdin(), &mut inbuf)?;
+            None
+        } else {
+            Read::read_to_string(&mut File::open(path)?, &mut inbuf)?;
+            Some(path)
+        };
+        let mut output = Vec::new();
+        let mut broken = 0;
+        let mut stats = LinkStats::default();
+
+        if ARGS.stats {
+            stats = LinkStats::new(&inbuf, &options(file, &inbuf).parser_options);
+            let kinds: Vec<_> = stats
+                .kinds
+                .iter()
+                .map(|(kind, n)| format!("{}: {}", kind, n))
+                .collect();
+            let kinds = if kinds.is_empty() {
+                "no links".to_string()
+            } else {
+                kinds.join(", ")
+            };
+            writeln!(output, "{}: {}", path.display(), kinds)?;
+        } else if ARGS.check {
+            // The parser options depend on the input, e.g. with `--format auto`.
+            let mut link_checker = checker
+                .take()
+                .unwrap_or_else(|| LinkChecker::new("").online(ARGS.online))
+                .parser_options(options(file, &inbuf).parser_options);
+            let report = match file {
+                Some(file) => link_checker.check_links_at(&inbuf, file),
+                None => link_checker.check_links(&inbuf),
+            };
+            *checker = Some(link_checker);
+            for link in report
+                .broken()
+                .filter(|l| dest_filter.as_ref().is_none_or(|f| f.matches(&l.dest)))
+            {
+                let line = inbuf[..link.span.start].matches('\n').count() + 1;
+                writeln!(
+                    output,
+                    "{}:{}: {}: {}",
+                    path.display(),
+                    line,
+                    link.dest,
+                    link.status
+                )?;
+                broken += 1;
+            }
+        } else if let Some(out_dir) = &ARGS.out_dir {
+            let mut outname = out_dir.join(name).into_os_string();
+            // Rewritten inputs keep their file name.
+            if substitutions.is_empty() {
+                outname.push(".");
+                outname.push(suffix);
+            }
+            let outname = PathBuf::from(outname);
+            if let Some(parent) = outname.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut output = File::create(outname)?;
+            if ARGS.csv {
+                writeln!(output, "text,dest,title,kind,file,line")?;
+            }
+            match &page {
+                Some(page) => {
+                    let (head, tail) = page.wrapper(&name.to_string_lossy());
+                    output.write_all(head.as_bytes())?;
+                    render(&inbuf, file, &mut output)?;
+                    output.write_all(tail.as_bytes())?;
+                }
+                None => render(&inbuf, file, &mut output)?,
+            }
+        } else {
+            render(&inbuf, file, &mut output)?;
+        }
+        Ok((output, broken, stats))
+    };
 
-        renderer((&inbuf, &mut output))?;
+    // Where to print the output, if not in `ARGS.out_dir`.
+    let mut output = if let Some(outname) = &ARGS.output {
+        let file = File::create(Path::new(&outname))?;
+        Box::new(file) as Box<dyn Write>
     } else {
-        for filename in ARGS.inputs.iter() {
-            let mut inbuf = String::new();
-            let mut file = File::open(filename)?;
-            Read::read_to_string(&mut file, &mut inbuf)?;
+        Box::new(io::stdout()) as Box<dyn Write>
+    };
+    // Number of broken links found with `--check`.
+    let mut broken = 0;
+    // Link counts of all inputs for `--stats`.
+    let mut stats = LinkStats::default();
+    if ARGS.csv && ARGS.out_dir.is_none() {
+        writeln!(output, "text,dest,title,kind,file,line")?;
+    }
 
-            renderer((&inbuf, &mut output))?;
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(ARGS.threads)
+        .build_global()
+        .map_err(io::Error::other)?;
+    let files = input_files(&ARGS.inputs, globs.as_ref())?;
+    // The page wrapping the output of all inputs.
+    let wrapper = match &page {
+        Some(page) if ARGS.out_dir.is_none() => {
+            let title = match files.as_slice() {
+                [(path, _)] if path != Path::new("-") => path.to_string_lossy(),
+                _ => Cow::Borrowed("atext2html"),
+            };
+            Some(page.wrapper(&title))
         }
+        _ => None,
     };
+    if let Some((head, _)) = &wrapper {
+        output.write_all(head.as_bytes())?;
+    }
+    // With `--chunk-size`, the inputs are streamed one after the other.
+    if let Some(chunk_size) = ARGS.chunk_size {
+        for (path, _) in &files {
+            // The content of streamed inputs is unknown in advance.
+            let file = (path != Path::new("-")).then_some(path.as_path());
+            let options = options(file, "");
+            match file {
+                None => stream(io::stdin().lock(), &options, chunk_size, &mut output)?,
+                Some(path) => stream(
+                    BufReader::new(File::open(path)?),
+                    &options,
+                    chunk_size,
+                    &mut output,
+                )?,
+            }
+        }
+    } else {
+        // The files of a chunk are processed in parallel, their output is
+        // printed in input order.
+        for chunk in files.chunks(CHUNK_SIZE) {
+            let results: Vec<_> = chunk
+                .par_iter()
+                .map_init(
+                    || None,
+                    |checker, (path, name)| process(checker, path, name),
+                )
+                .collect();
+            for result in results {
+                let (file_output, file_broken, file_stats) = result?;
+                output.write_all(&file_output)?;
+                broken += file_broken;
+                stats.merge(&file_stats);
+            }
+        }
+    }
+
+    if let Some((_, tail)) = &wrapper {
+        output.write_all(tail.as_bytes())?;
+    }
+    if ARGS.stats {
+        writeln!(output, "\n{}", stats)?;
+    }
+    if broken > 0 {
+        output.flush()?;
+        eprintln!("{} broken link(s) found", broken);
+        process::exit(1);
+    }
 
     Ok(())
 }
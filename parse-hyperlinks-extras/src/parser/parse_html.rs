@@ -38,7 +38,7 @@ use std::borrow::Cow;
 /// assert_eq!(r.0, "abc\nabc");
 /// assert_eq!(r.1, (Cow::from("text2"), Cow::from("destination2")));
 /// ```
-pub fn take_img(i: &str) -> nom::IResult<&str, (&str, (Cow<str>, Cow<str>))> {
+pub fn take_img(i: &str) -> nom::IResult<&str, (&str, (Cow<'_, str>, Cow<'_, str>))> {
     let mut j = i;
     let mut skip_count = 0;
 
@@ -96,7 +96,9 @@ pub fn take_img(i: &str) -> nom::IResult<&str, (&str, (Cow<str>, Cow<str>))> {
 /// assert_eq!(r.0, "abc\nabc");
 /// assert_eq!(r.1, (Cow::from("text2"), Cow::from("dest2"), Cow::from("title2")));
 /// ```
-pub fn take_text2dest(i: &str) -> nom::IResult<&str, (&str, (Cow<str>, Cow<str>, Cow<str>))> {
+pub fn take_text2dest(
+    i: &str,
+) -> nom::IResult<&str, (&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>))> {
     let mut j = i;
     let mut skip_count = 0;
 
@@ -130,7 +132,6 @@ pub fn take_text2dest(i: &str) -> nom::IResult<&str, (&str, (Cow<str>, Cow<str>,
 
 /// Consumes the input until the parser finds an HTML formatted _inline image_ (`Link::Image`).
 /// and HTML formatted hyperlinks _text2dest_ (`Link::Text2Dest`).
-
 ///
 /// The parser consumes the finding and returns
 /// `Ok((remaining_input, (skipped_input, Link)))` or some error.
@@ -166,7 +167,7 @@ pub fn take_text2dest(i: &str) -> nom::IResult<&str, (&str, (Cow<str>, Cow<str>,
 /// assert_eq!(r.0, "abc\nabc");
 /// assert_eq!(r.1, Link::Image2Dest(Cow::from("cde"), Cow::from("alt5"), Cow::from("src5"), Cow::from("fgh"), Cow::from("dest5"), Cow::from("title5")));
 /// ```
-pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
+pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link<'_>)> {
     let mut j = i;
     let mut skip_count = 0;
 
@@ -253,4 +254,17 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_take_text2dest() {
+        let (i, r) =
+            take_text2dest(r#"abc<a href="a&#x2F;b&#47c" title="It&#8217;s">It&#8217;s</a>abc"#)
+                .unwrap();
+        assert_eq!(i, "abc");
+        assert_eq!(r.0, "abc");
+        assert_eq!(
+            r.1,
+            (Cow::from("It’s"), Cow::from("a/b/c"), Cow::from("It’s"))
+        );
+    }
 }
@@ -0,0 +1,60 @@
+/* C bindings for the hyperlink extractor Parse-hyperlinks.
+ * Link with the `parse_hyperlinks_ffi` library. */
+
+#ifndef PARSE_HYPERLINKS_H
+#define PARSE_HYPERLINKS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A UTF-8 string, that is not null terminated. */
+typedef struct {
+    const uint8_t *ptr;
+    size_t len;
+} PhStr;
+
+/* The kind of a `PhLink`. */
+typedef enum {
+    /* `text` is the link text, `dest` the link destination. */
+    PH_LINK_KIND_LINK = 0,
+    /* `text` is the image alt text, `dest` the image source, `title` is
+     * empty. */
+    PH_LINK_KIND_IMAGE = 1,
+} PhLinkKind;
+
+/* A link extracted by `ph_extract_links()`. */
+typedef struct {
+    PhLinkKind kind;
+    PhStr text;
+    PhStr dest;
+    PhStr title;
+    /* Byte range of the link's source in the input. */
+    size_t span_start;
+    size_t span_end;
+} PhLink;
+
+/* The links returned by `ph_extract_links()`. */
+typedef struct {
+    const PhLink *links;
+    size_t len;
+} PhLinks;
+
+/* Extracts the hyperlinks of the UTF-8 encoded `input` of `len` bytes.
+ * Reference links are resolved, a link with embedded image yields two
+ * entries: the image and the link. Returns `NULL` if `input` is not valid
+ * UTF-8. The result must be released with `ph_links_free()`. */
+PhLinks *ph_extract_links(const uint8_t *input, size_t len);
+
+/* Releases the links returned by `ph_extract_links()`. Does nothing if
+ * `links` is `NULL`. */
+void ph_links_free(PhLinks *links);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PARSE_HYPERLINKS_H */
@@ -0,0 +1,219 @@
+//! C bindings for the hyperlink extractor
+//! [Parse-hyperlinks](https://crates.io/crates/parse-hyperlinks). The C
+//! declarations are in `include/parse_hyperlinks.h`.
+//!
+//! ```c
+//! #include "parse_hyperlinks.h"
+//!
+//! const char *text = "abc[text](dest)abc";
+//! PhLinks *links = ph_extract_links((const uint8_t *)text, strlen(text));
+//! for (size_t i = 0; links != NULL && i < links->len; i++) {
+//!     const PhLink *l = &links->links[i];
+//!     printf("%.*s -> %.*s\n", (int)l->text.len, l->text.ptr,
+//!            (int)l->dest.len, l->dest.ptr);
+//! }
+//! ph_links_free(links);
+//! ```
+
+use parse_hyperlinks::iterator::MarkupLink;
+use parse_hyperlinks::parser::Link;
+use std::ptr;
+use std::slice;
+
+/// A UTF-8 string, that is not null terminated.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PhStr {
+    /// First byte of the string.
+    pub ptr: *const u8,
+    /// Length in bytes.
+    pub len: usize,
+}
+
+/// The kind of a `PhLink`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhLinkKind {
+    /// A hyperlink: `text` is the link text, `dest` the link destination.
+    Link = 0,
+    /// An image: `text` is the image alt text, `dest` the image source,
+    /// `title` is empty.
+    Image = 1,
+}
+
+/// A link extracted by `ph_extract_links()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PhLink {
+    /// Link or image.
+    pub kind: PhLinkKind,
+    /// The link text or the image alt text.
+    pub text: PhStr,
+    /// The link destination or the image source.
+    pub dest: PhStr,
+    /// The link title.
+    pub title: PhStr,
+    /// Byte offset of the link's source in the input.
+    pub span_start: usize,
+    /// Byte offset after the link's source in the input.
+    pub span_end: usize,
+}
+
+/// The links returned by `ph_extract_links()`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PhLinks {
+    /// Array of `len` links.
+    pub links: *const PhLink,
+    /// Number of links.
+    pub len: usize,
+}
+
+/// The allocation behind a `PhLinks` pointer: the C-visible header is
+/// followed by the memory its pointers refer to.
+#[repr(C)]
+struct Allocation {
+    /// Must be the first field: C sees a pointer to the allocation as a
+    /// pointer to `PhLinks`.
+    header: PhLinks,
+    links: Vec<PhLink>,
+    /// The strings the links point to. Moving a `String` does not move its
+    /// heap buffer, so the pointers stay valid.
+    strings: Vec<String>,
+}
+
+impl Allocation {
+    /// Extracts the links of `input`. Reference links are resolved, a link
+    /// with embedded image yields two entries: the image and the link.
+    fn new(input: &str) -> Box<Self> {
+        let mut strings = Vec::new();
+        let mut links = Vec::new();
+        let mut push = |kind, text: &str, dest: &str, title: &str, span_start, span_end| {
+            let mut s = |s: &str| {
+                strings.push(s.to_string());
+                let s = strings.last().map(String::as_str).unwrap_or_default();
+                PhStr {
+                    ptr: s.as_ptr(),
+                    len: s.len(),
+                }
+            };
+            links.push(PhLink {
+                kind,
+                text: s(text),
+                dest: s(dest),
+                title: s(title),
+                span_start,
+                span_end,
+            });
+        };
+        for ((_, consumed, remaining), link) in MarkupLink::new(input, false) {
+            let end = input.len() - remaining.len();
+            let start = end - consumed.len();
+            match link {
+                Link::Text2Dest(text, dest, title) => {
+                    push(PhLinkKind::Link, &text, &dest, &title, start, end)
+                }
+                Link::Image(alt, src) => push(PhLinkKind::Image, &alt, &src, "", start, end),
+                Link::Image2Dest(text1, alt, src, text2, dest, title) => {
+                    push(PhLinkKind::Image, &alt, &src, "", start, end);
+                    let text = format!("{}{}{}", text1, alt, text2);
+                    push(PhLinkKind::Link, &text, &dest, &title, start, end);
+                }
+                _ => {}
+            }
+        }
+
+        let mut allocation = Box::new(Self {
+            header: PhLinks {
+                links: ptr::null(),
+                len: 0,
+            },
+            links,
+            strings,
+        });
+        allocation.header.links = allocation.links.as_ptr();
+        allocation.header.len = allocation.links.len();
+        allocation
+    }
+}
+
+/// Extracts the hyperlinks of the UTF-8 encoded `input` of `len` bytes.
+/// Returns `NULL` if `input` is not valid UTF-8. The result must be
+/// released with `ph_links_free()`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes. It may be `NULL` if `len`
+/// is 0.
+#[no_mangle]
+pub unsafe extern "C" fn ph_extract_links(input: *const u8, len: usize) -> *mut PhLinks {
+    let input = if len == 0 {
+        &[]
+    } else {
+        // SAFETY: guaranteed by the caller.
+        unsafe { slice::from_raw_parts(input, len) }
+    };
+    match std::str::from_utf8(input) {
+        Ok(input) => Box::into_raw(Allocation::new(input)).cast(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Releases the links returned by `ph_extract_links()`. Does nothing if
+/// `links` is `NULL`.
+///
+/// # Safety
+///
+/// `links` must be `NULL` or returned by `ph_extract_links()` and not
+/// released before.
+#[no_mangle]
+pub unsafe extern "C" fn ph_links_free(links: *mut PhLinks) {
+    if !links.is_null() {
+        // SAFETY: `links` was created by `Box::into_raw()` in
+        // `ph_extract_links()`.
+        drop(unsafe { Box::from_raw(links.cast::<Allocation>()) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Converts `s` back into a `&str`.
+    fn str(s: PhStr) -> &'static str {
+        unsafe { std::str::from_utf8(slice::from_raw_parts(s.ptr, s.len)).unwrap() }
+    }
+
+    #[test]
+    fn test_ph_extract_links() {
+        let i = "abc[text1][label1]abc[![alt2](src2)](dest2 \"title2\")\n\n[label1]: dest1\n";
+        unsafe {
+            let links = ph_extract_links(i.as_ptr(), i.len());
+            assert!(!links.is_null());
+            let l = slice::from_raw_parts((*links).links, (*links).len);
+            assert_eq!(l.len(), 3);
+
+            assert_eq!(l[0].kind, PhLinkKind::Link);
+            assert_eq!(str(l[0].text), "text1");
+            assert_eq!(str(l[0].dest), "dest1");
+            assert_eq!(str(l[0].title), "");
+            assert_eq!((l[0].span_start, l[0].span_end), (3, 18));
+
+            assert_eq!(l[1].kind, PhLinkKind::Image);
+            assert_eq!(str(l[1].text), "alt2");
+            assert_eq!(str(l[1].dest), "src2");
+            assert_eq!(l[2].kind, PhLinkKind::Link);
+            assert_eq!(str(l[2].dest), "dest2");
+            assert_eq!(str(l[2].title), "title2");
+            assert_eq!(l[1].span_start, l[2].span_start);
+            ph_links_free(links);
+
+            let links = ph_extract_links(ptr::null(), 0);
+            assert_eq!((*links).len, 0);
+            ph_links_free(links);
+
+            assert!(ph_extract_links(b"\xff".as_ptr(), 1).is_null());
+            ph_links_free(ptr::null_mut());
+        }
+    }
+}
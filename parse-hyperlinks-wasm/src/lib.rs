@@ -0,0 +1,97 @@
+//! JavaScript bindings for the hyperlink extractor
+//! [Parse-hyperlinks](https://crates.io/crates/parse-hyperlinks).
+//!
+//! Build an npm package in the directory `pkg/` with
+//! [wasm-pack](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ```sh
+//! cd parse-hyperlinks-wasm
+//! wasm-pack build --target web
+//! ```
+//!
+//! Usage in JavaScript:
+//!
+//! ```js
+//! import init, { extract_links, text_links2html } from "./pkg/parse_hyperlinks_wasm.js";
+//!
+//! await init();
+//! const links = extract_links("abc[text](dest)abc");
+//! // [{ start: 3, end: 15, link: { Text2Dest: ["text", "dest", ""] } }]
+//! const html = text_links2html("abc[text](dest)abc");
+//! ```
+
+use parse_hyperlinks::iterator::MarkupLink;
+use parse_hyperlinks::parser::Link;
+use parse_hyperlinks::renderer;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A link with its byte range in the input, as passed to JavaScript.
+#[derive(Debug, PartialEq, Serialize)]
+struct JsLink<'a> {
+    /// First byte of the link's source.
+    start: usize,
+    /// Byte after the link's source.
+    end: usize,
+    /// The link, reference links are resolved.
+    link: Link<'a>,
+}
+
+/// Extracts the hyperlinks of `text` and returns them as array of objects
+/// `{ start, end, link }`, see the module documentation. `start` and `end`
+/// are byte offsets in the UTF-8 encoded `text`.
+#[wasm_bindgen]
+pub fn extract_links(text: &str) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&js_links(text))?)
+}
+
+/// Renders `text` as HTML with clickable hyperlinks, see
+/// `renderer::text_links2html()`.
+#[wasm_bindgen]
+pub fn text_links2html(text: &str) -> String {
+    renderer::text_links2html(text)
+}
+
+/// The hyperlinks of `text` with their byte ranges.
+fn js_links(text: &str) -> Vec<JsLink<'_>> {
+    MarkupLink::new(text, false)
+        .map(|((_, consumed, remaining), link)| {
+            let end = text.len() - remaining.len();
+            JsLink {
+                start: end - consumed.len(),
+                end,
+                link,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_js_links() {
+        let links = js_links("abc[text](dest)abc\n[a][b]\n\n[b]: c\n");
+        assert_eq!(
+            links,
+            [
+                JsLink {
+                    start: 3,
+                    end: 15,
+                    link: Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")),
+                },
+                JsLink {
+                    start: 19,
+                    end: 25,
+                    link: Link::Text2Dest(Cow::from("a"), Cow::from("c"), Cow::from("")),
+                }
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&links[0]).unwrap(),
+            r#"{"start":3,"end":15,"link":{"Text2Dest":["text","dest",""]}}"#
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! Benchmarks of the search for links with `take_link()`.
+//!
+//! ```sh
+//! cargo bench -p parse-hyperlinks
+//! ```
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use parse_hyperlinks::parser::parse::take_link;
+
+/// Counts the links in `input`.
+fn count_links(input: &str) -> usize {
+    let mut i = input;
+    let mut n = 0;
+    while let Ok((j, _)) = take_link(i) {
+        n += 1;
+        i = j;
+    }
+    n
+}
+
+/// Prose with a few links per paragraph.
+fn prose() -> String {
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
+        eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad \
+        minim veniam, quis [nostrud](https://example.com/nostrud) exercitation \
+        ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute \
+        irure dolor in `reprehenderit <https://example.com>`_ in voluptate \
+        velit esse cillum dolore eu fugiat nulla pariatur.\n\n";
+    paragraph.repeat(10_000)
+}
+
+/// Long lines without whitespace, e.g. minified code or base64 data.
+fn dense() -> String {
+    let line =
+        "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVphYmNkZWZnaGlqa2xtbm9wcXJzdHV2d3h5ejAxMjM0NTY3ODk"
+            .repeat(100);
+    format!("{line}\n[link](dest)\n").repeat(1_000)
+}
+
+fn bench_take_link(c: &mut Criterion) {
+    let mut group = c.benchmark_group("take_link");
+    for (name, input) in [("prose", prose()), ("dense", dense())] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| b.iter(|| count_links(black_box(&input))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_take_link);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+//! Runs every parser on arbitrary UTF-8 input.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    parse_hyperlinks::fuzz_all(input);
+});
@@ -0,0 +1,13 @@
+//! Extracts all links from arbitrary bytes, that are not guaranteed to be
+//! valid UTF-8.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use parse_hyperlinks::parser::parse::take_link_bytes;
+
+fuzz_target!(|input: &[u8]| {
+    let mut i = input;
+    while let Ok((j, _)) = take_link_bytes(i) {
+        i = j;
+    }
+});
@@ -0,0 +1,1008 @@
+//! Module for analysing the hyperlinks of many files over time.
+//!
+//! An `Index` stores the links extracted from a set of files together with
+//! a content hash of each file. `update_index()` re-parses only the files,
+//! whose content has changed since the last run. With the `serde` feature
+//! enabled, the index can be persisted with `save_index()` and
+//! `load_index()`. `diff_indexes()` compares two snapshots of an index.
+//! `link_id()` assigns every link and every finding about a link a stable
+//! identifier, that reports, caches and ignore lists can refer to.
+//! `duplicate_images()` finds identical local images referenced under
+//! different paths. A `Report` sums up the findings about a set of files,
+//! `LinkStats` counts their links.
+
+use crate::headings::anchors;
+use crate::headings::SlugStyle;
+use crate::iterator::broken_references;
+use crate::iterator::unused_definitions;
+use crate::iterator::MarkupLink;
+use crate::iterator::Span;
+use crate::link_index::link_kinds;
+use crate::parser::html::html_anchors;
+use crate::parser::parse::take_link_lang_with;
+use crate::parser::parse::ParserOptions;
+use crate::parser::Link;
+use crate::parser::MarkupLang;
+use crate::url::is_html_file;
+use crate::url::is_markup_file;
+use crate::url::local_path;
+use crate::url::scheme;
+use percent_encoding::percent_decode_str;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use std::io::BufRead;
+#[cfg(feature = "serde")]
+use std::io::Write;
+#[cfg(feature = "serde")]
+use thiserror::Error;
+
+/// Version of the on-disk index format written by `save_index()`.
+pub const INDEX_VERSION: u32 = 1;
+
+/// A link extracted from a file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexedLink {
+    /// Byte offset of the link's first byte in the file.
+    pub offset: usize,
+    /// Length of the link's source in bytes.
+    pub len: usize,
+    /// The resolved link: `Link::Text2Dest`, `Link::Image2Dest` or
+    /// `Link::Image`.
+    pub link: Link<'static>,
+}
+
+/// The links of one file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexEntry {
+    /// Hash of the file's content, see `content_hash()`.
+    pub hash: u64,
+    /// All links found in the file in order of appearance.
+    pub links: Vec<IndexedLink>,
+}
+
+impl IndexedLink {
+    /// The link's stable identifier, see `link_id()`. `path` is the file the
+    /// link was extracted from.
+    ///
+    /// ```
+    /// use parse_hyperlinks::analysis::{extract_links, link_id};
+    /// use std::path::Path;
+    ///
+    /// let links = extract_links("abc[text](dest)abc");
+    /// assert_eq!(links[0].id(Path::new("a.md")), link_id(Path::new("a.md"), &(3..15), "dest"));
+    /// ```
+    pub fn id(&self, path: &Path) -> u64 {
+        let dest = match &self.link {
+            Link::Text2Dest(_, dest, _) | Link::Image(_, dest) => dest,
+            Link::Image2Dest(_, _, _, _, dest, _) => dest,
+            _ => "",
+        };
+        link_id(path, &(self.offset..self.offset + self.len), dest)
+    }
+}
+
+impl IndexEntry {
+    /// Extracts the links of `input`.
+    pub fn new(input: &str) -> Self {
+        Self {
+            hash: content_hash(input.as_bytes()),
+            links: extract_links(input),
+        }
+    }
+}
+
+/// The links of a set of files. The key is the file's path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    /// The indexed files and their links.
+    pub files: BTreeMap<PathBuf, IndexEntry>,
+}
+
+/// Extracts all links of `input` with `iterator::MarkupLink`, link
+/// references are resolved.
+///
+/// ```
+/// use parse_hyperlinks::analysis::extract_links;
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let links = extract_links("abc[text](dest)abc");
+/// assert_eq!(links[0].offset, 3);
+/// assert_eq!(links[0].len, 12);
+/// assert_eq!(links[0].link, Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")));
+/// ```
+pub fn extract_links(input: &str) -> Vec<IndexedLink> {
+    MarkupLink::new(input, false)
+        .map(|((_, consumed, remaining), link)| IndexedLink {
+            offset: input.len() - consumed.len() - remaining.len(),
+            len: consumed.len(),
+            link: link.into_owned(),
+        })
+        .collect()
+}
+
+/// 64 bit FNV-1a hash of `bytes`. The hash is stable across platforms and
+/// releases, so it can be stored on disk.
+///
+/// ```
+/// use parse_hyperlinks::analysis::content_hash;
+///
+/// assert_eq!(content_hash(b""), 0xcbf29ce484222325);
+/// assert_ne!(content_hash(b"abc"), content_hash(b"abd"));
+/// ```
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    fnv1a(0xcbf29ce484222325, bytes)
+}
+
+/// Continues the FNV-1a `hash` with `bytes`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Stable identifier of a link or of a finding about a link: the 64 bit
+/// FNV-1a hash of the file `path`, the link's byte range `span` in that
+/// file and its destination `dest`. Findings without destination, e.g.
+/// unresolvable references, pass their link label instead.
+///
+/// Like `content_hash()`, the identifier is stable across platforms and
+/// releases. It changes when the link moves within its file.
+///
+/// ```
+/// use parse_hyperlinks::analysis::link_id;
+/// use std::path::Path;
+///
+/// let id = link_id(Path::new("a.md"), &(3..15), "dest");
+/// assert_eq!(id, link_id(Path::new("a.md"), &(3..15), "dest"));
+/// assert_ne!(id, link_id(Path::new("b.md"), &(3..15), "dest"));
+/// assert_ne!(id, link_id(Path::new("a.md"), &(4..15), "dest"));
+/// assert_ne!(id, link_id(Path::new("a.md"), &(3..15), "dest2"));
+/// ```
+pub fn link_id(path: &Path, span: &Span, dest: &str) -> u64 {
+    // Separate the variable length fields, so that e.g. `("ab", "c")` and
+    // `("a", "bc")` do not collide.
+    let mut hash = fnv1a(0xcbf29ce484222325, path.to_string_lossy().as_bytes());
+    hash = fnv1a(hash, &[0]);
+    hash = fnv1a(hash, &(span.start as u64).to_le_bytes());
+    hash = fnv1a(hash, &(span.end as u64).to_le_bytes());
+    fnv1a(hash, dest.as_bytes())
+}
+
+/// Brings `index` up to date with the files `paths`. Only files whose content
+/// hash differs from the indexed one are re-parsed. Files that do not exist
+/// anymore are removed from the index, files not listed in `paths` are kept.
+/// Returns the paths of the added, changed and removed files.
+///
+/// ```no_run
+/// use parse_hyperlinks::analysis::{update_index, Index};
+///
+/// let mut index = Index::default();
+/// let changed = update_index(&mut index, &["README.md", "doc/a.md"]).unwrap();
+/// assert_eq!(changed.len(), 2);
+/// // Nothing changed meanwhile.
+/// let changed = update_index(&mut index, &["README.md", "doc/a.md"]).unwrap();
+/// assert!(changed.is_empty());
+/// ```
+pub fn update_index<P: AsRef<Path>>(index: &mut Index, paths: &[P]) -> io::Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if index.files.remove(path).is_some() {
+                    changed.push(path.to_path_buf());
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let hash = content_hash(input.as_bytes());
+        if index.files.get(path).map(|e| e.hash) == Some(hash) {
+            continue;
+        }
+        index
+            .files
+            .insert(path.to_path_buf(), IndexEntry::new(&input));
+        changed.push(path.to_path_buf());
+    }
+    Ok(changed)
+}
+
+/// The link differences of one file between two index snapshots.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileDiff {
+    /// Links found only in the new snapshot.
+    pub added: Vec<IndexedLink>,
+    /// Links found only in the old snapshot.
+    pub removed: Vec<IndexedLink>,
+    /// Links with the same visible text, but a different destination, title
+    /// or image source: `(old, new)`.
+    pub changed: Vec<(IndexedLink, IndexedLink)>,
+}
+
+impl FileDiff {
+    /// True if no link has been added, removed or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two index snapshots and reports the added, removed and changed
+/// links of every file. Links are compared by content: a link that only
+/// moved within its file is not reported. Files without link differences
+/// are omitted from the result.
+///
+/// ```
+/// use parse_hyperlinks::analysis::{diff_indexes, Index, IndexEntry};
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+/// use std::path::PathBuf;
+///
+/// let mut old = Index::default();
+/// old.files.insert("a.md".into(), IndexEntry::new("[a](b) [c](d)"));
+/// let mut new = Index::default();
+/// new.files.insert("a.md".into(), IndexEntry::new("[c](d) [a](e) [f](g)"));
+///
+/// let diff = diff_indexes(&old, &new);
+/// let file = &diff[&PathBuf::from("a.md")];
+/// assert_eq!(file.added[0].link, Link::Text2Dest(Cow::from("f"), Cow::from("g"), Cow::from("")));
+/// assert!(file.removed.is_empty());
+/// assert_eq!(file.changed[0].0.link, Link::Text2Dest(Cow::from("a"), Cow::from("b"), Cow::from("")));
+/// assert_eq!(file.changed[0].1.link, Link::Text2Dest(Cow::from("a"), Cow::from("e"), Cow::from("")));
+/// ```
+pub fn diff_indexes(old: &Index, new: &Index) -> BTreeMap<PathBuf, FileDiff> {
+    let empty = IndexEntry {
+        hash: 0,
+        links: Vec::new(),
+    };
+    let mut res = BTreeMap::new();
+    for path in old.files.keys().chain(new.files.keys()) {
+        if res.contains_key(path) {
+            continue;
+        }
+        let old_entry = old.files.get(path).unwrap_or(&empty);
+        let new_entry = new.files.get(path).unwrap_or(&empty);
+        if old.files.contains_key(path)
+            && new.files.contains_key(path)
+            && old_entry.hash == new_entry.hash
+        {
+            continue;
+        }
+        let diff = diff_links(&old_entry.links, &new_entry.links);
+        if !diff.is_empty() {
+            res.insert(path.clone(), diff);
+        }
+    }
+    res
+}
+
+/// Compares the links of one file.
+fn diff_links(old: &[IndexedLink], new: &[IndexedLink]) -> FileDiff {
+    // Pair identical links first.
+    let mut new_used = vec![false; new.len()];
+    let mut removed = Vec::new();
+    for o in old {
+        match (0..new.len()).find(|&n| !new_used[n] && new[n].link == o.link) {
+            Some(n) => new_used[n] = true,
+            None => removed.push(o),
+        }
+    }
+
+    // Of the remaining links, pair those with the same visible text.
+    let mut diff = FileDiff::default();
+    for o in removed {
+        match (0..new.len()).find(|&n| !new_used[n] && same_text(&new[n].link, &o.link)) {
+            Some(n) => {
+                new_used[n] = true;
+                diff.changed.push((o.clone(), new[n].clone()));
+            }
+            None => diff.removed.push(o.clone()),
+        }
+    }
+    diff.added = new
+        .iter()
+        .zip(new_used)
+        .filter(|(_, used)| !used)
+        .map(|(n, _)| n.clone())
+        .collect();
+    diff
+}
+
+/// True if both links are of the same type and show the same text.
+fn same_text(a: &Link, b: &Link) -> bool {
+    match (a, b) {
+        (Link::Text2Dest(a, _, _), Link::Text2Dest(b, _, _)) => a == b,
+        (Link::Image(a, _), Link::Image(b, _)) => a == b,
+        (Link::Image2Dest(a1, a2, _, a3, _, _), Link::Image2Dest(b1, b2, _, b3, _, _)) => {
+            a1 == b1 && a2 == b2 && a3 == b3
+        }
+        _ => false,
+    }
+}
+
+/// Reads all local images referenced by the files in `index` and reports
+/// those with identical content, but different paths. Each group lists the
+/// paths of one image content in lexical order.
+///
+/// Image sources are resolved relative to the directory of the file
+/// referencing them. Sources with a URL scheme (e.g. `https:` or `data:`)
+/// are ignored, so are images that do not exist.
+///
+/// ```no_run
+/// use parse_hyperlinks::analysis::{duplicate_images, update_index, Index};
+///
+/// let mut index = Index::default();
+/// update_index(&mut index, &["README.md", "doc/a.md"]).unwrap();
+/// for group in duplicate_images(&index).unwrap() {
+///     println!("identical images: {:?}", group);
+/// }
+/// ```
+pub fn duplicate_images(index: &Index) -> io::Result<Vec<Vec<PathBuf>>> {
+    let mut images = BTreeSet::new();
+    for (path, entry) in &index.files {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for indexed in &entry.links {
+            let src = match &indexed.link {
+                Link::Image(_, src) | Link::Image2Dest(_, _, src, _, _, _) => src,
+                _ => continue,
+            };
+            if let Some(image) = local_path(dir, src) {
+                images.insert(normalize_path(&image));
+            }
+        }
+    }
+
+    let mut by_hash: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    for image in images {
+        let content = match fs::read(&image) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        by_hash
+            .entry(content_hash(&content))
+            .or_default()
+            .push(image);
+    }
+
+    let mut res: Vec<_> = by_hash
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .collect();
+    res.sort();
+    Ok(res)
+}
+
+/// Link counts of one or more inputs, e.g. for documentation health
+/// dashboards. Reference links are not resolved: every link is counted in
+/// the form it appears in the input. The `Display` implementation prints
+/// one table per category.
+///
+/// ```
+/// use parse_hyperlinks::analysis::LinkStats;
+/// use parse_hyperlinks::parser::parse::ParserOptions;
+/// use parse_hyperlinks::parser::MarkupLang;
+///
+/// let options = ParserOptions::default();
+/// let mut stats = LinkStats::new("[a](https://x) [b][l] ![c](c.png)", &options);
+/// stats.merge(&LinkStats::new(".. _l: mailto:a@b.c\n", &options));
+/// assert_eq!(stats.inputs, 2);
+/// assert_eq!(stats.kinds["inline"], 1);
+/// assert_eq!(stats.kinds["reference"], 1);
+/// assert_eq!(stats.kinds["definition"], 1);
+/// assert_eq!(stats.kinds["image"], 1);
+/// assert_eq!(stats.schemes["https"], 1);
+/// assert_eq!(stats.schemes["relative"], 1);
+/// assert_eq!(stats.langs[&MarkupLang::Markdown], 3);
+/// assert_eq!(stats.langs[&MarkupLang::Rst], 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Number of counted inputs.
+    pub inputs: usize,
+    /// Number of links per kind: `inline` (inline links), `reference`
+    /// (reference links and cross-references), `definition` (link reference
+    /// definitions and aliases) and `image` (images, also those inside
+    /// inline links).
+    pub kinds: BTreeMap<&'static str, usize>,
+    /// Number of link destinations and image sources per lowercase URL
+    /// scheme, `relative` for destinations without scheme.
+    pub schemes: BTreeMap<String, usize>,
+    /// Number of links per markup language.
+    pub langs: BTreeMap<MarkupLang, usize>,
+}
+
+impl LinkStats {
+    /// Counts the links of `input`.
+    pub fn new(input: &str, options: &ParserOptions) -> Self {
+        let mut stats = LinkStats {
+            inputs: 1,
+            ..LinkStats::default()
+        };
+        let mut i = input;
+        while let Ok((j, (_, link, lang))) = take_link_lang_with(i, options) {
+            i = j;
+            *stats.langs.entry(lang).or_default() += 1;
+            let dests = match &link {
+                Link::Image2Dest(_, _, src, _, dest, _) => [Some(&**src), Some(&**dest)],
+                link => [link.dest(), None],
+            };
+            for kind in link_kinds(&link) {
+                *stats.kinds.entry(kind).or_default() += 1;
+            }
+            for dest in dests.into_iter().flatten() {
+                let scheme = scheme(dest).map_or("relative".to_string(), |s| s.to_lowercase());
+                *stats.schemes.entry(scheme).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// Adds the counts of `other`.
+    pub fn merge(&mut self, other: &LinkStats) {
+        self.inputs += other.inputs;
+        for (kind, n) in &other.kinds {
+            *self.kinds.entry(kind).or_default() += n;
+        }
+        for (scheme, n) in &other.schemes {
+            *self.schemes.entry(scheme.clone()).or_default() += n;
+        }
+        for (lang, n) in &other.langs {
+            *self.langs.entry(*lang).or_default() += n;
+        }
+    }
+}
+
+impl fmt::Display for LinkStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Inputs: {}", self.inputs)?;
+        writeln!(f, "Links per kind:")?;
+        for (kind, n) in &self.kinds {
+            writeln!(f, "  {kind}: {n}")?;
+        }
+        writeln!(f, "Destinations per scheme:")?;
+        for (scheme, n) in &self.schemes {
+            writeln!(f, "  {scheme}: {n}")?;
+        }
+        writeln!(f, "Links per markup language:")?;
+        for (lang, n) in &self.langs {
+            writeln!(f, "  {lang:?}: {n}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Quality report about the hyperlinks of a set of files, see
+/// `Report::new()`. `renderer::report2html_writer()` renders it as a
+/// self-contained HTML page. Spans are byte ranges in the file the finding
+/// refers to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// The analysed files in lexical order.
+    pub files: Vec<PathBuf>,
+    /// Number of hyperlinks after resolving link references.
+    pub links: usize,
+    /// Number of images after resolving link references.
+    pub images: usize,
+    /// Number of hyperlinks and images whose destination has a URL scheme.
+    pub external: usize,
+    /// Reference links and images, that can not be resolved:
+    /// `(file, link_label, span)`.
+    pub broken_references: Vec<(PathBuf, String, Span)>,
+    /// Local hyperlinks and images, whose target does not exist:
+    /// `(file, link_destination, span)`.
+    pub broken_links: Vec<(PathBuf, String, Span)>,
+    /// Local hyperlinks, whose fragment does not match any anchor of the
+    /// target file: `(file, link_destination, span)`.
+    pub dangling_fragments: Vec<(PathBuf, String, Span)>,
+    /// Link reference definitions, that are never referred to:
+    /// `(file, link_label, span)`.
+    pub unused_definitions: Vec<(PathBuf, String, Span)>,
+    /// The link graph: `(file, target)` for every hyperlink between two of
+    /// the analysed files.
+    pub graph: BTreeSet<(PathBuf, PathBuf)>,
+}
+
+impl Report {
+    /// Analyses the `(path, content)` pairs of `sources`. Local link
+    /// destinations are resolved relative to the directory of the file
+    /// containing them. A target, that is not among `sources`, is looked up
+    /// in the file system. Destinations with a URL scheme are counted, but
+    /// not checked.
+    ///
+    /// Fragments are only verified for links to files among `sources`:
+    /// against the anchors of HTML files (`.html` or `.htm`) and against the
+    /// heading anchors (`SlugStyle::GitHub`) of Markdown, reStructuredText
+    /// and Asciidoc files.
+    ///
+    /// ```
+    /// use parse_hyperlinks::analysis::Report;
+    /// use std::path::PathBuf;
+    ///
+    /// let report = Report::new(&[
+    ///     ("doc/a.md", "[b](b.html#sec) [c](c.md) [d][x]\n[y]: https://getreu.net"),
+    ///     ("doc/b.html", r#"<h1 id="title">B</h1>"#),
+    /// ]);
+    /// assert_eq!(report.links, 2);
+    /// assert_eq!(report.broken_links[0].1, "c.md");
+    /// assert_eq!(report.dangling_fragments[0].1, "b.html#sec");
+    /// assert_eq!(report.broken_references[0].1, "x");
+    /// assert_eq!(report.unused_definitions[0].1, "y");
+    /// assert!(report.graph.contains(&(PathBuf::from("doc/a.md"), PathBuf::from("doc/b.html"))));
+    /// ```
+    pub fn new<P: AsRef<Path>, S: AsRef<str>>(sources: &[(P, S)]) -> Self {
+        let sources: BTreeMap<_, _> = sources
+            .iter()
+            .map(|(path, input)| (normalize_path(path.as_ref()), input.as_ref()))
+            .collect();
+        let mut report = Report {
+            files: sources.keys().cloned().collect(),
+            ..Report::default()
+        };
+
+        for (path, input) in &sources {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            for indexed in extract_links(input) {
+                let span = indexed.offset..indexed.offset + indexed.len;
+                let (src, dest) = match &indexed.link {
+                    Link::Text2Dest(_, dest, _) => (None, Some(dest)),
+                    Link::Image(_, src) => (Some(src), None),
+                    Link::Image2Dest(_, _, src, _, dest, _) => (Some(src), Some(dest)),
+                    _ => continue,
+                };
+                report.links += dest.is_some() as usize;
+                report.images += src.is_some() as usize;
+                for (dest, is_image) in [(src, true), (dest, false)] {
+                    if let Some(dest) = dest {
+                        report.check(&sources, path, dir, dest, is_image, &span);
+                    }
+                }
+            }
+
+            report.broken_references.extend(
+                broken_references(input)
+                    .into_iter()
+                    .map(|(_, label, span)| (path.clone(), label.to_string(), span)),
+            );
+            report.unused_definitions.extend(
+                unused_definitions(input)
+                    .into_iter()
+                    .map(|(label, span)| (path.clone(), label.to_string(), span)),
+            );
+        }
+        report
+    }
+
+    /// Same as `Report::new()`, but reads the content of the files `paths`.
+    pub fn read<P: AsRef<Path>>(paths: &[P]) -> io::Result<Self> {
+        let sources = paths
+            .iter()
+            .map(|path| Ok((path.as_ref(), fs::read_to_string(path)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::new(&sources))
+    }
+
+    /// Checks the destination `dest` found at `span` in the file `path`.
+    fn check(
+        &mut self,
+        sources: &BTreeMap<PathBuf, &str>,
+        path: &Path,
+        dir: &Path,
+        dest: &str,
+        is_image: bool,
+        span: &Span,
+    ) {
+        if scheme(dest).is_some() {
+            self.external += 1;
+            return;
+        }
+        let (target, fragment) = match dest.split_once('#') {
+            Some((target, fragment)) => (target, Some(fragment)),
+            None => (dest, None),
+        };
+        let target = match local_path(dir, target) {
+            Some(target) => normalize_path(&target),
+            // A link to a fragment of the same file.
+            None => path.to_path_buf(),
+        };
+
+        let content = match sources.get(&target) {
+            Some(content) => content,
+            None => {
+                if !target.exists() {
+                    self.broken_links
+                        .push((path.to_path_buf(), dest.to_string(), span.clone()));
+                }
+                return;
+            }
+        };
+        if !is_image && target != path {
+            self.graph.insert((path.to_path_buf(), target.clone()));
+        }
+
+        if let Some(fragment) = fragment.filter(|f| !f.is_empty()) {
+            let fragment = percent_decode_str(fragment).decode_utf8_lossy();
+            let found = if is_html_file(&target) {
+                html_anchors(content).contains(&fragment)
+            } else if is_markup_file(&target) {
+                anchors(content, SlugStyle::GitHub)
+                    .iter()
+                    .any(|a| *a == fragment)
+            } else {
+                true
+            };
+            if !found {
+                self.dangling_fragments
+                    .push((path.to_path_buf(), dest.to_string(), span.clone()));
+            }
+        }
+    }
+}
+
+/// Removes `.` and resolves `..` path components lexically, without
+/// accessing the file system.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(res.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                res.pop();
+            }
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Errors occurring while reading or writing an index.
+#[cfg(feature = "serde")]
+#[derive(Debug, Error)]
+pub enum IndexError {
+    /// Can not read or write the index file.
+    #[error("can not access index file: {0}")]
+    Io(#[from] io::Error),
+    /// The index file is not well formed.
+    #[error("malformed index file: {0}")]
+    Format(#[from] serde_json::Error),
+    /// The index file was written in an unsupported format version.
+    #[error("unsupported index version {0}, expected {INDEX_VERSION}")]
+    Version(u32),
+}
+
+/// First line of the index file.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndexHeader {
+    version: u32,
+}
+
+/// Any other line of the index file.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndexLine {
+    path: PathBuf,
+    #[serde(flatten)]
+    entry: IndexEntry,
+}
+
+/// Writes `index` to `writer` in JSON Lines format: the first line holds the
+/// format version, every following line the links of one file.
+#[cfg(feature = "serde")]
+pub fn save_index<W: Write>(index: &Index, mut writer: W) -> Result<(), IndexError> {
+    serde_json::to_writer(
+        &mut writer,
+        &IndexHeader {
+            version: INDEX_VERSION,
+        },
+    )?;
+    writer.write_all(b"\n")?;
+    for (path, entry) in &index.files {
+        serde_json::to_writer(
+            &mut writer,
+            &IndexLine {
+                path: path.clone(),
+                entry: entry.clone(),
+            },
+        )?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Reads an index written by `save_index()`.
+///
+/// ```
+/// use parse_hyperlinks::analysis::{load_index, save_index, Index, IndexEntry};
+///
+/// let mut index = Index::default();
+/// index.files.insert("a.md".into(), IndexEntry::new("[a](b)"));
+///
+/// let mut buf = Vec::new();
+/// save_index(&index, &mut buf).unwrap();
+/// assert_eq!(load_index(&buf[..]).unwrap(), index);
+/// ```
+#[cfg(feature = "serde")]
+pub fn load_index<R: BufRead>(reader: R) -> Result<Index, IndexError> {
+    let mut lines = reader.lines();
+    let header: IndexHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => return Ok(Index::default()),
+    };
+    if header.version != INDEX_VERSION {
+        return Err(IndexError::Version(header.version));
+    }
+
+    let mut index = Index::default();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let IndexLine { path, entry } = serde_json::from_str(&line)?;
+        index.files.insert(path, entry);
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_link_stats() {
+        let options = ParserOptions::default();
+        let i = "[![a](a.png)](HTTPS://x) `b <b_>`_\n\n.. _b: c_\n";
+        let stats = LinkStats::new(i, &options);
+        assert_eq!(
+            stats.to_string(),
+            "Inputs: 1\n\
+             Links per kind:\n  definition: 1\n  image: 1\n  inline: 1\n  reference: 1\n\
+             Destinations per scheme:\n  https: 1\n  relative: 1\n\
+             Links per markup language:\n  Markdown: 1\n  Rst: 2\n"
+        );
+        let mut sum = LinkStats::default();
+        sum.merge(&stats);
+        sum.merge(&stats);
+        assert_eq!(sum.inputs, 2);
+        assert_eq!(sum.kinds["inline"], 2);
+        assert_eq!(sum.langs[&MarkupLang::Rst], 4);
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let i = "abc[a][l]abc![b](c)\n[l]: d\n";
+        let links = extract_links(i);
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links[0],
+            IndexedLink {
+                offset: 3,
+                len: 6,
+                link: Link::Text2Dest(Cow::from("a"), Cow::from("d"), Cow::from("")),
+            }
+        );
+        assert_eq!(
+            &i[links[1].offset..links[1].offset + links[1].len],
+            "![b](c)"
+        );
+    }
+
+    #[test]
+    fn test_link_id() {
+        let path = Path::new("doc/a.md");
+        let links = extract_links("[a](b) ![c][d]\n[d]: e\n");
+        assert_eq!(links[0].id(path), link_id(path, &(0..6), "b"));
+        assert_eq!(links[1].id(path), link_id(path, &(7..14), "e"));
+        assert_ne!(links[0].id(path), links[0].id(Path::new("doc/b.md")));
+        // Fixed value: must not change across releases.
+        assert_eq!(link_id(Path::new(""), &(0..0), ""), 0x4dfa4cffd1f7979f);
+    }
+
+    #[test]
+    fn test_update_index() {
+        let dir = std::env::temp_dir().join(format!("parse-hyperlinks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.md");
+        let b = dir.join("b.md");
+        fs::write(&a, "[a](b)").unwrap();
+        fs::write(&b, "no links").unwrap();
+
+        let mut index = Index::default();
+        let changed = update_index(&mut index, &[&a, &b]).unwrap();
+        assert_eq!(changed, vec![a.clone(), b.clone()]);
+        assert_eq!(index.files[&a].links.len(), 1);
+        assert!(index.files[&b].links.is_empty());
+
+        let changed = update_index(&mut index, &[&a, &b]).unwrap();
+        assert!(changed.is_empty());
+
+        fs::write(&b, "[c](d)").unwrap();
+        fs::remove_file(&a).unwrap();
+        let changed = update_index(&mut index, &[&a, &b]).unwrap();
+        assert_eq!(changed, vec![a.clone(), b.clone()]);
+        assert!(!index.files.contains_key(&a));
+        assert_eq!(index.files[&b].links.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_images() {
+        let dir = std::env::temp_dir().join(format!("parse-hyperlinks-img-{}", std::process::id()));
+        fs::create_dir_all(dir.join("doc/img")).unwrap();
+        fs::write(dir.join("logo.png"), "logo").unwrap();
+        fs::write(dir.join("doc/img/logo%20copy.png"), "x").unwrap();
+        fs::write(dir.join("doc/img/logo copy.png"), "logo").unwrap();
+        fs::write(dir.join("doc/img/other.png"), "other").unwrap();
+
+        let mut index = Index::default();
+        index.files.insert(
+            dir.join("doc/a.md"),
+            IndexEntry::new(
+                "![a](../logo.png) ![b](img/logo%20copy.png) ![c](./img/other.png#x)
+<img src=\"https://getreu.net/logo.png\"/> ![d](img/missing.png)",
+            ),
+        );
+        index.files.insert(
+            dir.join("b.md"),
+            IndexEntry::new("![a](logo.png) ![b](doc/img/../img/other.png) [c](logo.png)"),
+        );
+
+        let res = duplicate_images(&index).unwrap();
+        assert_eq!(
+            res,
+            vec![vec![
+                dir.join("doc/img/logo copy.png"),
+                dir.join("logo.png")
+            ]]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("a/./b/../c")),
+            PathBuf::from("a/c")
+        );
+        assert_eq!(normalize_path(Path::new("../a/..")), PathBuf::from(".."));
+        assert_eq!(
+            normalize_path(Path::new("/a/b/../../c")),
+            PathBuf::from("/c")
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let dir = std::env::temp_dir().join(format!("parse-hyperlinks-rep-{}", std::process::id()));
+        fs::create_dir_all(dir.join("img")).unwrap();
+        fs::write(dir.join("img/logo.png"), "logo").unwrap();
+
+        let a = "# Local\n\n[b](./doc/b.html#sec) [b2](doc/b.html#title) [c](c.md) \
+                 ![logo](img/logo.png) ![x](img/x.png) [y][Y] [z][z] \
+                 [w](https://getreu.net) [self](#local) [self2](#missing)\n\
+                 [y]: doc/b.html\n[unused]: dest\n";
+        let b = r#"<h1 id="title">B</h1><a href="../a.md">a</a>"#;
+        let report = Report::new(&[(dir.join("a.md"), a), (dir.join("doc/b.html"), b)]);
+
+        assert_eq!(report.files, vec![dir.join("a.md"), dir.join("doc/b.html")]);
+        assert_eq!(report.links, 8);
+        assert_eq!(report.images, 2);
+        assert_eq!(report.external, 1);
+        let dests = |findings: &[(PathBuf, String, Span)]| {
+            findings.iter().map(|f| f.1.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(dests(&report.broken_links), vec!["c.md", "img/x.png"]);
+        assert_eq!(
+            dests(&report.dangling_fragments),
+            vec!["./doc/b.html#sec", "#missing"]
+        );
+        assert_eq!(dests(&report.broken_references), vec!["z"]);
+        assert_eq!(dests(&report.unused_definitions), vec!["unused"]);
+        assert_eq!(&a[report.unused_definitions[0].2.clone()], "[unused]: dest");
+        assert_eq!(
+            report.graph.into_iter().collect::<Vec<_>>(),
+            vec![
+                (dir.join("a.md"), dir.join("doc/b.html")),
+                (dir.join("doc/b.html"), dir.join("a.md")),
+            ]
+        );
+
+        fs::write(dir.join("a.md"), a).unwrap();
+        let report = Report::read(&[dir.join("a.md")]).unwrap();
+        assert_eq!(
+            dests(&report.broken_links),
+            vec![
+                "./doc/b.html#sec",
+                "doc/b.html#title",
+                "c.md",
+                "img/x.png",
+                "doc/b.html"
+            ]
+        );
+        assert!(Report::read(&[dir.join("missing.md")]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff_indexes() {
+        let mut old = Index::default();
+        old.files
+            .insert("same.md".into(), IndexEntry::new("[a](b) ![c](d)"));
+        old.files
+            .insert("moved.md".into(), IndexEntry::new("[a](b) ![c](d)"));
+        old.files
+            .insert("gone.md".into(), IndexEntry::new("[a](b) ![c](d)"));
+        old.files
+            .insert("edited.md".into(), IndexEntry::new("[a](b) ![c](d) [e](f)"));
+        let mut new = Index::default();
+        new.files
+            .insert("same.md".into(), IndexEntry::new("[a](b) ![c](d)"));
+        new.files
+            .insert("moved.md".into(), IndexEntry::new("![c](d)\n\n[a](b)"));
+        new.files.insert("new.md".into(), IndexEntry::new("[a](b)"));
+        new.files
+            .insert("edited.md".into(), IndexEntry::new("[a](b) ![c](x) [g](f)"));
+
+        let diff = diff_indexes(&old, &new);
+        assert_eq!(
+            diff.keys().collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("edited.md"),
+                &PathBuf::from("gone.md"),
+                &PathBuf::from("new.md")
+            ]
+        );
+        assert_eq!(diff[&PathBuf::from("gone.md")].removed.len(), 2);
+        assert_eq!(diff[&PathBuf::from("new.md")].added.len(), 1);
+
+        let edited = &diff[&PathBuf::from("edited.md")];
+        assert_eq!(edited.changed.len(), 1);
+        assert_eq!(
+            edited.changed[0].1.link,
+            Link::Image(Cow::from("c"), Cow::from("x"))
+        );
+        assert_eq!(edited.removed.len(), 1);
+        assert_eq!(
+            edited.removed[0].link,
+            Link::Text2Dest(Cow::from("e"), Cow::from("f"), Cow::from(""))
+        );
+        assert_eq!(edited.added.len(), 1);
+        assert_eq!(
+            edited.added[0].link,
+            Link::Text2Dest(Cow::from("g"), Cow::from("f"), Cow::from(""))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_index() {
+        assert_eq!(load_index(&b""[..]).unwrap(), Index::default());
+        assert!(matches!(
+            load_index(&b"{\"version\":999}\n"[..]),
+            Err(IndexError::Version(999))
+        ));
+        assert!(matches!(
+            load_index(&b"{\"version\":1}\n{\"path\":1}\n"[..]),
+            Err(IndexError::Format(_))
+        ));
+    }
+}
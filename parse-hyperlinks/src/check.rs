@@ -0,0 +1,584 @@
+//! Module verifying link destinations. This module is only available with the
+//! `check` feature enabled.
+//!
+//! `LinkChecker` verifies all hyperlinks and images of an input text: the
+//! HTTP status of URLs, the existence of local files and the anchors of
+//! `#fragment`s. It returns a `CheckReport`, e.g. for "broken link" CI
+//! checks. For external `dest#fragment` links, `FragmentChecker` fetches the
+//! target page and verifies, that an element with the `id` (or `<a name=…>`)
+//! `fragment` exists. The fragments of local Markdown, reStructuredText and
+//! Asciidoc files, and of the checked document itself, are verified against
+//! the anchors of their headings (see `headings::anchors()`).
+
+use crate::headings::anchors;
+use crate::headings::SlugStyle;
+use crate::iterator::MarkupLink;
+use crate::iterator::Span;
+use crate::parser::html::html_anchors;
+use crate::parser::parse::ParserOptions;
+use crate::parser::Link;
+use crate::url::is_html_file;
+use crate::url::is_http;
+use crate::url::is_markup_file;
+use crate::url::local_path;
+use crate::url::scheme;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors occurring while verifying a link destination.
+#[derive(Debug, Error)]
+pub enum CheckError {
+    /// The target page could not be fetched.
+    #[error("can not fetch `{url}`: {source}")]
+    Fetch {
+        url: String,
+        source: Box<ureq::Error>,
+    },
+    /// The target page could not be read.
+    #[error("can not read `{url}`: {source}")]
+    Read { url: String, source: std::io::Error },
+}
+
+/// Fetches remote HTML pages and checks if they contain the anchors the
+/// `dest#fragment` links refer to. The anchors of every fetched page are
+/// cached, so each page is downloaded only once.
+///
+/// ```no_run
+/// use parse_hyperlinks::check::FragmentChecker;
+///
+/// let mut checker = FragmentChecker::new();
+/// let res = checker.check("https://getreu.net/#about").unwrap();
+/// assert_eq!(res, Some(true));
+/// ```
+#[derive(Debug)]
+pub struct FragmentChecker {
+    /// HTTP client used to fetch the target pages.
+    agent: ureq::Agent,
+    /// Anchors found in already fetched pages. The key is the page's URL
+    /// without fragment.
+    anchors: HashMap<String, HashSet<String>>,
+}
+
+impl FragmentChecker {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::with_agent(ureq::Agent::new())
+    }
+
+    /// Constructor using a custom configured HTTP client, e.g. with timeouts
+    /// or a proxy.
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        Self {
+            agent,
+            anchors: HashMap::new(),
+        }
+    }
+
+    /// Checks the fragment of the external link destination `dest`.
+    ///
+    /// Returns `Ok(None)`, when `dest` is not an `http:` or `https:` URL or
+    /// when it has no fragment: there is nothing to check. Otherwise, the
+    /// target page is fetched and `Ok(Some(true))` is returned if an element
+    /// with the `id` (or `<a name=…>`) `fragment` exists, `Ok(Some(false))`
+    /// if not.
+    pub fn check(&mut self, dest: &str) -> Result<Option<bool>, CheckError> {
+        let (url, fragment) = match split_fragment(dest) {
+            Some(split) => split,
+            None => return Ok(None),
+        };
+
+        if !self.anchors.contains_key(url) {
+            let body = self
+                .agent
+                .get(url)
+                .call()
+                .map_err(|e| CheckError::Fetch {
+                    url: url.to_string(),
+                    source: Box::new(e),
+                })?
+                .into_string()
+                .map_err(|e| CheckError::Read {
+                    url: url.to_string(),
+                    source: e,
+                })?;
+            let anchors = html_anchors(&body)
+                .into_iter()
+                .map(|a| a.to_string())
+                .collect();
+            self.anchors.insert(url.to_string(), anchors);
+        }
+
+        let fragment = percent_decode_str(fragment).decode_utf8_lossy();
+        Ok(Some(self.anchors[url].contains(&*fragment)))
+    }
+}
+
+impl Default for FragmentChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The result of checking a link destination, see `LinkChecker::check()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The destination exists. If it has a fragment, the anchor exists too.
+    Ok,
+    /// The server answered with this HTTP error status code.
+    Http(u16),
+    /// The server could not be reached or the target could not be read.
+    Unreachable(String),
+    /// The local file does not exist.
+    NotFound,
+    /// The target exists, but has no anchor matching the fragment.
+    MissingAnchor,
+    /// The destination was not checked, e.g. a `mailto:` link.
+    Skipped,
+}
+
+impl LinkStatus {
+    /// True if the destination is known to be broken.
+    pub fn is_broken(&self) -> bool {
+        !matches!(self, LinkStatus::Ok | LinkStatus::Skipped)
+    }
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkStatus::Ok => f.write_str("ok"),
+            LinkStatus::Http(code) => write!(f, "HTTP status {code}"),
+            LinkStatus::Unreachable(e) => write!(f, "unreachable: {e}"),
+            LinkStatus::NotFound => f.write_str("file not found"),
+            LinkStatus::MissingAnchor => f.write_str("missing anchor"),
+            LinkStatus::Skipped => f.write_str("skipped"),
+        }
+    }
+}
+
+impl From<&ureq::Error> for LinkStatus {
+    fn from(e: &ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(code, _) => LinkStatus::Http(*code),
+            e => LinkStatus::Unreachable(e.to_string()),
+        }
+    }
+}
+
+impl From<CheckError> for LinkStatus {
+    fn from(e: CheckError) -> Self {
+        match e {
+            CheckError::Fetch { source, .. } => LinkStatus::from(&*source),
+            e => LinkStatus::Unreachable(e.to_string()),
+        }
+    }
+}
+
+/// A checked hyperlink or image source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedLink {
+    /// The link's byte range in the input text.
+    pub span: Span,
+    /// The checked link destination or image source.
+    pub dest: String,
+    /// The result of the check.
+    pub status: LinkStatus,
+}
+
+/// The result of `LinkChecker::check_links()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    /// All checked destinations in order of appearance. A `Link::Image2Dest`
+    /// yields two entries: the image source and the link destination.
+    pub links: Vec<CheckedLink>,
+}
+
+impl CheckReport {
+    /// Iterator over the broken destinations.
+    pub fn broken(&self) -> impl Iterator<Item = &CheckedLink> {
+        self.links.iter().filter(|l| l.status.is_broken())
+    }
+
+    /// True if no destination is broken.
+    pub fn is_ok(&self) -> bool {
+        self.broken().next().is_none()
+    }
+}
+
+/// Verifies link destinations: `http:` and `https:` URLs are requested and
+/// their HTTP status is checked, local destinations are resolved relative
+/// to a base directory and must exist. Fragments are checked against the
+/// anchors of HTML targets and against the heading anchors of Markdown,
+/// reStructuredText and Asciidoc targets. The result of every requested URL
+/// is cached.
+///
+/// Destinations with another URL scheme (e.g. `mailto:`) are not checked.
+///
+/// ```no_run
+/// use parse_hyperlinks::check::LinkChecker;
+///
+/// let mut checker = LinkChecker::new("doc");
+/// let report = checker.check_links("[a](a.md) [b](https://getreu.net/#about)");
+/// for link in report.broken() {
+///     println!("{}: {:?}", link.dest, link.status);
+/// }
+/// assert!(report.is_ok());
+/// ```
+#[derive(Debug)]
+pub struct LinkChecker {
+    /// Directory local destinations are resolved against.
+    base_dir: PathBuf,
+    /// HTTP client used to request URLs.
+    agent: ureq::Agent,
+    /// Checks the fragments of URLs.
+    fragments: FragmentChecker,
+    /// Status of already requested URLs without fragment.
+    urls: HashMap<String, LinkStatus>,
+    /// The style of the heading anchors of markup documents.
+    slug_style: SlugStyle,
+    /// If false, URLs are not requested.
+    online: bool,
+    /// Options of the link parser.
+    parser_options: ParserOptions,
+}
+
+impl LinkChecker {
+    /// Constructor. Local destinations are resolved against `base_dir`. The
+    /// empty path denotes the current working directory.
+    pub fn new<P: Into<PathBuf>>(base_dir: P) -> Self {
+        Self::with_agent(base_dir, ureq::Agent::new())
+    }
+
+    /// Constructor using a custom configured HTTP client, e.g. with timeouts
+    /// or a proxy.
+    pub fn with_agent<P: Into<PathBuf>>(base_dir: P, agent: ureq::Agent) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            fragments: FragmentChecker::with_agent(agent.clone()),
+            agent,
+            urls: HashMap::new(),
+            slug_style: SlugStyle::default(),
+            online: true,
+            parser_options: ParserOptions::default(),
+        }
+    }
+
+    /// If `online` is false, `http:` and `https:` URLs are not requested
+    /// and their status is `LinkStatus::Skipped`. Only local destinations
+    /// are checked then. The default is `true`.
+    ///
+    /// ```
+    /// use parse_hyperlinks::check::{LinkChecker, LinkStatus};
+    ///
+    /// let mut checker = LinkChecker::new("").online(false);
+    /// assert_eq!(checker.check("https://getreu.net"), LinkStatus::Skipped);
+    /// ```
+    pub fn online(mut self, online: bool) -> Self {
+        self.online = online;
+        self
+    }
+
+    /// Sets the style of the heading anchors `#fragment`s of markup
+    /// documents are checked against. The default is `SlugStyle::GitHub`.
+    ///
+    /// ```
+    /// use parse_hyperlinks::check::{LinkChecker, LinkStatus};
+    /// use parse_hyperlinks::headings::SlugStyle;
+    ///
+    /// let i = "# Hello,  World\n\n[a](#hello-world)";
+    ///
+    /// let mut checker = LinkChecker::new("").slug_style(SlugStyle::GitLab);
+    /// assert!(checker.check_links(i).is_ok());
+    /// let mut checker = LinkChecker::new("");
+    /// assert_eq!(checker.check_links(i).links[0].status, LinkStatus::MissingAnchor);
+    /// ```
+    pub fn slug_style(mut self, slug_style: SlugStyle) -> Self {
+        self.slug_style = slug_style;
+        self
+    }
+
+    /// Sets the options of the link parser, e.g. to restrict it to some
+    /// markup languages. `check_links()` and `check_links_at()` check only
+    /// the links it recognizes.
+    ///
+    /// ```
+    /// use parse_hyperlinks::check::LinkChecker;
+    /// use parse_hyperlinks::parser::parse::ParserOptions;
+    /// use parse_hyperlinks::parser::{MarkupLang, MarkupLangs};
+    ///
+    /// let options = ParserOptions {
+    ///     markup_langs: MarkupLangs::empty().with(MarkupLang::Html),
+    ///     ..ParserOptions::default()
+    /// };
+    /// let mut checker = LinkChecker::new("").online(false).parser_options(options);
+    /// let report = checker.check_links("[a](missing.md) <a href=\"Cargo.toml\">b</a>");
+    /// assert_eq!(report.links.len(), 1);
+    /// assert!(report.is_ok());
+    /// ```
+    pub fn parser_options(mut self, parser_options: ParserOptions) -> Self {
+        self.parser_options = parser_options;
+        self
+    }
+
+    /// Checks the destinations of all hyperlinks and images of `input`.
+    /// Link references are resolved first, unresolvable references are
+    /// not reported (see `iterator::broken_references()`). Links to a
+    /// `#fragment` of `input` itself must refer to one of its anchors.
+    pub fn check_links(&mut self, input: &str) -> CheckReport {
+        let mut report = CheckReport::default();
+        let mut input_anchors = None;
+        for ((_, consumed, remaining), link) in
+            MarkupLink::new(input, false).parser_options(self.parser_options)
+        {
+            let offset = input.len() - consumed.len() - remaining.len();
+            let span = offset..offset + consumed.len();
+            let dests = match &link {
+                Link::Text2Dest(_, dest, _) => [None, Some(dest)],
+                Link::Image(_, src) => [Some(src), None],
+                Link::Image2Dest(_, _, src, _, dest, _) => [Some(src), Some(dest)],
+                _ => continue,
+            };
+            for dest in dests.into_iter().flatten() {
+                let status = match dest.strip_prefix('#').filter(|f| !f.is_empty()) {
+                    Some(fragment) => {
+                        let anchors =
+                            input_anchors.get_or_insert_with(|| anchors(input, self.slug_style));
+                        let fragment = percent_decode_str(fragment).decode_utf8_lossy();
+                        if anchors.iter().any(|a| *a == fragment) {
+                            LinkStatus::Ok
+                        } else {
+                            LinkStatus::MissingAnchor
+                        }
+                    }
+                    None => self.check(dest),
+                };
+                report.links.push(CheckedLink {
+                    span: span.clone(),
+                    dest: dest.to_string(),
+                    status,
+                });
+            }
+        }
+        report
+    }
+
+    /// Same as `check_links()` for `input` read from the file `path`: local
+    /// destinations are resolved against the directory containing `path`
+    /// instead of the base directory.
+    ///
+    /// ```
+    /// use parse_hyperlinks::check::{LinkChecker, LinkStatus};
+    ///
+    /// let mut checker = LinkChecker::new("").online(false);
+    /// let report = checker.check_links_at("[a](lib.rs) [b](src/lib.rs)", "src/main.rs");
+    /// assert_eq!(report.links[0].status, LinkStatus::Ok);
+    /// assert_eq!(report.links[1].status, LinkStatus::NotFound);
+    /// ```
+    pub fn check_links_at<P: AsRef<Path>>(&mut self, input: &str, path: P) -> CheckReport {
+        let dir = path
+            .as_ref()
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let base_dir = std::mem::replace(&mut self.base_dir, dir);
+        let report = self.check_links(input);
+        self.base_dir = base_dir;
+        report
+    }
+
+    /// Checks the link destination `dest`. Fragments of the checked
+    /// document itself (`#fragment`) are skipped, `check_links()` verifies
+    /// them.
+    ///
+    /// ```
+    /// use parse_hyperlinks::check::{LinkChecker, LinkStatus};
+    ///
+    /// let mut checker = LinkChecker::new("");
+    /// assert_eq!(checker.check("Cargo.toml"), LinkStatus::Ok);
+    /// assert_eq!(checker.check("missing.md"), LinkStatus::NotFound);
+    /// assert_eq!(checker.check("mailto:a@b.c"), LinkStatus::Skipped);
+    /// ```
+    pub fn check(&mut self, dest: &str) -> LinkStatus {
+        if is_http(dest) && !self.online {
+            return LinkStatus::Skipped;
+        }
+        if is_http(dest) {
+            if split_fragment(dest).is_some() {
+                return match self.fragments.check(dest) {
+                    Ok(Some(false)) => LinkStatus::MissingAnchor,
+                    Ok(_) => LinkStatus::Ok,
+                    Err(e) => LinkStatus::from(e),
+                };
+            }
+            let url = dest.split('#').next().unwrap_or_default();
+            if let Some(status) = self.urls.get(url) {
+                return status.clone();
+            }
+            // Some servers do not implement `HEAD`.
+            let status = match self.agent.head(url).call() {
+                Err(ureq::Error::Status(405, _)) => self.agent.get(url).call(),
+                res => res,
+            }
+            .map_or_else(|e| LinkStatus::from(&e), |_| LinkStatus::Ok);
+            self.urls.insert(url.to_string(), status.clone());
+            return status;
+        }
+
+        if scheme(dest).is_some() || dest.starts_with('#') {
+            return LinkStatus::Skipped;
+        }
+        let path = match local_path(&self.base_dir, dest) {
+            Some(path) => path,
+            None => return LinkStatus::Skipped,
+        };
+        if !path.exists() {
+            return LinkStatus::NotFound;
+        }
+        match dest.split_once('#') {
+            Some((_, fragment))
+                if !fragment.is_empty() && (is_html_file(&path) || is_markup_file(&path)) =>
+            {
+                let content = match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => return LinkStatus::Unreachable(e.to_string()),
+                };
+                let fragment = percent_decode_str(fragment).decode_utf8_lossy();
+                let found = if is_html_file(&path) {
+                    html_anchors(&content).contains(&fragment)
+                } else {
+                    anchors(&content, self.slug_style)
+                        .iter()
+                        .any(|a| *a == fragment)
+                };
+                if found {
+                    LinkStatus::Ok
+                } else {
+                    LinkStatus::MissingAnchor
+                }
+            }
+            _ => LinkStatus::Ok,
+        }
+    }
+}
+
+/// Splits an external link destination into `(url, fragment)`.
+/// Returns `None` if `dest` is not an `http:` or `https:` URL or if the
+/// fragment is empty.
+fn split_fragment(dest: &str) -> Option<(&str, &str)> {
+    if !is_http(dest) {
+        return None;
+    }
+    match dest.split_once('#') {
+        Some((url, fragment)) if !fragment.is_empty() => Some((url, fragment)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_fragment() {
+        assert_eq!(
+            split_fragment("https://getreu.net/a.html#sec"),
+            Some(("https://getreu.net/a.html", "sec"))
+        );
+        assert_eq!(
+            split_fragment("HTTP://getreu.net/#sec"),
+            Some(("HTTP://getreu.net/", "sec"))
+        );
+        assert_eq!(split_fragment("https://getreu.net/a.html#"), None);
+        assert_eq!(split_fragment("https://getreu.net/a.html"), None);
+        assert_eq!(split_fragment("doc/a.html#sec"), None);
+        assert_eq!(split_fragment("mailto:a@b.c#x"), None);
+    }
+
+    #[test]
+    fn test_check_without_fragment() {
+        let mut checker = FragmentChecker::new();
+        assert_eq!(checker.check("https://getreu.net/").unwrap(), None);
+        assert_eq!(checker.check("#local").unwrap(), None);
+    }
+
+    #[test]
+    fn test_link_checker() {
+        let dir = std::env::temp_dir().join(format!("parse-hyperlinks-chk-{}", std::process::id()));
+        fs::create_dir_all(dir.join("doc")).unwrap();
+        fs::write(dir.join("doc/a b.html"), r#"<h1 id="title">A</h1>"#).unwrap();
+        fs::write(dir.join("logo.png"), "").unwrap();
+        fs::write(dir.join("doc/c.md"), "# Usage\n").unwrap();
+
+        let mut checker = LinkChecker::new(&dir);
+        assert_eq!(checker.check("doc/a%20b.html"), LinkStatus::Ok);
+        assert_eq!(checker.check("doc/a%20b.html#title"), LinkStatus::Ok);
+        assert_eq!(
+            checker.check("./doc/a%20b.html#x"),
+            LinkStatus::MissingAnchor
+        );
+        assert_eq!(checker.check("doc/?q"), LinkStatus::Ok);
+        assert_eq!(checker.check("doc/b.html"), LinkStatus::NotFound);
+        assert_eq!(checker.check("doc/c.md#usage"), LinkStatus::Ok);
+        assert_eq!(checker.check("doc/c.md#x"), LinkStatus::MissingAnchor);
+        assert_eq!(checker.check("#local"), LinkStatus::Skipped);
+        assert_eq!(checker.check("tel:123"), LinkStatus::Skipped);
+
+        let report = checker.check_links("Intro\n=====\n[a](#intro) [b](#x)");
+        assert_eq!(report.links[0].status, LinkStatus::Ok);
+        assert_eq!(report.links[1].status, LinkStatus::MissingAnchor);
+
+        let i = "[a](doc/a%20b.html#title) [![logo](logo.png)](missing.md) [m](mailto:a@b.c)";
+        let report = checker.check_links(i);
+        assert_eq!(
+            report
+                .links
+                .iter()
+                .map(|l| (l.dest.as_str(), l.status.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("doc/a%20b.html#title", LinkStatus::Ok),
+                ("logo.png", LinkStatus::Ok),
+                ("missing.md", LinkStatus::NotFound),
+                ("mailto:a@b.c", LinkStatus::Skipped),
+            ]
+        );
+        assert!(!report.is_ok());
+        let broken = report.broken().collect::<Vec<_>>();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(
+            &i[broken[0].span.clone()],
+            "[![logo](logo.png)](missing.md)"
+        );
+
+        // Destinations are relative to the checked file.
+        let report = checker.check_links_at("[a](c.md#usage) [b](logo.png)", dir.join("doc/d.md"));
+        assert_eq!(report.links[0].status, LinkStatus::Ok);
+        assert_eq!(report.links[1].status, LinkStatus::NotFound);
+        assert_eq!(checker.check("logo.png"), LinkStatus::Ok);
+
+        let mut checker = LinkChecker::new(&dir).online(false);
+        assert_eq!(checker.check("https://localhost:1/"), LinkStatus::Skipped);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_link_status() {
+        assert!(LinkStatus::Http(404).is_broken());
+        assert!(LinkStatus::MissingAnchor.is_broken());
+        assert!(!LinkStatus::Skipped.is_broken());
+        assert!(!LinkStatus::Ok.is_broken());
+        assert_eq!(LinkStatus::Http(404).to_string(), "HTTP status 404");
+        let e = CheckError::Read {
+            url: "http://a.b".to_string(),
+            source: std::io::Error::from(std::io::ErrorKind::InvalidData),
+        };
+        assert!(matches!(LinkStatus::from(e), LinkStatus::Unreachable(_)));
+    }
+}
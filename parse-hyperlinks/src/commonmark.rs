@@ -0,0 +1,349 @@
+//! Module measuring the CommonMark compliance of the Markdown link parsers.
+//!
+//! The [CommonMark Spec](https://spec.commonmark.org/) comes with a
+//! machine-readable list of examples (`spec.json`), each with its Markdown
+//! source and the expected HTML output. `run_spec()` feeds the examples of
+//! the link related sections (`LINK_SECTIONS`) to `MarkupLink` and compares
+//! the extracted links with the `<a>` and `<img>` elements of the expected
+//! HTML. The `SpecReport` lists the failing examples and the pass rate, so
+//! regressions in spec coverage become visible. The integration test
+//! `tests/commonmark_spec.rs` runs the vendored `tests/data/spec.json` and
+//! fails, when the pass rate drops.
+//!
+//! Only link destinations, link titles and image sources are compared. Link
+//! text is rendered HTML in the spec, but raw Markdown here, so it is
+//! ignored. Destinations are compared percent-decoded, because the spec's
+//! renderer percent-encodes them.
+
+use crate::iterator::MarkupLink;
+use crate::parser::html::attribute_list;
+use crate::parser::html::tag_content;
+use crate::parser::Link;
+use percent_encoding::percent_decode_str;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The sections of the CommonMark Spec, whose examples `run_spec()` checks.
+pub const LINK_SECTIONS: [&str; 4] = ["Links", "Images", "Link reference definitions", "Autolinks"];
+
+/// One example of the CommonMark Spec. The field names match the ones in
+/// `spec.json`, other fields are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct SpecExample {
+    /// The Markdown source.
+    pub markdown: String,
+    /// The expected HTML output.
+    pub html: String,
+    /// The example number.
+    pub example: u32,
+    /// The title of the spec section the example belongs to.
+    pub section: String,
+}
+
+/// Reads the examples from the content of the CommonMark Spec's `spec.json`.
+///
+/// ```
+/// use parse_hyperlinks::commonmark::spec_examples;
+///
+/// let json = r#"[{"markdown": "[link](/uri \"title\")\n",
+///     "html": "<p><a href=\"/uri\" title=\"title\">link</a></p>\n",
+///     "example": 482, "start_line": 7489, "end_line": 7493,
+///     "section": "Links"}]"#;
+/// let examples = spec_examples(json).unwrap();
+/// assert_eq!(examples[0].example, 482);
+/// ```
+#[cfg(feature = "serde")]
+pub fn spec_examples(json: &str) -> Result<Vec<SpecExample>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// A link as far as `run_spec()` compares it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpecLink {
+    /// A hyperlink with destination and title.
+    Anchor(String, String),
+    /// An image with its source.
+    Image(String),
+}
+
+/// The outcome of one example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecResult {
+    /// The example number.
+    pub example: u32,
+    /// The title of the spec section the example belongs to.
+    pub section: String,
+    /// The links of the expected HTML output.
+    pub expected: Vec<SpecLink>,
+    /// The links found in the Markdown source.
+    pub actual: Vec<SpecLink>,
+}
+
+impl SpecResult {
+    /// True if the links found are the expected ones.
+    pub fn passed(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// The outcome of `run_spec()`. Its `Display` implementation prints the
+/// pass rate per section and the numbers of the failing examples.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecReport {
+    /// The outcome of each checked example in input order.
+    pub results: Vec<SpecResult>,
+}
+
+impl SpecReport {
+    /// Number of passing examples.
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+
+    /// The failing examples.
+    pub fn failed(&self) -> impl Iterator<Item = &SpecResult> {
+        self.results.iter().filter(|r| !r.passed())
+    }
+
+    /// The share of passing examples between `0.0` and `1.0`. An empty
+    /// report has a pass rate of `1.0`.
+    pub fn pass_rate(&self) -> f64 {
+        if self.results.is_empty() {
+            return 1.0;
+        }
+        self.passed() as f64 / self.results.len() as f64
+    }
+}
+
+impl fmt::Display for SpecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Key: section, value: (passed, total, failing examples).
+        let mut sections: BTreeMap<&str, (usize, usize, Vec<u32>)> = BTreeMap::new();
+        for r in &self.results {
+            let s = sections.entry(&r.section).or_default();
+            s.1 += 1;
+            if r.passed() {
+                s.0 += 1;
+            } else {
+                s.2.push(r.example);
+            }
+        }
+        for (section, (passed, total, failed)) in sections {
+            write!(f, "{section}: {passed}/{total} passed")?;
+            if !failed.is_empty() {
+                let failed: Vec<_> = failed.iter().map(|n| n.to_string()).collect();
+                write!(f, ", failed: {}", failed.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "Total: {}/{} passed ({:.1}%)",
+            self.passed(),
+            self.results.len(),
+            self.pass_rate() * 100.0
+        )
+    }
+}
+
+/// Checks the examples of the `LINK_SECTIONS` against the Markdown link
+/// parsers. Examples of other sections are skipped.
+///
+/// ```
+/// use parse_hyperlinks::commonmark::run_spec;
+/// use parse_hyperlinks::commonmark::SpecExample;
+///
+/// let examples = [SpecExample {
+///     markdown: "[link](/my uri)\n".to_string(),
+///     html: "<p>[link](/my uri)</p>\n".to_string(),
+///     example: 488,
+///     section: "Links".to_string(),
+/// }];
+/// let report = run_spec(&examples);
+/// assert_eq!(report.passed(), 1);
+/// assert_eq!(report.pass_rate(), 1.0);
+/// ```
+pub fn run_spec(examples: &[SpecExample]) -> SpecReport {
+    let results = examples
+        .iter()
+        .filter(|e| LINK_SECTIONS.contains(&e.section.as_str()))
+        .map(|e| {
+            let mut actual = Vec::new();
+            for (_, link) in MarkupLink::new(&e.markdown, false) {
+                push_spec_links(&link, &mut actual);
+            }
+            SpecResult {
+                example: e.example,
+                section: e.section.clone(),
+                expected: html_spec_links(&e.html),
+                actual,
+            }
+        })
+        .collect();
+    SpecReport { results }
+}
+
+/// Extracts the `<a>` and `<img>` elements of `html` in document order.
+/// Unlike the HTML link parsers, it accepts empty destinations.
+fn html_spec_links(html: &str) -> Vec<SpecLink> {
+    let mut links = Vec::new();
+    let mut i = html;
+    while let Some(n) = i.find('<') {
+        i = &i[n + 1..];
+        let is_img = i.starts_with("img ");
+        if !is_img && !i.starts_with("a ") {
+            continue;
+        }
+        let Ok((j, content)) = tag_content(i) else {
+            continue;
+        };
+        i = j;
+        let Ok((_, attributes)) = attribute_list(content.split_once(' ').unwrap_or_default().1)
+        else {
+            continue;
+        };
+        let value = |name| {
+            attributes
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+                .unwrap_or_default()
+        };
+        if is_img {
+            links.push(SpecLink::Image(decode(&value("src"))));
+        } else {
+            links.push(SpecLink::Anchor(decode(&value("href")), value("title")));
+        }
+    }
+    links
+}
+
+/// Percent-decodes a destination.
+fn decode(dest: &str) -> String {
+    percent_decode_str(dest).decode_utf8_lossy().into_owned()
+}
+
+/// Appends the `SpecLink`s of `link`.
+fn push_spec_links(link: &Link, links: &mut Vec<SpecLink>) {
+    match link {
+        Link::Text2Dest(_, dest, title) => {
+            links.push(SpecLink::Anchor(decode(dest), title.to_string()))
+        }
+        Link::Image(_, src) => links.push(SpecLink::Image(decode(src))),
+        Link::Image2Dest(_, _, src, _, dest, title) => {
+            links.push(SpecLink::Anchor(decode(dest), title.to_string()));
+            links.push(SpecLink::Image(decode(src)));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example(example: u32, section: &str, markdown: &str, html: &str) -> SpecExample {
+        SpecExample {
+            markdown: markdown.to_string(),
+            html: html.to_string(),
+            example,
+            section: section.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_run_spec() {
+        let examples = [
+            example(
+                482,
+                "Links",
+                "[link](/uri \"title\")\n",
+                "<p><a href=\"/uri\" title=\"title\">link</a></p>\n",
+            ),
+            example(484, "Links", "[link]()\n", "<p><a href=\"\">link</a></p>\n"),
+            example(
+                487,
+                "Links",
+                "[link](/my uri)\n",
+                "<p>[link](/my uri)</p>\n",
+            ),
+            example(
+                501,
+                "Links",
+                "[link](foo%20b&auml;)\n",
+                "<p><a href=\"foo%20b%C3%A4\">link</a></p>\n",
+            ),
+            example(
+                505,
+                "Links",
+                "[link](/url \"title \\\"&quot;\")\n",
+                "<p><a href=\"/url\" title=\"title &quot;&quot;\">link</a></p>\n",
+            ),
+            example(
+                192,
+                "Link reference definitions",
+                "[foo]: /url \"title\"\n\n[foo]\n",
+                "<p><a href=\"/url\" title=\"title\">foo</a></p>\n",
+            ),
+            example(
+                209,
+                "Link reference definitions",
+                "[foo]: /url \"title\" ok\n",
+                "<p>[foo]: /url &quot;title&quot; ok</p>\n",
+            ),
+            example(
+                571,
+                "Images",
+                "![foo](/url \"title\")\n",
+                "<p><img src=\"/url\" alt=\"foo\" title=\"title\" /></p>\n",
+            ),
+            example(
+                519,
+                "Links",
+                "[![moon](moon.jpg)](/uri)\n",
+                "<p><a href=\"/uri\"><img src=\"moon.jpg\" alt=\"moon\" /></a></p>\n",
+            ),
+            example(
+                594,
+                "Autolinks",
+                "<http://foo.bar.baz>\n",
+                "<p><a href=\"http://foo.bar.baz\">http://foo.bar.baz</a></p>\n",
+            ),
+            example(
+                1,
+                "Tabs",
+                "\tfoo\tbaz\t\tbim\n",
+                "<pre><code>foo\tbaz\t\tbim\n</code></pre>\n",
+            ),
+        ];
+        let report = run_spec(&examples);
+        assert_eq!(report.results.len(), 10);
+        assert_eq!(
+            report.failed().map(|r| r.example).collect::<Vec<u32>>(),
+            [0u32; 0]
+        );
+        assert_eq!(report.pass_rate(), 1.0);
+        assert!(report
+            .to_string()
+            .ends_with("Total: 10/10 passed (100.0%)\n"));
+
+        // A failing example.
+        let examples = [example(
+            482,
+            "Links",
+            "[link](/uri \"title\")\n",
+            "<p><a href=\"/other\">link</a></p>\n",
+        )];
+        let report = run_spec(&examples);
+        assert_eq!(report.passed(), 0);
+        assert_eq!(
+            report.results[0].actual,
+            [SpecLink::Anchor("/uri".to_string(), "title".to_string())]
+        );
+        assert_eq!(
+            report.to_string(),
+            "Links: 0/1 passed, failed: 482\nTotal: 0/1 passed (0.0%)\n"
+        );
+    }
+}
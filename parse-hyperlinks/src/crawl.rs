@@ -0,0 +1,384 @@
+//! Module implementing a small, rate-limited crawler. This module is only
+//! available with the `check` feature enabled.
+//!
+//! Starting from some seed pages (or the pages listed in a `sitemap.xml`), the
+//! crawler extracts the hyperlinks of every fetched page with
+//! `iterator::MarkupLink`, follows links to the same host up to a depth limit,
+//! and returns the aggregated link graph together with a broken-link report.
+//! Images are not fetched, their existence is checked with a `HEAD` request.
+
+use crate::check::CheckError;
+use crate::iterator::MarkupLink;
+use crate::parser::html::decode_entities;
+use crate::parser::html::html_base;
+use crate::parser::html_img::html_img_candidates;
+use crate::parser::Link;
+use crate::resolve::resolve;
+use crate::url::is_http;
+use crate::url::same_host;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Options controlling `crawl()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlOptions {
+    /// Links are followed up to this depth. The seed pages have depth 0.
+    pub max_depth: usize,
+    /// The crawler stops after fetching this number of pages.
+    pub max_pages: usize,
+    /// Pause between two requests.
+    pub delay: Duration,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_pages: 100,
+            delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The result of a crawl.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CrawlReport {
+    /// The successfully fetched pages in crawl order.
+    pub pages: Vec<String>,
+    /// The link graph: `(page, link_destination)` for every link found in
+    /// the fetched pages. Relative destinations are resolved against `page`.
+    pub links: Vec<(String, String)>,
+    /// Broken links: `(page, link_destination, error)` for every followed
+    /// destination that could not be fetched and every checked image, that
+    /// does not exist. `page` is empty for seeds.
+    pub broken: Vec<(String, String, String)>,
+}
+
+/// Crawls the web starting from the `seeds` pages.
+///
+/// Only `http:` and `https:` links to the host of the page they were found on
+/// are followed. Images on this host are checked with a `HEAD` request, but
+/// never fetched. Between two requests the crawler pauses `options.delay`.
+///
+/// ```no_run
+/// use parse_hyperlinks::crawl::{crawl, CrawlOptions};
+///
+/// let report = crawl(&["https://getreu.net/"], &CrawlOptions::default());
+/// for (page, dest, err) in report.broken {
+///     println!("{page}: {dest}: {err}");
+/// }
+/// ```
+pub fn crawl(seeds: &[&str], options: &CrawlOptions) -> CrawlReport {
+    let agent = ureq::Agent::new();
+    let first = Cell::new(true);
+    let pause = || {
+        if !first.replace(false) {
+            sleep(options.delay);
+        }
+    };
+    crawl_with(
+        seeds,
+        options,
+        |url| {
+            pause();
+            fetch(&agent, url)
+        },
+        |url| {
+            pause();
+            head(&agent, url)
+        },
+    )
+}
+
+/// Fetches the sitemap `sitemap_url` and crawls all pages listed in its
+/// `<loc>` elements.
+pub fn crawl_sitemap(sitemap_url: &str, options: &CrawlOptions) -> Result<CrawlReport, CheckError> {
+    let sitemap = fetch(&ureq::Agent::new(), sitemap_url)?;
+    let seeds = sitemap_urls(&sitemap);
+    let seeds = seeds.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    sleep(options.delay);
+    Ok(crawl(&seeds, options))
+}
+
+/// Same as `crawl()`, but pages are retrieved with the function `fetch` and
+/// images are checked with the function `check_image` instead of HTTP
+/// requests. Both are also responsible for rate limiting.
+///
+/// ```
+/// use parse_hyperlinks::crawl::{crawl_with, CrawlOptions};
+///
+/// let report = crawl_with(
+///     &["http://a.b/"],
+///     &CrawlOptions::default(),
+///     |url| {
+///         Ok(match url {
+///             "http://a.b/" => r#"<a href="c.html">c</a> <img src="d.png">"#.to_string(),
+///             _ => "[home](/)".to_string(),
+///         })
+///     },
+///     |_| Ok(()),
+/// );
+/// assert_eq!(report.pages, vec!["http://a.b/", "http://a.b/c.html"]);
+/// assert!(report.broken.is_empty());
+/// ```
+pub fn crawl_with<F, G>(
+    seeds: &[&str],
+    options: &CrawlOptions,
+    mut fetch: F,
+    mut check_image: G,
+) -> CrawlReport
+where
+    F: FnMut(&str) -> Result<String, CheckError>,
+    G: FnMut(&str) -> Result<(), CheckError>,
+{
+    let mut report = CrawlReport::default();
+    let mut visited = HashSet::new();
+    // `(url, depth, referring page)`
+    let mut queue = seeds
+        .iter()
+        .map(|s| (s.to_string(), 0, String::new()))
+        .collect::<VecDeque<_>>();
+
+    while let Some((url, depth, referrer)) = queue.pop_front() {
+        if report.pages.len() >= options.max_pages {
+            break;
+        }
+        if !visited.insert(url.clone()) {
+            continue;
+        }
+        let body = match fetch(&url) {
+            Ok(body) => body,
+            Err(e) => {
+                report.broken.push((referrer, url, e.to_string()));
+                continue;
+            }
+        };
+
+        // Relative destinations refer to the page's `<base>`, if any.
+        let base = match html_base(&body) {
+            Some(base) => resolve(&url, base).into_owned(),
+            None => url.clone(),
+        };
+        let (dests, images) = link_destinations(&body);
+        for src in images {
+            let src = resolve(&base, Cow::Borrowed(&src)).into_owned();
+            let image = src.split('#').next().unwrap_or_default().to_string();
+            if is_http(&image) && same_host(&image, &url) && visited.insert(image.clone()) {
+                if let Err(e) = check_image(&image) {
+                    report.broken.push((url.clone(), image, e.to_string()));
+                }
+            }
+            report.links.push((url.clone(), src));
+        }
+        for dest in dests {
+            let dest = resolve(&base, Cow::Borrowed(&dest)).into_owned();
+            let page = dest.split('#').next().unwrap_or_default().to_string();
+            if depth < options.max_depth
+                && is_http(&page)
+                && same_host(&page, &url)
+                && !visited.contains(&page)
+            {
+                queue.push_back((page, depth + 1, url.clone()));
+            }
+            report.links.push((url.clone(), dest));
+        }
+        report.pages.push(url);
+    }
+    report
+}
+
+/// Retrieves the page `url`.
+fn fetch(agent: &ureq::Agent, url: &str) -> Result<String, CheckError> {
+    agent
+        .get(url)
+        .call()
+        .map_err(|e| CheckError::Fetch {
+            url: url.to_string(),
+            source: Box::new(e),
+        })?
+        .into_string()
+        .map_err(|e| CheckError::Read {
+            url: url.to_string(),
+            source: e,
+        })
+}
+
+/// Checks the existence of the image `url` with a `HEAD` request.
+fn head(agent: &ureq::Agent, url: &str) -> Result<(), CheckError> {
+    // Some servers do not implement `HEAD`.
+    match agent.head(url).call() {
+        Err(ureq::Error::Status(405, _)) => agent.get(url).call(),
+        res => res,
+    }
+    .map(|_| ())
+    .map_err(|e| CheckError::Fetch {
+        url: url.to_string(),
+        source: Box::new(e),
+    })
+}
+
+/// Returns `(link_destinations, image_sources)` found in `input`. For HTML
+/// images, all `srcset` candidates are returned.
+fn link_destinations(input: &str) -> (Vec<String>, Vec<String>) {
+    let mut dests = Vec::new();
+    let mut images = Vec::new();
+    for ((_, consumed, _), link) in MarkupLink::new(input, false) {
+        match link {
+            Link::Text2Dest(_, dest, _) => dests.push(dest.to_string()),
+            Link::Image(_, src) => match html_img_candidates(consumed) {
+                Ok((_, candidates)) => images.extend(
+                    candidates
+                        .iter()
+                        .filter_map(|l| l.dest().map(str::to_string)),
+                ),
+                Err(_) => images.push(src.to_string()),
+            },
+            Link::Image2Dest(_, _, src, _, dest, _) => {
+                images.push(src.to_string());
+                dests.push(dest.to_string());
+            }
+            _ => {}
+        }
+    }
+    (dests, images)
+}
+
+/// Extracts the URLs listed in the `<loc>` elements of a sitemap.
+fn sitemap_urls(xml: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut i = xml;
+    while let Some(start) = i.find("<loc>") {
+        i = &i[start + "<loc>".len()..];
+        if let Some(end) = i.find("</loc>") {
+            urls.push(decode_entities(i[..end].trim()).to_string());
+            i = &i[end..];
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_sitemap_urls() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>http://a.b/</loc></url>
+  <url><loc> http://a.b/c?d=1&amp;e=2 </loc><lastmod>2020-01-01</lastmod></url>
+</urlset>"#;
+        assert_eq!(
+            sitemap_urls(xml),
+            vec!["http://a.b/", "http://a.b/c?d=1&e=2"]
+        );
+    }
+
+    #[test]
+    fn test_crawl_with() {
+        let site: HashMap<&str, &str> = [
+            (
+                "http://a.b/",
+                r#"<a href="c.html">c</a> [d](d.html) [x](http://x.y/)"#,
+            ),
+            ("http://a.b/c.html", r#"![img](img/i.png) [e](/e.html#sec)"#),
+            ("http://a.b/d.html", r#"[home](/) [f](f.html) ![g](g.png)"#),
+            ("http://a.b/img/i.png", ""),
+            ("http://a.b/e.html", ""),
+        ]
+        .into_iter()
+        .collect();
+        let not_found = |url: &str| CheckError::Read {
+            url: url.to_string(),
+            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+        };
+        let fetch = |url: &str| {
+            // Images are never fetched.
+            assert!(!url.ends_with(".png"), "{}", url);
+            site.get(url)
+                .map(|s| s.to_string())
+                .ok_or_else(|| not_found(url))
+        };
+        let check_image = |url: &str| site.get(url).map(|_| ()).ok_or_else(|| not_found(url));
+
+        let options = CrawlOptions {
+            max_depth: 1,
+            ..CrawlOptions::default()
+        };
+        let report = crawl_with(&["http://a.b/"], &options, fetch, check_image);
+        assert_eq!(
+            report.pages,
+            vec!["http://a.b/", "http://a.b/c.html", "http://a.b/d.html"]
+        );
+        assert_eq!(report.links.len(), 8);
+        assert_eq!(
+            report.links[4],
+            (
+                "http://a.b/c.html".to_string(),
+                "http://a.b/e.html#sec".to_string()
+            )
+        );
+        assert_eq!(report.broken.len(), 1);
+        assert_eq!(report.broken[0].0, "http://a.b/d.html");
+        assert_eq!(report.broken[0].1, "http://a.b/g.png");
+
+        let report = crawl_with(
+            &["http://a.b/"],
+            &CrawlOptions::default(),
+            fetch,
+            check_image,
+        );
+        assert_eq!(
+            report.pages,
+            vec![
+                "http://a.b/",
+                "http://a.b/c.html",
+                "http://a.b/d.html",
+                "http://a.b/e.html"
+            ]
+        );
+        assert_eq!(report.broken.len(), 2);
+        assert_eq!(report.broken[1].0, "http://a.b/d.html");
+        assert_eq!(report.broken[1].1, "http://a.b/f.html");
+
+        let options = CrawlOptions {
+            max_pages: 2,
+            ..CrawlOptions::default()
+        };
+        let report = crawl_with(&["http://a.b/"], &options, fetch, check_image);
+        assert_eq!(report.pages.len(), 2);
+
+        // `<link>` elements are followed, relative to `<base>`.
+        let report = crawl_with(
+            &["http://a.b/x/"],
+            &CrawlOptions::default(),
+            |url| {
+                Ok(match url {
+                    "http://a.b/x/" => {
+                        r#"<base href="/y/"><link rel="canonical" href="c.html">"#.to_string()
+                    }
+                    _ => String::new(),
+                })
+            },
+            check_image,
+        );
+        assert_eq!(report.pages, vec!["http://a.b/x/", "http://a.b/y/c.html"]);
+
+        assert_eq!(
+            link_destinations(
+                r#"<picture><source srcset="a.webp 1x, b.webp 2x"><img src=c.png srcset="d.png 2x"></picture>"#
+            )
+            .1,
+            vec!["a.webp", "b.webp", "c.png", "d.png"]
+        );
+        assert_eq!(
+            link_destinations("[![a](b.png)](c.html)"),
+            (vec!["c.html".to_string()], vec!["b.png".to_string()])
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Module reading the text parts of emails.
+//!
+//! `Email::parse()` takes a raw message in the
+//! [RFC 5322](https://www.rfc-editor.org/rfc/rfc5322) format, walks its
+//! MIME structure and keeps the `text/plain` and `text/html` parts, that are
+//! not attachments. Their content is decoded from `quoted-printable` or
+//! `base64` and from the `charset` (UTF-8 or ISO-8859-1, other charsets are
+//! read as UTF-8). `Email::links()` extracts the hyperlinks of all parts,
+//! each tagged with its part. In `text/html` parts, only HTML links are
+//! recognized. `mbox_messages()` splits a mailbox file into its messages.
+
+use crate::iterator::MarkupLink;
+use crate::iterator::Span;
+use crate::parser::parse::ParserOptions;
+use crate::parser::Link;
+use crate::parser::MarkupLang;
+use crate::parser::MarkupLangs;
+use base64::engine::general_purpose::GeneralPurpose;
+use base64::engine::general_purpose::GeneralPurposeConfig;
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use std::borrow::Cow;
+
+/// Maximum nesting depth of multipart bodies and encapsulated messages.
+/// Deeper parts are ignored.
+const MAX_DEPTH: usize = 16;
+
+/// A decoded `text/plain` or `text/html` part of an email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextPart {
+    /// The part specifier as IMAP numbers it, e.g. `1` for the body of a
+    /// single part message, or `2.1` for the first part of the second part.
+    pub section: String,
+    /// The lowercase media type: `text/plain` or `text/html`.
+    pub content_type: String,
+    /// The decoded content.
+    pub text: String,
+}
+
+/// The text parts of an email.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Email {
+    /// The text parts in message order.
+    pub parts: Vec<TextPart>,
+}
+
+impl Email {
+    /// Reads the text parts of the raw message `raw`. Malformed messages
+    /// are read as far as possible.
+    ///
+    /// ```
+    /// use parse_hyperlinks::email::Email;
+    ///
+    /// let raw = b"From: a@example.org\r\n\
+    ///     Content-Type: multipart/alternative; boundary=\"b\"\r\n\
+    ///     \r\n\
+    ///     --b\r\n\
+    ///     Content-Type: text/plain; charset=utf-8\r\n\
+    ///     Content-Transfer-Encoding: quoted-printable\r\n\
+    ///     \r\n\
+    ///     See <https://example.org/a=3Db>\r\n\
+    ///     --b\r\n\
+    ///     Content-Type: text/html\r\n\
+    ///     Content-Transfer-Encoding: base64\r\n\
+    ///     \r\n\
+    ///     PGEgaHJlZj0iaHR0cHM6Ly9leGFtcGxlLm9yZyI+ZXhhbXBsZTwvYT4=\r\n\
+    ///     --b--\r\n";
+    /// let email = Email::parse(raw);
+    /// assert_eq!(email.parts.len(), 2);
+    /// assert_eq!(email.parts[0].section, "1");
+    /// assert_eq!(email.parts[0].text, "See <https://example.org/a=b>");
+    /// assert_eq!(email.parts[1].content_type, "text/html");
+    /// assert_eq!(email.parts[1].text, "<a href=\"https://example.org\">example</a>");
+    /// ```
+    pub fn parse(raw: &[u8]) -> Self {
+        let mut parts = Vec::new();
+        collect_parts(raw, "", true, 0, &mut parts);
+        Self { parts }
+    }
+
+    /// Iterates over the hyperlinks of all text parts, like
+    /// `MarkupLink::new(text, false)` does for each part. Every link comes
+    /// with the index of its part in `self.parts` and its byte range in the
+    /// part's text.
+    ///
+    /// ```
+    /// use parse_hyperlinks::email::Email;
+    /// use parse_hyperlinks::parser::Link;
+    /// use std::borrow::Cow;
+    ///
+    /// let email = Email::parse(b"Subject: Hi\n\nSee [text](dest).\n");
+    /// let links: Vec<_> = email.links().collect();
+    /// assert_eq!(links.len(), 1);
+    /// let (part, span, link) = &links[0];
+    /// assert_eq!((*part, span.clone()), (0, 4..16));
+    /// assert_eq!(&email.parts[0].text[span.clone()], "[text](dest)");
+    /// assert_eq!(link, &Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")));
+    /// ```
+    pub fn links(&self) -> impl Iterator<Item = (usize, Span, Link<'_>)> + '_ {
+        self.parts.iter().enumerate().flat_map(|(index, part)| {
+            let text = part.text.as_str();
+            let mut options = ParserOptions::default();
+            if part.content_type == "text/html" {
+                options.markup_langs = MarkupLangs::empty().with(MarkupLang::Html);
+            }
+            MarkupLink::new(text, false).parser_options(options).map(
+                move |((_, consumed, remaining), link)| {
+                    let end = text.len() - remaining.len();
+                    (index, end - consumed.len()..end, link)
+                },
+            )
+        })
+    }
+}
+
+/// Splits a mailbox in the `mbox` format into its messages. A message starts
+/// after a `From ` line, that is the first line of the input or follows an
+/// empty line. The `>From ` quoting of `mboxrd` is not undone. Input without
+/// `From ` line is one message.
+///
+/// ```
+/// use parse_hyperlinks::email::mbox_messages;
+///
+/// let mbox = b"From a@b Thu Jan  1 00:00:00 1970\nSubject: 1\n\nHi,\nFrom here.\n\n\
+///              From c@d Thu Jan  1 00:00:00 1970\nSubject: 2\n\n";
+/// let messages = mbox_messages(mbox);
+/// assert_eq!(messages.len(), 2);
+/// assert_eq!(messages[0], b"Subject: 1\n\nHi,\nFrom here.\n\n");
+/// assert_eq!(messages[1], b"Subject: 2\n\n");
+/// ```
+pub fn mbox_messages(mbox: &[u8]) -> Vec<&[u8]> {
+    let mut messages = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    let mut blank = true;
+    for line in mbox.split_inclusive(|b| *b == b'\n') {
+        if blank && line.starts_with(b"From ") {
+            if pos > start {
+                messages.push(&mbox[start..pos]);
+            }
+            start = pos + line.len();
+        }
+        blank = line.trim_ascii().is_empty();
+        pos += line.len();
+    }
+    if pos > start {
+        messages.push(&mbox[start..]);
+    }
+    messages
+}
+
+/// The MIME headers of an entity.
+#[derive(Debug, Default)]
+struct Headers {
+    /// Lowercase media type, e.g. `text/plain`.
+    content_type: String,
+    /// The parameters of `Content-Type`, names in lowercase.
+    params: Vec<(String, String)>,
+    /// Lowercase `Content-Transfer-Encoding`.
+    encoding: String,
+    /// True, if `Content-Disposition` is `attachment`.
+    attachment: bool,
+}
+
+impl Headers {
+    /// Parses the header section `raw`. Folded lines are unfolded.
+    fn parse(raw: &[u8]) -> Self {
+        let raw = String::from_utf8_lossy(raw);
+        let mut fields: Vec<String> = Vec::new();
+        for line in raw.lines() {
+            match fields.last_mut() {
+                Some(field) if line.starts_with([' ', '\t']) => field.push_str(line),
+                _ => fields.push(line.to_string()),
+            }
+        }
+
+        let mut headers = Headers {
+            content_type: "text/plain".to_string(),
+            ..Headers::default()
+        };
+        for field in &fields {
+            let Some((name, value)) = field.split_once(':') else {
+                continue;
+            };
+            let mut values = value.split(';').map(str::trim);
+            let value = values.next().unwrap_or_default().to_ascii_lowercase();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-type" => {
+                    headers.content_type = value;
+                    headers.params = values
+                        .filter_map(|p| p.split_once('='))
+                        .map(|(n, v)| {
+                            (n.trim().to_ascii_lowercase(), unquote(v.trim()).to_string())
+                        })
+                        .collect();
+                }
+                "content-transfer-encoding" => headers.encoding = value,
+                "content-disposition" => headers.attachment = value == "attachment",
+                _ => {}
+            }
+        }
+        headers
+    }
+
+    /// The value of the `Content-Type` parameter `name`.
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Adds the text parts of the entity `raw` with the part specifier
+/// `section` to `parts`. If `is_message`, `raw` is a message: a single part
+/// body gets the specifier `section.1`, as IMAP numbers it.
+fn collect_parts(
+    raw: &[u8],
+    section: &str,
+    is_message: bool,
+    depth: usize,
+    parts: &mut Vec<TextPart>,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let (header, body) = split_header(raw);
+    let headers = Headers::parse(header);
+    let child = |n: usize| match section {
+        "" => n.to_string(),
+        s => format!("{s}.{n}"),
+    };
+    match headers.content_type.as_str() {
+        t if t.starts_with("multipart/") => {
+            let Some(boundary) = headers.param("boundary") else {
+                return;
+            };
+            for (i, part) in split_multipart(body, boundary).into_iter().enumerate() {
+                collect_parts(part, &child(i + 1), false, depth + 1, parts);
+            }
+        }
+        "message/rfc822" => collect_parts(body, section, true, depth + 1, parts),
+        "text/plain" | "text/html" if !headers.attachment => {
+            let body = match headers.encoding.as_str() {
+                "quoted-printable" => Cow::Owned(decode_quoted_printable(body)),
+                "base64" => decode_base64(body).map_or(Cow::Borrowed(body), Cow::Owned),
+                _ => Cow::Borrowed(body),
+            };
+            let text = match headers
+                .param("charset")
+                .map(str::to_ascii_lowercase)
+                .as_deref()
+            {
+                Some("iso-8859-1" | "latin1") => body.iter().map(|b| char::from(*b)).collect(),
+                _ => String::from_utf8_lossy(&body).into_owned(),
+            };
+            parts.push(TextPart {
+                section: if is_message {
+                    child(1)
+                } else {
+                    section.to_string()
+                },
+                content_type: headers.content_type,
+                text,
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Splits an entity into header section and body at the first empty line.
+fn split_header(raw: &[u8]) -> (&[u8], &[u8]) {
+    let mut pos = 0;
+    for line in raw.split_inclusive(|b| *b == b'\n') {
+        if line == b"\n" || line == b"\r\n" {
+            return (&raw[..pos], &raw[pos + line.len()..]);
+        }
+        pos += line.len();
+    }
+    (raw, &[])
+}
+
+/// Splits a multipart body into its parts. The preamble and the epilogue
+/// are dropped, as well as the line break before each delimiter line.
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut start = None;
+    let mut pos = 0;
+    for line in body.split_inclusive(|b| *b == b'\n') {
+        if let Some(rest) = line.strip_prefix(delimiter.as_bytes()) {
+            let rest = rest.trim_ascii_end();
+            if rest.is_empty() || rest == b"--" {
+                if let Some(start) = start {
+                    let part: &[u8] = &body[start..pos];
+                    let part = part.strip_suffix(b"\n").unwrap_or(part);
+                    parts.push(part.strip_suffix(b"\r").unwrap_or(part));
+                }
+                if rest == b"--" {
+                    return parts;
+                }
+                start = Some(pos + line.len());
+            }
+        }
+        pos += line.len();
+    }
+    // Not closed.
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+/// Decodes the `quoted-printable` content transfer encoding. Invalid escapes
+/// are kept as they are.
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'=' {
+            let rest = &input[i + 1..];
+            // Soft line break.
+            if let Some(n) = [&b"\r\n"[..], b"\n"].iter().find(|e| rest.starts_with(e)) {
+                i += 1 + n.len();
+                continue;
+            }
+            let hex = rest
+                .get(..2)
+                .and_then(|h| core::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let Some(b) = hex {
+                res.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        res.push(input[i]);
+        i += 1;
+    }
+    res
+}
+
+/// Decodes the `base64` content transfer encoding, ignoring whitespace and
+/// missing padding.
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+    let engine = GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let input: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    engine.decode(input).ok()
+}
+
+/// Removes the double quotes around a parameter value.
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email() {
+        let raw = b"Content-Type: multipart/mixed;\n boundary=outer\n\n\
+            preamble\n\
+            --outer\n\
+            Content-Type: multipart/alternative; boundary=\"inner\"\n\n\
+            --inner\n\n\
+            [a](b)\n\
+            --inner\n\
+            Content-Type: text/html; charset=ISO-8859-1\n\n\
+            <a href=\"c\">\xfc</a> [d](e)\n\
+            --inner--\n\
+            --outer\n\
+            Content-Type: text/plain\n\
+            Content-Disposition: attachment; filename=f.txt\n\n\
+            [f](g)\n\
+            --outer\n\
+            Content-Type: message/rfc822\n\n\
+            Subject: forwarded\n\n\
+            [h](i)\n\
+            --outer\n\
+            Content-Type: image/png\n\n\
+            [j](k)\n\
+            --outer--\n\
+            epilogue [l](m)\n";
+        let email = Email::parse(raw);
+        let parts: Vec<_> = email
+            .parts
+            .iter()
+            .map(|p| (p.section.as_str(), p.content_type.as_str(), p.text.as_str()))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ("1.1", "text/plain", "[a](b)"),
+                ("1.2", "text/html", "<a href=\"c\">ü</a> [d](e)"),
+                ("3.1", "text/plain", "[h](i)"),
+            ]
+        );
+        let links: Vec<_> = email
+            .links()
+            .map(|(part, span, link)| (part, span, link.dest().unwrap().to_string()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (0, 0..6, "b".to_string()),
+                (1, 0..18, "c".to_string()),
+                (2, 0..6, "i".to_string()),
+            ]
+        );
+
+        // No header, not closed, malformed.
+        assert_eq!(Email::parse(b"[a](b)").parts[0].text, "");
+        assert_eq!(Email::parse(b"\n[a](b)").parts[0].text, "[a](b)");
+        let raw = b"Content-Type: multipart/mixed; boundary=b\n\n--b\n\n[a](b)\n";
+        assert_eq!(Email::parse(raw).parts[0].text, "[a](b)\n");
+        let raw = b"Content-Type: multipart/mixed\n\n--b\n\n[a](b)\n";
+        assert!(Email::parse(raw).parts.is_empty());
+        let raw = b"Content-Transfer-Encoding: base64\n\n!!!";
+        assert_eq!(Email::parse(raw).parts[0].text, "!!!");
+    }
+
+    #[test]
+    fn test_decode_quoted_printable() {
+        assert_eq!(
+            decode_quoted_printable(b"a=3Db=\r\nc=\nd =C3=BC =x= =4"),
+            "a=bcd ü =x= =4".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_mbox_messages() {
+        assert!(mbox_messages(b"").is_empty());
+        assert_eq!(mbox_messages(b"Subject: a\n\nb"), [b"Subject: a\n\nb"]);
+        assert!(mbox_messages(b"From a\n").is_empty());
+    }
+}
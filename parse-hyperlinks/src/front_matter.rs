@@ -0,0 +1,218 @@
+//! Module recognizing the front matter of a document: a YAML block between
+//! `---` lines or a TOML block between `+++` lines at the very start of the
+//! input, as static site generators like Jekyll, Hugo or Zola use it. Its
+//! `key: value` lines are easily mistaken for reStructuredText or Asciidoc
+//! link reference definitions, therefore `iterator::MarkupLink` skips the
+//! front matter by default. `front_matter_links()` extracts the URLs of
+//! the keys `FRONT_MATTER_LINK_KEYS` instead.
+
+use crate::iterator::Span;
+use crate::parser::Link;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use nom::Offset;
+
+/// The keys of the front matter, whose values are link destinations:
+/// a single URL, or a list of URLs.
+pub const FRONT_MATTER_LINK_KEYS: [&str; 2] = ["url", "links"];
+
+/// The format of the front matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrontMatterKind {
+    /// YAML between `---` lines. The closing line may also be `...`.
+    Yaml,
+    /// TOML between `+++` lines.
+    Toml,
+}
+
+/// Returns the format and the byte range of the front matter at the start
+/// of `input`, including the delimiter lines. To tell a YAML front matter
+/// from a reStructuredText section title with overline, which looks
+/// similar, a non-empty front matter must contain at least one `key: value`
+/// (YAML) or `key = value` (TOML) line.
+///
+/// ```
+/// use parse_hyperlinks::front_matter::{front_matter, FrontMatterKind};
+///
+/// let i = "---\ntitle: Home\n---\n[text](dest)";
+/// assert_eq!(front_matter(i), Some((FrontMatterKind::Yaml, 0..20)));
+///
+/// let i = "+++\ntitle = \"Home\"\n+++\n";
+/// assert_eq!(front_matter(i), Some((FrontMatterKind::Toml, 0..23)));
+///
+/// // A reStructuredText section title.
+/// assert_eq!(front_matter("---\nTitle\n---\n"), None);
+/// ```
+pub fn front_matter(input: &str) -> Option<(FrontMatterKind, Span)> {
+    let first = input.split_inclusive('\n').next()?;
+    let kind = match first.trim_end() {
+        "---" => FrontMatterKind::Yaml,
+        "+++" => FrontMatterKind::Toml,
+        _ => return None,
+    };
+    let mut end = first.len();
+    let mut empty = true;
+    let mut has_key = false;
+    for line in input[end..].split_inclusive('\n') {
+        end += line.len();
+        match (kind, line.trim_end()) {
+            (FrontMatterKind::Yaml, "---" | "...") | (FrontMatterKind::Toml, "+++") => {
+                return (empty || has_key).then_some((kind, 0..end));
+            }
+            (_, l) if l.trim().is_empty() => {}
+            _ => {
+                empty = false;
+                has_key |= key_value(kind, line).is_some();
+            }
+        }
+    }
+    None
+}
+
+/// Extracts the URLs of the keys `FRONT_MATTER_LINK_KEYS` from the front
+/// matter of `input`. They are returned as `Link::Label2Dest` with the key
+/// as _link label_, together with the byte range of the URL in `input`.
+/// Besides single values, lists are recognized: YAML block sequences
+/// (`- url` lines) and inline lists (`[url1, url2]`).
+///
+/// ```
+/// use parse_hyperlinks::front_matter::front_matter_links;
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let i = "---\nurl: https://a.org\nlinks:\n  - \"https://b.org\"\n---\n";
+/// let links = front_matter_links(i);
+/// assert_eq!(links.len(), 2);
+/// assert_eq!(links[0].0, 9..22);
+/// assert_eq!(
+///     links[1].1,
+///     Link::Label2Dest(Cow::from("links"), Cow::from("https://b.org"), Cow::from(""))
+/// );
+/// ```
+pub fn front_matter_links(input: &str) -> Vec<(Span, Link<'_>)> {
+    let mut links = Vec::new();
+    let Some((kind, span)) = front_matter(input) else {
+        return links;
+    };
+    let mut push = |key, value| links.extend(entry(input, key, value));
+    // The key of the YAML block sequence we are in.
+    let mut list_key = None;
+    for line in input[span].split_inclusive('\n') {
+        if let (Some(key), Some(item)) = (list_key, line.trim_start().strip_prefix("- ")) {
+            push(key, item);
+            continue;
+        }
+        list_key = None;
+        let Some((key, value)) = key_value(kind, line) else {
+            continue;
+        };
+        if !FRONT_MATTER_LINK_KEYS.contains(&key) {
+            continue;
+        }
+        let value = value.trim();
+        if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            list.split(',').for_each(|item| push(key, item));
+        } else if value.is_empty() && kind == FrontMatterKind::Yaml {
+            list_key = Some(key);
+        } else {
+            push(key, value);
+        }
+    }
+    links
+}
+
+/// The `Link::Label2Dest` for the URL `value` of `key` and its byte range in
+/// `input`, if `value` is not empty.
+fn entry<'a>(input: &'a str, key: &'a str, value: &'a str) -> Option<(Span, Link<'a>)> {
+    let value = unquote(value.trim());
+    let start = input.offset(value);
+    (!value.is_empty()).then(|| {
+        let link = Link::Label2Dest(Cow::Borrowed(key), Cow::Borrowed(value), Cow::Borrowed(""));
+        (start..start + value.len(), link)
+    })
+}
+
+/// Splits a `key: value` (YAML) or `key = value` (TOML) line. The key
+/// consists of alphanumerics, `_`, `-` and `.`.
+fn key_value(kind: FrontMatterKind, line: &str) -> Option<(&str, &str)> {
+    let separator = match kind {
+        FrontMatterKind::Yaml => ':',
+        FrontMatterKind::Toml => '=',
+    };
+    let (key, value) = line.trim_start().split_once(separator)?;
+    let key = key.trim_end();
+    (!key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && (kind == FrontMatterKind::Toml
+            || value.is_empty()
+            || value.starts_with([' ', '\t', '\r', '\n'])))
+    .then_some((key, value))
+}
+
+/// Removes the quotes around a value.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|q| value.strip_prefix(q)?.strip_suffix(q))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_matter() {
+        assert_eq!(front_matter(""), None);
+        assert_eq!(
+            front_matter("---\n---\n"),
+            Some((FrontMatterKind::Yaml, 0..8))
+        );
+        assert_eq!(
+            front_matter("---\r\na: b\r\n...\r\nc"),
+            Some((FrontMatterKind::Yaml, 0..16))
+        );
+        // Not closed.
+        assert_eq!(front_matter("---\na: b\n"), None);
+        // Not at the start.
+        assert_eq!(front_matter("\n---\na: b\n---\n"), None);
+        // TOML is not closed by `---`.
+        assert_eq!(front_matter("+++\na = 1\n---\n"), None);
+        // `http://a` is no YAML key.
+        assert_eq!(front_matter("---\nhttp://a\n---\n"), None);
+    }
+
+    #[test]
+    fn test_front_matter_links() {
+        let i = "---\ntitle: a: b\nurl: 'https://a'\nlinks: [https://b, \"c\"]\n\
+                 links:\n- d\n  - e\nother:\n- f\n---\nurl: g\n";
+        let links: Vec<_> = front_matter_links(i)
+            .into_iter()
+            .map(|(span, link)| {
+                assert_eq!(&i[span], link.dest().unwrap());
+                (
+                    link.label().unwrap().to_string(),
+                    link.dest().unwrap().to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("url", "https://a"),
+            ("links", "https://b"),
+            ("links", "c"),
+            ("links", "d"),
+            ("links", "e"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(links, expected);
+
+        let i = "+++\nurl = \"https://a\"\nlinks = [\"b\"]\n+++\n";
+        let links = front_matter_links(i);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[1].0, 32..33);
+
+        assert!(front_matter_links("url: a").is_empty());
+    }
+}
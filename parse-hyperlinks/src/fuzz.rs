@@ -0,0 +1,285 @@
+//! Entry point for fuzzing harnesses, see `fuzz_all()`.
+//!
+//! All public parsers and iterators of this crate are meant to never panic on
+//! any UTF-8 input: they return an error instead, when the input contains no
+//! link. They are implemented without `catch_unwind()`: byte offsets are
+//! derived from the remaining input of the parsers or from ASCII delimiters,
+//! so slicing always happens on `char` boundaries. Functions taking byte
+//! offsets as arguments, e.g. `link_index::LinkIndex::apply_edit()`, document
+//! when they panic.
+//!
+//! The targets in `parse-hyperlinks/fuzz/` check this guarantee with
+//! [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz):
+//!
+//! ```sh
+//! cd parse-hyperlinks
+//! cargo +nightly fuzz run fuzz_all
+//! ```
+use crate::iterator::{
+    broken_references, find_first, first_link, link_at, markup_lang_links, unused_definitions,
+    LabelMap, LineIndex, MarkupLink,
+};
+use crate::link_index::LinkIndex;
+use crate::parser::asciidoc::*;
+use crate::parser::creole::*;
+use crate::parser::html::*;
+use crate::parser::html_img::*;
+use crate::parser::markdown::*;
+use crate::parser::markdown_img::*;
+use crate::parser::org::*;
+use crate::parser::parse::{
+    detect_markup, needs_allocation, take_link, take_link_bytes, take_link_with,
+    take_text2dest_label2dest, LinkText, ParserOptions, UrlSchemes, WikiDialect, CONTACT_SCHEMES,
+};
+use crate::parser::restructured_text::*;
+use crate::parser::wikitext::*;
+use crate::parser::{bare_uri_link, url_scheme, MarkupLang, MarkupLangs};
+use crate::resolve::resolve_link;
+use crate::url::{host, normalize_link, same_host, scheme};
+use crate::{take_until_unbalanced, take_until_unbalanced_tag};
+
+/// Runs every public parser, iterator and renderer of this crate on `input`
+/// and discards the results. A fuzzing harness calls this function with
+/// arbitrary input: any panic is a bug.
+///
+/// ```
+/// parse_hyperlinks::fuzz_all("[a](b) `c <d>`_ <a href=\"e\">f</a> |ü|_");
+/// parse_hyperlinks::fuzz_all("`🦀__\n.. _🦀:\n[[");
+/// ```
+pub fn fuzz_all(input: &str) {
+    // The parsers, applied at the start of `input`. Every `char` boundary is
+    // a possible start, when the iterators skip text.
+    for (start, _) in input.char_indices() {
+        parse_at(&input[start..]);
+    }
+    parse_at("");
+
+    // Link extraction for all option combinations.
+    for link_text in [LinkText::Raw, LinkText::Plain] {
+        for sphinx_roles in [false, true] {
+            let mut options = ParserOptions {
+                sphinx_roles,
+                link_text,
+                ..ParserOptions::default()
+            };
+            for lang in [
+                MarkupLang::Markdown,
+                MarkupLang::Rst,
+                MarkupLang::Adoc,
+                MarkupLang::Html,
+                MarkupLang::Wikitext,
+                MarkupLang::Org,
+            ] {
+                options.markup_langs = MarkupLangs::empty().with(lang);
+                extract(input, options);
+            }
+            options.markup_langs = MarkupLangs::ALL;
+            extract(input, options);
+        }
+    }
+    for wiki_dialect in [WikiDialect::Creole, WikiDialect::DokuWiki] {
+        let options = ParserOptions {
+            wiki_dialect,
+            ..ParserOptions::default()
+        };
+        extract(input, options);
+    }
+    for url_schemes in [UrlSchemes::Any, UrlSchemes::Only(&["https"])] {
+        let options = ParserOptions {
+            url_schemes,
+            ..ParserOptions::default()
+        };
+        extract(input, options);
+    }
+    let options = ParserOptions {
+        md_link_attributes: true,
+        gfm: true,
+        bare_uri_schemes: &CONTACT_SCHEMES,
+        ..ParserOptions::default()
+    };
+    extract(input, options);
+
+    // Queries over all links.
+    let _ = find_first(input);
+    if let Some((span, _)) = first_link(input) {
+        assert!(input.get(span).is_some());
+    }
+    if let Some((span, _)) = link_at(input, input.len() / 2) {
+        assert!(span.contains(&(input.len() / 2)));
+    }
+    let _ = markup_lang_links(input).count();
+    let _ = broken_references(input);
+    let _ = unused_definitions(input);
+    let _ = detect_markup(input);
+    let _ = needs_allocation(input);
+    let _ = LabelMap::from(input).len();
+    let line_index = LineIndex::new(input);
+    let _ = line_index.links().count();
+    let _ = line_index.position(input.len() / 2);
+    let _ = crate::front_matter::front_matter_links(input);
+    let _ = crate::lint::lint(input);
+    let _ = crate::headings::headings(input);
+    for style in [
+        crate::headings::SlugStyle::GitHub,
+        crate::headings::SlugStyle::GitLab,
+        crate::headings::SlugStyle::Pandoc,
+    ] {
+        let _ = crate::headings::anchors(input, style);
+    }
+
+    // Incremental re-parsing: delete and re-insert the second half.
+    let mut index = LinkIndex::new(input);
+    let mut middle = input.len() / 2;
+    while !input.is_char_boundary(middle) {
+        middle -= 1;
+    }
+    let _ = index.overlapping(middle..middle).count();
+    index.apply_edit(middle..input.len(), "");
+    index.apply_edit(middle..middle, &input[middle..]);
+
+    #[cfg(feature = "std")]
+    fuzz_std(input);
+}
+
+/// Runs the parsers, that expect to start at a link, on `i`.
+fn parse_at(i: &str) {
+    let _ = take_link(i);
+    let _ = take_link_bytes(i.as_bytes());
+    let _ = take_text2dest_label2dest(i);
+    let _ = take_until_unbalanced('(', ')')(i);
+    let _ = take_until_unbalanced('[', ']')(i);
+    let _ = take_until_unbalanced_tag("<a", "</a>")(i);
+
+    let _ = md_text2dest_link(i);
+    let _ = md_label2dest_link(i);
+    let _ = md_text2label_link(i);
+    let _ = md_link_attributes(i);
+    let _ = md_gfm_autolink_link(i);
+    let _ = bare_uri_link(i, &CONTACT_SCHEMES);
+    let _ = md_img_link(i);
+    let _ = md_img2label_link(i);
+    let _ = md_img2dest_link(i);
+    let _ = md_plain_text(i);
+
+    let _ = rst_text2dest_link(i);
+    let _ = rst_text_label2dest_link(i);
+    let _ = rst_text2label_link(i);
+    let _ = rst_substitution2label_link(i);
+    let _ = rst_role2target_link(i);
+    let _ = rst_label2dest_link(i);
+    let _ = rst_label2label_link(i);
+    let _ = rst_image_link(i);
+    let _ = rst_substitution_image_link(i);
+
+    let _ = adoc_text2dest_link(i);
+    let _ = adoc_link_attributes(i);
+    let _ = adoc_img_link(i);
+    let _ = adoc_label2dest_link(i);
+    let _ = adoc_text2label_link(i);
+
+    let _ = html_text2dest_link(i);
+    let _ = html_area2dest_link(i);
+    let _ = html_rel2dest_link(i);
+    let _ = html_link_attributes(i);
+    let _ = html_img_link(i);
+    let _ = html_source_link(i);
+    let _ = html_img_candidates(i);
+    let _ = html_img2dest_link(i);
+    let _ = html_base(i);
+    let _ = attribute_list(i);
+    let _ = decode_entities(i);
+
+    let _ = wikitext_text2dest_link(i);
+    let _ = wikitext_free_url_link(i);
+    let _ = creole_text2dest_link(i);
+    let _ = creole_img_link(i);
+    let _ = dokuwiki_text2dest_link(i);
+    let _ = dokuwiki_img_link(i);
+
+    let _ = org_text2dest_link(i);
+    let _ = org_text2label_link(i);
+    let _ = org_target_link(i);
+    let _ = org_name_link(i);
+
+    let _ = url_scheme(i);
+    let _ = scheme(i);
+    let _ = host(i);
+    let _ = same_host(i, i);
+}
+
+/// Iterates over the links of `input` with `options` and post-processes
+/// them.
+fn extract(input: &str, options: ParserOptions) {
+    let mut i = input;
+    while let Ok((j, (_, link))) = take_link_with(i, &options) {
+        let _ = normalize_link(resolve_link(input, link));
+        i = j;
+    }
+    for render_label in [false, true] {
+        let links = MarkupLink::new(input, render_label)
+            .normalize_urls(true)
+            .unresolved_references(true)
+            .front_matter_links(render_label)
+            .parser_options(options);
+        for (_, link) in links {
+            let _ = resolve_link(input, link);
+        }
+    }
+}
+
+/// Runs the renderers and rewriters.
+#[cfg(feature = "std")]
+fn fuzz_std(input: &str) {
+    use crate::renderer::*;
+    use crate::rewrite::*;
+    use std::io::BufReader;
+
+    let _ = text_links2html(input);
+    let _ = text_rawlinks2html(input);
+    let _ = links2html(input);
+    let _ = links2markdown(input);
+    let _ = text_links2text(input);
+    let _ = links2csv(input);
+    let _ = links2tsv(input);
+    let _ = inline2reference(input);
+    let _ = reference2inline(input);
+    if let Some(label) =
+        markup_lang_links(input).find_map(|(_, link, _)| link.label().map(|la| la.to_string()))
+    {
+        let _ = rename_label(input, &label, "b c");
+    }
+    for from in [MarkupLang::Markdown, MarkupLang::Rst, MarkupLang::Adoc] {
+        for to in [MarkupLang::Markdown, MarkupLang::Rst, MarkupLang::Adoc] {
+            let _ = convert_links(input, from, to);
+        }
+    }
+    let reader = BufReader::with_capacity(16, input.as_bytes());
+    let _ = crate::iterator::MarkupLinkStream::with_max_len(reader, 64).count();
+    let _ = crate::analysis::extract_links(input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzz_all() {
+        // Inputs found by fuzzing.
+        for input in [
+            "image::ahref[[",
+            "image:my_logo.png[] image:a-b[]",
+            "`\u{a0}__//=+`_ ",
+            "`a\u{2028}b <c_>`_ ",
+            "`🦀__\n.. _🦀:\n[[",
+            "ü__ `ü <ü>`__ |ü|_ :ü:`ü`",
+            "&#xD800; &#99999999999; &am <a href=&;>x</a>",
+            "<a href=$>>figure::|'<base href=<ü></a>",
+            "---\nurl: a\nlinks: [b, 'c']\n...\n[d](e)",
+            "[[a|{{b?c|d}}]] {{ e }} [[f\n]] {{{{}}",
+            "[a](b) <c@d> [e]: f\n`g <h>`_\n.. _i: j\nlink:k[l] [m n] <img src=o>",
+        ] {
+            fuzz_all(input);
+        }
+        fuzz_all("");
+    }
+}
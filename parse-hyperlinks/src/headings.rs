@@ -0,0 +1,360 @@
+//! Module extracting the headings of Markdown, reStructuredText and Asciidoc
+//! documents and computing their anchors.
+//!
+//! Renderers derive an anchor (`id`) from the text of every heading, so that
+//! `#fragment` links can refer to it. `headings()` finds the headings,
+//! `slug()` computes the anchor of a heading's text in the style of a
+//! renderer (`SlugStyle`) and `anchors()` lists all anchors of a document,
+//! e.g. to verify intra-document links.
+
+use crate::iterator::Span;
+use crate::parser::html::html_anchors;
+use crate::parser::markdown::md_plain_text;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// The rules deriving an anchor from a heading's text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlugStyle {
+    /// GitHub: lowercase, punctuation removed, spaces replaced by `-`.
+    #[default]
+    GitHub,
+    /// GitLab: like `GitHub`, but runs of `-` are collapsed.
+    GitLab,
+    /// Pandoc: lowercase, punctuation except `_`, `-` and `.` removed,
+    /// whitespace replaced by `-` and everything before the first letter
+    /// removed. An empty anchor becomes `section`.
+    Pandoc,
+}
+
+/// A heading of a document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Heading<'a> {
+    /// The byte range of the heading in the input, including its
+    /// underline, without the final line break.
+    pub span: Span,
+    /// The level of the heading, starting with 1. ATX and Asciidoc headings
+    /// have the level of their number of `#` or `=` markers. Underlined
+    /// headings get their level in order of appearance of their adornment
+    /// style, as in reStructuredText.
+    pub level: usize,
+    /// The plain text of the heading, inline markup is stripped.
+    pub text: Cow<'a, str>,
+}
+
+/// Returns the headings of `input` in order of appearance. Recognized are
+/// Markdown ATX (`# text`) and setext headings (text underlined with `=` or
+/// `-`), reStructuredText section titles (text underlined, and optionally
+/// overlined, with punctuation characters) and Asciidoc section titles
+/// (`== text`). Lines in fenced code blocks are skipped.
+///
+/// ```
+/// use parse_hyperlinks::headings::headings;
+///
+/// let i = "# Intro *1*\n\nSetup\n=====\n\nabc\n\n== Usage\n";
+/// let headings = headings(i);
+/// assert_eq!(headings.len(), 3);
+/// assert_eq!(headings[0].text, "Intro 1");
+/// assert_eq!((headings[1].level, &*headings[1].text), (1, "Setup"));
+/// assert_eq!(&i[headings[1].span.clone()], "Setup\n=====");
+/// assert_eq!((headings[2].level, &*headings[2].text), (2, "Usage"));
+/// ```
+pub fn headings(input: &str) -> Vec<Heading<'_>> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        lines.push((offset, line.trim_end_matches(['\n', '\r'])));
+        offset += line.len();
+    }
+
+    let mut headings = Vec::new();
+    // Adornment styles of underlined headings in order of appearance.
+    let mut styles: Vec<(char, bool)> = Vec::new();
+    let mut level = |style| {
+        if !styles.contains(&style) {
+            styles.push(style);
+        }
+        styles.iter().position(|s| *s == style).unwrap_or_default() + 1
+    };
+    let is_text = |line: &str| {
+        !line.trim().is_empty() && !line.starts_with("    ") && !line.starts_with('\t')
+    };
+    let mut fence: Option<(char, usize)> = None;
+    let mut n = 0;
+    while n < lines.len() {
+        let (offset, line) = lines[n];
+        let next = lines.get(n + 1).map(|(_, l)| *l);
+        n += 1;
+
+        if let Some((c, len)) = code_fence(line) {
+            match fence {
+                None => fence = Some((c, len)),
+                Some((d, open))
+                    if c == d && len >= open && line.trim_start()[len..].trim().is_empty() =>
+                {
+                    fence = None
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+
+        if let Some((level, text)) = atx_heading(line).or_else(|| adoc_heading(line)) {
+            headings.push(Heading {
+                span: offset..offset + line.len(),
+                level,
+                text: md_plain_text(text),
+            });
+            continue;
+        }
+
+        // Overlined and underlined heading.
+        if let (Some(c), Some(text), Some(&(under_offset, under))) =
+            (adornment(line), next, lines.get(n + 1))
+        {
+            if is_text(text) && adornment(under) == Some(c) && line.trim_end() == under.trim_end() {
+                headings.push(Heading {
+                    span: offset..under_offset + under.len(),
+                    level: level((c, true)),
+                    text: md_plain_text(text.trim()),
+                });
+                n += 2;
+                continue;
+            }
+        }
+
+        // Underlined heading.
+        if let Some(under) = next.filter(|_| is_text(line) && atx_heading(line).is_none()) {
+            let text = line.trim();
+            let is_heading = match adornment(under) {
+                Some('=' | '-') => true,
+                Some(_) => under.trim_end().chars().count() >= text.chars().count(),
+                None => false,
+            };
+            if is_heading {
+                headings.push(Heading {
+                    span: offset..lines[n].0 + under.len(),
+                    level: level((under.chars().next().unwrap_or_default(), false)),
+                    text: md_plain_text(text),
+                });
+                n += 1;
+            }
+        }
+    }
+    headings
+}
+
+/// Parses an ATX heading and returns its level and text.
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start_matches(' ');
+    if line.len() - text.len() > 3 {
+        return None;
+    }
+    let level = text.len() - text.trim_start_matches('#').len();
+    let text = &text[level..];
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    // Remove the optional closing sequence.
+    let text = text.trim();
+    let stripped = text.trim_end_matches('#');
+    let text = if stripped.is_empty() {
+        stripped
+    } else if stripped.ends_with([' ', '\t']) {
+        stripped.trim_end()
+    } else {
+        text
+    };
+    Some((level, text))
+}
+
+/// Parses an Asciidoc section title and returns its level and text.
+fn adoc_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.len() - line.trim_start_matches('=').len();
+    let text = line[level..].strip_prefix(' ')?.trim();
+    ((1..=6).contains(&level) && !text.is_empty()).then_some((level, text))
+}
+
+/// If `line` consists of one repeated punctuation character only, returns
+/// it.
+fn adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let c = line.chars().next().filter(|c| c.is_ascii_punctuation())?;
+    line.chars().all(|d| d == c).then_some(c)
+}
+
+/// If `line` opens or closes a fenced code block, returns the fence
+/// character and the fence length.
+fn code_fence(line: &str) -> Option<(char, usize)> {
+    let fence = line.trim_start_matches(' ');
+    if line.len() - fence.len() > 3 {
+        return None;
+    }
+    let c = fence.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = fence.len() - fence.trim_start_matches(c).len();
+    (len >= 3).then_some((c, len))
+}
+
+/// Computes the anchor of a heading with the plain text `text`.
+///
+/// ```
+/// use parse_hyperlinks::headings::{slug, SlugStyle};
+///
+/// let text = "1. What's new in v2.0 -- Überblick";
+/// assert_eq!(slug(text, SlugStyle::GitHub), "1-whats-new-in-v20----überblick");
+/// assert_eq!(slug(text, SlugStyle::GitLab), "1-whats-new-in-v20-überblick");
+/// assert_eq!(slug(text, SlugStyle::Pandoc), "whats-new-in-v2.0----überblick");
+/// ```
+pub fn slug(text: &str, style: SlugStyle) -> String {
+    let text = text.trim().to_lowercase();
+    match style {
+        SlugStyle::GitHub => text
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                '-' | '_' => Some(c),
+                c if c.is_alphanumeric() => Some(c),
+                _ => None,
+            })
+            .collect(),
+        SlugStyle::GitLab => {
+            let mut slug = String::new();
+            for c in text.chars() {
+                let c = match c {
+                    ' ' | '-' => '-',
+                    '_' => c,
+                    c if c.is_alphanumeric() => c,
+                    _ => continue,
+                };
+                if !(c == '-' && slug.ends_with('-')) {
+                    slug.push(c);
+                }
+            }
+            slug
+        }
+        SlugStyle::Pandoc => {
+            let slug: String = text
+                .chars()
+                .skip_while(|c| !c.is_alphabetic())
+                .filter_map(|c| match c {
+                    c if c.is_whitespace() => Some('-'),
+                    '-' | '_' | '.' => Some(c),
+                    c if c.is_alphanumeric() => Some(c),
+                    _ => None,
+                })
+                .collect();
+            if slug.is_empty() {
+                "section".to_string()
+            } else {
+                slug
+            }
+        }
+    }
+}
+
+/// Returns the anchors of `input`: the anchors of all headings in the
+/// style `style`, followed by the `id`s of embedded HTML elements. Repeated
+/// heading anchors get a numeric suffix (`-1`, `-2`, ...), as the renderers
+/// do.
+///
+/// ```
+/// use parse_hyperlinks::headings::{anchors, SlugStyle};
+///
+/// let i = "# Usage\n\n## Usage\n\n<a id=\"top\"></a>\n";
+/// assert_eq!(anchors(i, SlugStyle::GitHub), ["usage", "usage-1", "top"]);
+/// ```
+pub fn anchors(input: &str, style: SlugStyle) -> Vec<String> {
+    let mut anchors: Vec<String> = Vec::new();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for heading in headings(input) {
+        let slug = slug(&heading.text, style);
+        let count = counts.entry(slug.clone()).or_default();
+        anchors.push(match *count {
+            0 => slug,
+            n => format!("{slug}-{n}"),
+        });
+        *count += 1;
+    }
+    anchors.extend(html_anchors(input).into_iter().map(|a| a.into_owned()));
+    anchors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings() {
+        let i = "\
+## ATX *heading* ##
+#hashtag
+    # indented code
+
+```
+# code
+```
+
+=====
+Title
+=====
+
+Section
+-------
+
+Sub
+~~~
+
+~
+text
+~~~~
+
+### ###
+";
+        let headings: Vec<_> = headings(i)
+            .into_iter()
+            .map(|h| (h.level, h.text.into_owned()))
+            .collect();
+        assert_eq!(
+            headings,
+            [
+                (2, "ATX heading".to_string()),
+                (1, "Title".to_string()),
+                (2, "Section".to_string()),
+                (3, "Sub".to_string()),
+                (3, "text".to_string()),
+                (3, "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_atx_heading() {
+        assert_eq!(atx_heading("# a #"), Some((1, "a")));
+        assert_eq!(atx_heading("   ###### a#"), Some((6, "a#")));
+        assert_eq!(atx_heading("#"), Some((1, "")));
+        assert_eq!(atx_heading("####### a"), None);
+        assert_eq!(atx_heading("    # a"), None);
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("  Hello,  World!", SlugStyle::GitHub), "hello--world");
+        assert_eq!(slug("  Hello,  World!", SlugStyle::GitLab), "hello-world");
+        assert_eq!(slug("  Hello,  World!", SlugStyle::Pandoc), "hello--world");
+        assert_eq!(slug("snake_case", SlugStyle::GitHub), "snake_case");
+        assert_eq!(slug("2024", SlugStyle::Pandoc), "section");
+    }
+
+    #[test]
+    fn test_anchors() {
+        let i = "# A\n\nA\n=\n\n# A\n";
+        assert_eq!(anchors(i, SlugStyle::GitLab), ["a", "a-1", "a-2"]);
+    }
+}
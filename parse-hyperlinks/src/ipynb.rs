@@ -0,0 +1,158 @@
+//! Module reading the Markdown cells of Jupyter notebooks (`.ipynb` files).
+//!
+//! A notebook is a JSON document in the
+//! [nbformat 4](https://nbformat.readthedocs.io/en/latest/format_description.html)
+//! format. `Notebook::from_json()` keeps its Markdown cells only, and
+//! `Notebook::links()` extracts their hyperlinks together with the index of
+//! the cell and the byte range of the link in the cell's source. As Jupyter
+//! renders every cell on its own, link references are resolved within the
+//! same cell only.
+
+use crate::iterator::MarkupLink;
+use crate::iterator::Span;
+use crate::parser::Link;
+
+/// A Markdown cell of a notebook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownCell {
+    /// Position of the cell among all cells of the notebook, code cells
+    /// included.
+    pub index: usize,
+    /// The source of the cell, its lines joined.
+    pub source: String,
+}
+
+/// The Markdown cells of a notebook.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Notebook {
+    /// The Markdown cells in notebook order.
+    pub cells: Vec<MarkdownCell>,
+}
+
+/// The parts of the notebook JSON we need, other fields are ignored.
+#[derive(serde::Deserialize)]
+struct RawNotebook {
+    cells: Vec<RawCell>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawCell {
+    cell_type: String,
+    source: RawSource,
+}
+
+/// nbformat allows the source as a string or as a list of lines.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RawSource {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Notebook {
+    /// Reads the Markdown cells from the content of an `.ipynb` file.
+    ///
+    /// ```
+    /// use parse_hyperlinks::ipynb::Notebook;
+    ///
+    /// let json = r##"{"cells": [
+    ///     {"cell_type": "code", "source": "print('[a](b)')", "outputs": []},
+    ///     {"cell_type": "markdown", "source": ["# Title\n", "[text](dest)"]}
+    ///   ], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"##;
+    /// let notebook = Notebook::from_json(json).unwrap();
+    /// assert_eq!(notebook.cells.len(), 1);
+    /// assert_eq!(notebook.cells[0].index, 1);
+    /// assert_eq!(notebook.cells[0].source, "# Title\n[text](dest)");
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let raw: RawNotebook = serde_json::from_str(json)?;
+        let cells = raw
+            .cells
+            .into_iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.cell_type == "markdown")
+            .map(|(index, cell)| MarkdownCell {
+                index,
+                source: match cell.source {
+                    RawSource::Text(s) => s,
+                    RawSource::Lines(lines) => lines.concat(),
+                },
+            })
+            .collect();
+        Ok(Self { cells })
+    }
+
+    /// Iterates over the hyperlinks of all Markdown cells, like
+    /// `MarkupLink::new(source, false)` does for each cell. Every link comes
+    /// with the index of its cell in the notebook and its byte range in the
+    /// cell's source.
+    ///
+    /// ```
+    /// use parse_hyperlinks::ipynb::Notebook;
+    /// use parse_hyperlinks::parser::Link;
+    /// use std::borrow::Cow;
+    ///
+    /// let json = r#"{"cells": [
+    ///     {"cell_type": "markdown", "source": "See [text][label]\n\n[label]: dest"}
+    ///   ]}"#;
+    /// let notebook = Notebook::from_json(json).unwrap();
+    /// let links: Vec<_> = notebook.links().collect();
+    /// assert_eq!(links.len(), 1);
+    /// let (cell, span, link) = &links[0];
+    /// assert_eq!((*cell, span.clone()), (0, 4..17));
+    /// assert_eq!(&notebook.cells[0].source[span.clone()], "[text][label]");
+    /// assert_eq!(link, &Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")));
+    /// ```
+    pub fn links(&self) -> impl Iterator<Item = (usize, Span, Link<'_>)> + '_ {
+        self.cells.iter().flat_map(|cell| {
+            let source = cell.source.as_str();
+            MarkupLink::new(source, false).map(move |((_, consumed, remaining), link)| {
+                let end = source.len() - remaining.len();
+                (cell.index, end - consumed.len()..end, link)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_notebook() {
+        let json = r#"{"cells": [
+            {"cell_type": "markdown", "source": ["[a]: b\n", "\n", "<c@d>"]},
+            {"cell_type": "raw", "source": "[e](f)"},
+            {"cell_type": "markdown", "source": "[g](h) [a]", "attachments": {}}
+          ], "nbformat": 4}"#;
+        let notebook = Notebook::from_json(json).unwrap();
+        assert_eq!(
+            notebook.cells.iter().map(|c| c.index).collect::<Vec<_>>(),
+            [0, 2]
+        );
+        let links: Vec<_> = notebook.links().collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    0,
+                    8..13,
+                    Link::Text2Dest(Cow::from("c@d"), Cow::from("mailto:c@d"), Cow::from(""))
+                ),
+                (
+                    2,
+                    0..6,
+                    Link::Text2Dest(Cow::from("g"), Cow::from("h"), Cow::from(""))
+                ),
+            ]
+        );
+
+        assert!(Notebook::from_json("{}").is_err());
+        assert!(Notebook::from_json(r#"{"cells": [{"cell_type": "markdown"}]}"#).is_err());
+        assert_eq!(
+            Notebook::from_json(r#"{"cells": []}"#).unwrap(),
+            Notebook::default()
+        );
+    }
+}
@@ -1,17 +1,135 @@
 //! Module providing an iterator over the hyperlinks found in the input text.  Consult the
 //! documentation of `parser::parse::take_link()` to see a list of supported markup languages. The
-//! iterator resolves link references.
+//! iterator resolves link references. Link labels match case-insensitively and
+//! whitespace-neutrally (see `normalize_label()`). For inputs too large to
+//! be held in memory, `MarkupLinkStream` parses a `BufRead` chunk by chunk,
+//! but does not resolve link references.
 
+use crate::front_matter::front_matter;
+use crate::front_matter::front_matter_links;
 use crate::parser::parse::take_link;
+use crate::parser::parse::take_link_lang;
+use crate::parser::parse::take_link_lang_with;
+use crate::parser::parse::take_link_with;
+use crate::parser::parse::ParserOptions;
+use crate::parser::parse::LABEL_LEN_MAX;
+use crate::parser::wikitext::wikitext_number_link;
 use crate::parser::Link;
-use std::borrow::Cow;
+use crate::parser::MarkupLang;
+use crate::url::normalize_link;
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use alloc::collections::VecDeque;
+use alloc::format;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::iter;
+use core::mem::swap;
+use core::mem::take;
+use core::ops::Range;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::mem::swap;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::io::BufRead;
+// Without `std`, there are no hash maps: fall back to B-tree maps.
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+
+/// A byte range in the input text.
+pub type Span = Range<usize>;
+
+/// A line/column position in the input text. All values count from 0, as
+/// in the Language Server Protocol. `column` counts Unicode scalar values,
+/// `column_utf16` UTF-16 code units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number.
+    pub line: usize,
+    /// The column in characters.
+    pub column: usize,
+    /// The column in UTF-16 code units.
+    pub column_utf16: usize,
+}
+
+/// Converts byte offsets of the input text into line/column positions,
+/// e.g. for editor diagnostics. Lines end with `\n`. The constructor scans
+/// the input once, the conversion only scans the line in question.
+///
+/// ```
+/// use parse_hyperlinks::iterator::LineIndex;
+/// use parse_hyperlinks::iterator::Position;
+///
+/// let i = "abc\näöü[text](dest)";
+/// let index = LineIndex::new(i);
+///
+/// let (positions, _link) = index.links().next().unwrap();
+/// assert_eq!(positions.start, Position { line: 1, column: 3, column_utf16: 3 });
+/// assert_eq!(positions.end, Position { line: 1, column: 15, column_utf16: 15 });
+/// assert_eq!(index.position(4), Position { line: 1, column: 0, column_utf16: 0 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    /// The input text.
+    input: &'a str,
+    /// Byte offsets of the first byte of each line.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Constructor indexing the line starts of `input`.
+    pub fn new(input: &'a str) -> Self {
+        let line_starts = iter::once(0)
+            .chain(input.match_indices('\n').map(|(n, _)| n + 1))
+            .collect();
+        Self { input, line_starts }
+    }
+
+    /// The position of the byte offset `offset`. Offsets past the end of
+    /// the input are treated as the end of the input, offsets inside a
+    /// multi-byte character as the start of that character.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.input.len());
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let text = &self.input[self.line_starts[line]..offset];
+        Position {
+            line,
+            column: text.chars().count(),
+            column_utf16: text.encode_utf16().count(),
+        }
+    }
+
+    /// The positions of the first byte and of the end of `span`.
+    pub fn span_position(&self, span: &Span) -> Range<Position> {
+        self.position(span.start)..self.position(span.end)
+    }
+
+    /// Iterates over the links of the input like `MarkupLink::new(input,
+    /// false)` does, but every link comes with the positions of its source.
+    pub fn links(&self) -> impl Iterator<Item = (Range<Position>, Link<'a>)> + '_ {
+        let input = self.input;
+        MarkupLink::new(input, false).map(move |((_, consumed, remaining), link)| {
+            let end = input.len() - remaining.len();
+            (self.span_position(&(end - consumed.len()..end)), link)
+        })
+    }
+}
 
 #[derive(Debug, PartialEq)]
 /// A collection of `Link` objects grouped by link type.
 struct MarkupLinkCollection<'a> {
-    /// Vector storing all `Link::Text2Dest`, `Link::Text2Label` and `Link::TextLabel2Dest` links.
+    /// Vector storing all `Link::Text2Dest`, `Link::Text2Label`, `Link::Image2Label` and
+    /// `Link::TextLabel2Dest` links.
     /// The tuple is defined as follows: `(link_first_byte_offset, link_len, Link)`.
     text2dest_label: Vec<(usize, usize, Link<'a>)>,
     /// Vector for `Link::Label2Label` links.
@@ -20,6 +138,11 @@ struct MarkupLinkCollection<'a> {
     /// The `HashMap`'s key is the `link_label` of the link, the value its
     /// `(link_destination, link_title)`.
     label2dest: HashMap<Cow<'a, str>, (Cow<'a, str>, Cow<'a, str>)>,
+    /// Number of anonymous `Link::Text2Label` links inserted so far.
+    anonymous_text2label_counter: usize,
+    /// Number of anonymous `Link::Label2Label` and `Link::Label2Dest` links
+    /// inserted so far.
+    anonymous_label2x_counter: usize,
 }
 
 impl<'a> MarkupLinkCollection<'a> {
@@ -28,6 +151,8 @@ impl<'a> MarkupLinkCollection<'a> {
             text2dest_label: Vec::new(),
             label2label: Vec::new(),
             label2dest: HashMap::new(),
+            anonymous_text2label_counter: 0,
+            anonymous_label2x_counter: 0,
         }
     }
 
@@ -40,95 +165,132 @@ impl<'a> MarkupLinkCollection<'a> {
     fn from(input: &'a str, render_label2dest: bool) -> Self {
         let mut i = input;
         let mut hc = MarkupLinkCollection::new();
-        let mut anonymous_text2label_counter = 0;
-        let mut anonymous_label2x_counter = 0;
         // This index refers to `input`.
         let mut input_idx = 0;
 
         while let Ok((j, (skipped, res))) = take_link(i) {
-            match res {
-                // `Text2Dest` is stored without modification in `hc.text2dest_label`.
-                l if matches!(l, Link::Text2Dest { .. })
-                    || matches!(l, Link::Image2Dest { .. })
-                    || matches!(l, Link::Image { .. }) =>
-                {
-                    let link_offset = input_idx + skipped.len();
-                    let link_len = i.len() - j.len() - skipped.len();
-                    hc.text2dest_label.push((link_offset, link_len, l));
-                }
+            let link_offset = input_idx + skipped.len();
+            let link_len = i.len() - j.len() - skipped.len();
+            hc.insert(input, link_offset, link_len, res, render_label2dest);
 
-                // `Text2label` is stored without modification in `hc.text2dest_label`.
-                Link::Text2Label(text, mut label) => {
-                    if label == "_" {
-                        anonymous_text2label_counter += 1;
-                        label = Cow::Owned(format!("_{}", anonymous_text2label_counter));
-                    }
-                    let link_offset = input_idx + skipped.len();
-                    let link_len = i.len() - j.len() - skipped.len();
-                    hc.text2dest_label
-                        .push((link_offset, link_len, Link::Text2Label(text, label)))
-                }
-                //`TextLabel2Dest` are cloned and stored in `hc.text2dest_label` as `Text2Dest`
-                // and in `hc.label2dest` (repacked in a `HashMap`).
-                Link::TextLabel2Dest(tl, d, t) => {
-                    let link_offset = input_idx + skipped.len();
-                    let link_len = i.len() - j.len() - skipped.len();
-                    hc.text2dest_label.push((
+            // Prepare next iteration.
+            input_idx += i.len() - j.len();
+            i = j;
+        }
+
+        hc
+    }
+
+    /// Stores the `link` found at `input[link_offset..link_offset + link_len]`
+    /// according to its category. Anonymous labels are numbered in order of
+    /// insertion.
+    fn insert(
+        &mut self,
+        input: &'a str,
+        link_offset: usize,
+        link_len: usize,
+        link: Link<'a>,
+        render_label2dest: bool,
+    ) {
+        match link {
+            // `Text2Dest` is stored without modification in `self.text2dest_label`.
+            l @ (Link::Text2Dest(_, _, _)
+            | Link::Image2Dest(_, _, _, _, _, _)
+            | Link::Image(_, _)) => {
+                self.text2dest_label.push((link_offset, link_len, l));
+            }
+
+            // `Text2label` is stored without modification in `self.text2dest_label`.
+            Link::Text2Label(text, label) => {
+                let label = anonymous_label(label, &mut self.anonymous_text2label_counter);
+                self.text2dest_label
+                    .push((link_offset, link_len, Link::Text2Label(text, label)))
+            }
+            // `Image2label` is stored without modification in `self.text2dest_label`.
+            l @ Link::Image2Label(_, _) => self.text2dest_label.push((link_offset, link_len, l)),
+            //`TextLabel2Dest` are cloned and stored in `self.text2dest_label` as `Text2Dest`
+            // and in `self.label2dest` (repacked in a `HashMap`).
+            Link::TextLabel2Dest(tl, d, t) => {
+                self.text2dest_label.push((
+                    link_offset,
+                    link_len,
+                    Link::Text2Dest(tl.clone(), d.clone(), t.clone()),
+                ));
+                self.insert_label2dest(tl, d, t);
+            }
+
+            // `Label2Label` are unpacked and stored in `self.label2label`.
+            Link::Label2Label(from, to) => {
+                let from = anonymous_label(from, &mut self.anonymous_label2x_counter);
+                self.insert_label2label(from, to);
+            }
+
+            // `Label2Dest` are unpacked and stored as `HashMap` in `self.label2dest`:
+            Link::Label2Dest(l, d, t) => {
+                let l = anonymous_label(l, &mut self.anonymous_label2x_counter);
+                // Some want to have link reference definitions clickable
+                // too. Strictly speaking they are not links, this is why
+                // this is optional.
+                if render_label2dest {
+                    self.text2dest_label.push((
                         link_offset,
                         link_len,
-                        Link::Text2Dest(tl.clone(), d.clone(), t.clone()),
+                        Link::Text2Dest(
+                            Cow::from(&input[link_offset..link_offset + link_len]),
+                            d.clone(),
+                            t.clone(),
+                        ),
                     ));
+                };
+                self.insert_label2dest(l, d, t);
+            }
+            _ => unreachable!(),
+        };
+    }
 
-                    // Silently ignore when overwriting a key that exists already.
-                    hc.label2dest.insert(tl, (d, t));
-                }
-
-                // `Label2Label` are unpacked and stored in `hc.label2label`.
-                Link::Label2Label(mut from, to) => {
-                    if from == "_" {
-                        anonymous_label2x_counter += 1;
-                        from = Cow::Owned(format!("_{}", anonymous_label2x_counter));
-                    }
-                    hc.label2label.push((from, to));
-                }
-
-                // `Label2Dest` are unpacked and stored as `HashMap` in `hc.label2dest`:
-                Link::Label2Dest(mut l, d, t) => {
-                    if l == "_" {
-                        anonymous_label2x_counter += 1;
-                        l = Cow::Owned(format!("_{}", anonymous_label2x_counter));
-                    }
-                    // Some want to have link reference definitions clickable
-                    // too. Strictly speaking they are not links, this is why
-                    // this is optional.
-                    if render_label2dest {
-                        let link_offset = input_idx + skipped.len();
-                        let link_len = i.len() - j.len() - skipped.len();
-                        hc.text2dest_label.push((
-                            link_offset,
-                            link_len,
-                            Link::Text2Dest(
-                                Cow::from(&input[link_offset..link_offset + link_len]),
-                                d.clone(),
-                                t.clone(),
-                            ),
-                        ));
-                    };
+    /// Reads through the whole `input`, but stores only the link reference
+    /// definitions: `Link::Label2Dest`, `Link::TextLabel2Dest` and
+    /// `Link::Label2Label`. All other links are dropped, so that the memory
+    /// footprint depends only on the number of definitions, not on the size
+    /// of `input`. `options` must be the ones of the links, that are
+    /// resolved against the definitions, so that both split `input` the same
+    /// way.
+    #[inline]
+    fn definitions(input: &'a str, options: &ParserOptions) -> Self {
+        let mut i = input;
+        let mut hc = MarkupLinkCollection::new();
 
-                    // Silently ignore when overwriting a key that exists already.
-                    hc.label2dest.insert(l, (d, t));
+        while let Ok((j, (_, res))) = take_link_with(i, options) {
+            match res {
+                Link::TextLabel2Dest(tl, d, t) => hc.insert_label2dest(tl, d, t),
+                // With `render_label2dest == false`, these are not stored in
+                // `hc.text2dest_label`.
+                l @ (Link::Label2Label(_, _) | Link::Label2Dest(_, _, _)) => {
+                    hc.insert(input, 0, 0, l, false)
                 }
-                _ => unreachable!(),
+                _ => {}
             };
-
-            // Prepare next iteration.
-            input_idx += i.len() - j.len();
             i = j;
         }
 
         hc
     }
 
+    /// Stores a link reference definition in `self.label2dest`.
+    fn insert_label2dest(&mut self, label: Cow<'a, str>, dest: Cow<'a, str>, title: Cow<'a, str>) {
+        // Silently ignore when overwriting a key that exists already.
+        if let Some(label) = normalize_label(label) {
+            self.label2dest.insert(label, (dest, title));
+        }
+    }
+
+    /// Stores a reference alias in `self.label2label`.
+    fn insert_label2label(&mut self, from: Cow<'a, str>, to: Cow<'a, str>) {
+        if let (Some(from), Some(to)) = (normalize_label(from), normalize_label(to)) {
+            self.label2label.push((from, to));
+        }
+    }
+
     /// Takes one by one, one item from `HyperlinkCollection::label2label` and
     /// searches the corresponding label in `HyperlinkCollection::label2dest`.
     /// When found, add a new item to `HyperlinkCollection::label2dest`. Continue
@@ -166,53 +328,102 @@ impl<'a> MarkupLinkCollection<'a> {
     /// label in `HyperlinkCollection::label2dest`. The associated
     /// `Link::Text2Label` and `Link::Label2Dest` are resolved into a new
     /// `Link::Text2Dest` object. Then the item form the fist list is replaced by
-    /// this new object. After this operation the
+    /// this new object. `Link::Image2Label` objects are resolved the same way
+    /// into `Link::Image` objects. After this operation the
     /// `HyperlinkCollection::text2text_label` list contains only
-    /// `Link::Text2Dest` and `Link::Image2Dest` objects (and some unresolvable
-    /// `Link::Text2Label` objects).
+    /// `Link::Text2Dest`, `Link::Image2Dest` and `Link::Image` objects (and some
+    /// unresolvable `Link::Text2Label` and `Link::Image2Label` objects).
     #[inline]
     fn resolve_text2label_references(&mut self) {
-        let mut idx = 0;
-        while idx < self.text2dest_label.len() {
-            // If we can not resolve the label, we just skip it.
-            if let (input_offset, len, Link::Text2Label(text, label)) = &self.text2dest_label[idx] {
-                if let Some((dest, title)) = &self.label2dest.get(&*label) {
-                    let new_link = if text == "" {
-                        (
-                            *input_offset,
-                            *len,
-                            Link::Text2Dest(dest.clone(), dest.clone(), title.clone()),
-                        )
-                    } else {
-                        (
-                            *input_offset,
-                            *len,
-                            Link::Text2Dest(text.clone(), dest.clone(), title.clone()),
-                        )
-                    };
-                    self.text2dest_label[idx] = new_link;
-                };
-            };
-            // We advance in the loop because we increment `idx`.
-            idx += 1;
+        self.text2dest_label = take(&mut self.text2dest_label)
+            .into_iter()
+            .map(|(input_offset, len, link)| (input_offset, len, self.resolve(link)))
+            .collect();
+    }
+
+    /// Resolves a `Link::Text2Label` into a `Link::Text2Dest` and a
+    /// `Link::Image2Label` into a `Link::Image` by searching its label in
+    /// `HyperlinkCollection::label2dest`. When the label can not be found, or
+    /// for any other link type, `link` is returned unchanged.
+    fn resolve(&self, link: Link<'a>) -> Link<'a> {
+        match link {
+            Link::Text2Label(text, label) => {
+                match normalize_label(label.clone()).and_then(|l| self.label2dest.get(&l)) {
+                    Some((dest, title)) if text.is_empty() => {
+                        Link::Text2Dest(dest.clone(), dest.clone(), title.clone())
+                    }
+                    Some((dest, title)) => Link::Text2Dest(text, dest.clone(), title.clone()),
+                    None => Link::Text2Label(text, label),
+                }
+            }
+            Link::Image2Label(alt, label) => {
+                match normalize_label(label.clone()).and_then(|l| self.label2dest.get(&l)) {
+                    Some((src, _)) => Link::Image(alt, src.clone()),
+                    None => Link::Image2Label(alt, label),
+                }
+            }
+            l => l,
         }
     }
 }
 
+/// Replaces the anonymous label `_` by a numbered label `_1`, `_2`, ...
+/// `counter` holds the number of anonymous labels seen so far.
+fn anonymous_label<'a>(label: Cow<'a, str>, counter: &mut usize) -> Cow<'a, str> {
+    if label == "_" {
+        *counter += 1;
+        Cow::Owned(format!("_{}", counter))
+    } else {
+        label
+    }
+}
+
+/// Normalizes a link label as described in the [CommonMark
+/// Spec](https://spec.commonmark.org/0.30/#matches): the label is case
+/// folded, leading and trailing whitespace is stripped and consecutive
+/// internal whitespace is collapsed to a single space. Two labels match, if
+/// their normalized forms are equal. Returns `None` if the label has more
+/// than `LABEL_LEN_MAX` characters: such a label can not match.
+pub(crate) fn normalize_label(label: Cow<'_, str>) -> Option<Cow<'_, str>> {
+    if label.chars().count() > LABEL_LEN_MAX {
+        return None;
+    }
+
+    let is_normalized = label.chars().all(|c| !c.is_uppercase() && c != 'ß')
+        && !label.starts_with(char::is_whitespace)
+        && !label.ends_with(char::is_whitespace)
+        && !label.contains(|c: char| c.is_whitespace() && c != ' ')
+        && !label.contains("  ");
+    if is_normalized {
+        return Some(label);
+    }
+
+    let collapsed = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Lower case first, then upper case: this is how Unicode case folding
+    // maps e.g. `ẞ` and `SS` to the same string.
+    Some(Cow::Owned(
+        collapsed.to_lowercase().to_uppercase().to_lowercase(),
+    ))
+}
+
 #[derive(Debug, PartialEq)]
 /// The interator's state.
 enum Status<'a> {
     /// Initial state. Iterator is not started.
     Init,
+    /// The URLs of the front matter are returned. The tuple holds the
+    /// remaining URLs in reverse order, the end of the last returned URL and
+    /// the end of the front matter.
+    FrontMatter(Vec<(Span, Link<'a>)>, usize, usize),
     /// So far only `Text2Dest` and `Image2Dest` links are coming, no links
     /// need to be resolved.
     DirectSearch(&'a str),
-    /// As soon as the first reference appears, the remaining text is read and
-    /// all links are resolved. The tuple describes a resolved link. The first
-    /// integer index points to the first byte of the link in `self.input`, the
-    /// second interger is the lenght of the link in `input` bytes. Then follows
-    /// the `Link`.
-    ResolvedLinks(Vec<(usize, usize, Link<'a>)>),
+    /// As soon as the first reference appears, the remaining text is read
+    /// once to collect the link reference definitions only. Then the links
+    /// are parsed and resolved one by one on demand. The tuple holds the
+    /// remaining text input, the collected definitions and the number of
+    /// anonymous references seen so far.
+    ResolvedLinks(&'a str, MarkupLinkCollection<'a>, usize),
     /// All links have been returned. From now on only `None` are returned.
     End,
 }
@@ -351,6 +562,24 @@ pub struct MarkupLink<'a> {
     /// link: with the full link reference definition's source as _link text_ and
     /// the definition's destination as _link destination_.
     render_label: bool,
+    /// If true, link destinations and image sources are normalized with
+    /// `url::url_normalize()`.
+    normalize_urls: bool,
+    /// If true, reference links and images, that can not be resolved, are
+    /// returned too.
+    unresolved_references: bool,
+    /// Options passed to the parser `parse::take_link_lang_with()`.
+    parser_options: ParserOptions,
+    /// Number of Wikitext links without link text returned so far, see
+    /// `parser::wikitext::wikitext_number_link()`.
+    wikitext_counter: usize,
+    /// If true, the front matter of the input is not searched for links.
+    skip_front_matter: bool,
+    /// If true, the URLs of the front matter are returned.
+    front_matter_links: bool,
+    /// Length of the skipped input before the remaining input of
+    /// `Status::DirectSearch`, that is not returned yet: the front matter.
+    pending_skip: usize,
 }
 
 /// Constructor for the `MarkupLink` struct.
@@ -406,6 +635,146 @@ impl<'a> MarkupLink<'a> {
             last_output_offset: 0,
             last_output_len: 0,
             render_label,
+            normalize_urls: false,
+            unresolved_references: false,
+            parser_options: ParserOptions::default(),
+            wikitext_counter: 0,
+            skip_front_matter: true,
+            front_matter_links: false,
+            pending_skip: 0,
+        }
+    }
+
+    /// If `normalize_urls` is true, the iterator normalizes all link
+    /// destinations and image sources with `url::url_normalize()`, e.g. to
+    /// deduplicate links across documents.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::iterator::MarkupLink;
+    /// use std::borrow::Cow;
+    ///
+    /// let i = "abc[text1](HTTPS://GETREU.net:443)abc";
+    ///
+    /// let mut iter = MarkupLink::new(i, false).normalize_urls(true);
+    /// assert_eq!(iter.next().unwrap().1, Link::Text2Dest(Cow::from("text1"), Cow::from("https://getreu.net/"), Cow::from("")));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn normalize_urls(mut self, normalize_urls: bool) -> Self {
+        self.normalize_urls = normalize_urls;
+        self
+    }
+
+    /// By default, reference links and reference images, whose label has no
+    /// matching link reference definition, are skipped. If
+    /// `unresolved_references` is true, the iterator returns them as
+    /// `Link::Text2Label` and `Link::Image2Label`. Anonymous labels are
+    /// numbered in order of appearance: `_1`, `_2`, ...
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::iterator::MarkupLink;
+    /// use std::borrow::Cow;
+    ///
+    /// let i = "abc[text1][label1]abc[text2](dest2)";
+    ///
+    /// let mut iter = MarkupLink::new(i, false).unresolved_references(true);
+    /// assert_eq!(iter.next().unwrap().1, Link::Text2Label(Cow::from("text1"), Cow::from("label1")));
+    /// assert_eq!(iter.next().unwrap().1, Link::Text2Dest(Cow::from("text2"), Cow::from("dest2"), Cow::from("")));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn unresolved_references(mut self, unresolved_references: bool) -> Self {
+        self.unresolved_references = unresolved_references;
+        self
+    }
+
+    /// Sets the options of the link parser, e.g. to recognize Sphinx
+    /// cross-references or to strip the inline markup of Markdown link texts.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::parser::parse::{LinkText, ParserOptions};
+    /// use parse_hyperlinks::iterator::MarkupLink;
+    /// use std::borrow::Cow;
+    ///
+    /// let i = "abc[**text1**][label1]abc\n[label1]: dest1\n";
+    /// let options = ParserOptions {
+    ///     link_text: LinkText::Plain,
+    ///     ..ParserOptions::default()
+    /// };
+    ///
+    /// let mut iter = MarkupLink::new(i, false).parser_options(options);
+    /// assert_eq!(iter.next().unwrap().1, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from("")));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn parser_options(mut self, parser_options: ParserOptions) -> Self {
+        self.parser_options = parser_options;
+        self
+    }
+
+    /// By default, a YAML or TOML front matter at the start of the input, see
+    /// `front_matter::front_matter()`, is skipped: its `key: value` lines
+    /// are easily mistaken for reStructuredText or Asciidoc links. If
+    /// `skip_front_matter` is false, the front matter is parsed like the rest
+    /// of the input.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::iterator::MarkupLink;
+    /// use std::borrow::Cow;
+    ///
+    /// let i = "---\nsummary: `a <b>`__\n---\n[text](dest)";
+    ///
+    /// let mut iter = MarkupLink::new(i, false);
+    /// let ((skipped, _, _), link) = iter.next().unwrap();
+    /// assert_eq!(skipped, "---\nsummary: `a <b>`__\n---\n");
+    /// assert_eq!(link, Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")));
+    /// assert_eq!(iter.next(), None);
+    ///
+    /// let mut iter = MarkupLink::new(i, false).skip_front_matter(false);
+    /// assert_eq!(iter.next().unwrap().1, Link::Text2Dest(Cow::from("a"), Cow::from("b"), Cow::from("")));
+    /// ```
+    #[inline]
+    pub fn skip_front_matter(mut self, skip_front_matter: bool) -> Self {
+        self.skip_front_matter = skip_front_matter;
+        self
+    }
+
+    /// If `front_matter_links` is true, the URLs of the skipped front matter,
+    /// see `front_matter::front_matter_links()`, are returned first, as
+    /// `Text2Dest` links with the URL as _link text_.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::iterator::MarkupLink;
+    /// use std::borrow::Cow;
+    ///
+    /// let i = "---\nurl: https://a.org\n---\n[text](dest)";
+    ///
+    /// let mut iter = MarkupLink::new(i, false).front_matter_links(true);
+    /// let ((skipped, consumed, _), link) = iter.next().unwrap();
+    /// assert_eq!((skipped, consumed), ("---\nurl: ", "https://a.org"));
+    /// assert_eq!(link, Link::Text2Dest(Cow::from("https://a.org"), Cow::from("https://a.org"), Cow::from("")));
+    /// let ((skipped, _, _), _) = iter.next().unwrap();
+    /// assert_eq!(skipped, "\n---\n");
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn front_matter_links(mut self, front_matter_links: bool) -> Self {
+        self.front_matter_links = front_matter_links;
+        self
+    }
+
+    /// Numbers Wikitext links without link text as MediaWiki does: their
+    /// link text becomes `[1]`, `[2]`, ...
+    fn number_wikitext_link(&mut self, link: Link<'a>, lang: MarkupLang) -> Link<'a> {
+        if lang == MarkupLang::Wikitext {
+            wikitext_number_link(link, &mut self.wikitext_counter)
+        } else {
+            link
         }
     }
 }
@@ -432,14 +801,16 @@ impl<'a> Iterator for MarkupLink<'a> {
     ///    some reference link, e.g. `Text2label`, `Label2Dest` or
     ///    `Label2Label` link, it switches into `Status::ResolvedLinks` mode.
     ///    The transition happens as follows:
-    ///    1. The `next()` method consumes all the remaining `input` and
-    ///       calls the `populate_collection()`,
-    ///       `resolve_label2label_references()` and
-    ///       `resolve_text2label_references()` methods.
+    ///    1. The `next()` method reads through the remaining `input` once
+    ///       and collects only the link reference definitions with
+    ///       `MarkupLinkCollection::definitions()`. Then it calls
+    ///       `resolve_label2label_references()`.
     ///       From now on,
-    ///    2. the `next()` method outputs and deletes
-    ///       `HyperlinkCollection::Dest2Text_label[0]`.
-    ///       Not resolved `Text2Label` are ignored.
+    ///    2. the `next()` method parses the remaining `input` link by link,
+    ///       resolves each reference with `MarkupLinkCollection::resolve()`
+    ///       and outputs it. Not resolved `Text2Label` are ignored.
+    ///       No other links are kept in memory, which makes the iterator
+    ///       suitable for very large inputs.
     fn next(&mut self) -> Option<Self::Item> {
         let mut output = None;
         let mut status = Status::Init;
@@ -450,24 +821,60 @@ impl<'a> Iterator for MarkupLink<'a> {
         while again {
             status = match status {
                 // Advance state machine and match one more time.
-                Status::Init => Status::DirectSearch(self.input),
+                Status::Init => {
+                    let end = match front_matter(self.input) {
+                        Some((_, span)) if self.skip_front_matter => span.end,
+                        _ => 0,
+                    };
+                    self.pending_skip = end;
+                    if self.front_matter_links && end > 0 {
+                        let mut links = front_matter_links(self.input);
+                        links.reverse();
+                        Status::FrontMatter(links, 0, end)
+                    } else {
+                        Status::DirectSearch(&self.input[end..])
+                    }
+                }
+
+                Status::FrontMatter(mut links, last_end, end) => match links.pop() {
+                    Some((span, link)) => {
+                        let skipped = &self.input[last_end..span.start];
+                        let consumed = &self.input[span.clone()];
+                        let link = match link {
+                            Link::Label2Dest(_, d, t) => Link::Text2Dest(Cow::from(consumed), d, t),
+                            l => l,
+                        };
+                        output = Some(((skipped, consumed, &self.input[span.end..]), link));
+                        self.pending_skip = end - span.end;
+                        again = false;
+                        Status::FrontMatter(links, span.end, end)
+                    }
+                    None => Status::DirectSearch(&self.input[end..]),
+                },
 
                 Status::DirectSearch(input) => {
                     // We stay in direct mode.
-                    match take_link(input) {
-                        Ok((remaining_input, (skipped, link)))
-                            if match link {
-                                Link::Text2Dest(_, _, _) => true,
-                                Link::Image2Dest(_, _, _, _, _, _) => true,
-                                Link::Image(_, _) => true,
-                                _ => false,
-                            } =>
+                    match take_link_lang_with(input, &self.parser_options) {
+                        Ok((remaining_input, (skipped, link, lang)))
+                            if matches!(
+                                link,
+                                Link::Text2Dest(_, _, _)
+                                    | Link::Image2Dest(_, _, _, _, _, _)
+                                    | Link::Image(_, _)
+                            ) =>
                         {
                             let consumed =
                                 &input[skipped.len()..input.len() - remaining_input.len()];
+                            let link = self.number_wikitext_link(link, lang);
+                            // The skipped front matter is returned with the
+                            // first link.
+                            let start =
+                                self.input.len() - input.len() - take(&mut self.pending_skip);
+                            let skipped =
+                                &self.input[start..self.input.len() - input.len() + skipped.len()];
                             // Assinig output.
                             output = Some(((skipped, consumed, remaining_input), link));
-                            debug_assert_eq!(input, {
+                            debug_assert_eq!(&self.input[start..], {
                                 let mut s = "".to_string();
                                 s.push_str(skipped);
                                 s.push_str(consumed);
@@ -480,59 +887,73 @@ impl<'a> Iterator for MarkupLink<'a> {
                         }
                         _ => {
                             // We switch to resolving mode.
-                            self.input = input;
-                            let mut hc = MarkupLinkCollection::from(input, self.render_label);
+                            self.input = &self.input
+                                [self.input.len() - input.len() - take(&mut self.pending_skip)..];
+                            let mut hc =
+                                MarkupLinkCollection::definitions(input, &self.parser_options);
                             hc.resolve_label2label_references();
-                            hc.resolve_text2label_references();
-                            let mut resolved_links = Vec::new();
-                            swap(&mut hc.text2dest_label, &mut resolved_links);
 
                             // Advance state machine and match one more time.
-                            Status::ResolvedLinks(resolved_links)
+                            Status::ResolvedLinks(input, hc, 0)
                         }
                     }
                 }
 
-                Status::ResolvedLinks(mut resolved_links) => {
-                    while !resolved_links.is_empty() {
-                        // if let (input_offset, len, Link::Text2Dest(te, de, ti)) =
-                        //     resolved_links.remove(0)
-                        // Ok((remaining_input, (skipped, link)))
-                        match resolved_links.remove(0) {
-                            (input_offset, len, link)
-                                if match link {
-                                    Link::Text2Dest(_, _, _) => true,
-                                    Link::Image2Dest(_, _, _, _, _, _) => true,
-                                    Link::Image(_, _) => true,
-                                    _ => false,
-                                } =>
-                            {
-                                let skipped = &self.input[(self.last_output_offset
-                                    + self.last_output_len)
-                                    ..input_offset];
-                                let consumed = &self.input[input_offset..input_offset + len];
-                                let remaining_input = &self.input[input_offset + len..];
-                                // Assign output.
-                                output = Some(((skipped, consumed, remaining_input), link));
-                                debug_assert_eq!(self.input, {
-                                    let mut s = (&self.input
-                                        [..self.last_output_offset + self.last_output_len])
-                                        .to_string();
-                                    s.push_str(skipped);
-                                    s.push_str(consumed);
-                                    s.push_str(remaining_input);
-                                    s
-                                });
-                                self.last_output_offset = input_offset;
-                                self.last_output_len = len;
-                                break;
+                Status::ResolvedLinks(mut input, hc, mut anonymous_text2label_counter) => {
+                    while let Ok((remaining_input, (skipped, link, lang))) =
+                        take_link_lang_with(input, &self.parser_options)
+                    {
+                        let input_offset = self.input.len() - input.len() + skipped.len();
+                        let len = input.len() - remaining_input.len() - skipped.len();
+                        input = remaining_input;
+                        let link = match link {
+                            l @ (Link::Text2Dest(_, _, _)
+                            | Link::Image2Dest(_, _, _, _, _, _)
+                            | Link::Image(_, _)) => l,
+                            Link::Text2Label(text, label) => {
+                                let label =
+                                    anonymous_label(label, &mut anonymous_text2label_counter);
+                                hc.resolve(Link::Text2Label(text, label))
                             }
-                            _ => {}
+                            l @ Link::Image2Label(_, _) => hc.resolve(l),
+                            Link::TextLabel2Dest(tl, d, t) => Link::Text2Dest(tl, d, t),
+                            Link::Label2Dest(_, d, t) if self.render_label => Link::Text2Dest(
+                                Cow::from(&self.input[input_offset..input_offset + len]),
+                                d,
+                                t,
+                            ),
+                            // Definitions and not resolvable references are not
+                            // returned.
+                            _ => continue,
                         };
+                        let is_unresolved =
+                            matches!(link, Link::Text2Label(_, _) | Link::Image2Label(_, _));
+                        if is_unresolved && !self.unresolved_references {
+                            continue;
+                        }
+                        let link = self.number_wikitext_link(link, lang);
+
+                        let skipped = &self.input
+                            [(self.last_output_offset + self.last_output_len)..input_offset];
+                        let consumed = &self.input[input_offset..input_offset + len];
+                        // Assign output.
+                        output = Some(((skipped, consumed, remaining_input), link));
+                        debug_assert_eq!(self.input, {
+                            let mut s = self.input
+                                [..self.last_output_offset + self.last_output_len]
+                                .to_string();
+                            s.push_str(skipped);
+                            s.push_str(consumed);
+                            s.push_str(remaining_input);
+                            s
+                        });
+                        self.last_output_offset = input_offset;
+                        self.last_output_len = len;
+                        break;
                     }
                     again = false;
                     if output.is_some() {
-                        Status::ResolvedLinks(resolved_links)
+                        Status::ResolvedLinks(input, hc, anonymous_text2label_counter)
                     } else {
                         Status::End
                     }
@@ -546,10 +967,383 @@ impl<'a> Iterator for MarkupLink<'a> {
             }
         }
         swap(&mut status, &mut self.status);
-        output
+        if self.normalize_urls {
+            output.map(|(split, link)| (split, normalize_link(link)))
+        } else {
+            output
+        }
+    }
+}
+
+/// The resolved _link reference definitions_ of some input text: a map from
+/// _link label_ to `(link_destination, link_title)`.
+///
+/// The map is built from all `Label2Dest` and `TextLabel2Dest` links. `Label2Label`
+/// reference aliases are resolved and appear as additional labels. The labels
+/// are stored normalized (see `normalize_label()`), and so are the queries of
+/// `LabelMap::get()`. Anonymous labels are numbered in order of appearance:
+/// `_1`, `_2`, ...
+///
+/// ```
+/// use parse_hyperlinks::iterator::LabelMap;
+/// use std::borrow::Cow;
+///
+/// let i = r#"abc[text1][Label1]abc
+/// [label1]: dest1 "title1"
+/// .. _label2: `label1`_
+/// "#;
+///
+/// let lm = LabelMap::from(i);
+/// assert_eq!(lm.len(), 2);
+/// assert_eq!(lm.get("LABEL1"), Some(&(Cow::from("dest1"), Cow::from("title1"))));
+/// assert_eq!(lm.get("label2"), Some(&(Cow::from("dest1"), Cow::from("title1"))));
+/// assert_eq!(lm.get("text1"), None);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct LabelMap<'a> {
+    /// Key: normalized `link_label`, value: `(link_destination, link_title)`.
+    map: HashMap<Cow<'a, str>, (Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> LabelMap<'a> {
+    /// Returns `(link_destination, link_title)` of the link reference
+    /// definition matching `label`.
+    pub fn get(&self, label: &str) -> Option<&(Cow<'a, str>, Cow<'a, str>)> {
+        normalize_label(Cow::Borrowed(label)).and_then(|label| self.map.get(&*label))
+    }
+
+    /// Number of labels in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// True if the map contains no labels.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterator over all `(link_label, (link_destination, link_title))`
+    /// entries in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Cow<'a, str>, &(Cow<'a, str>, Cow<'a, str>))> {
+        self.map.iter()
+    }
+
+    /// Returns the underlying `HashMap`.
+    pub fn into_inner(self) -> HashMap<Cow<'a, str>, (Cow<'a, str>, Cow<'a, str>)> {
+        self.map
+    }
+}
+
+impl<'a> From<&'a str> for LabelMap<'a> {
+    /// Reads through the whole `input` and collects all link reference
+    /// definitions.
+    fn from(input: &'a str) -> Self {
+        let mut hc = MarkupLinkCollection::from(input, false);
+        hc.resolve_label2label_references();
+        Self { map: hc.label2dest }
+    }
+}
+
+/// Returns all _reference links_ (`Text2Label`) and _reference images_
+/// (`Image2Label`) of `input`, that can not be resolved, because no matching
+/// _link reference definition_ exists. Each item is `(link_text, link_label,
+/// span)`, where `span` is the link's byte range in `input`. For reference
+/// images, `link_text` is the image's alt text. Anonymous labels are numbered in
+/// order of appearance: `_1`, `_2`, ...
+///
+/// ```
+/// use parse_hyperlinks::iterator::broken_references;
+/// use std::borrow::Cow;
+///
+/// let i = r#"abc[text1][label1]abc[text2][label2]abc
+/// [label1]: dest1
+/// "#;
+///
+/// let r = broken_references(i);
+/// assert_eq!(r, vec![(Cow::from("text2"), Cow::from("label2"), 21..36)]);
+/// assert_eq!(&i[r[0].2.clone()], "[text2][label2]");
+/// ```
+pub fn broken_references(input: &str) -> Vec<(Cow<'_, str>, Cow<'_, str>, Span)> {
+    let mut hc = MarkupLinkCollection::from(input, false);
+    hc.resolve_label2label_references();
+    hc.resolve_text2label_references();
+    hc.text2dest_label
+        .into_iter()
+        .filter_map(|(offset, len, link)| match link {
+            Link::Text2Label(text, label) | Link::Image2Label(text, label) => {
+                Some((text, label, offset..offset + len))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns all _link reference definitions_ (`Label2Dest` and the alias of
+/// `Label2Label`) of `input`, that no reference link, reference image or
+/// alias refers to. Each item is `(link_label, span)`, where `span` is the
+/// definition's byte range in `input`. Anonymous labels are numbered in
+/// order of appearance: `_1`, `_2`, ... An anonymous definition is unused,
+/// when there are fewer anonymous references than anonymous definitions.
+///
+/// ```
+/// use parse_hyperlinks::iterator::unused_definitions;
+/// use std::borrow::Cow;
+///
+/// let i = r#"abc[text1][Label1]abc
+/// [label1]: dest1
+/// [label2]: dest2
+/// "#;
+///
+/// let r = unused_definitions(i);
+/// assert_eq!(r, vec![(Cow::from("label2"), 38..53)]);
+/// assert_eq!(&i[r[0].1.clone()], "[label2]: dest2");
+/// ```
+pub fn unused_definitions(input: &str) -> Vec<(Cow<'_, str>, Span)> {
+    let mut definitions = Vec::new();
+    let mut references = HashSet::new();
+    let mut anonymous_text2label_counter = 0;
+    let mut anonymous_label2x_counter = 0;
+    let mut i = input;
+
+    while let Ok((j, (skipped, link))) = take_link(i) {
+        let offset = input.len() - i.len() + skipped.len();
+        let span = offset..input.len() - j.len();
+        match link {
+            Link::Label2Dest(label, _, _) => {
+                let label = anonymous_label(label, &mut anonymous_label2x_counter);
+                definitions.push((label, span));
+            }
+            Link::Label2Label(from, to) => {
+                let from = anonymous_label(from, &mut anonymous_label2x_counter);
+                definitions.push((from, span));
+                references.extend(normalize_label(to));
+            }
+            Link::Text2Label(_, label) => {
+                let label = anonymous_label(label, &mut anonymous_text2label_counter);
+                references.extend(normalize_label(label));
+            }
+            Link::Image2Label(_, label) => references.extend(normalize_label(label)),
+            _ => {}
+        }
+        i = j;
+    }
+
+    definitions
+        .into_iter()
+        .filter(|(label, _)| {
+            normalize_label(Cow::Borrowed(&**label)).is_some_and(|l| !references.contains(&*l))
+        })
+        .collect()
+}
+
+/// Default for `MarkupLinkStream`'s maximum chunk length in bytes.
+pub const STREAM_CHUNK_LEN_MAX: usize = 1 << 20;
+
+/// Iterator over all hyperlinks of a `BufRead` input, e.g. a large file or a
+/// pipe. Unlike `MarkupLink`, this iterator never holds the whole input in
+/// memory: it reads the input line by line and parses it in chunks ending
+/// at blank lines (paragraphs). A chunk is also cut at the first line end
+/// after `max_len` bytes, which bounds the buffer size for inputs without
+/// blank lines. A link spanning such a cut is not recognized.
+///
+/// As resolving link references requires the whole input, this iterator
+/// returns the links as the parser `parser::parse::take_link()` finds them:
+/// references and link reference definitions are not resolved. Each item
+/// is the link's byte range in the input together with the `Link`.
+///
+/// ```
+/// use parse_hyperlinks::iterator::MarkupLinkStream;
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let i = "abc[text1](dest1)abc\n\nabc[text2][label2]\n[label2]: dest2\n";
+///
+/// let mut iter = MarkupLinkStream::new(i.as_bytes());
+/// let (span, link) = iter.next().unwrap().unwrap();
+/// assert_eq!(&i[span], "[text1](dest1)");
+/// assert_eq!(link, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from("")));
+/// assert_eq!(iter.next().unwrap().unwrap().1, Link::Text2Label(Cow::from("text2"), Cow::from("label2")));
+/// assert_eq!(iter.next().unwrap().unwrap().1, Link::Label2Dest(Cow::from("label2"), Cow::from("dest2"), Cow::from("")));
+/// assert!(iter.next().is_none());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MarkupLinkStream<R> {
+    /// The input.
+    reader: R,
+    /// The chunk currently read, always empty between calls of `next()`.
+    chunk: String,
+    /// Byte offset of `chunk`'s first byte in the input.
+    chunk_offset: usize,
+    /// A chunk is completed at the first line end after `max_len` bytes.
+    max_len: usize,
+    /// Links parsed, but not returned yet.
+    links: VecDeque<(Span, Link<'static>)>,
+    /// True when the end of the input or a read error has been reached.
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> MarkupLinkStream<R> {
+    /// Constructor reading chunks of at most `STREAM_CHUNK_LEN_MAX` bytes
+    /// (plus the rest of the line).
+    pub fn new(reader: R) -> Self {
+        Self::with_max_len(reader, STREAM_CHUNK_LEN_MAX)
+    }
+
+    /// Constructor reading chunks of at most `max_len` bytes (plus the rest
+    /// of the line).
+    pub fn with_max_len(reader: R, max_len: usize) -> Self {
+        Self {
+            reader,
+            chunk: String::new(),
+            chunk_offset: 0,
+            max_len,
+            links: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Reads the next chunk and parses its links into `self.links`.
+    fn read_chunk(&mut self) -> io::Result<()> {
+        loop {
+            let n = self.reader.read_line(&mut self.chunk)?;
+            if n == 0 {
+                self.done = true;
+                break;
+            }
+            let line = &self.chunk[self.chunk.len() - n..];
+            if (line.trim().is_empty() && self.chunk.len() > n) || self.chunk.len() >= self.max_len
+            {
+                break;
+            }
+        }
+
+        let mut i = self.chunk.as_str();
+        while let Ok((j, (skipped, link))) = take_link(i) {
+            let link_offset = self.chunk_offset + self.chunk.len() - i.len() + skipped.len();
+            let link_len = i.len() - j.len() - skipped.len();
+            self.links
+                .push_back((link_offset..link_offset + link_len, link.into_owned()));
+            i = j;
+        }
+        self.chunk_offset += self.chunk.len();
+        self.chunk.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for MarkupLinkStream<R> {
+    type Item = io::Result<(Span, Link<'static>)>;
+    /// Returns the next link or the error, that occurred while reading the
+    /// input. After an error, the iterator returns `None`.
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.links.is_empty() && !self.done {
+            if let Err(e) = self.read_chunk() {
+                self.done = true;
+                self.links.clear();
+                return Some(Err(e));
+            }
+        }
+        self.links.pop_front().map(Ok)
     }
 }
 
+/// Minimum chunk length in bytes, `par_hyperlinks()` splits its input into.
+#[cfg(feature = "rayon")]
+const PAR_CHUNK_LEN_MIN: usize = 1 << 16;
+
+/// Extracts all hyperlinks of `input` in parallel and resolves link
+/// references. The result is the same as `MarkupLink::new(input, false)`
+/// would return, but each link comes with its byte range in `input`. This
+/// function is only available with the `rayon` feature enabled.
+///
+/// The input is split into chunks at blank lines, which no link can span.
+/// The chunks are parsed in parallel, then link reference definitions are
+/// collected and references resolved across all chunks.
+///
+/// ```
+/// use parse_hyperlinks::iterator::par_hyperlinks;
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let i = "abc[text1][label1]abc\n\n[text2](dest2)\n\n[label1]: dest1\n";
+///
+/// let links = par_hyperlinks(i);
+/// assert_eq!(links.len(), 2);
+/// assert_eq!(&i[links[0].0.clone()], "[text1][label1]");
+/// assert_eq!(links[0].1, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from("")));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_hyperlinks(input: &str) -> Vec<(Span, Link<'_>)> {
+    let chunk_len = input.len() / (rayon::current_num_threads() * 4);
+    par_hyperlinks_chunked(input, chunk_len.max(PAR_CHUNK_LEN_MIN))
+}
+
+/// Implements `par_hyperlinks()` with chunks of at least `chunk_len` bytes.
+#[cfg(feature = "rayon")]
+fn par_hyperlinks_chunked(input: &str, chunk_len: usize) -> Vec<(Span, Link<'_>)> {
+    use rayon::prelude::*;
+
+    let chunks: Vec<Vec<(usize, usize, Link)>> = split_at_blank_lines(input, chunk_len)
+        .into_par_iter()
+        .map(|(chunk_offset, chunk)| {
+            let mut links = Vec::new();
+            let mut i = chunk;
+            while let Ok((j, (skipped, link))) = take_link(i) {
+                let link_offset = chunk_offset + chunk.len() - i.len() + skipped.len();
+                links.push((link_offset, i.len() - j.len() - skipped.len(), link));
+                i = j;
+            }
+            links
+        })
+        .collect();
+
+    // Anonymous labels are numbered in order of appearance, so the links
+    // must be inserted sequentially.
+    let mut hc = MarkupLinkCollection::new();
+    let front_matter_end = front_matter(input).map_or(0, |(_, span)| span.end);
+    for (link_offset, link_len, link) in chunks.into_iter().flatten() {
+        if link_offset < front_matter_end {
+            continue;
+        }
+        hc.insert(input, link_offset, link_len, link, false);
+    }
+    hc.resolve_label2label_references();
+    hc.resolve_text2label_references();
+    hc.text2dest_label
+        .into_iter()
+        .filter(|(_, _, link)| {
+            matches!(
+                link,
+                Link::Text2Dest(_, _, _) | Link::Image2Dest(_, _, _, _, _, _) | Link::Image(_, _)
+            )
+        })
+        .map(|(link_offset, link_len, link)| (link_offset..link_offset + link_len, link))
+        .collect()
+}
+
+/// Splits `input` into chunks of at least `min_len` bytes. Each chunk,
+/// except the last, ends with a blank line. Returns the chunks with their
+/// byte offset in `input`.
+#[cfg(feature = "rayon")]
+fn split_at_blank_lines(input: &str, min_len: usize) -> Vec<(usize, &str)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        let from = (start + min_len).min(input.len());
+        // `\n` is ASCII, so `end` is always a char boundary.
+        let end = input.as_bytes()[from..]
+            .windows(2)
+            .position(|w| w == b"\n\n")
+            .map_or(input.len(), |n| from + n + 2);
+        chunks.push((start, &input[start..end]));
+        start = end;
+    }
+    chunks
+}
+
 /// Recognizes hyperlinks in all supported markup languages
 /// and returns the first hyperlink found as
 /// `Some(Link::Text2Dest` or `Some(Link::Image2Dest)`.
@@ -568,14 +1362,227 @@ impl<'a> Iterator for MarkupLink<'a> {
 /// let r = find_first(i);
 /// assert_eq!(r, Some(Link::Text2Dest(Cow::from("t"), Cow::from("v"), Cow::from("w"))));
 /// ```
-pub fn find_first(i: &str) -> Option<Link> {
+pub fn find_first(i: &str) -> Option<Link<'_>> {
     MarkupLink::new(i, false).next().map(|(_, l)| l)
 }
 
+/// Same as `find_first()`, but the link comes with its byte range in
+/// `input`, e.g. to open the link under the cursor.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::iterator::first_link;
+/// use std::borrow::Cow;
+///
+/// let i = "abc![alt](src)abc";
+///
+/// let (span, link) = first_link(i).unwrap();
+/// assert_eq!(span, 3..14);
+/// assert_eq!(link, Link::Image(Cow::from("alt"), Cow::from("src")));
+/// assert_eq!(first_link("abc"), None);
+/// ```
+pub fn first_link(input: &str) -> Option<(Span, Link<'_>)> {
+    MarkupLink::new(input, false)
+        .next()
+        .map(|((_, consumed, remaining), link)| {
+            let end = input.len() - remaining.len();
+            (end - consumed.len()..end, link)
+        })
+}
+
+/// Returns the link containing the byte `offset` of `input`, e.g. the link
+/// under the cursor, together with its byte range in `input`. Link
+/// references are resolved and link reference definitions are returned like
+/// `MarkupLink::new(input, true)` does. Returns `None`, if there is no link
+/// at `offset` or the reference can not be resolved.
+///
+/// As links never span blank lines, only the paragraph around `offset` is
+/// parsed. The whole `input` is parsed only for references and definitions,
+/// to resolve them.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::iterator::link_at;
+/// use std::borrow::Cow;
+///
+/// let i = "abc[text1](dest1) [text2][label2]\n\n[label2]: dest2\n";
+///
+/// assert_eq!(
+///     link_at(i, 5),
+///     Some((3..17, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from(""))))
+/// );
+/// assert_eq!(
+///     link_at(i, 18),
+///     Some((18..33, Link::Text2Dest(Cow::from("text2"), Cow::from("dest2"), Cow::from(""))))
+/// );
+/// assert_eq!(link_at(i, 17), None);
+/// ```
+pub fn link_at(input: &str, offset: usize) -> Option<(Span, Link<'_>)> {
+    let front_matter_end = front_matter(input).map_or(0, |(_, span)| span.end);
+    if offset < front_matter_end || offset >= input.len() || !input.is_char_boundary(offset) {
+        return None;
+    }
+    // `\n` is ASCII, so `start` and `end` are always char boundaries.
+    let start = input[..offset]
+        .rfind("\n\n")
+        .map_or(0, |n| n + 2)
+        .max(front_matter_end);
+    let end = input[offset..]
+        .find("\n\n")
+        .map_or(input.len(), |n| offset + n + 2);
+
+    let mut i = &input[start..end];
+    while let Ok((j, (skipped, link, lang))) = take_link_lang(i) {
+        let link_start = end - i.len() + skipped.len();
+        let span = link_start..end - j.len();
+        i = j;
+        if span.end <= offset {
+            continue;
+        }
+        if span.start > offset {
+            return None;
+        }
+        return match link {
+            // Wikitext links may be numbered in order of appearance.
+            l @ (Link::Text2Dest(_, _, _)
+            | Link::Image2Dest(_, _, _, _, _, _)
+            | Link::Image(_, _))
+                if lang != MarkupLang::Wikitext =>
+            {
+                Some((span, l))
+            }
+            _ => MarkupLink::new(input, true)
+                .map(|((_, consumed, remaining), link)| {
+                    let end = input.len() - remaining.len();
+                    (end - consumed.len()..end, link)
+                })
+                .find(|(s, _)| s.start >= span.start)
+                .filter(|(s, _)| *s == span),
+        };
+    }
+    None
+}
+
+/// Iterates over all links of `input` as `parser::parse::take_link_lang()`
+/// finds them, i.e. link references are not resolved. Every link comes with
+/// its byte range in `input` and the markup language, whose grammar matched
+/// it, e.g. to count the links per markup language.
+///
+/// ```
+/// use parse_hyperlinks::iterator::markup_lang_links;
+/// use parse_hyperlinks::parser::MarkupLang;
+///
+/// let i = "abc[text1](dest1)abc https://dest2[text2] abc[text3][label3]";
+///
+/// let langs: Vec<MarkupLang> = markup_lang_links(i).map(|(_, _, lang)| lang).collect();
+/// assert_eq!(langs, [MarkupLang::Markdown, MarkupLang::Adoc, MarkupLang::Markdown]);
+/// ```
+pub fn markup_lang_links(input: &str) -> impl Iterator<Item = (Span, Link<'_>, MarkupLang)> {
+    let mut i = input;
+    iter::from_fn(move || {
+        let (j, (skipped, link, lang)) = take_link_lang(i).ok()?;
+        let start = input.len() - i.len() + skipped.len();
+        i = j;
+        Some((start..input.len() - j.len(), link, lang))
+    })
+}
+
+/// Iterates over all images of `input`, in any markup language, as
+/// `(image_alt, image_src, span)`, e.g. to find images without alt text.
+/// `span` is the byte range in `input` of the image, or of the link the
+/// image is embedded in. Reference images are resolved, those without
+/// matching _link reference definition_ are omitted.
+///
+/// The alt text is the one a renderer shows: without alt text, Asciidoc
+/// images get one derived from the file name and reStructuredText images the
+/// image source.
+///
+/// ```
+/// use parse_hyperlinks::iterator::image_alts;
+/// use std::borrow::Cow;
+///
+/// let i = r#"![](a.png) <img src="b.png" alt="B">
+/// [![c](c.png)](dest) ![d][d]
+///
+/// .. image:: e.png
+///
+/// [d]: d.png
+/// "#;
+///
+/// let alts: Vec<_> = image_alts(i).collect();
+/// assert_eq!(alts, [
+///     (Cow::from(""), Cow::from("a.png"), 0..10),
+///     (Cow::from("B"), Cow::from("b.png"), 11..36),
+///     (Cow::from("c"), Cow::from("c.png"), 37..56),
+///     (Cow::from("d"), Cow::from("d.png"), 57..64),
+///     (Cow::from("e.png"), Cow::from("e.png"), 66..82),
+/// ]);
+/// assert_eq!(alts.iter().filter(|(alt, _, _)| alt.is_empty()).count(), 1);
+/// ```
+pub fn image_alts(input: &str) -> impl Iterator<Item = (Cow<'_, str>, Cow<'_, str>, Span)> {
+    MarkupLink::new(input, false).filter_map(move |((_, consumed, remaining), link)| {
+        let end = input.len() - remaining.len();
+        let span = end - consumed.len()..end;
+        match link {
+            Link::Image(alt, src) | Link::Image2Dest(_, alt, src, _, _, _) => {
+                Some((alt, src, span))
+            }
+            _ => None,
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_line_index() {
+        let i = "a\n\nb😀c\r\nd";
+        let index = LineIndex::new(i);
+        let p = |line, column, column_utf16| Position {
+            line,
+            column,
+            column_utf16,
+        };
+        assert_eq!(index.position(0), p(0, 0, 0));
+        assert_eq!(index.position(1), p(0, 1, 1));
+        assert_eq!(index.position(2), p(1, 0, 0));
+        assert_eq!(index.position(3), p(2, 0, 0));
+        // Inside `😀`.
+        assert_eq!(index.position(5), p(2, 1, 1));
+        assert_eq!(index.position(8), p(2, 2, 3));
+        assert_eq!(index.position(11), p(3, 0, 0));
+        assert_eq!(index.position(100), p(3, 1, 1));
+        assert_eq!(index.span_position(&(0..3)), p(0, 0, 0)..p(2, 0, 0));
+
+        let index = LineIndex::new("");
+        assert_eq!(index.position(0), p(0, 0, 0));
+        assert_eq!(index.links().next(), None);
+    }
+
+    #[test]
+    fn test_normalize_label() {
+        let res = normalize_label(Cow::from("foo bar")).unwrap();
+        assert!(matches!(res, Cow::Borrowed(..)));
+        assert_eq!(res, "foo bar");
+
+        let res = normalize_label(Cow::from(" Foo \t\n BAR ")).unwrap();
+        assert!(matches!(res, Cow::Owned(..)));
+        assert_eq!(res, "foo bar");
+
+        assert_eq!(
+            normalize_label(Cow::from("ẞ")),
+            normalize_label(Cow::from("SS"))
+        );
+        assert_eq!(normalize_label(Cow::from("ΑΓΩ")).unwrap(), "αγω");
+
+        let label = "a".repeat(LABEL_LEN_MAX);
+        assert!(normalize_label(Cow::from(label.as_str())).is_some());
+        let label = "a".repeat(LABEL_LEN_MAX + 1);
+        assert!(normalize_label(Cow::from(label.as_str())).is_none());
+    }
+
     #[test]
     fn test_populate_collection() {
         let i = r#"[md label1]: md_destination1 "md title1"
@@ -1053,6 +2060,60 @@ Some more text."#;
         assert_eq!(item.1, expected);
     }
 
+    #[test]
+    fn test_image_alts() {
+        let i = "![a][x] <a href=\"d\"><img src=\"b.png\" alt=\"\"></a> [t](d) ![c](c.png)";
+        assert_eq!(
+            image_alts(i).collect::<Vec<_>>(),
+            [
+                (Cow::from(""), Cow::from("b.png"), 8..48),
+                (Cow::from("c"), Cow::from("c.png"), 56..67)
+            ]
+        );
+        assert_eq!(image_alts("").count(), 0);
+    }
+
+    #[test]
+    fn test_broken_references() {
+        let i = r#"abc `text1`__ abc `text2`__ abc ![alt3][img3] [text4]
+__ dest1
+.. _img3: `label5`_
+"#;
+        let r = broken_references(i);
+        assert_eq!(
+            r,
+            vec![
+                (Cow::from("text2"), Cow::from("_2"), 18..27),
+                (Cow::from("alt3"), Cow::from("img3"), 32..45),
+                (Cow::from("text4"), Cow::from("text4"), 46..53),
+            ]
+        );
+        assert!(broken_references("[a](b)").is_empty());
+    }
+
+    #[test]
+    fn test_unused_definitions() {
+        let i = r#"abc `text1`__ abc ![alt2][IMG2] abc [text3]
+__ dest1
+__ dest2
+.. _img2: `label4`_
+.. _label4: dest4
+.. _label5: dest5
+[label6]: dest6
+"#;
+        let r = unused_definitions(i);
+        assert_eq!(
+            r,
+            vec![
+                (Cow::from("_2"), 53..61),
+                (Cow::from("label5"), 100..117),
+                (Cow::from("label6"), 118..133),
+            ]
+        );
+        assert_eq!(&i[r[1].1.clone()], ".. _label5: dest5");
+        assert!(unused_definitions("[text1] [text1]: dest1").is_empty());
+    }
+
     #[test]
     fn test_next2() {
         let i = r#"[te\_xt](ur\_l)[te_xt](ur_l)"#;
@@ -1071,4 +2132,317 @@ Some more text."#;
         //eprintln!("item: {:#?}", item);
         assert_eq!(item.1, expected);
     }
+
+    #[test]
+    fn test_next3() {
+        let i = r#"abc[Foo]abc[Foo Bar][]abc[t][FOO
+BAR]
+[foo]: /url1 "title1"
+[foo  bar]: /url2
+"#;
+        let mut iter = MarkupLink::new(i, false);
+
+        let expected = Link::Text2Dest(Cow::from("Foo"), Cow::from("/url1"), Cow::from("title1"));
+        assert_eq!(iter.next().unwrap().1, expected);
+        let expected = Link::Text2Dest(Cow::from("Foo Bar"), Cow::from("/url2"), Cow::from(""));
+        assert_eq!(iter.next().unwrap().1, expected);
+        let expected = Link::Text2Dest(Cow::from("t"), Cow::from("/url2"), Cow::from(""));
+        assert_eq!(iter.next().unwrap().1, expected);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_next4() {
+        let i = r#"abc![alt1][img]abc![Alt2][]abc![alt3]abc![alt4][nodef]
+[img]: /img1.png "title1"
+[alt2]: /img2.png
+[ALT3]: /img3.png
+"#;
+        let mut iter = MarkupLink::new(i, false);
+
+        let expected = Link::Image(Cow::from("alt1"), Cow::from("/img1.png"));
+        let item = iter.next().unwrap();
+        assert_eq!(item.0 .0, "abc");
+        assert_eq!(item.0 .1, "![alt1][img]");
+        assert_eq!(item.1, expected);
+        let expected = Link::Image(Cow::from("Alt2"), Cow::from("/img2.png"));
+        assert_eq!(iter.next().unwrap().1, expected);
+        let expected = Link::Image(Cow::from("alt3"), Cow::from("/img3.png"));
+        assert_eq!(iter.next().unwrap().1, expected);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_next5() {
+        let i = r#"abc[a](b)abc `text1`__ abc `text2`__ abc ![alt3][img3] [text4]
+__ dest1
+`text5 <dest5_>`_ abc text6_ [c](d)
+.. _img3: `label5`_
+.. _label5: img3.png
+.. _dest5: dest5
+.. _text6: dest6
+__ dest2
+"#;
+        for render_label in [false, true] {
+            let mut hc = MarkupLinkCollection::from(i, render_label);
+            hc.resolve_label2label_references();
+            hc.resolve_text2label_references();
+            let expected = hc
+                .text2dest_label
+                .into_iter()
+                .filter(|(_, _, l)| matches!(l, Link::Text2Dest(_, _, _) | Link::Image(_, _)))
+                .map(|(offset, len, l)| (&i[offset..offset + len], l))
+                .collect::<Vec<_>>();
+
+            let res = MarkupLink::new(i, render_label)
+                .map(|((_, consumed, _), l)| (consumed, l))
+                .collect::<Vec<_>>();
+            assert_eq!(res, expected);
+        }
+        assert_eq!(MarkupLink::new(i, false).count(), 7);
+    }
+
+    #[test]
+    fn test_next_rst_substitution() {
+        let i = "abc |badge|_ abc |other|__ abc\n\n\
+            .. |badge| image:: https://ci/badge.svg\n   :alt: Build\n\
+            .. _badge: https://ci\n\
+            __ https://other\n";
+        let res = MarkupLink::new(i, false)
+            .map(|(_, l)| l)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            res,
+            [
+                Link::Text2Dest(Cow::from("badge"), Cow::from("https://ci"), Cow::from("")),
+                Link::Text2Dest(
+                    Cow::from("other"),
+                    Cow::from("https://other"),
+                    Cow::from("")
+                ),
+                Link::Image(Cow::from("Build"), Cow::from("https://ci/badge.svg")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_wikitext_numbering() {
+        // In direct mode and in resolving mode, after the reference link.
+        let i = "[https://a] [https://b b] [//c] [d][e] [https://f]\n\n[e]: g\n";
+        let res = MarkupLink::new(i, false)
+            .map(|(_, l)| l.text().unwrap_or_default().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(res, ["[1]", "b", "[2]", "d", "[3]"]);
+    }
+
+    #[test]
+    fn test_next_org() {
+        let i = "See [[tab][the table]] and [[Target]].\n\n#+NAME: tab\n| a |\n<<target>>\n";
+        let options = ParserOptions {
+            markup_langs: crate::parser::MarkupLangs::ALL,
+            ..ParserOptions::default()
+        };
+        let res = MarkupLink::new(i, false)
+            .parser_options(options)
+            .map(|(_, l)| l)
+            .collect::<Vec<_>>();
+        let c = Cow::from;
+        assert_eq!(
+            res,
+            [
+                Link::Text2Dest(c("the table"), c("#tab"), c("")),
+                Link::Text2Dest(c("Target"), c("#target"), c("")),
+            ]
+        );
+        // Without `MarkupLang::Org`, no Org-mode links are found.
+        assert_eq!(MarkupLink::new(i, false).count(), 0);
+    }
+
+    #[test]
+    fn test_next_definitions_parser_options() {
+        // With the default options, the definition is part of the
+        // reStructuredText link `` `a [l]: d <b>`_ ``.
+        let i = "[t][l] `a\n[l]: d\n<b>`_\n";
+        let options = ParserOptions {
+            markup_langs: crate::parser::MarkupLangs::empty().with(MarkupLang::Markdown),
+            ..ParserOptions::default()
+        };
+        let res = MarkupLink::new(i, false)
+            .parser_options(options)
+            .map(|(_, l)| l)
+            .collect::<Vec<_>>();
+        let c = Cow::from;
+        assert_eq!(res, [Link::Text2Dest(c("t"), c("d"), c(""))]);
+    }
+
+    #[test]
+    fn test_markup_link_stream() {
+        let i = r#"abc[a](b)abc `text1`__ abc
+__ dest1
+
+abc<a href="dest2">text2</a>
+
+abc ![alt3](img3.png) [t](d "ti
+tle") ééé
+.. _img3: dest3
+"#;
+        let mut expected = Vec::new();
+        let mut j = i;
+        while let Ok((k, (skipped, link))) = take_link(j) {
+            let offset = i.len() - j.len() + skipped.len();
+            expected.push((offset..offset + j.len() - k.len() - skipped.len(), link));
+            j = k;
+        }
+        assert_eq!(expected.len(), 7);
+
+        for max_len in [1, 40, STREAM_CHUNK_LEN_MAX] {
+            let res = MarkupLinkStream::with_max_len(i.as_bytes(), max_len)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            if max_len == 1 {
+                // The multi-line link is cut.
+                assert_eq!(res.len(), 6);
+            } else {
+                assert_eq!(res, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_markup_link_stream_error() {
+        let i = b"[a](b)\n\n[c](\xff)\n\n[d](e)";
+        let mut iter = MarkupLinkStream::new(&i[..]);
+        assert_eq!(iter.next().unwrap().unwrap().0, 0..6);
+        assert_eq!(
+            iter.next().unwrap().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(iter.next().is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_split_at_blank_lines() {
+        let i = "a\n\nbb\n\n\nccc";
+        assert_eq!(
+            split_at_blank_lines(i, 1),
+            vec![(0, "a\n\n"), (3, "bb\n\n"), (7, "\nccc")]
+        );
+        assert_eq!(
+            split_at_blank_lines(i, 4),
+            vec![(0, "a\n\nbb\n\n"), (7, "\nccc")]
+        );
+        assert_eq!(split_at_blank_lines(i, 100), vec![(0, i)]);
+        assert!(split_at_blank_lines("", 1).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_hyperlinks() {
+        let para = r#"abc[a](b)abc `text1`__ abc `text2`__ abc ![alt3][img3] [text4]
+__ dest1
+
+`text5 <dest5_>`_ abc text6_ [c](d) [Label7]
+
+.. _img3: `label5`_
+
+.. _label5: img3.png
+.. _dest5: dest5
+.. _text6: dest6
+__ dest2
+
+[label7]: dest7
+"#;
+        let i = para.repeat(20);
+        let expected = MarkupLink::new(&i, false)
+            .map(|((skipped, consumed, _), link)| {
+                let offset = consumed.as_ptr() as usize - i.as_ptr() as usize;
+                assert_eq!(
+                    skipped.as_ptr() as usize + skipped.len(),
+                    consumed.as_ptr() as usize
+                );
+                (offset..offset + consumed.len(), link)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), 20 * 8);
+
+        for chunk_len in [1, 50, 1000, i.len()] {
+            assert_eq!(par_hyperlinks_chunked(&i, chunk_len), expected);
+        }
+        assert_eq!(par_hyperlinks(&i), expected);
+
+        let i = format!("---\nsummary: `a <b>`__\n---\n{i}");
+        assert_eq!(par_hyperlinks(&i).len(), 20 * 8);
+    }
+
+    #[test]
+    fn test_next_front_matter() {
+        let i = "+++\nurl = \"https://a\"\nlinks = [\"b\", \"c\"]\ndesc = \"[d](e)\"\n+++\n\
+                 [f][g]\n\n[g]: h\n";
+        let res = MarkupLink::new(i, false)
+            .front_matter_links(true)
+            .map(|((skipped, consumed, _), link)| {
+                (skipped, consumed, link.dest().unwrap().to_string())
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("+++\nurl = \"", "https://a", "https://a"),
+            ("\"\nlinks = [\"", "b", "b"),
+            ("\", \"", "c", "c"),
+            ("\"]\ndesc = \"[d](e)\"\n+++\n", "[f][g]", "h"),
+        ]
+        .map(|(s, c, d)| (s, c, d.to_string()));
+        assert_eq!(res, expected);
+
+        // The front matter is skipped also when there is no link.
+        let i = "---\na: `b <c>`_\n---\n";
+        assert_eq!(MarkupLink::new(i, false).next(), None);
+        assert_eq!(
+            MarkupLink::new(i, false).skip_front_matter(false).count(),
+            1
+        );
+        // No front matter.
+        let i = "--- `b <c>`_\n---\n";
+        assert_eq!(MarkupLink::new(i, false).count(), 1);
+    }
+
+    #[test]
+    fn test_first_link() {
+        // A resolved reference, the span covers the reference only.
+        let i = "---\na: [b](c)\n---\nabc [t][u]\n\n[u]: v \"w\"\n";
+        let (span, link) = first_link(i).unwrap();
+        assert_eq!(&i[span], "[t][u]");
+        assert_eq!(Some(link), find_first(i));
+        // A link inside an image link.
+        let i = "[![a](b)](c)";
+        let (span, link) = first_link(i).unwrap();
+        assert_eq!(span, 0..i.len());
+        assert!(matches!(link, Link::Image2Dest(_, _, _, _, _, _)));
+        assert_eq!(first_link("[a][b]"), None);
+    }
+
+    #[test]
+    fn test_link_at() {
+        let i = "---\na: [b](c)\n---\n\
+                 abc [t][u] `v <w>`_ ![x](y)\n\n\
+                 [u]: v \"w\"\n\
+                 [https://a] [https://b b] [[c]] [d][]\n\n\
+                 e __ f\n\n\
+                 __ g\n\n\
+                 [h](i \"ä\") <j@k>";
+        let links: Vec<_> = MarkupLink::new(i, true)
+            .map(|((_, consumed, remaining), link)| {
+                let end = i.len() - remaining.len();
+                (end - consumed.len()..end, link)
+            })
+            .collect();
+        assert_eq!(links.len(), 10);
+        for offset in 0..=i.len() + 1 {
+            let expected = links
+                .iter()
+                .find(|(span, _)| span.contains(&offset) && i.is_char_boundary(offset))
+                .cloned();
+            assert_eq!(link_at(i, offset), expected, "offset {}", offset);
+        }
+    }
 }
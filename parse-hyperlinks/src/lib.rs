@@ -10,12 +10,54 @@
 //! specification
 //! and the [Wikitext v1.0.0](https://www.mediawiki.org/wiki/Specs/wikitext/1.0.0)
 //! specification.
+//!
+//! The default feature `std` enables everything, that needs the standard
+//! library: the renderers, the analysis tools and `iterator::MarkupLinkStream`.
+//! Without it, the crate is `no_std` and requires only `alloc`: the parsers,
+//! the iterators, `resolve` and the URL helpers in `url` remain available,
+//! e.g. for embedded devices:
+//!
+//! ```toml
+//! parse-hyperlinks = { version = "0.27", default-features = false }
+//! ```
 #![allow(dead_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "check")]
+pub mod check;
+#[cfg(feature = "std")]
+pub mod commonmark;
+#[cfg(feature = "check")]
+pub mod crawl;
+#[cfg(feature = "email")]
+pub mod email;
+pub mod front_matter;
+mod fuzz;
+pub mod headings;
+#[cfg(feature = "ipynb")]
+pub mod ipynb;
 pub mod iterator;
+pub mod link_index;
+pub mod lint;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+#[cfg(feature = "std")]
+pub mod meta;
 pub mod parser;
+#[cfg(feature = "std")]
 pub mod renderer;
+pub mod resolve;
+#[cfg(feature = "std")]
+pub mod rewrite;
+pub mod url;
+
+pub use fuzz::fuzz_all;
 
+use core::fmt;
 use nom::error::Error;
 use nom::error::ErrorKind;
 use nom::error::ParseError;
@@ -42,41 +84,263 @@ pub fn take_until_unbalanced(
     opening_bracket: char,
     closing_bracket: char,
 ) -> impl Fn(&str) -> IResult<&str, &str> {
+    take_until_unbalanced_with(
+        opening_bracket,
+        closing_bracket,
+        &UnbalancedOptions::default(),
+    )
+}
+
+/// How `take_until_unbalanced_with()` recognizes brackets, that do not
+/// count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    /// This character makes the following character literal, e.g. `\` in
+    /// `\(`.
+    Char(char),
+    /// A doubled bracket, e.g. `((` or `))`, is a literal bracket.
+    Doubling,
+    /// Every bracket counts.
+    Disabled,
+}
+
+/// Options controlling `take_until_unbalanced_with()`.
+///
+/// ```
+/// use parse_hyperlinks::Escape;
+/// use parse_hyperlinks::UnbalancedOptions;
+///
+/// let options = UnbalancedOptions {
+///     escape: Escape::Char('`'),
+///     ..Default::default()
+/// };
+/// assert_eq!(options.max_depth, None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnbalancedOptions {
+    /// How brackets are escaped, default is `Escape::Char('\\')`.
+    pub escape: Escape,
+    /// When set, more deeply nested brackets are an error. This guards
+    /// against pathological inputs.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for UnbalancedOptions {
+    fn default() -> Self {
+        Self {
+            escape: Escape::Char('\\'),
+            max_depth: None,
+        }
+    }
+}
+
+/// Like `take_until_unbalanced()`, but the escaping and the maximum nesting
+/// depth are configurable. Exceeding `max_depth` results in an
+/// `ErrorKind::TooLarge` error.
+///
+/// ```
+/// use parse_hyperlinks::take_until_unbalanced_with;
+/// use parse_hyperlinks::Escape;
+/// use parse_hyperlinks::UnbalancedOptions;
+///
+/// let options = UnbalancedOptions {
+///     escape: Escape::Doubling,
+///     max_depth: Some(1),
+/// };
+/// let parser = take_until_unbalanced_with('[', ']', &options);
+/// assert_eq!(parser("a[b]]]c]d"), Ok(("]d", "a[b]]]c")));
+/// assert!(parser("a[b[c]]d]").is_err());
+/// ```
+pub fn take_until_unbalanced_with(
+    opening_bracket: char,
+    closing_bracket: char,
+    options: &UnbalancedOptions,
+) -> impl Fn(&str) -> IResult<&str, &str> {
+    let parser = take_until_unbalanced_located(opening_bracket, closing_bracket, options);
+    move |i: &str| parser(i).map_err(|e| e.map(Error::from))
+}
+
+/// The error of `take_until_unbalanced_located()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnbalancedError<I> {
+    /// The input the failing parser got.
+    pub input: I,
+    /// `ErrorKind::TakeUntil` if an opening bracket is not closed,
+    /// `ErrorKind::TooLarge` if the brackets are nested too deep.
+    pub kind: ErrorKind,
+    /// The byte offset in `input` of the first unmatched opening bracket,
+    /// or of the opening bracket exceeding the maximum nesting depth.
+    /// `None`, when the error comes from another parser.
+    pub position: Option<usize>,
+}
+
+impl<I> ParseError<I> for UnbalancedError<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind,
+            position: None,
+        }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> From<UnbalancedError<I>> for Error<I> {
+    fn from(e: UnbalancedError<I>) -> Self {
+        Error::new(e.input, e.kind)
+    }
+}
+
+impl<I> fmt::Display for UnbalancedError<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, self.position) {
+            (ErrorKind::TakeUntil, Some(n)) => write!(f, "unmatched opening bracket at byte {}", n),
+            (ErrorKind::TooLarge, Some(n)) => {
+                write!(f, "brackets nested too deep at byte {}", n)
+            }
+            (kind, _) => write!(f, "error {:?}", kind),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: fmt::Debug> std::error::Error for UnbalancedError<I> {}
+
+/// Like `take_until_unbalanced_with()`, but the error reports the position
+/// of the offending opening bracket.
+///
+/// ```
+/// use parse_hyperlinks::take_until_unbalanced_located;
+///
+/// let parser = take_until_unbalanced_located('(', ')', &Default::default());
+/// let e = match parser("u(())r(l(a)bc") {
+///     Err(nom::Err::Error(e)) => e,
+///     _ => panic!(),
+/// };
+/// assert_eq!(e.position, Some(6));
+/// assert_eq!(e.to_string(), "unmatched opening bracket at byte 6");
+/// ```
+pub fn take_until_unbalanced_located(
+    opening_bracket: char,
+    closing_bracket: char,
+    options: &UnbalancedOptions,
+) -> impl Fn(&str) -> IResult<&str, &str, UnbalancedError<&str>> {
+    let options = *options;
     move |i: &str| {
         let mut index = 0;
         let mut bracket_counter = 0;
-        while let Some(n) = &i[index..].find(&[opening_bracket, closing_bracket, '\\'][..]) {
-            index += n;
-            let mut it = i[index..].chars();
-            match it.next() {
-                Some(c) if c == '\\' => {
-                    // Skip the escape char `\`.
-                    index += '\\'.len_utf8();
+        // Position of the outermost open bracket.
+        let mut outermost = 0;
+        let escape_char = match options.escape {
+            Escape::Char(c) => Some(c),
+            _ => None,
+        };
+        let doubling = options.escape == Escape::Doubling;
+        let error = |kind, position| {
+            Err(Err::Error(UnbalancedError {
+                input: i,
+                kind,
+                position: Some(position),
+            }))
+        };
+        let mut it = i.chars();
+        while let Some(c) = it.next() {
+            index += c.len_utf8();
+            match c {
+                c if Some(c) == escape_char => {
                     // Skip also the following char.
                     if let Some(c) = it.next() {
                         index += c.len_utf8();
                     }
                 }
-                Some(c) if c == opening_bracket => {
+                c if doubling
+                    && (c == opening_bracket || c == closing_bracket)
+                    && i[index..].starts_with(c) =>
+                {
+                    // Skip the second bracket.
+                    it.next();
+                    index += c.len_utf8();
+                }
+                c if c == opening_bracket => {
+                    let position = index - opening_bracket.len_utf8();
+                    if bracket_counter == 0 {
+                        outermost = position;
+                    }
                     bracket_counter += 1;
-                    index += opening_bracket.len_utf8();
+                    if options.max_depth.is_some_and(|max| bracket_counter > max) {
+                        return error(ErrorKind::TooLarge, position);
+                    }
                 }
-                Some(c) if c == closing_bracket => {
-                    // Closing bracket.
+                c if c == closing_bracket => {
+                    // We found the unmatched closing bracket.
+                    if bracket_counter == 0 {
+                        // We do not consume it.
+                        index -= closing_bracket.len_utf8();
+                        return Ok((&i[index..], &i[0..index]));
+                    };
                     bracket_counter -= 1;
-                    index += closing_bracket.len_utf8();
                 }
-                // Can not happen.
-                _ => unreachable!(),
-            };
-            // We found the unmatched closing bracket.
-            if bracket_counter == -1 {
-                // We do not consume it.
-                index -= closing_bracket.len_utf8();
-                return Ok((&i[index..], &i[0..index]));
+                _ => {}
             };
         }
 
+        if bracket_counter == 0 {
+            Ok(("", i))
+        } else {
+            error(ErrorKind::TakeUntil, outermost)
+        }
+    }
+}
+
+/// Like `take_until_unbalanced()`, but the brackets are strings, e.g. `{{`
+/// and `}}` for templates. Escaped characters like `\{` are not considered
+/// as part of a bracket. Fails when `opening_tag` or `closing_tag` is empty.
+///
+/// ```
+/// use nom::bytes::complete::tag;
+/// use nom::sequence::delimited;
+/// use parse_hyperlinks::take_until_unbalanced_tag;
+///
+/// let mut parser = delimited(tag("{{"), take_until_unbalanced_tag("{{", "}}"), tag("}}"));
+/// assert_eq!(parser("{{a{{b}}c}}abc"), Ok(("abc", "a{{b}}c")));
+/// assert_eq!(parser("{{a\\{{b}}c}}abc"), Ok(("c}}abc", "a\\{{b")));
+/// ```
+pub fn take_until_unbalanced_tag<'t>(
+    opening_tag: &'t str,
+    closing_tag: &'t str,
+) -> impl Fn(&str) -> IResult<&str, &str> + 't {
+    move |i: &str| {
+        if opening_tag.is_empty() || closing_tag.is_empty() {
+            return Err(Err::Error(Error::from_error_kind(i, ErrorKind::TakeUntil)));
+        }
+        let mut index = 0;
+        let mut bracket_counter = 0;
+        while let Some(c) = i[index..].chars().next() {
+            let rest = &i[index..];
+            if c == '\\' {
+                // Skip the escape char `\` and the following char.
+                index += c.len_utf8();
+                if let Some(c) = i[index..].chars().next() {
+                    index += c.len_utf8();
+                }
+            } else if rest.starts_with(opening_tag) {
+                bracket_counter += 1;
+                index += opening_tag.len();
+            } else if rest.starts_with(closing_tag) {
+                bracket_counter -= 1;
+                // We found the unmatched closing tag, we do not consume it.
+                if bracket_counter == -1 {
+                    return Ok((&i[index..], &i[0..index]));
+                };
+                index += closing_tag.len();
+            } else {
+                index += c.len_utf8();
+            }
+        }
+
         if bracket_counter == 0 {
             Ok(("", i))
         } else {
@@ -141,4 +405,97 @@ mod tests {
             Ok(("üabc", "€uü€€üürl"))
         );
     }
+
+    #[test]
+    fn test_take_until_unbalanced_with() {
+        let options = UnbalancedOptions {
+            escape: Escape::Char('`'),
+            ..Default::default()
+        };
+        let p = take_until_unbalanced_with('(', ')', &options);
+        assert_eq!(p("u`((\\))rl)abc"), Ok((")rl)abc", "u`((\\)")));
+
+        let options = UnbalancedOptions {
+            escape: Escape::Disabled,
+            ..Default::default()
+        };
+        let p = take_until_unbalanced_with('(', ')', &options);
+        assert_eq!(p("u\\()rl)abc"), Ok((")abc", "u\\()rl")));
+
+        let options = UnbalancedOptions {
+            escape: Escape::Doubling,
+            ..Default::default()
+        };
+        let p = take_until_unbalanced_with('(', ')', &options);
+        assert_eq!(p("u((x(y)z))w)abc"), Ok((")abc", "u((x(y)z))w")));
+        assert_eq!(p("a(b))c)d"), Ok(("", "a(b))c)d")));
+
+        let options = UnbalancedOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let p = take_until_unbalanced_with('(', ')', &options);
+        assert_eq!(p("u(())rl)abc"), Ok((")abc", "u(())rl")));
+        assert_eq!(
+            p("u((()))rl)abc"),
+            Err(nom::Err::Error(nom::error::Error::new(
+                "u((()))rl)abc",
+                ErrorKind::TooLarge
+            )))
+        );
+    }
+
+    #[test]
+    fn test_take_until_unbalanced_located() {
+        let p = take_until_unbalanced_located('(', ')', &UnbalancedOptions::default());
+        assert_eq!(p("u(())rl)abc"), Ok((")abc", "u(())rl")));
+        assert_eq!(
+            p("u(()r(l"),
+            Err(nom::Err::Error(UnbalancedError {
+                input: "u(()r(l",
+                kind: ErrorKind::TakeUntil,
+                position: Some(1)
+            }))
+        );
+        assert_eq!(
+            p("€()(\\)"),
+            Err(nom::Err::Error(UnbalancedError {
+                input: "€()(\\)",
+                kind: ErrorKind::TakeUntil,
+                position: Some(5)
+            }))
+        );
+
+        let options = UnbalancedOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let p = take_until_unbalanced_located('(', ')', &options);
+        let e = match p("u()(())") {
+            Err(nom::Err::Error(e)) => e,
+            _ => panic!(),
+        };
+        assert_eq!((e.kind, e.position), (ErrorKind::TooLarge, Some(4)));
+    }
+
+    #[test]
+    fn test_take_until_unbalanced_tag() {
+        let p = take_until_unbalanced_tag("{{", "}}");
+        assert_eq!(p("abc"), Ok(("", "abc")));
+        assert_eq!(p("a}b}}c"), Ok(("}}c", "a}b")));
+        assert_eq!(p("a{{b}}c}}d"), Ok(("}}d", "a{{b}}c")));
+        assert_eq!(p("a\\{{b}}c"), Ok(("}}c", "a\\{{b")));
+        assert_eq!(p("a{{b\\}}c}}d"), Ok(("", "a{{b\\}}c}}d")));
+        assert_eq!(
+            p("a{{b"),
+            Err(nom::Err::Error(nom::error::Error::new(
+                "a{{b",
+                ErrorKind::TakeUntil
+            )))
+        );
+
+        let p = take_until_unbalanced_tag("<<", ">>");
+        assert_eq!(p("ü<<€>>ü>>abc"), Ok((">>abc", "ü<<€>>ü")));
+        assert!(take_until_unbalanced_tag("", ">>")("abc").is_err());
+    }
 }
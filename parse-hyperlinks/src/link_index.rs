@@ -0,0 +1,362 @@
+//! Module keeping the links of a document up to date while it is edited.
+//!
+//! A `LinkIndex` owns a copy of the document and its links. The document is
+//! divided into _blocks_, which are separated by blank lines. Links never
+//! span blank lines, so after an edit, `LinkIndex::apply_edit()` re-parses
+//! only the blocks the edit touches, instead of the whole document. This
+//! keeps editor integrations responsive even for very large files.
+//!
+//! The query methods, e.g. `LinkIndex::overlapping()` or
+//! `LinkIndex::references_to()`, answer the questions editors and link
+//! checkers ask about a document's links.
+
+use crate::iterator::normalize_label;
+use crate::iterator::Span;
+use crate::parser::parse::take_link_lang;
+use crate::parser::Link;
+use crate::parser::MarkupLang;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// A link of the indexed document as recognized by
+/// `parser::parse::take_link_lang()`. Reference links and link reference
+/// definitions are not resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkEntry {
+    /// The link's byte range in the document.
+    pub span: Span,
+    /// The link.
+    pub link: Link<'static>,
+    /// The markup language, whose grammar matched the link.
+    pub lang: MarkupLang,
+}
+
+/// A block of the document: a run of lines followed by the blank lines
+/// separating it from the next block.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    /// The block's byte range in the document.
+    span: Span,
+    /// The links of the block in order of appearance.
+    links: Vec<LinkEntry>,
+}
+
+/// The links of a document, updated incrementally when the document changes.
+///
+/// ```
+/// use parse_hyperlinks::link_index::LinkIndex;
+///
+/// let mut index = LinkIndex::new("[a](b)\n\n[c](d)\n");
+/// assert_eq!(index.links().count(), 2);
+///
+/// // Replace `d` with `e/f`: only the second block is parsed again.
+/// let reparsed = index.apply_edit(12..13, "e/f");
+/// assert_eq!(reparsed, 8..17);
+/// assert_eq!(index.text(), "[a](b)\n\n[c](e/f)\n");
+/// let last = index.links().last().unwrap();
+/// assert_eq!(last.span, 8..16);
+/// assert_eq!(last.link.dest(), Some("e/f"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkIndex {
+    /// The indexed document.
+    text: String,
+    /// The blocks of `text` in order. They cover `text` without gaps, an
+    /// empty `text` has one empty block.
+    blocks: Vec<Block>,
+}
+
+impl LinkIndex {
+    /// Indexes the links of `input`.
+    pub fn new(input: &str) -> Self {
+        let mut index = LinkIndex {
+            text: input.to_string(),
+            blocks: Vec::new(),
+        };
+        index.blocks = split_blocks(input, 0..input.len())
+            .into_iter()
+            .map(|span| parse_block(input, span))
+            .collect();
+        index
+    }
+
+    /// The indexed document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// All links of the document in order of appearance.
+    pub fn links(&self) -> impl Iterator<Item = &LinkEntry> {
+        self.blocks.iter().flat_map(|b| b.links.iter())
+    }
+
+    /// The links whose destination or image source starts with `prefix`.
+    pub fn by_dest_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a LinkEntry> {
+        self.links().filter(move |e| match &e.link {
+            Link::Image2Dest(_, _, src, _, dest, _) => {
+                src.starts_with(prefix) || dest.starts_with(prefix)
+            }
+            link => link.dest().is_some_and(|d| d.starts_with(prefix)),
+        })
+    }
+
+    /// The links with a _link label_ matching `label`, see `Link::label()`:
+    /// reference links and the link reference definitions of `label`.
+    /// Labels match as described in the CommonMark Spec, see
+    /// `LinkIndex::references_to()`.
+    pub fn by_label<'a>(&'a self, label: &str) -> impl Iterator<Item = &'a LinkEntry> {
+        let label = normalize_label(Cow::Borrowed(label)).map(Cow::into_owned);
+        self.links().filter(move |e| {
+            let other = e
+                .link
+                .label()
+                .and_then(|l| normalize_label(Cow::Borrowed(l)));
+            label.is_some() && other.as_deref() == label.as_deref()
+        })
+    }
+
+    /// The links of the kind `kind`: `inline` (inline links), `reference`
+    /// (reference links and cross-references), `definition` (link reference
+    /// definitions and aliases) or `image` (images, also those inside inline
+    /// links). These are the kinds `analysis::LinkStats` counts.
+    pub fn by_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a LinkEntry> {
+        self.links()
+            .filter(move |e| link_kinds(&e.link).contains(&kind))
+    }
+
+    /// The links overlapping the byte range `range`. An empty `range`
+    /// selects the links containing the position `range.start`, e.g. the
+    /// link under the cursor. Only the blocks overlapping `range` are
+    /// searched.
+    pub fn overlapping(&self, range: Span) -> impl Iterator<Item = &LinkEntry> {
+        let end = range.end.max(range.start + 1);
+        let first = self.blocks.partition_point(|b| b.span.end <= range.start);
+        let last = self.blocks.partition_point(|b| b.span.start < end);
+        self.blocks[first..last.max(first)]
+            .iter()
+            .flat_map(|b| b.links.iter())
+            .filter(move |e| e.span.start < end && range.start < e.span.end)
+    }
+
+    /// The references using the label `label`: reference links, images and
+    /// aliases referring to `label`, but not the link reference definition
+    /// of `label` itself. Labels match as described in the [CommonMark
+    /// Spec](https://spec.commonmark.org/0.30/#matches), ignoring case and
+    /// differences in whitespace.
+    ///
+    /// ```
+    /// use parse_hyperlinks::link_index::LinkIndex;
+    ///
+    /// let index = LinkIndex::new("[a][My  Label] [b][c]\n\n[my label]: d\n");
+    /// let refs: Vec<_> = index.references_to("MY LABEL").map(|e| e.span.clone()).collect();
+    /// assert_eq!(refs, [0..14]);
+    /// assert_eq!(index.by_label("my label").count(), 2);
+    /// ```
+    pub fn references_to<'a>(&'a self, label: &str) -> impl Iterator<Item = &'a LinkEntry> {
+        let label = normalize_label(Cow::Borrowed(label)).map(Cow::into_owned);
+        self.links().filter(move |e| {
+            let target = match &e.link {
+                Link::Text2Label(_, la) | Link::Image2Label(_, la) | Link::Label2Label(_, la) => la,
+                _ => return false,
+            };
+            label.is_some() && normalize_label(Cow::Borrowed(target)).as_deref() == label.as_deref()
+        })
+    }
+
+    /// Replaces the byte range `range` of the document with `new_text` and
+    /// updates the links. Only the blocks overlapping the edit are parsed
+    /// again, the spans of the links behind are shifted. Returns the byte
+    /// range of the updated document, that was parsed again.
+    ///
+    /// Panics, if `range` is out of bounds or does not lie on `char`
+    /// boundaries, like `String::replace_range()`.
+    pub fn apply_edit(&mut self, range: Span, new_text: &str) -> Span {
+        // The blocks touched by the edit. An edit at the start of a block
+        // may join it with the previous one.
+        let block_of = |pos: usize| {
+            self.blocks
+                .partition_point(|b| b.span.end <= pos)
+                .min(self.blocks.len() - 1)
+        };
+        let mut first = block_of(range.start.saturating_sub(1));
+        let mut last = block_of(range.end);
+
+        self.text.replace_range(range.clone(), new_text);
+        let shift = |pos: usize| pos - range.len() + new_text.len();
+
+        // Extend the region until it starts and ends at block boundaries of
+        // the updated document.
+        let mut start = self.blocks[first].span.start;
+        while !is_block_start(&self.text, start) {
+            first -= 1;
+            start = self.blocks[first].span.start;
+        }
+        let mut end = shift(self.blocks[last].span.end);
+        while end < self.text.len() && !is_block_start(&self.text, end) {
+            last += 1;
+            end = shift(self.blocks[last].span.end);
+        }
+
+        let new_blocks: Vec<_> = split_blocks(&self.text, start..end)
+            .into_iter()
+            .map(|span| parse_block(&self.text, span))
+            .collect();
+        for block in &mut self.blocks[last + 1..] {
+            block.span = shift(block.span.start)..shift(block.span.end);
+            for entry in &mut block.links {
+                entry.span = shift(entry.span.start)..shift(entry.span.end);
+            }
+        }
+        self.blocks.splice(first..=last, new_blocks);
+        start..end
+    }
+}
+
+/// True if a block starts at the byte offset `pos` of `text`: at the
+/// beginning of `text` or of a non-blank line following a blank line.
+fn is_block_start(text: &str, pos: usize) -> bool {
+    if pos == 0 {
+        return true;
+    }
+    if pos >= text.len() || !text[..pos].ends_with('\n') {
+        return false;
+    }
+    let line = text[pos..].split('\n').next().unwrap_or_default();
+    let before = &text[..pos - 1];
+    let prev_line = &before[before.rfind('\n').map_or(0, |n| n + 1)..];
+    !line.trim().is_empty() && prev_line.trim().is_empty()
+}
+
+/// Splits `text[range]` into blocks and returns their byte ranges. `range`
+/// must start at a block boundary.
+fn split_blocks(text: &str, range: Span) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut block_start = range.start;
+    let mut pos = range.start;
+    let mut prev_blank = false;
+    for line in text[range.clone()].split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if !blank && prev_blank && pos > block_start {
+            spans.push(block_start..pos);
+            block_start = pos;
+        }
+        prev_blank = blank;
+        pos += line.len();
+    }
+    spans.push(block_start..range.end);
+    spans
+}
+
+/// The kinds of `link`, see `LinkIndex::by_kind()`. Inline links with
+/// embedded image have two kinds.
+pub(crate) fn link_kinds(link: &Link) -> &'static [&'static str] {
+    match link {
+        Link::Text2Dest(..) | Link::TextLabel2Dest(..) => &["inline"],
+        Link::Text2Label(..) | Link::Role2Target(..) => &["reference"],
+        Link::Label2Dest(..) | Link::Label2Label(..) => &["definition"],
+        Link::Image(..) | Link::Image2Label(..) => &["image"],
+        Link::Image2Dest(..) => &["image", "inline"],
+    }
+}
+
+/// Parses the links of the block `text[span]`.
+fn parse_block(text: &str, span: Span) -> Block {
+    let block = &text[span.clone()];
+    let mut links = Vec::new();
+    let mut i = block;
+    while let Ok((j, (skipped, link, lang))) = take_link_lang(i) {
+        let start = span.start + block.len() - i.len() + skipped.len();
+        let end = span.start + block.len() - j.len();
+        links.push(LinkEntry {
+            span: start..end,
+            link: link.into_owned(),
+            lang,
+        });
+        i = j;
+    }
+    Block { span, links }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_blocks() {
+        let i = "a\nb\n\n\nc\n  \nd";
+        assert_eq!(split_blocks(i, 0..i.len()), [0..6, 6..11, 11..12]);
+        let empty: Span = 0..0;
+        assert_eq!(split_blocks("", 0..0), [empty]);
+        assert!(is_block_start(i, 6));
+        assert!(!is_block_start(i, 5));
+        assert!(!is_block_start(i, 2));
+    }
+
+    #[test]
+    fn test_queries() {
+        let i = "[a](https://x/1) ![b](img.png)\n\n[c][l] `d <https://x/2>`_\n\n[L]: https://y\n.. _e: l_\n";
+        let index = LinkIndex::new(i);
+        let spans = |it: &mut dyn Iterator<Item = &LinkEntry>| -> Vec<(usize, usize)> {
+            it.map(|e| (e.span.start, e.span.end)).collect()
+        };
+        assert_eq!(
+            spans(&mut index.by_dest_prefix("https://x/")),
+            [(0, 16), (39, 57)]
+        );
+        assert_eq!(spans(&mut index.by_kind("image")), [(17, 30)]);
+        assert_eq!(
+            spans(&mut index.by_kind("definition")),
+            [(59, 73), (74, 83)]
+        );
+        assert_eq!(spans(&mut index.by_label("l")), [(32, 38), (59, 73)]);
+        assert_eq!(spans(&mut index.references_to("l")), [(32, 38), (74, 83)]);
+        assert_eq!(spans(&mut index.overlapping(10..20)), [(0, 16), (17, 30)]);
+        assert_eq!(spans(&mut index.overlapping(16..17)), []);
+        assert_eq!(spans(&mut index.overlapping(33..33)), [(32, 38)]);
+        assert_eq!(spans(&mut index.overlapping(38..38)), []);
+        assert_eq!(index.overlapping(0..i.len()).count(), index.links().count());
+    }
+
+    #[test]
+    fn test_apply_edit() {
+        let i = "abc [a](b) abc\n\n`c <d>`_ abc\nabc [e][f]\n\n[f]: g\n\nend <https://h>\n";
+        let edits = [
+            // Within a block.
+            (5..6, "A"),
+            // Join two blocks by removing the blank line.
+            (15..16, ""),
+            // Split a block.
+            (28..28, "\n\n"),
+            // Insert a link at the very beginning.
+            (0..0, "[x](y)"),
+            // Delete everything up to the last block.
+            (0..56, ""),
+            // Append.
+            (16..16, "\n[z](w)"),
+        ];
+        let mut index = LinkIndex::new(i);
+        for (range, new_text) in edits {
+            index.apply_edit(range, new_text);
+            let expected = LinkIndex::new(index.text());
+            assert_eq!(index, expected, "after replacing with {:?}", new_text);
+        }
+        assert_eq!(index.text(), "end <https://h>\n\n[z](w)");
+        assert_eq!(index.links().count(), 2);
+
+        let mut index = LinkIndex::new("");
+        index.apply_edit(0..0, "[a](b)");
+        assert_eq!(index, LinkIndex::new("[a](b)"));
+
+        // Insert and delete line breaks everywhere.
+        let i = "[a](b)\n\n\n[c][d] e\n\n[d]: <f>\n  \n`g <h>`_\n";
+        for pos in 0..i.len() {
+            let mut index = LinkIndex::new(i);
+            index.apply_edit(pos..pos, "\n");
+            assert_eq!(index, LinkIndex::new(index.text()), "inserted at {}", pos);
+            index.apply_edit(pos..pos + 2, "");
+            assert_eq!(index, LinkIndex::new(index.text()), "deleted at {}", pos);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Module reporting near-miss hyperlinks: constructs, that look like a link,
+//! but do not parse as one, e.g. because of an unbalanced bracket or a
+//! missing trailing `_` in reStructuredText. The parsers skip them silently,
+//! `lint()` tells the author.
+//!
+//! The checks are heuristics applied to the text between the recognized
+//! links. They can not know the author's intention, so expect some false
+//! positives in texts using brackets as plain punctuation.
+
+use crate::iterator::Span;
+use crate::parser::parse::take_link;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The reason, why `lint()` reports a construct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintReason {
+    /// `](` is not part of a Markdown inline link, e.g. because the link
+    /// destination is not closed by `)`.
+    MalformedInlineLink,
+    /// An opening bracket `[` without closing bracket `]` in the same
+    /// paragraph, or vice versa.
+    UnbalancedBracket,
+    /// A reStructuredText hyperlink reference `` `text <dest>` `` without
+    /// trailing `_` or `__`.
+    MissingReferenceUnderscore,
+    /// A line starting like a link reference definition (`[label]:` or
+    /// `.. _label`), that does not parse, e.g. because the destination or the
+    /// colon after the label is missing. reStructuredText internal hyperlink
+    /// targets `.. _label:` without destination are valid.
+    MalformedLinkDefinition,
+    /// An HTML `<a href=` tag, that does not parse, e.g. because the closing
+    /// `</a>` is missing.
+    MalformedHtmlLink,
+}
+
+impl fmt::Display for LintReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintReason::MalformedInlineLink => {
+                "`](` does not start a valid link destination, check the closing `)`"
+            }
+            LintReason::UnbalancedBracket => "unbalanced bracket",
+            LintReason::MissingReferenceUnderscore => {
+                "hyperlink reference without trailing `_` or `__`"
+            }
+            LintReason::MalformedLinkDefinition => {
+                "link reference definition without valid destination"
+            }
+            LintReason::MalformedHtmlLink => "`<a href=` without valid closing `</a>`",
+        })
+    }
+}
+
+/// A near-miss hyperlink found by `lint()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lint {
+    /// The byte range of the construct in the input.
+    pub span: Span,
+    /// Why the construct was reported.
+    pub reason: LintReason,
+}
+
+/// Reports the constructs of `input`, that look like hyperlinks, but do not
+/// parse as such. The findings are ordered by their position.
+///
+/// ```
+/// use parse_hyperlinks::lint::lint;
+/// use parse_hyperlinks::lint::LintReason;
+///
+/// let i = "abc[text1](dest1 abc\n\n`text2 <dest2>` abc [text3](dest3)\n.. _label4 dest4\n";
+///
+/// let lints = lint(i);
+/// assert_eq!(lints.len(), 3);
+/// assert_eq!(lints[0].reason, LintReason::MalformedInlineLink);
+/// assert_eq!(&i[lints[0].span.clone()], "](");
+/// assert_eq!(lints[1].reason, LintReason::MissingReferenceUnderscore);
+/// assert_eq!(&i[lints[1].span.clone()], "`text2 <dest2>`");
+/// assert_eq!(lints[2].reason, LintReason::MalformedLinkDefinition);
+/// assert_eq!(&i[lints[2].span.clone()], ".. _label4 dest4");
+///
+/// // A reStructuredText internal hyperlink target is no near-miss.
+/// assert!(lint(".. _label5:\n\nabc\n").is_empty());
+/// ```
+pub fn lint(input: &str) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut push = |span: Span, reason| lints.push(Lint { span, reason });
+
+    // The text between the links with its byte offset.
+    let mut gaps = Vec::new();
+    let mut i = input;
+    while let Ok((j, (skipped, _))) = take_link(i) {
+        gaps.push((input.len() - i.len(), skipped));
+        i = j;
+    }
+    gaps.push((input.len() - i.len(), i));
+
+    // Open brackets of the current paragraph. Links count as plain text, so
+    // brackets around a link balance.
+    let mut brackets: Vec<usize> = Vec::new();
+    let mut last_end = 0;
+    for (offset, gap) in gaps {
+        if input[last_end..offset].contains("\n\n") {
+            brackets.clear();
+        }
+        last_end = offset + gap.len();
+
+        let mut chars = gap.char_indices().peekable();
+        let mut line_start = offset == 0 || input[..offset].ends_with('\n');
+        while let Some((n, c)) = chars.next() {
+            let pos = offset + n;
+            if line_start {
+                let line = gap[n..].lines().next().unwrap_or_default();
+                if is_definition_start(line) {
+                    let def = line.trim_end();
+                    let start = pos + def.len() - def.trim_start().len();
+                    push(start..pos + def.len(), LintReason::MalformedLinkDefinition);
+                }
+            }
+            line_start = c == '\n';
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\n' if chars.peek().is_some_and(|(_, c)| *c == '\n') => {
+                    for n in brackets.drain(..) {
+                        push(n..n + 1, LintReason::UnbalancedBracket);
+                    }
+                }
+                '[' => brackets.push(pos),
+                ']' if gap[n..].starts_with("](") => {
+                    push(pos..pos + 2, LintReason::MalformedInlineLink);
+                    brackets.pop();
+                }
+                ']' if brackets.pop().is_none() => {
+                    push(pos..pos + 1, LintReason::UnbalancedBracket);
+                }
+                '`' => {
+                    if let Some(len) = rst_reference_len(&gap[n..]) {
+                        push(pos..pos + len, LintReason::MissingReferenceUnderscore);
+                        // Skip the reference.
+                        while chars.peek().is_some_and(|(m, _)| *m < n + len) {
+                            chars.next();
+                        }
+                    }
+                }
+                '<' if gap[n..]
+                    .get(..8)
+                    .is_some_and(|s| s.eq_ignore_ascii_case("<a href=")) =>
+                {
+                    push(pos..pos + 8, LintReason::MalformedHtmlLink);
+                }
+                _ => {}
+            }
+        }
+    }
+    for n in brackets {
+        push(n..n + 1, LintReason::UnbalancedBracket);
+    }
+
+    lints.sort_by_key(|l| l.span.start);
+    lints
+}
+
+/// True if `line` starts like a Markdown or reStructuredText link reference
+/// definition. reStructuredText internal hyperlink targets `.. _label:`,
+/// which have no destination, are excluded.
+fn is_definition_start(line: &str) -> bool {
+    let line = line.trim_start_matches([' ', '\t']);
+    if let Some(name) = line.strip_prefix(".. _") {
+        // A backquoted name may contain colons.
+        let start = match name.strip_prefix('`') {
+            Some(quoted) => quoted.find('`').map_or(name.len(), |n| n + 2),
+            None => 0,
+        };
+        // The name ends with a colon followed by whitespace.
+        let colon = name[start..]
+            .match_indices(':')
+            .map(|(n, _)| start + n + 1)
+            .find(|&n| name[n..].is_empty() || name[n..].starts_with(char::is_whitespace));
+        return colon.is_none_or(|n| !name[n..].trim().is_empty());
+    }
+    line.strip_prefix('[')
+        .and_then(|l| l.find("]:"))
+        .is_some_and(|n| n > 0)
+}
+
+/// If `i` starts with `` `text <dest>` `` not followed by `_`, returns the
+/// length of the reference.
+fn rst_reference_len(i: &str) -> Option<usize> {
+    let end = i[1..].find('`')? + 1;
+    let text = &i[1..end];
+    let is_reference = text.ends_with('>') && text.contains('<') && !text.contains('\n');
+    (is_reference && !i[end + 1..].starts_with('_')).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        // Brackets around links and escaped brackets balance.
+        assert_eq!(lint("[see [text](dest)] \\[ abc\n"), []);
+        assert_eq!(lint("abc `text <dest>`_ abc\n[label]: dest\n"), []);
+
+        let lints = lint("a [b\n\nc\n");
+        assert_eq!(
+            lints,
+            [Lint {
+                span: 2..3,
+                reason: LintReason::UnbalancedBracket
+            }]
+        );
+        let lints = lint("a b] c\n\n<a href=\"x\">y\n");
+        assert_eq!(
+            lints,
+            [
+                Lint {
+                    span: 3..4,
+                    reason: LintReason::UnbalancedBracket
+                },
+                Lint {
+                    span: 8..16,
+                    reason: LintReason::MalformedHtmlLink
+                },
+            ]
+        );
+
+        let lints = lint("  .. _label dest\n");
+        assert_eq!(lints[0].span, 2..16);
+        assert_eq!(lints[0].reason, LintReason::MalformedLinkDefinition);
+
+        // reStructuredText internal hyperlink targets are valid.
+        assert_eq!(lint("  .. _label:\n\nabc\n"), []);
+        assert_eq!(lint(".. __: \n.. _`a: b`:\n"), []);
+    }
+
+    #[test]
+    fn test_is_definition_start() {
+        assert!(is_definition_start("[label]: "));
+        assert!(!is_definition_start("[]: dest"));
+        assert!(!is_definition_start(".. _label:"));
+        assert!(!is_definition_start(" .. _label:  "));
+        assert!(!is_definition_start(".. _`a: b`:"));
+        assert!(is_definition_start(".. _label"));
+        assert!(is_definition_start(".. _label dest"));
+        assert!(is_definition_start(".. _a:b"));
+        assert!(is_definition_start(".. _`a: b`"));
+        assert!(is_definition_start(".. _`a: b"));
+    }
+
+    #[test]
+    fn test_rst_reference_len() {
+        assert_eq!(rst_reference_len("`a <b>` c"), Some(7));
+        assert_eq!(rst_reference_len("`a <b>`_ c"), None);
+        assert_eq!(rst_reference_len("`a` <b>`"), None);
+        assert_eq!(rst_reference_len("`a"), None);
+    }
+}
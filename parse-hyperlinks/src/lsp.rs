@@ -0,0 +1,101 @@
+//! Module providing the hyperlinks of a document as
+//! [`lsp_types::DocumentLink`](https://docs.rs/lsp-types/latest/lsp_types/struct.DocumentLink.html)s,
+//! the answer to the Language Server Protocol's `textDocument/documentLink`
+//! request. This module is only available with the `lsp` feature enabled.
+
+use crate::iterator::LineIndex;
+use crate::iterator::MarkupLink;
+use crate::iterator::Position;
+use crate::parser::Link;
+use lsp_types::DocumentLink;
+use lsp_types::Url;
+
+/// Extracts the hyperlinks of `input` as `DocumentLink`s. Reference links
+/// are resolved, so are link reference definitions. The range of a link
+/// covers its whole source, positions count UTF-16 code units, as the
+/// protocol requires by default. The link title, if any, becomes the
+/// tooltip.
+///
+/// Relative destinations are joined with `base`, usually the URL of the
+/// document. Without `base`, or when the destination is no valid URL,
+/// the link has no target. Links with embedded image target their link
+/// destination, images their source.
+///
+/// ```
+/// use lsp_types::Url;
+/// use parse_hyperlinks::lsp::document_links;
+///
+/// let i = "abc\n[text1][label1] ![alt2](img/a.png)\n\n[label1]: https://getreu.net \"title1\"\n";
+/// let base = Url::parse("file:///doc/a.md").unwrap();
+///
+/// let links = document_links(i, Some(&base));
+/// assert_eq!(links.len(), 3);
+/// assert_eq!(links[0].range.start, lsp_types::Position::new(1, 0));
+/// assert_eq!(links[0].range.end, lsp_types::Position::new(1, 15));
+/// assert_eq!(links[0].target.as_ref().unwrap().as_str(), "https://getreu.net/");
+/// assert_eq!(links[0].tooltip.as_deref(), Some("title1"));
+/// assert_eq!(links[1].target.as_ref().unwrap().as_str(), "file:///doc/img/a.png");
+/// assert_eq!(links[2].range.start, lsp_types::Position::new(3, 0));
+/// ```
+pub fn document_links(input: &str, base: Option<&Url>) -> Vec<DocumentLink> {
+    let index = LineIndex::new(input);
+    MarkupLink::new(input, true)
+        .filter_map(|((_, consumed, remaining), link)| {
+            let (dest, title) = match link {
+                Link::Text2Dest(_, dest, title) | Link::Image2Dest(_, _, _, _, dest, title) => {
+                    (dest, title)
+                }
+                Link::Image(_, src) => (src, "".into()),
+                _ => return None,
+            };
+            let end = input.len() - remaining.len();
+            let span = index.span_position(&(end - consumed.len()..end));
+            let target = match base {
+                Some(base) => base.join(&dest),
+                None => Url::parse(&dest),
+            };
+            Some(DocumentLink {
+                range: lsp_types::Range::new(lsp_position(span.start), lsp_position(span.end)),
+                target: target.ok(),
+                tooltip: (!title.is_empty()).then(|| title.into_owned()),
+                data: None,
+            })
+        })
+        .collect()
+}
+
+/// Converts `position` into a position counting UTF-16 code units.
+fn lsp_position(position: Position) -> lsp_types::Position {
+    let n = |n| u32::try_from(n).unwrap_or(u32::MAX);
+    lsp_types::Position::new(n(position.line), n(position.column_utf16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_links() {
+        let i = "😀[text1](dest1)\n<a href=\"https://getreu.net\">text2</a>";
+
+        let links = document_links(i, None);
+        assert_eq!(links.len(), 2);
+        // `😀` counts as 2 UTF-16 code units.
+        assert_eq!(links[0].range.start, lsp_types::Position::new(0, 2));
+        assert_eq!(links[0].range.end, lsp_types::Position::new(0, 16));
+        // A relative destination without base.
+        assert_eq!(links[0].target, None);
+        assert_eq!(links[0].tooltip, None);
+        assert_eq!(
+            links[1].target.as_ref().unwrap().as_str(),
+            "https://getreu.net/"
+        );
+
+        let base = Url::parse("https://getreu.net/doc/").unwrap();
+        let links = document_links(i, Some(&base));
+        assert_eq!(
+            links[0].target.as_ref().unwrap().as_str(),
+            "https://getreu.net/doc/dest1"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Module collecting per-link metadata, that does not fit into `Link`.
+//!
+//! Some markup languages attach additional attributes to a hyperlink, e.g.
+//! `rel` and `class` in HTML, `window=_blank` in Asciidoc or a Pandoc
+//! attribute block `{.class}` after a Markdown link. `LinkMeta`
+//! stores these attributes in a side-map keyed by the link's byte range
+//! (`Span`) in the input text. The same span is reported by the iterators
+//! in `iterator`, so renderers and exporters can look up the attributes of
+//! every link they process.
+
+use crate::iterator::Span;
+use crate::parser::asciidoc::adoc_link_attributes;
+use crate::parser::html::html_link_attributes;
+use crate::parser::markdown::md_link_attributes;
+use crate::parser::parse::take_link_lang_with;
+use crate::parser::parse::ParserOptions;
+use crate::parser::MarkupLang;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Attributes of a link as `(name, value)` pairs in order of appearance.
+pub type Attributes<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+/// Side-map holding the attributes of all links of an input text. The key
+/// is the link's byte range in the input text. Links without attributes
+/// have no entry.
+///
+/// ```
+/// use parse_hyperlinks::meta::LinkMeta;
+/// use std::borrow::Cow;
+///
+/// let i = r#"abc<a href="dest" rel="nofollow">text</a>abc
+/// https://getreu.net[text,window=_blank]
+/// [text](dest)
+/// "#;
+///
+/// let meta = LinkMeta::from(i);
+/// assert_eq!(meta.len(), 2);
+/// assert_eq!(meta.attribute(&(3..41), "rel"), Some(&Cow::from("nofollow")));
+/// assert_eq!(meta.attribute(&(45..83), "window"), Some(&Cow::from("_blank")));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LinkMeta<'a> {
+    /// Key: the link's byte range, value: the link's attributes.
+    map: HashMap<Span, Attributes<'a>>,
+}
+
+impl<'a> LinkMeta<'a> {
+    /// Constructor of an empty side-map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all attributes of the link at `span`.
+    pub fn get(&self, span: &Span) -> Option<&[(Cow<'a, str>, Cow<'a, str>)]> {
+        self.map.get(span).map(|a| a.as_slice())
+    }
+
+    /// Returns the value of the attribute `name` of the link at `span`.
+    /// Attribute names are compared ASCII case-insensitively. If the
+    /// attribute is given more than once, the last value wins.
+    pub fn attribute(&self, span: &Span, name: &str) -> Option<&Cow<'a, str>> {
+        self.map
+            .get(span)?
+            .iter()
+            .rev()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+    }
+
+    /// Appends the attribute `(name, value)` to the link at `span`.
+    pub fn insert(&mut self, span: Span, name: Cow<'a, str>, value: Cow<'a, str>) {
+        self.map.entry(span).or_default().push((name, value));
+    }
+
+    /// Same as `LinkMeta::from()`, but the links are parsed with `options`.
+    /// With `ParserOptions::md_link_attributes`, the attributes of Markdown
+    /// links are collected, too.
+    ///
+    /// ```
+    /// use parse_hyperlinks::meta::LinkMeta;
+    /// use parse_hyperlinks::parser::parse::ParserOptions;
+    /// use std::borrow::Cow;
+    ///
+    /// let options = ParserOptions {
+    ///     md_link_attributes: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let meta = LinkMeta::from_with("abc [text](dest){target=_blank} abc", &options);
+    /// assert_eq!(meta.attribute(&(4..31), "target"), Some(&Cow::from("_blank")));
+    /// ```
+    pub fn from_with(input: &'a str, options: &ParserOptions) -> Self {
+        let mut meta = Self::new();
+        let mut i = input;
+        while let Ok((j, (skipped, _, markup_lang))) = take_link_lang_with(i, options) {
+            let offset = input.len() - i.len() + skipped.len();
+            let consumed = &i[skipped.len()..i.len() - j.len()];
+            let attributes = match markup_lang {
+                MarkupLang::Markdown if options.md_link_attributes => consumed
+                    .rfind('{')
+                    .and_then(|n| md_link_attributes(&consumed[n..]).ok())
+                    .filter(|(rest, _)| rest.is_empty())
+                    .map(|(_, a)| a)
+                    .unwrap_or_default(),
+                _ => html_link_attributes(consumed)
+                    .or_else(|_| adoc_link_attributes(consumed))
+                    .map(|(_, a)| a)
+                    .unwrap_or_default(),
+            };
+            if !attributes.is_empty() {
+                meta.map.insert(offset..offset + consumed.len(), attributes);
+            }
+            i = j;
+        }
+        meta
+    }
+
+    /// Number of links with attributes.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// True if no link has attributes.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterator over all `(span, attributes)` entries in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Span, &Attributes<'a>)> {
+        self.map.iter()
+    }
+}
+
+impl<'a> From<&'a str> for LinkMeta<'a> {
+    /// Reads through the whole `input` and collects the attributes of all
+    /// HTML and Asciidoc hyperlinks and images.
+    fn from(input: &'a str) -> Self {
+        Self::from_with(input, &ParserOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_meta_from() {
+        let i = r#"<img src="i.png" alt="a" class="logo" width="20"/>
+abc `rst <dest>`_ abc link:https://getreu.net[Doc^] abc
+<a class='x' href="dest" class="y">text</a>"#;
+        let meta = LinkMeta::from(i);
+        assert_eq!(meta.len(), 3);
+
+        let img = 0..50;
+        assert_eq!(
+            meta.get(&img),
+            Some(
+                &[
+                    (Cow::from("class"), Cow::from("logo")),
+                    (Cow::from("width"), Cow::from("20"))
+                ][..]
+            )
+        );
+
+        let adoc = i.find("link:").unwrap();
+        let adoc = adoc..adoc + "link:https://getreu.net[Doc^]".len();
+        assert_eq!(meta.attribute(&adoc, "WINDOW"), Some(&Cow::from("_blank")));
+
+        let a = i.find("<a").unwrap()..i.len();
+        assert_eq!(meta.attribute(&a, "class"), Some(&Cow::from("y")));
+        assert_eq!(meta.attribute(&a, "rel"), None);
+    }
+
+    #[test]
+    fn test_link_meta_from_with() {
+        let i = "[a](b){.c .d} [e](f) {g=h} link:https://i[j,window=_blank]";
+        let options = ParserOptions {
+            md_link_attributes: true,
+            ..ParserOptions::default()
+        };
+        let meta = LinkMeta::from_with(i, &options);
+        assert_eq!(meta.len(), 2);
+        assert_eq!(meta.attribute(&(0..13), "class"), Some(&Cow::from("c d")));
+        let adoc = i.find("link:").unwrap()..i.len();
+        assert_eq!(meta.attribute(&adoc, "window"), Some(&Cow::from("_blank")));
+
+        assert_eq!(LinkMeta::from(i).len(), 1);
+    }
+
+    #[test]
+    fn test_link_meta_insert() {
+        let mut meta = LinkMeta::new();
+        assert!(meta.is_empty());
+        meta.insert(1..5, Cow::from("rel"), Cow::from("me"));
+        meta.insert(1..5, Cow::from("class"), Cow::from("x"));
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta.get(&(1..5)).unwrap().len(), 2);
+        assert_eq!(meta.get(&(1..6)), None);
+        assert_eq!(meta.iter().count(), 1);
+    }
+}
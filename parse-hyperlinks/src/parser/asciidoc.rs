@@ -2,31 +2,52 @@
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::generic_error;
 use crate::parser::parse::LABEL_LEN_MAX;
 use crate::parser::percent_decode;
+use crate::parser::url_start;
 use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::bytes::complete::tag_no_case;
 use nom::character::complete::char;
 use nom::character::complete::space0;
 use nom::combinator::peek;
-use std::borrow::Cow;
+use nom::combinator::verify;
+use nom::error::VerboseError;
 
 /// Wrapper around `adoc_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn adoc_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = adoc_text2dest(i)?;
+pub fn adoc_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    adoc_text2dest_link_generic(i)
+}
+
+/// Same as `adoc_text2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn adoc_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("adoc_text2dest_link", adoc_text2dest_link_generic)(i)
+}
+
+/// Same as `adoc_text2dest_link()`, but generic over the error type.
+fn adoc_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = adoc_text2dest_generic(i)?;
     Ok((i, Link::Text2Dest(te, de, ti)))
 }
 
 /// Parses an Asciidoc _inline link_.
 ///
-/// This parser expects to start at the first letter of `http://`,
-/// `https://`, `link:http://` or `link:https://` (preceded by optional
-/// whitespaces) to succeed.
+/// This parser expects to start at the first letter of an URL like
+/// `https://`, or of `link:https://` (preceded by optional whitespaces) to
+/// succeed. Any scheme followed by `://` is accepted, as well as `mailto:`.
+/// `parse::take_link_with()` recognizes only the schemes of
+/// `ParserOptions::url_schemes`.
 ///
-/// When it starts at the letter `h` or `l`, the caller must guarantee, that:
+/// When it starts at a letter, the caller must guarantee, that:
 /// * the parser is at the beginning of the input _or_
 /// * the preceding byte is a newline `\n` _or_
 /// * the preceding bytes are whitespaces _or_
@@ -34,7 +55,10 @@ pub fn adoc_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///
 /// When ist starts at a whitespace no further guarantee is required.
 ///
-/// `link_title` is always the empty `Cow::Borrowed("")`.
+/// When the brackets contain named attributes, the _link text_ is the first
+/// positional attribute and the _link title_ is the value of the `title`
+/// attribute, if any. Otherwise, `link_title` is the empty
+/// `Cow::Borrowed("")`.
 /// ```
 /// use parse_hyperlinks::parser::Link;
 /// use parse_hyperlinks::parser::asciidoc::adoc_text2dest;
@@ -52,33 +76,262 @@ pub fn adoc_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///   adoc_text2dest("https://destination abc"),
 ///   Ok((" abc", (Cow::from("https://destination"), Cow::from("https://destination"), Cow::from(""))))
 /// );
+/// assert_eq!(
+///   adoc_text2dest(r#"link:https://destination["name, here",title="Title",window=_blank]abc"#),
+///   Ok(("abc", (Cow::from("name, here"), Cow::from("https://destination"), Cow::from("Title"))))
+/// );
 /// ```
-pub fn adoc_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn adoc_text2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    adoc_text2dest_generic(i)
+}
+
+/// Same as `adoc_text2dest()`, but generic over the error type.
+fn adoc_text2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     let (i, (link_destination, link_text)) = nom::sequence::preceded(
         space0,
         nom::sequence::pair(
-            adoc_inline_link_destination,
-            nom::combinator::opt(adoc_link_text),
+            generic_error(adoc_inline_link_destination),
+            nom::combinator::opt(generic_error(adoc_link_text)),
         ),
     )(i)?;
 
-    let link_text = if let Some(lt) = link_text {
-        if lt.is_empty() {
-            link_destination.clone()
-        } else {
-            lt
-        }
-    } else {
+    let (link_text, link_title) = match link_text {
+        Some(Cow::Borrowed(lt)) => match adoc_text_title(lt) {
+            Some((te, ti)) => (Cow::Borrowed(te), Cow::Borrowed(ti)),
+            None => (Cow::Borrowed(lt), Cow::Borrowed("")),
+        },
+        Some(Cow::Owned(lt)) => match adoc_text_title(&lt) {
+            Some((te, ti)) => (Cow::Owned(te.to_string()), Cow::Owned(ti.to_string())),
+            None => (Cow::Owned(lt), Cow::Borrowed("")),
+        },
+        None => (Cow::Borrowed(""), Cow::Borrowed("")),
+    };
+    let link_text = if link_text.is_empty() {
         link_destination.clone()
+    } else {
+        link_text
+    };
+
+    Ok((i, (link_text, link_destination, link_title)))
+}
+
+/// Splits the bracket content `content` of a link macro into _link text_
+/// and _link title_, if it contains named attributes. The _link text_ is
+/// the first positional attribute, the _link title_ the value of the
+/// `title` attribute. Returns `None` without named attributes: then the
+/// whole `content` is the link text.
+fn adoc_text_title(content: &str) -> Option<(&str, &str)> {
+    let items = adoc_attribute_items(content);
+    if !items
+        .iter()
+        .any(|item| adoc_named_attribute(item).is_some())
+    {
+        return None;
+    }
+    let text = match items[0] {
+        item if adoc_named_attribute(item).is_some() => "",
+        item => adoc_unquote(item.trim()),
+    };
+    let title = items
+        .iter()
+        .rev()
+        .filter_map(|item| adoc_named_attribute(item))
+        .find(|(name, _)| *name == "title")
+        .map_or("", |(_, value)| value);
+    Some((text, title))
+}
+
+/// Splits a macro's attribute list at the commas outside of double quotes.
+fn adoc_attribute_items(content: &str) -> Vec<&str> {
+    let mut in_quotes = false;
+    let mut items = Vec::new();
+    let mut start = 0;
+    for (n, c) in content.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                items.push(&content[start..n]);
+                start = n + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&content[start..]);
+    items
+}
+
+/// Parses the named attribute `name=value` or `name="value"` and returns
+/// `(name, value)`. The value is trimmed and unquoted.
+fn adoc_named_attribute(item: &str) -> Option<(&str, &str)> {
+    let (name, value) = item.trim().split_once('=')?;
+    let name = name.trim();
+    (!name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_'))
+    .then(|| (name, adoc_unquote(value.trim())))
+}
+
+/// Removes enclosing double quotes from `value`, if any.
+fn adoc_unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Parses an Asciidoc _inline link_ and returns its named attributes, e.g.
+/// `window=_blank` in `https://destination[text,window=_blank]`. A trailing `^`
+/// in the link text is the shorthand for `window=_blank`.
+///
+/// This parser expects to start where `adoc_text2dest()` starts.
+/// ```
+/// use parse_hyperlinks::parser::asciidoc::adoc_link_attributes;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   adoc_link_attributes(r#"https://destination[name,role="ext",window=_blank]abc"#),
+///   Ok(("abc", vec![(Cow::from("role"), Cow::from("ext")),
+///                   (Cow::from("window"), Cow::from("_blank"))]))
+/// );
+/// ```
+pub fn adoc_link_attributes(i: &str) -> nom::IResult<&str, Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+    let (i, content) = nom::sequence::preceded(
+        nom::sequence::pair(space0, adoc_inline_link_destination),
+        adoc_link_text,
+    )(i)?;
+
+    let mut attributes = Vec::new();
+    for (n, item) in adoc_attribute_items(&content).into_iter().enumerate() {
+        match adoc_named_attribute(item) {
+            Some((name, value)) => {
+                attributes.push((Cow::Owned(name.to_string()), Cow::Owned(value.to_string())));
+            }
+            _ if n == 0 && item.trim().ends_with('^') => {
+                attributes.push((Cow::Borrowed("window"), Cow::Borrowed("_blank")));
+            }
+            _ => {}
+        }
+    }
+
+    Ok((i, attributes))
+}
+
+/// Wrapper around `adoc_img()` that packs the result in `Link::Image`.
+pub fn adoc_img_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    adoc_img_link_generic(i)
+}
+
+/// Same as `adoc_img_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn adoc_img_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("adoc_img_link", adoc_img_link_generic)(i)
+}
+
+/// Same as `adoc_img_link()`, but generic over the error type.
+fn adoc_img_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src)) = adoc_img_generic(i)?;
+    Ok((i, Link::Image(alt, src)))
+}
+
+/// Parses an Asciidoc _inline image_ `image:target[alt]` or _block image_
+/// `image::target[alt]`.
+///
+/// It returns either `Ok((i, (image_alt, image_src)))` or some error.
+///
+/// The _image alt_ text is the first positional attribute, which may be
+/// quoted to contain commas. Further attributes, e.g. the width in
+/// `image::diagram.svg[Diagram,300]`, are ignored. Without alt text, the
+/// alt text is derived from the file name, as Asciidoctor does: the
+/// extension is removed and `-` and `_` are replaced by spaces.
+///
+/// This parser expects to start at the letter `i` of `image:` with the same
+/// guarantees as `adoc_text2dest()`.
+/// ```
+/// use parse_hyperlinks::parser::asciidoc::adoc_img;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   adoc_img("image:logo.png[Alt]abc"),
+///   Ok(("abc", (Cow::from("Alt"), Cow::from("logo.png"))))
+/// );
+/// assert_eq!(
+///   adoc_img("image::images/my_diagram.svg[,300]abc"),
+///   Ok(("abc", (Cow::from("my diagram"), Cow::from("images/my_diagram.svg"))))
+/// );
+/// ```
+pub fn adoc_img(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    adoc_img_generic(i)
+}
+
+/// Same as `adoc_img()`, but generic over the error type.
+fn adoc_img_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (src, attributes)) = nom::sequence::preceded(
+        nom::sequence::pair(tag("image:"), nom::combinator::opt(char(':'))),
+        nom::sequence::pair(
+            nom::bytes::complete::take_till1(|c| {
+                c == '[' || c == ' ' || c == '\t' || c == '\r' || c == '\n'
+            }),
+            generic_error(adoc_link_text),
+        ),
+    )(i)?;
+
+    let alt = match attributes {
+        Cow::Borrowed(a) => adoc_img_alt(a).map(Cow::Borrowed),
+        Cow::Owned(a) => adoc_img_alt(&a).map(|alt| Cow::Owned(alt.to_string())),
     };
+    let alt = alt.unwrap_or_else(|| {
+        let name = src.rsplit('/').next().unwrap_or(src);
+        let name = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        if name.contains(['-', '_']) {
+            Cow::Owned(name.replace(['-', '_'], " "))
+        } else {
+            Cow::Borrowed(name)
+        }
+    });
+
+    Ok((i, (alt, Cow::Borrowed(src))))
+}
 
-    Ok((i, (link_text, link_destination, Cow::Borrowed(""))))
+/// Returns the first positional attribute of the image macro attributes
+/// `attributes`, if not empty.
+fn adoc_img_alt(attributes: &str) -> Option<&str> {
+    let alt = match attributes.trim_start().strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+        None => {
+            let alt = attributes.split(',').next().unwrap_or_default();
+            if alt.contains('=') {
+                ""
+            } else {
+                alt
+            }
+        }
+    };
+    let alt = alt.trim();
+    (!alt.is_empty()).then_some(alt)
 }
 
 /// Wrapper around `adoc_label2dest()` that packs the result in
 /// `Link::Label2Dest`.
-pub fn adoc_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = adoc_label2dest(i)?;
+pub fn adoc_label2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    adoc_label2dest_link_generic(i)
+}
+
+/// Same as `adoc_label2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn adoc_label2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("adoc_label2dest_link", adoc_label2dest_link_generic)(i)
+}
+
+/// Same as `adoc_label2dest_link()`, but generic over the error type.
+fn adoc_label2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = adoc_label2dest_generic(i)?;
     Ok((i, Link::Label2Dest(te, de, ti)))
 }
 
@@ -103,21 +356,28 @@ pub fn adoc_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("\nabc", (Cow::from("label"), Cow::from("https://destination"), Cow::from(""))))
 /// );
 /// ```
-pub fn adoc_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn adoc_label2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    adoc_label2dest_generic(i)
+}
+
+/// Same as `adoc_label2dest()`, but generic over the error type.
+fn adoc_label2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     let (i, (link_label, link_destination)) = nom::sequence::preceded(
         space0,
         nom::sequence::pair(
-            adoc_parse_colon_reference,
+            generic_error(adoc_parse_colon_reference),
             nom::sequence::delimited(
                 nom::character::complete::space1,
-                adoc_link_reference_definition_destination,
+                generic_error(adoc_link_reference_definition_destination),
                 nom::character::complete::space0,
             ),
         ),
     )(i)?;
 
     if !i.is_empty() {
-        let _ = peek::<&str, _, nom::error::Error<_>, _>(nom::character::complete::newline)(i)?;
+        let _ = peek::<&str, _, E, _>(nom::character::complete::newline)(i)?;
     };
 
     Ok((
@@ -132,8 +392,21 @@ pub fn adoc_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<s
 
 /// Wrapper around `adoc_text2label()` that packs the result in
 /// `Link::Text2Label`.
-pub fn adoc_text2label_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, la)) = adoc_text2label(i)?;
+pub fn adoc_text2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    adoc_text2label_link_generic(i)
+}
+
+/// Same as `adoc_text2label_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn adoc_text2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("adoc_text2label_link", adoc_text2label_link_generic)(i)
+}
+
+/// Same as `adoc_text2label_link()`, but generic over the error type.
+fn adoc_text2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, la)) = adoc_text2label_generic(i)?;
     Ok((i, Link::Text2Label(te, la)))
 }
 
@@ -142,8 +415,8 @@ pub fn adoc_text2label_link(i: &str) -> nom::IResult<&str, Link> {
 /// There are three kinds of reference links `Text2Label`: full, collapsed, and
 /// shortcut.
 /// 1. A full reference link `{label}[text]` consists of a link label immediately
-/// followed by a link text. The label matches a link reference definition
-/// elsewhere in the document.
+///    followed by a link text. The label matches a link reference definition
+///    elsewhere in the document.
 /// 2. A collapsed reference link `{label}[]` consists of a link label that matches
 ///    a link reference definition elsewhere in the document, followed by the string
 ///    `[]`. In this case, the function returns an empty _link text_ `""`,
@@ -174,10 +447,22 @@ pub fn adoc_text2label_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from(""), Cow::from("link-label"))))
 /// );
 /// ```
-pub fn adoc_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+pub fn adoc_text2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    adoc_text2label_generic(i)
+}
+
+/// Same as `adoc_text2label()`, but generic over the error type.
+fn adoc_text2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
     let (i, (link_label, link_text)) = alt((
-        nom::sequence::pair(adoc_parse_curly_bracket_reference, adoc_link_text),
-        nom::combinator::map(adoc_parse_curly_bracket_reference, |s| (s, Cow::from(""))),
+        nom::sequence::pair(
+            generic_error(adoc_parse_curly_bracket_reference),
+            generic_error(adoc_link_text),
+        ),
+        nom::combinator::map(generic_error(adoc_parse_curly_bracket_reference), |s| {
+            (s, Cow::from(""))
+        }),
     ))(i)?;
 
     // Check that there is no `[` or `{` following. Do not consume.
@@ -192,7 +477,7 @@ pub fn adoc_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
 /// last letter `]`. A sequence of whitespaces including newlines, will be
 /// replaced by one space. There must be not contain more than one newline
 /// per sequence. The string can contain the `\]` which is replaced by `]`.
-fn adoc_link_text(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_link_text(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::sequence::delimited(char('['), remove_newline_take_till(']'), char(']'))(i)
 }
 
@@ -244,7 +529,7 @@ fn remove_newline_take_till<'a>(
                     // This is the only Ok exit from the while loop.
                     c if c == pat => return Ok((k, res)),
                     // We stopped at an escaped character.
-                    c if c == '\\' => {
+                    '\\' => {
                         // Consume the escape `\`.
                         let (l, _) = char('\\')(k)?;
                         // `pat` is the only valid escaped character (not even `\\` is special in
@@ -261,7 +546,7 @@ fn remove_newline_take_till<'a>(
                         l
                     }
                     // We stopped at a newline.
-                    c if c == '\n' => {
+                    '\n' => {
                         // Now consume the `\n`.
                         let (l, _) = char('\n')(k)?;
                         let (l, _) = space0(l)?;
@@ -295,7 +580,7 @@ fn remove_newline_take_till<'a>(
 /// The parser succeeds, if one of the variants:
 /// `adoc_parse_http_link_destination()` or
 /// `adoc_parse_escaped_link_destination()` succeeds and returns its result.
-fn adoc_link_reference_definition_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_link_reference_definition_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     alt((
         adoc_parse_http_link_destination,
         adoc_parse_escaped_link_destination,
@@ -306,7 +591,7 @@ fn adoc_link_reference_definition_destination(i: &str) -> nom::IResult<&str, Cow
 /// The parser succeeds, if one of the variants:
 /// `adoc_parse_http_link_destination()`, `adoc_parse_literal_link_destination()`
 /// or `adoc_parse_escaped_link_destination()` succeeds and returns its result.
-fn adoc_inline_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_inline_link_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     alt((
         adoc_parse_http_link_destination,
         adoc_parse_literal_link_destination,
@@ -314,26 +599,32 @@ fn adoc_inline_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
     ))(i)
 }
 
-/// Parses a link destination in URL form starting with `http://` or `https://`
-/// and ending with `[`. The latter is peeked, but no consumed.
-fn adoc_parse_http_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
+/// Parses a link destination in URL form starting with `scheme://` or
+/// `mailto:`, see `parser::url_start()`, and ending with `[`. The latter is
+/// peeked, but no consumed.
+fn adoc_parse_http_link_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     let (j, s) = nom::sequence::preceded(
-        peek(alt((tag_no_case("http://"), (tag_no_case("https://"))))),
+        peek(adoc_url_start),
         nom::bytes::complete::take_till1(|c| c == '[' || c == ' ' || c == '\t' || c == '\n'),
     )(i)?;
     Ok((j, Cow::Borrowed(s)))
 }
 
-/// Parses a link destination starting with `link:http://` or `link:https://` ending
-/// with `]`, whitespace or newline. The later is peeked, but not consumed. The URL can contain percent
+/// Parses the start of an URL, see `parser::url_start()`. The names of the
+/// macros `image:` and `link:` are no schemes.
+fn adoc_url_start(i: &str) -> nom::IResult<&str, &str> {
+    verify(url_start, |s: &str| {
+        !s.eq_ignore_ascii_case("image") && !s.eq_ignore_ascii_case("link")
+    })(i)
+}
+
+/// Parses a link destination starting with `link:scheme://` or `link:mailto:`
+/// ending with `]`, whitespace or newline. The later is peeked, but not consumed. The URL can contain percent
 /// encoded characters, which are decoded.
-fn adoc_parse_escaped_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_parse_escaped_link_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::combinator::map_parser(
         nom::sequence::preceded(
-            nom::sequence::pair(
-                tag("link:"),
-                peek(alt((tag_no_case("http://"), (tag_no_case("https://"))))),
-            ),
+            nom::sequence::pair(tag("link:"), peek(adoc_url_start)),
             nom::bytes::complete::take_till1(|c| {
                 c == '[' || c == ' ' || c == '\t' || c == '\r' || c == '\n'
             }),
@@ -344,7 +635,7 @@ fn adoc_parse_escaped_link_destination(i: &str) -> nom::IResult<&str, Cow<str>>
 
 /// Parses a link destination starting with `link:+++` ending with `++`. Everything in
 /// between is taken as it is without any transformation.
-fn adoc_parse_literal_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_parse_literal_link_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     let (j, s) = nom::sequence::preceded(
         tag("link:"),
         nom::sequence::delimited(tag("++"), nom::bytes::complete::take_until("++"), tag("++")),
@@ -356,7 +647,7 @@ fn adoc_parse_literal_link_destination(i: &str) -> nom::IResult<&str, Cow<str>>
 ///
 /// The parser expects to start at the opening `{` to succeed.
 /// The result is always a borrowed reference.
-fn adoc_parse_curly_bracket_reference(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn adoc_parse_curly_bracket_reference(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::combinator::map(
         nom::combinator::verify(
             nom::sequence::delimited(
@@ -494,6 +785,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_adoc_link_attributes() {
+        assert_eq!(
+            adoc_link_attributes("https://destination[name^]abc"),
+            Ok(("abc", vec![(Cow::from("window"), Cow::from("_blank"))]))
+        );
+        assert_eq!(
+            adoc_link_attributes(r#" link:https://destination[ "a, b" , x = y ]abc"#),
+            Ok(("abc", vec![(Cow::from("x"), Cow::from("y"))]))
+        );
+        assert_eq!(
+            adoc_link_attributes("https://destination[name]abc"),
+            Ok(("abc", vec![]))
+        );
+        assert!(adoc_link_attributes("https://destination abc").is_err());
+    }
+
+    #[test]
+    fn test_adoc_text_title() {
+        assert_eq!(adoc_text_title("name"), None);
+        assert_eq!(adoc_text_title("a, b"), None);
+        assert_eq!(
+            adoc_text_title(r#" "a, b" , title = "T, U" "#),
+            Some(("a, b", "T, U"))
+        );
+        assert_eq!(adoc_text_title("title=T"), Some(("", "T")));
+        assert_eq!(adoc_text_title("a,role=x"), Some(("a", "")));
+
+        // The link text defaults to the destination, also with attributes.
+        assert_eq!(
+            adoc_text2dest("https://destination[title=T]abc"),
+            Ok((
+                "abc",
+                (
+                    Cow::from("https://destination"),
+                    Cow::from("https://destination"),
+                    Cow::from("T")
+                )
+            ))
+        );
+        // Escaped brackets allocate.
+        assert_eq!(
+            adoc_text2dest(r#"https://destination[a,title="[b\]"]abc"#),
+            Ok((
+                "abc",
+                (
+                    Cow::from("a"),
+                    Cow::from("https://destination"),
+                    Cow::Owned::<str>("[b]".to_string())
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn test_adoc_img() {
+        assert_eq!(
+            adoc_img(r#"image::diagram.svg["Alt, text",300]abc"#),
+            Ok(("abc", (Cow::from("Alt, text"), Cow::from("diagram.svg"))))
+        );
+        assert_eq!(
+            adoc_img("image:a/b-c.d.png[width=30]abc"),
+            Ok(("abc", (Cow::from("b c.d"), Cow::from("a/b-c.d.png"))))
+        );
+        assert_eq!(
+            adoc_img("image:logo[Alt\\] text]abc"),
+            Ok(("abc", (Cow::from("Alt] text"), Cow::from("logo"))))
+        );
+        assert_eq!(
+            adoc_img_link("image:logo.png[Logo]"),
+            Ok(("", Link::Image(Cow::from("Logo"), Cow::from("logo.png"))))
+        );
+        assert!(adoc_img("image:logo.png abc").is_err());
+        assert!(adoc_img("image:[Alt]abc").is_err());
+        assert!(adoc_img("images:logo.png[Alt]abc").is_err());
+    }
+
     #[test]
     fn test_adoc_label2dest() {
         assert_eq!(
@@ -0,0 +1,275 @@
+//! This module implements parsers for Creole and DokuWiki hyperlinks.
+//!
+//! Both wiki dialects write links as `[[dest|text]]` and images, DokuWiki
+//! calls them media, as `{{src|alt}}`. `take_link_with()` uses these parsers
+//! instead of the MediaWiki ones in `wikitext`, when
+//! `ParserOptions::wiki_dialect` selects them.
+#![allow(dead_code)]
+#![allow(clippy::type_complexity)]
+
+use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use nom::bytes::complete::tag;
+use nom::bytes::complete::take_until;
+use nom::error::ErrorKind;
+use nom::error::VerboseError;
+use nom::sequence::delimited;
+
+/// Parse a [Creole](http://www.wikicreole.org/wiki/Creole1.0) link, e.g.
+/// `[[dest|text]]`.
+///
+/// The destination is an URL or the name of a wiki page. Without _link
+/// text_, the destination as written becomes the _link text_. An image as
+/// link text, e.g. `[[dest|{{src|alt}}]]`, results in a `Link::Image2Dest`,
+/// otherwise the link is a `Link::Text2Dest`.
+///
+/// The parser expects to start at the link start (`[[`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::creole::creole_text2dest_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     creole_text2dest_link("[[https://example.org|Example]]abc"),
+///     Ok(("abc", Link::Text2Dest(Cow::from("Example"), Cow::from("https://example.org"), Cow::from(""))))
+/// );
+/// assert_eq!(
+///     creole_text2dest_link("[[Home Page]]abc"),
+///     Ok(("abc", Link::Text2Dest(Cow::from("Home Page"), Cow::from("Home Page"), Cow::from(""))))
+/// );
+/// ```
+pub fn creole_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wiki_link(i, false)
+}
+
+/// Same as `creole_text2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn creole_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("creole_text2dest_link", |i| wiki_link(i, false))(i)
+}
+
+/// Parse a Creole image, e.g. `{{src|alt}}`, into a `Link::Image`. Without
+/// _image alt_, it is empty.
+///
+/// The parser expects to start at the image start (`{{`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::creole::creole_img_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     creole_img_link("{{logo.png|Our logo}}abc"),
+///     Ok(("abc", Link::Image(Cow::from("Our logo"), Cow::from("logo.png"))))
+/// );
+/// ```
+pub fn creole_img_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wiki_img_link(i, false)
+}
+
+/// Same as `creole_img_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn creole_img_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("creole_img_link", |i| wiki_img_link(i, false))(i)
+}
+
+/// Parse a [DokuWiki](https://www.dokuwiki.org/wiki:syntax#links) link,
+/// e.g. `[[dest|text]]`.
+///
+/// Same as `creole_text2dest_link()`, except that the media parameters of
+/// an embedded image are removed, see `dokuwiki_img_link()`. Internal links
+/// keep their namespace and section, e.g. `[[wiki:syntax#links]]` has the
+/// destination `wiki:syntax#links`.
+///
+/// The parser expects to start at the link start (`[[`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::creole::dokuwiki_text2dest_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     dokuwiki_text2dest_link("[[https://example.org|{{ logo.png?50 |Logo}}]]abc"),
+///     Ok(("abc", Link::Image2Dest(
+///         Cow::from(""),
+///         Cow::from("Logo"),
+///         Cow::from("logo.png"),
+///         Cow::from(""),
+///         Cow::from("https://example.org"),
+///         Cow::from(""),
+///     )))
+/// );
+/// ```
+pub fn dokuwiki_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wiki_link(i, true)
+}
+
+/// Same as `dokuwiki_text2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn dokuwiki_text2dest_link_verbose(
+    i: &str,
+) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("dokuwiki_text2dest_link", |i| wiki_link(i, true))(i)
+}
+
+/// Parse a DokuWiki media link, e.g. `{{ src?200x50 |alt}}`, into a
+/// `Link::Image`. The whitespace, that aligns the image, and the media
+/// parameters after `?` are not part of the _image source_.
+///
+/// The parser expects to start at the media start (`{{`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::creole::dokuwiki_img_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     dokuwiki_img_link("{{ wiki:logo.png?200x50|Logo}}abc"),
+///     Ok(("abc", Link::Image(Cow::from("Logo"), Cow::from("wiki:logo.png"))))
+/// );
+/// ```
+pub fn dokuwiki_img_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wiki_img_link(i, true)
+}
+
+/// Same as `dokuwiki_img_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn dokuwiki_img_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("dokuwiki_img_link", |i| wiki_img_link(i, true))(i)
+}
+
+/// Parses `[[dest|text]]`. If `dokuwiki`, media parameters of an embedded
+/// image are removed.
+fn wiki_link<'a, E: LinkError<'a>>(
+    i: &'a str,
+    dokuwiki: bool,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (j, inner) = delimited(tag("[["), take_until("]]"), tag("]]"))(i)?;
+    if inner.contains(['\n', '\r']) || inner.contains("[[") {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    let (dest, text) = match inner.split_once('|') {
+        Some((dest, text)) => (dest.trim(), text.trim()),
+        None => (inner.trim(), ""),
+    };
+    if dest.is_empty() {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    if let Ok(("", (alt, src))) = wiki_img::<E>(text, dokuwiki) {
+        return Ok((
+            j,
+            Link::Image2Dest(
+                Cow::from(""),
+                alt,
+                src,
+                Cow::from(""),
+                Cow::from(dest),
+                Cow::from(""),
+            ),
+        ));
+    }
+    let text = if text.is_empty() { dest } else { text };
+    Ok((
+        j,
+        Link::Text2Dest(Cow::from(text), Cow::from(dest), Cow::from("")),
+    ))
+}
+
+/// Parses `{{src|alt}}` into a `Link::Image`. If `dokuwiki`, media
+/// parameters after `?` are removed from `src`.
+fn wiki_img_link<'a, E: LinkError<'a>>(
+    i: &'a str,
+    dokuwiki: bool,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src)) = wiki_img(i, dokuwiki)?;
+    Ok((i, Link::Image(alt, src)))
+}
+
+/// Parses `{{src|alt}}` and returns `(alt, src)`. If `dokuwiki`, media
+/// parameters after `?` are removed from `src`.
+fn wiki_img<'a, E: LinkError<'a>>(
+    i: &'a str,
+    dokuwiki: bool,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (j, inner) = delimited(tag("{{"), take_until("}}"), tag("}}"))(i)?;
+    if inner.contains(['\n', '\r']) || inner.contains("{{") {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    let (src, alt) = inner.split_once('|').unwrap_or((inner, ""));
+    let src = match src.split_once('?') {
+        Some((src, _)) if dokuwiki => src,
+        _ => src,
+    };
+    let src = src.trim();
+    if src.is_empty() {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    Ok((j, (Cow::from(alt.trim()), Cow::from(src))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiki_link() {
+        assert_eq!(
+            creole_text2dest_link("[[ a | b c ]]"),
+            Ok((
+                "",
+                Link::Text2Dest(Cow::from("b c"), Cow::from("a"), Cow::from(""))
+            ))
+        );
+        assert_eq!(
+            creole_text2dest_link("[[a?b|{{c?d|e}}]]"),
+            Ok((
+                "",
+                Link::Image2Dest(
+                    Cow::from(""),
+                    Cow::from("e"),
+                    Cow::from("c?d"),
+                    Cow::from(""),
+                    Cow::from("a?b"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert_eq!(
+            dokuwiki_text2dest_link("[[wiki:syntax#links|]]"),
+            Ok((
+                "",
+                Link::Text2Dest(
+                    Cow::from("wiki:syntax#links"),
+                    Cow::from("wiki:syntax#links"),
+                    Cow::from("")
+                )
+            ))
+        );
+        // Text, that is not only an image.
+        assert_eq!(
+            dokuwiki_text2dest_link("[[a|{{b}} c]]"),
+            Ok((
+                "",
+                Link::Text2Dest(Cow::from("{{b}} c"), Cow::from("a"), Cow::from(""))
+            ))
+        );
+        assert!(creole_text2dest_link("[[|a]]").is_err());
+        assert!(creole_text2dest_link("[[a\nb]]").is_err());
+        assert!(creole_text2dest_link("[[a [[b]]").is_err());
+        assert!(creole_text2dest_link("[[a]").is_err());
+        assert!(creole_text2dest_link("[a]]").is_err());
+    }
+
+    #[test]
+    fn test_wiki_img() {
+        assert_eq!(
+            creole_img_link("{{a?b}}"),
+            Ok(("", Link::Image(Cow::from(""), Cow::from("a?b"))))
+        );
+        assert_eq!(
+            dokuwiki_img_link("{{ a?b |}}"),
+            Ok(("", Link::Image(Cow::from(""), Cow::from("a"))))
+        );
+        assert!(dokuwiki_img_link("{{?b}}").is_err());
+        assert!(creole_img_link("{{a\n}}").is_err());
+        assert!(creole_img_link("{a}}").is_err());
+    }
+}
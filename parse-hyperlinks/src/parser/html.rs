@@ -2,26 +2,52 @@
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::generic_error;
 use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::ops::Range;
 use html_escape::decode_html_entities;
 use nom::branch::alt;
 use nom::bytes::complete::is_not;
 use nom::bytes::complete::tag;
+use nom::bytes::complete::take_till1;
+use nom::bytes::complete::take_while;
 use nom::character::complete::alphanumeric1;
+use nom::character::complete::multispace0;
+use nom::character::complete::multispace1;
 use nom::error::Error;
 use nom::error::ErrorKind;
-use std::borrow::Cow;
+use nom::error::VerboseError;
 
 /// Wrapper around `html_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn html_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = html_text2dest(i)?;
+pub fn html_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_text2dest_link_generic(i)
+}
+
+/// Same as `html_text2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn html_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_text2dest_link", html_text2dest_link_generic)(i)
+}
+
+/// Same as `html_text2dest_link()`, but generic over the error type.
+fn html_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = html_text2dest_generic(i)?;
     Ok((i, Link::Text2Dest(te, de, ti)))
 }
 
 /// Parse an HTML _inline hyperlink_.
 ///
 /// It returns either `Ok((i, (link_text, link_destination, link_title)))` or some error.
+/// Nested tags like `<b>` or `<span>` are removed from `link_text`, only
+/// `<img>` tags are kept: `html_img2dest()` handles these.
 ///
 /// The parser expects to start at the link start (`<`) to succeed.
 /// ```
@@ -34,10 +60,17 @@ pub fn html_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from("name"), Cow::from("destination"), Cow::from("title"))))
 /// );
 /// ```
-pub fn html_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn html_text2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    html_text2dest_generic(i)
+}
+
+/// Same as `html_text2dest()`, but generic over the error type.
+fn html_text2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     let (i, ((link_destination, link_title), link_text)) = nom::sequence::terminated(
         nom::sequence::pair(
-            tag_a_opening,
+            generic_error(tag_a_opening),
             alt((
                 nom::bytes::complete::take_until("</a>"),
                 nom::bytes::complete::take_until("</A>"),
@@ -47,67 +80,413 @@ pub fn html_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<st
         // Here we deal with HTML.
         alt((tag("</a>"), tag("</A>"))),
     )(i)?;
-    let link_text = decode_html_entities(link_text);
+    let link_text = inner_text(link_text, true);
     Ok((i, (link_text, link_destination, link_title)))
 }
 
+/// Returns the text content of the HTML fragment `html`: nested tags like
+/// `<span>` or `</b>` and comments are removed, character references are
+/// decoded. If `keep_img` is true, `<img>` tags are kept verbatim, as they
+/// have no text content.
+pub(crate) fn inner_text(html: &str, keep_img: bool) -> Cow<'_, str> {
+    let mut text = String::new();
+    // End of the last removed tag.
+    let mut last = 0;
+    for (n, _) in html.match_indices('<') {
+        if n < last {
+            continue;
+        }
+        let rest = match nested_tag(&html[n..]) {
+            Ok((rest, name)) if !(keep_img && name.eq_ignore_ascii_case("img")) => rest,
+            _ => continue,
+        };
+        text.push_str(&html[last..n]);
+        last = html.len() - rest.len();
+    }
+    if last == 0 {
+        return decode_entities(html);
+    }
+    text.push_str(&html[last..]);
+    Cow::Owned(decode_entities(&text).into_owned())
+}
+
+/// Parses a start tag, an end tag or a comment and returns the element
+/// name. Comments have an empty name.
+fn nested_tag(i: &str) -> nom::IResult<&str, &str> {
+    alt((
+        nom::combinator::value(
+            "",
+            nom::sequence::tuple((
+                tag("<!--"),
+                nom::bytes::complete::take_until("-->"),
+                tag("-->"),
+            )),
+        ),
+        nom::sequence::delimited(
+            nom::sequence::pair(tag("<"), nom::combinator::opt(tag("/"))),
+            nom::combinator::verify(alphanumeric1, |s: &str| {
+                nom::character::is_alphabetic(s.as_bytes()[0])
+            }),
+            nom::sequence::pair(nom::combinator::opt(tag_content), tag(">")),
+        ),
+    ))(i)
+}
+
+/// Decodes the character references of `i`: named references like
+/// `&amp;` and numeric references like `&#47;` or `&#x2F;`. Numeric
+/// references are decoded as
+/// [HTML5](https://html.spec.whatwg.org/multipage/parsing.html#numeric-character-reference-end-state)
+/// does: the trailing `;` is optional, invalid code points become `U+FFFD`
+/// and the code points 128 to 159 are mapped as in Windows-1252.
+///
+/// ```
+/// use parse_hyperlinks::parser::html::decode_entities;
+///
+/// assert_eq!(decode_entities("It&#8217;s a&#x2F;b&#47c &amp; &#150; &#0;"), "It’s a/b/c & – \u{fffd}");
+/// assert_eq!(decode_entities("a & b &unknown; &#;"), "a & b &unknown; &#;");
+/// ```
+pub fn decode_entities(i: &str) -> Cow<'_, str> {
+    if !i.contains('&') {
+        return Cow::Borrowed(i);
+    }
+    let mut out = String::new();
+    // End of the last decoded reference.
+    let mut last = 0;
+    for (n, _) in i.match_indices('&') {
+        if n < last {
+            continue;
+        }
+        let Some((len, c)) = character_reference(&i[n..]) else {
+            continue;
+        };
+        out.push_str(&i[last..n]);
+        out.push_str(&c);
+        last = n + len;
+    }
+    if last == 0 {
+        return Cow::Borrowed(i);
+    }
+    out.push_str(&i[last..]);
+    Cow::Owned(out)
+}
+
+/// Decodes the character reference at the start of `i` and returns its
+/// length and its replacement.
+pub(crate) fn character_reference(i: &str) -> Option<(usize, Cow<'_, str>)> {
+    /// Windows-1252 characters replacing the code points 128 to 159.
+    const C1: [u32; 32] = [
+        0x20AC, 0x81, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160,
+        0x2039, 0x0152, 0x8D, 0x017D, 0x8F, 0x90, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013,
+        0x2014, 0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x9D, 0x017E, 0x0178,
+    ];
+
+    let rest = i.strip_prefix('&')?;
+    let Some(number) = rest.strip_prefix('#') else {
+        // A named reference.
+        let len = rest.find(|c: char| !c.is_ascii_alphanumeric())?;
+        if len == 0 || !rest[len..].starts_with(';') {
+            return None;
+        }
+        let reference = &i[..len + 2];
+        let decoded = decode_html_entities(reference);
+        return (decoded != reference).then_some((reference.len(), decoded));
+    };
+    let (radix, digits) = match number.strip_prefix(['x', 'X']) {
+        Some(digits) => (16, digits),
+        None => (10, number),
+    };
+    let len = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    if len == 0 {
+        return None;
+    }
+    let code = u32::from_str_radix(&digits[..len], radix).unwrap_or(u32::MAX);
+    let code = match code {
+        0x80..=0x9F => C1[code as usize - 0x80],
+        c => c,
+    };
+    let c = match code {
+        0 => '\u{fffd}',
+        c => char::from_u32(c).unwrap_or('\u{fffd}'),
+    };
+    let semicolon = usize::from(digits[len..].starts_with(';'));
+    let reference_len = i.len() - digits.len() + len + semicolon;
+    Some((reference_len, Cow::Owned(c.to_string())))
+}
+
 /// Parses a `<a ...>` opening tag and returns
 /// either `Ok((i, (link_destination, link_title)))` or some error.
-pub(crate) fn tag_a_opening(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+pub(crate) fn tag_a_opening(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
     nom::sequence::delimited(
         // HTML is case insensitive. XHTML, that is being XML is case sensitive.
         // Here we deal with HTML.
-        alt((tag("<a "), tag("<A "))),
-        nom::combinator::map_parser(is_not(">"), parse_attributes),
+        nom::sequence::terminated(alt((tag("<a"), tag("<A"))), multispace1),
+        nom::combinator::map_parser(tag_content, parse_attributes),
         tag(">"),
     )(i)
 }
 
+/// Consumes the attributes of a start tag until the closing `>`. Unlike
+/// `is_not(">")`, it skips `>` inside quoted attribute values.
+pub(crate) fn tag_content(i: &str) -> nom::IResult<&str, &str> {
+    let mut quote = None;
+    for (n, c) in i.char_indices() {
+        match (quote, c) {
+            (None, '>') if n > 0 => return Ok((&i[n..], &i[..n])),
+            (None, '>') => break,
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            _ => {}
+        }
+    }
+    Err(nom::Err::Error(Error::new(i, ErrorKind::IsNot)))
+}
+
 /// Parses attributes and returns `Ok((name, value))`.
-/// Boolean attributes are ignored, but silently consumed.
-fn attribute(i: &str) -> nom::IResult<&str, (&str, Cow<str>)> {
-    alt((
-        nom::sequence::pair(
+/// Boolean attributes are ignored, but silently consumed. Values can be
+/// double quoted, single quoted or unquoted, whitespace around `=` is
+/// allowed.
+fn attribute(i: &str) -> nom::IResult<&str, (&str, Cow<'_, str>)> {
+    let name = || {
+        nom::combinator::recognize(nom::sequence::pair(
             nom::combinator::verify(alphanumeric1, |s: &str| {
                 nom::character::is_alphabetic(s.as_bytes()[0])
             }),
-            alt((
-                nom::combinator::value(Cow::from(""), tag(r#"="""#)),
-                nom::combinator::value(Cow::from(""), tag(r#"=''"#)),
-                nom::combinator::map(
-                    nom::sequence::delimited(tag("=\""), is_not("\""), tag("\"")),
-                    |s: &str| decode_html_entities(s),
-                ),
-                nom::combinator::map(
-                    nom::sequence::delimited(tag("='"), is_not("'"), tag("'")),
-                    |s: &str| decode_html_entities(s),
-                ),
-                nom::combinator::map(nom::sequence::preceded(tag("="), is_not(" ")), |s: &str| {
-                    decode_html_entities(s)
-                }),
-            )),
+            take_while(|c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')),
+        ))
+    };
+    alt((
+        nom::sequence::pair(
+            name(),
+            nom::sequence::preceded(
+                nom::sequence::tuple((multispace0, tag("="), multispace0)),
+                alt((
+                    nom::combinator::value(Cow::from(""), tag(r#""""#)),
+                    nom::combinator::value(Cow::from(""), tag("''")),
+                    nom::combinator::map(
+                        nom::sequence::delimited(tag("\""), is_not("\""), tag("\"")),
+                        |s: &str| decode_entities(s),
+                    ),
+                    nom::combinator::map(
+                        nom::sequence::delimited(tag("'"), is_not("'"), tag("'")),
+                        |s: &str| decode_entities(s),
+                    ),
+                    nom::combinator::map(is_not(" \t\r\n\x0c"), |s: &str| decode_entities(s)),
+                )),
+            ),
         ),
         // Consume boolean attributes.
-        nom::combinator::value(
-            ("", Cow::from("")),
-            nom::combinator::verify(alphanumeric1, |s: &str| {
-                nom::character::is_alphabetic(s.as_bytes()[0])
-            }),
-        ),
+        nom::combinator::value(("", Cow::from("")), name()),
     ))(i)
 }
 
 /// Parses a whitespace separated list of attributes and returns a vector of (name, value).
-pub fn attribute_list<'a>(i: &'a str) -> nom::IResult<&'a str, Vec<(&'a str, Cow<str>)>> {
+pub fn attribute_list(i: &str) -> nom::IResult<&str, Vec<(&str, Cow<'_, str>)>> {
     let i = i.trim();
     nom::multi::separated_list1(nom::character::complete::multispace1, attribute)(i)
 }
 
+/// Wrapper around `html_area2dest()` that packs the result in
+/// `Link::Text2Dest`.
+pub fn html_area2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_area2dest_link_generic(i)
+}
+
+/// Same as `html_area2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn html_area2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_area2dest_link", html_area2dest_link_generic)(i)
+}
+
+/// Same as `html_area2dest_link()`, but generic over the error type.
+fn html_area2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = html_area2dest_generic(i)?;
+    Ok((i, Link::Text2Dest(te, de, ti)))
+}
+
+/// Parses an HTML image map area `<area href=...>`.
+///
+/// It returns either `Ok((i, (area_alt, link_destination, link_title)))` or
+/// some error.
+///
+/// The parser expects to start at the element start (`<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::html::html_area2dest;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   html_area2dest(r#"<area shape="rect" coords="0,0,9,9" href="dest" alt="alt">abc"#),
+///   Ok(("abc", (Cow::from("alt"), Cow::from("dest"), Cow::from(""))))
+/// );
+/// ```
+pub fn html_area2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    html_area2dest_generic(i)
+}
+
+/// Same as `html_area2dest()`, but generic over the error type.
+fn html_area2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(|i| void_element_link("area", "alt", i))(i)
+}
+
+/// Wrapper around `html_rel2dest()` that packs the result in
+/// `Link::Text2Dest`.
+pub fn html_rel2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_rel2dest_link_generic(i)
+}
+
+/// Same as `html_rel2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn html_rel2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_rel2dest_link", html_rel2dest_link_generic)(i)
+}
+
+/// Same as `html_rel2dest_link()`, but generic over the error type.
+fn html_rel2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = html_rel2dest_generic(i)?;
+    Ok((i, Link::Text2Dest(te, de, ti)))
+}
+
+/// Parses an HTML document link `<link rel=... href=...>`, e.g. a
+/// canonical or an alternate link. The link text is the value of the
+/// `rel` attribute.
+///
+/// It returns either `Ok((i, (link_rel, link_destination, link_title)))` or
+/// some error.
+///
+/// The parser expects to start at the element start (`<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::html::html_rel2dest;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   html_rel2dest(r#"<link rel="canonical" href="https://getreu.net/">abc"#),
+///   Ok(("abc", (Cow::from("canonical"), Cow::from("https://getreu.net/"), Cow::from(""))))
+/// );
+/// ```
+pub fn html_rel2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    html_rel2dest_generic(i)
+}
+
+/// Same as `html_rel2dest()`, but generic over the error type.
+fn html_rel2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(|i| void_element_link("link", "rel", i))(i)
+}
+
+/// Parses the void element `<name ...>` with a non-empty `href` attribute
+/// and returns `(text, href, title)`, where `text` is the value of the
+/// attribute `text_attribute`.
+fn void_element_link<'a>(
+    name: &'static str,
+    text_attribute: &'static str,
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>)> {
+    let (i, attributes) = nom::sequence::delimited(
+        nom::sequence::tuple((
+            tag("<"),
+            nom::bytes::complete::tag_no_case(name),
+            multispace1,
+        )),
+        tag_content,
+        tag(">"),
+    )(i)?;
+    let (_, attributes) = attribute_list(attributes)?;
+    let mut text = Cow::Borrowed("");
+    let (_, (href, title)) = parse_attributes_with("", attributes, |name, value| {
+        if name == text_attribute {
+            text = value;
+        }
+    })?;
+    Ok((i, (text, href, title)))
+}
+
+/// Finds the first `<base href=...>` element of an HTML document and
+/// returns its `href` attribute. Relative link destinations of the
+/// document are resolved against it, see `resolve::resolve()`.
+///
+/// ```
+/// use parse_hyperlinks::parser::html::html_base;
+///
+/// let html = r#"<head><base href="https://getreu.net/doc/"></head><a href="a.html">a</a>"#;
+/// assert_eq!(html_base(html).as_deref(), Some("https://getreu.net/doc/"));
+/// ```
+pub fn html_base(html: &str) -> Option<Cow<'_, str>> {
+    html.match_indices('<').find_map(|(n, _)| {
+        let (_, (_, href, _)) = void_element_link("base", "", &html[n..]).ok()?;
+        Some(href)
+    })
+}
+
+/// Parses the opening tag of an HTML _inline hyperlink_ (`<a ...>`) or
+/// _image_ (`<img ...>`) and returns all its attributes, except those already
+/// captured in `Link`: `href` and `title` for hyperlinks, `src` and `alt` for
+/// images. Boolean attributes are ignored.
+///
+/// The parser expects to start at the tag start (`<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::html::html_link_attributes;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   html_link_attributes(r#"<a href="dest" class="ext" rel="nofollow">text</a>"#),
+///   Ok(("text</a>", vec![(Cow::from("class"), Cow::from("ext")),
+///                        (Cow::from("rel"), Cow::from("nofollow"))]))
+/// );
+/// ```
+pub fn html_link_attributes(i: &str) -> nom::IResult<&str, Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+    let (i, (element, attributes)) = nom::sequence::pair(
+        nom::sequence::preceded(
+            tag("<"),
+            nom::sequence::terminated(
+                alt((
+                    nom::bytes::complete::tag_no_case("a"),
+                    nom::bytes::complete::tag_no_case("img"),
+                )),
+                multispace1,
+            ),
+        ),
+        nom::sequence::terminated(tag_content, tag(">")),
+    )(i)?;
+    let (_, attributes) = attribute_list(attributes)?;
+    let captured = if element.eq_ignore_ascii_case("a") {
+        ["href", "title"]
+    } else {
+        ["src", "alt"]
+    };
+
+    Ok((
+        i,
+        attributes
+            .into_iter()
+            .filter(|(name, _)| !name.is_empty() && !captured.contains(name))
+            .map(|(name, value)| (Cow::Borrowed(name), value))
+            .collect(),
+    ))
+}
+
 /// Extracts the `href` and `title` attributes and returns
 /// `Ok((link_destination, link_title))`. `link_title` can be empty,
 /// `link_destination` not.
-fn parse_attributes(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+fn parse_attributes(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
     let (i, attributes) = attribute_list(i)?;
+    parse_attributes_with(i, attributes, |_, _| {})
+}
+
+/// Extracts the `href` and `title` attributes of `attributes`, passes all
+/// others to `other`. Fails if `href` is empty. `i` is the remaining input.
+fn parse_attributes_with<'a>(
+    i: &'a str,
+    attributes: Vec<(&'a str, Cow<'a, str>)>,
+    mut other: impl FnMut(&'a str, Cow<'a, str>),
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>)> {
     let mut href = Cow::Borrowed("");
     let mut title = Cow::Borrowed("");
 
@@ -115,32 +494,139 @@ fn parse_attributes(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
         if name == "href" {
             // Make sure `href` is empty, it can appear only
             // once.
-            if !(&*href).is_empty() {
+            if !href.is_empty() {
                 return Err(nom::Err::Error(Error::new(name, ErrorKind::ManyMN)));
             }
             href = value;
         } else if name == "title" {
             // Make sure `title` is empty, it can appear only
             // once.
-            if !(&*title).is_empty() {
+            if !title.is_empty() {
                 return Err(nom::Err::Error(Error::new(name, ErrorKind::ManyMN)));
             }
             title = value;
+        } else {
+            other(name, value);
         }
     }
 
     // Assure that `href` is not empty.
-    if (&*href).is_empty() {
+    if href.is_empty() {
         return Err(nom::Err::Error(Error::new(i, ErrorKind::Eof)));
     };
 
     Ok((i, (href, title)))
 }
 
+/// Extracts all anchors of an HTML document: the values of all `id`
+/// attributes and of the `name` attributes of `<a>` elements.
+pub(crate) fn html_anchors(html: &str) -> Vec<Cow<'_, str>> {
+    html_anchor_iter(html).map(|(anchor, _)| anchor).collect()
+}
+
+/// Iterates over the anchors of an HTML document, i.e. the targets of
+/// `#fragment` links: the values of all `id` attributes and of the `name`
+/// attributes of `<a>` elements. Every anchor comes with the byte range of
+/// the start tag defining it. Comments are skipped.
+///
+/// ```
+/// use parse_hyperlinks::parser::html::html_anchor_iter;
+/// use std::borrow::Cow;
+///
+/// let html = r#"<h1 id="top">Title</h1><!-- <p id="old"> --><a name=end></a>"#;
+/// let anchors: Vec<_> = html_anchor_iter(html).collect();
+/// assert_eq!(anchors, [(Cow::from("top"), 0..13), (Cow::from("end"), 44..56)]);
+/// ```
+pub fn html_anchor_iter(html: &str) -> impl Iterator<Item = (Cow<'_, str>, Range<usize>)> {
+    let mut i = html;
+    core::iter::from_fn(move || {
+        while let Some(n) = i.find('<') {
+            i = &i[n..];
+            if let Some(comment) = i.strip_prefix("<!--") {
+                i = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+                continue;
+            }
+            match html_anchor(i) {
+                Ok((j, Some(anchor))) => {
+                    let start = html.len() - i.len();
+                    i = j;
+                    return Some((anchor, start..html.len() - j.len()));
+                }
+                Ok((j, None)) => i = j,
+                Err(_) => i = &i['<'.len_utf8()..],
+            }
+        }
+        None
+    })
+}
+
+/// Parses an HTML start tag and returns the anchor it defines, if any.
+fn html_anchor(i: &str) -> nom::IResult<&str, Option<Cow<'_, str>>> {
+    let (i, (name, attributes)) = nom::sequence::delimited(
+        tag("<"),
+        nom::sequence::pair(
+            take_till1(|c: char| !c.is_ascii_alphanumeric()),
+            nom::combinator::opt(tag_content),
+        ),
+        tag(">"),
+    )(i)?;
+    let is_a = name.eq_ignore_ascii_case("a");
+
+    let mut anchor = None;
+    let mut j = attributes.unwrap_or_default();
+    while let Ok((k, (attr, value))) = tag_attribute(j) {
+        if attr.eq_ignore_ascii_case("id") || (is_a && attr.eq_ignore_ascii_case("name")) {
+            anchor = Some(value);
+        }
+        j = k;
+    }
+    Ok((i, anchor.filter(|a| !a.is_empty())))
+}
+
+/// Parses one attribute inside a start tag. Unlike `attribute()`, this
+/// parser accepts any attribute name
+/// and values without quotes.
+fn tag_attribute(i: &str) -> nom::IResult<&str, (&str, Cow<'_, str>)> {
+    let (i, _) = nom::character::complete::multispace0(i)?;
+    let (i, name) = take_till1(|c: char| c.is_whitespace() || c == '=' || c == '/')(i)
+        .or_else(|_: nom::Err<nom::error::Error<&str>>| tag("/")(i))?;
+    let (i, value) = nom::combinator::opt(nom::sequence::preceded(
+        nom::sequence::tuple((
+            nom::character::complete::multispace0,
+            tag("="),
+            nom::character::complete::multispace0,
+        )),
+        alt((
+            nom::sequence::delimited(tag("\""), nom::combinator::opt(is_not("\"")), tag("\"")),
+            nom::sequence::delimited(tag("'"), nom::combinator::opt(is_not("'")), tag("'")),
+            nom::combinator::map(is_not(" \t\r\n"), Some),
+        )),
+    ))(i)?;
+    let value = decode_entities(value.flatten().unwrap_or_default());
+    Ok((i, (name, value)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_html_link_attributes() {
+        assert_eq!(
+            html_link_attributes(r#"<IMG src="x.png" alt="y" width=500 hidden/>abc"#),
+            Ok(("abc", vec![(Cow::from("width"), Cow::from("500"))]))
+        );
+        assert_eq!(
+            html_link_attributes(r#"<a href="x">abc"#),
+            Ok(("abc", vec![]))
+        );
+        assert_eq!(
+            html_link_attributes(r#"<a target="_blank" href="x" title="y">abc"#),
+            Ok(("abc", vec![(Cow::from("target"), Cow::from("_blank"))]))
+        );
+        assert!(html_link_attributes(r#"<p class="x">abc"#).is_err());
+    }
+
     #[test]
     fn test_html_text2dest() {
         let expected = (
@@ -214,6 +700,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inner_text() {
+        assert_eq!(
+            html_text2dest(r#"<a href="url"><b>bold</b> <span class="x">&amp;</span></a>abc"#),
+            Ok((
+                "abc",
+                (Cow::from("bold &"), Cow::from("url"), Cow::from("")),
+            ))
+        );
+        assert_eq!(inner_text("a<!-- <b> -->b<br/>c", false), "abc");
+        assert_eq!(inner_text("a<img src=x>b", false), "ab");
+        assert_eq!(inner_text("a<img src=x>b", true), "a<img src=x>b");
+        assert_eq!(inner_text("a < b </ c", true), "a < b </ c");
+        assert!(matches!(inner_text("a &lt; b", true), Cow::Owned(_)));
+        assert!(matches!(inner_text("a b", true), Cow::Borrowed(_)));
+    }
+
     #[test]
     fn test_tag_a_opening() {
         let expected = (
@@ -228,6 +731,52 @@ mod tests {
             tag_a_opening(r#"<A href="http://getreu.net" title="My blog">abc"#).unwrap(),
             expected
         );
+        assert_eq!(
+            tag_a_opening("<a\n  href=http://getreu.net\n  title='My blog'>abc").unwrap(),
+            expected
+        );
+        assert_eq!(
+            tag_a_opening(r#"<a data-x="1>2" href = http://getreu.net title="My blog">abc"#)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_html_void_element_links() {
+        let expected = (
+            "abc",
+            (Cow::from("alt"), Cow::from("dest"), Cow::from("title")),
+        );
+        assert_eq!(
+            html_area2dest(r#"<AREA title="title" href=dest alt='alt'>abc"#),
+            Ok(expected)
+        );
+        assert!(html_area2dest(r#"<area alt="alt">abc"#).is_err());
+        assert_eq!(
+            html_rel2dest_link(r#"<link rel="alternate" hreflang="de" href="/de/" />abc"#),
+            Ok((
+                "abc",
+                Link::Text2Dest(Cow::from("alternate"), Cow::from("/de/"), Cow::from(""))
+            ))
+        );
+        assert!(html_rel2dest(r#"<linked href="dest">abc"#).is_err());
+
+        assert_eq!(html_base("<a href=x>y</a>"), None);
+        assert_eq!(
+            html_base("a < b <BASE target=_top href=/doc/>").as_deref(),
+            Some("/doc/")
+        );
+    }
+
+    #[test]
+    fn test_tag_content() {
+        assert_eq!(
+            tag_content(r#"a="b>c" d='>'>e"#),
+            Ok((">e", r#"a="b>c" d='>'"#))
+        );
+        assert!(tag_content(">e").is_err());
+        assert!(tag_content(r#"a="b>c"#).is_err());
     }
 
     #[test]
@@ -314,6 +863,21 @@ mod tests {
             expected
         );
 
+        // Whitespace around `=`.
+        assert_eq!(
+            attribute("href = 'http://getreu.net' abc").unwrap(),
+            (" abc", ("href", Cow::from("http://getreu.net")))
+        );
+        // Unquoted values end at any whitespace.
+        assert_eq!(
+            attribute("href=http://getreu.net\tabc").unwrap(),
+            ("\tabc", ("href", Cow::from("http://getreu.net")))
+        );
+        assert_eq!(
+            attribute(r#"data-x_1="y" abc"#).unwrap(),
+            (" abc", ("data-x_1", Cow::from("y")))
+        );
+
         let expected = (" abc", ("", Cow::from("")));
         assert_eq!(attribute("bool abc").unwrap(), expected);
 
@@ -332,4 +896,50 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_html_anchors() {
+        let html = r#"<html><body data-x=1>
+<h1 id="title">Title</h1>
+<h2 class='a-b' ID = 'sec&amp;1'>Section</h2>
+<a name=old>old</a> <p name="no-anchor">
+<img src="x.png" id=""/> a < b
+<div
+  id="multi-line">
+"#;
+        assert_eq!(
+            html_anchors(html),
+            vec![
+                Cow::from("title"),
+                Cow::from("sec&1"),
+                Cow::from("old"),
+                Cow::from("multi-line")
+            ]
+        );
+
+        let spans: Vec<_> = html_anchor_iter(html).map(|(_, span)| span).collect();
+        assert_eq!(&html[spans[0].clone()], r#"<h1 id="title">"#);
+        assert_eq!(&html[spans[3].clone()], "<div\n  id=\"multi-line\">");
+
+        assert_eq!(html_anchor_iter("<!-- <a id=x>").count(), 0);
+        assert_eq!(html_anchor_iter("<!----><a id=x>").count(), 1);
+    }
+
+    #[test]
+    fn test_html_anchor() {
+        assert_eq!(
+            html_anchor(r#"<h1 id="title">abc"#),
+            Ok(("abc", Some(Cow::from("title"))))
+        );
+        assert_eq!(html_anchor(r#"<h1>abc"#), Ok(("abc", None)));
+        assert_eq!(
+            html_anchor(r#"<a href="x" name="y" />abc"#),
+            Ok(("abc", Some(Cow::from("y"))))
+        );
+        assert_eq!(
+            html_anchor(r#"<p title="a > b" id='c'>abc"#),
+            Ok(("abc", Some(Cow::from("c"))))
+        );
+        assert!(html_anchor("< h1>").is_err());
+    }
 }
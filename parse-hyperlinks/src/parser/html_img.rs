@@ -1,22 +1,39 @@
 //! This module implements parsers for HTML image elements.
 #![allow(dead_code)]
 
+use crate::parser::generic_error;
 use crate::parser::html::attribute_list;
+use crate::parser::html::inner_text;
 use crate::parser::html::tag_a_opening as href_tag_a_opening;
+use crate::parser::html::tag_content;
 use crate::parser::Link;
-use html_escape::decode_html_entities;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use nom::branch::alt;
-use nom::bytes::complete::is_not;
 use nom::bytes::complete::tag;
+use nom::character::complete::multispace1;
 use nom::error::Error;
 use nom::error::ErrorKind;
+use nom::error::VerboseError;
 use nom::sequence::tuple;
-use std::borrow::Cow;
 
 /// Wrapper around `html_img()` that packs the result in
 /// `Link::Image`.
-pub fn html_img_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (alt, src)) = html_img(i)?;
+pub fn html_img_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_img_link_generic(i)
+}
+
+/// Same as `html_img_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn html_img_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_img_link", html_img_link_generic)(i)
+}
+
+/// Same as `html_img_link()`, but generic over the error type.
+fn html_img_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src)) = html_img_generic(i)?;
     Ok((i, Link::Image(alt, src)))
 }
 
@@ -35,27 +52,142 @@ pub fn html_img_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from("my Dog"), Cow::from("/images/my&dog.png"))))
 /// );
 /// ```
-pub fn html_img(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
-    tag_img(i)
+pub fn html_img(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    html_img_generic(i)
+}
+
+/// Same as `html_img()`, but generic over the error type.
+fn html_img_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(tag_img)(i)
 }
 
 /// Parses a `<img ...>` tag and returns
 /// either `Ok((i, (img_alt, img_src)))` or some error.
 #[inline]
-fn tag_img(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+fn tag_img(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
     nom::sequence::delimited(
         // HTML is case insensitive. XHTML, that is being XML is case sensitive.
         // Here we deal with HTML.
-        alt((tag("<img "), tag("<IMG "))),
-        nom::combinator::map_parser(is_not(">"), parse_attributes),
+        nom::sequence::terminated(alt((tag("<img"), tag("<IMG"))), multispace1),
+        nom::combinator::map_parser(tag_content, parse_attributes),
         tag(">"),
     )(i)
 }
 
+/// Wrapper around `html_source()` that packs the result in
+/// `Link::Image`.
+pub fn html_source_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_source_link_generic(i)
+}
+
+/// Same as `html_source_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn html_source_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_source_link", html_source_link_generic)(i)
+}
+
+/// Same as `html_source_link()`, but generic over the error type.
+fn html_source_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src)) = html_source_generic(i)?;
+    Ok((i, Link::Image(alt, src)))
+}
+
+/// Parses an HTML media source `<source src=...>` or
+/// `<source srcset=...>`, as found inside `<picture>`, `<video>` and
+/// `<audio>` elements.
+///
+/// It returns either `Ok((i, (img_alt, img_src)))` or some error. `img_alt`
+/// is always empty. Without `src`, `img_src` is the first `srcset`
+/// candidate.
+///
+/// The parser expects to start at the element start (`<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::html_img::html_source;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   html_source(r#"<source srcset="dog.webp 1x, dog2.webp 2x" type="image/webp">abc"#),
+///   Ok(("abc", (Cow::from(""), Cow::from("dog.webp"))))
+/// );
+/// ```
+pub fn html_source(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    html_source_generic(i)
+}
+
+/// Same as `html_source()`, but generic over the error type.
+fn html_source_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (_, mut sources)) = generic_error(image_element("source"))(i)?;
+    Ok((i, (Cow::Borrowed(""), sources.swap_remove(0))))
+}
+
+/// Parses an HTML `<img ...>` or `<source ...>` element and returns one
+/// `Link::Image` per image source: the `src` attribute followed by all
+/// candidates of the `srcset` attribute. Duplicates are removed.
+///
+/// The parser expects to start at the element start (`<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::html_img::html_img_candidates;
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let (_, links) = html_img_candidates(
+///     r#"<img src="a.png" srcset="a.png 1x, a-2x.png 2x" alt="A">abc"#).unwrap();
+/// assert_eq!(links, vec![
+///     Link::Image(Cow::from("A"), Cow::from("a.png")),
+///     Link::Image(Cow::from("A"), Cow::from("a-2x.png")),
+/// ]);
+/// ```
+pub fn html_img_candidates(i: &str) -> nom::IResult<&str, Vec<Link<'_>>> {
+    let (i, (alt, sources)) = nom::branch::alt((image_element("img"), image_element("source")))(i)?;
+    Ok((
+        i,
+        sources
+            .into_iter()
+            .map(|src| Link::Image(alt.clone(), src))
+            .collect(),
+    ))
+}
+
+/// Parses the element `<name ...>` and returns `(img_alt, img_sources)`
+/// with `img_sources` as described in `parse_sources()`.
+#[allow(clippy::type_complexity)]
+fn image_element<'a>(
+    name: &'static str,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, (Cow<'a, str>, Vec<Cow<'a, str>>)> {
+    nom::sequence::delimited(
+        nom::sequence::tuple((
+            tag("<"),
+            nom::bytes::complete::tag_no_case(name),
+            multispace1,
+        )),
+        nom::combinator::map_parser(tag_content, parse_sources),
+        tag(">"),
+    )
+}
+
 /// Wrapper around `html_img()` that packs the result in
 /// `Link::Image`.
-pub fn html_img2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (text1, img_alt, img_src, text2, dest, title)) = html_img2dest(i)?;
+pub fn html_img2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    html_img2dest_link_generic(i)
+}
+
+/// Same as `html_img2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn html_img2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("html_img2dest_link", html_img2dest_link_generic)(i)
+}
+
+/// Same as `html_img2dest_link()`, but generic over the error type.
+fn html_img2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (text1, img_alt, img_src, text2, dest, title)) = html_img2dest_generic(i)?;
     Ok((
         i,
         Link::Image2Dest(text1, img_alt, img_src, text2, dest, title),
@@ -83,12 +215,42 @@ pub fn html_img2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///     Cow::from("after"), Cow::from("my doc.html"), Cow::from("title"),
 /// ))));
 /// ```
+#[allow(clippy::type_complexity)]
 pub fn html_img2dest(
     i: &str,
-) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>, Cow<str>, Cow<str>, Cow<str>)> {
+) -> nom::IResult<
+    &str,
+    (
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+    ),
+> {
+    html_img2dest_generic(i)
+}
+
+/// Same as `html_img2dest()`, but generic over the error type.
+#[allow(clippy::type_complexity)]
+fn html_img2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<
+    &'a str,
+    (
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+    ),
+    E,
+> {
     let (i, ((dest, title), text)) = nom::sequence::terminated(
         nom::sequence::pair(
-            href_tag_a_opening,
+            generic_error(href_tag_a_opening),
             alt((
                 nom::bytes::complete::take_until("</a>"),
                 nom::bytes::complete::take_until("</A>"),
@@ -101,48 +263,100 @@ pub fn html_img2dest(
 
     let (_, (text1, (img_alt, img_src), text2)) = tuple((
         nom::bytes::complete::take_until("<img"),
-        html_img,
+        html_img_generic,
         nom::combinator::rest,
     ))(text)?;
 
-    let text1 = decode_html_entities(text1);
-    let text2 = decode_html_entities(text2);
+    let text1 = inner_text(text1, false);
+    let text2 = inner_text(text2, false);
 
     Ok((i, (text1, img_alt, img_src, text2, dest, title)))
 }
 
 /// Extracts the `src` and `alt` attributes and returns
 /// `Ok((img_alt, img_src))`. `img_alt` can be empty,
-/// `img_src` not.
-fn parse_attributes(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+/// `img_src` not. Without `src`, `img_src` is the first `srcset`
+/// candidate.
+fn parse_attributes(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    let (i, (alt, mut sources)) = parse_sources(i)?;
+    Ok((i, (alt, sources.swap_remove(0))))
+}
+
+/// Extracts the `src`, `srcset` and `alt` attributes and returns
+/// `Ok((img_alt, img_sources))`. `img_sources` lists `src`, if any,
+/// followed by the `srcset` candidates without duplicates. It is never
+/// empty.
+fn parse_sources(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Vec<Cow<'_, str>>)> {
     let (i, attributes) = attribute_list(i)?;
     let mut src = Cow::Borrowed("");
+    let mut srcset = Cow::Borrowed("");
     let mut alt = Cow::Borrowed("");
 
     for (name, value) in attributes {
-        if name == "src" {
-            // Make sure `src` is empty, it can appear only
-            // once.
-            if !(&*src).is_empty() {
-                return Err(nom::Err::Error(Error::new(name, ErrorKind::ManyMN)));
-            }
-            src = value;
-        } else if name == "alt" {
-            // Make sure `title` is empty, it can appear only
-            // once.
-            if !(&*alt).is_empty() {
-                return Err(nom::Err::Error(Error::new(name, ErrorKind::ManyMN)));
-            }
-            alt = value;
+        let attribute = match name {
+            "src" => &mut src,
+            "srcset" => &mut srcset,
+            "alt" => &mut alt,
+            _ => continue,
+        };
+        // Make sure the attribute is empty, it can appear only
+        // once.
+        if !attribute.is_empty() {
+            return Err(nom::Err::Error(Error::new(name, ErrorKind::ManyMN)));
+        }
+        *attribute = value;
+    }
+
+    let mut sources = Vec::new();
+    if !src.is_empty() {
+        sources.push(src);
+    }
+    for candidate in srcset_candidates(srcset) {
+        if !sources.contains(&candidate) {
+            sources.push(candidate);
         }
     }
 
-    // Assure that `href` is not empty.
-    if (&*src).is_empty() {
+    // Assure that there is at least one source.
+    if sources.is_empty() {
         return Err(nom::Err::Error(Error::new(i, ErrorKind::Eof)));
     };
 
-    Ok((i, (alt, src)))
+    Ok((i, (alt, sources)))
+}
+
+/// Splits the value of a `srcset` attribute, e.g. `a.png 1x, b.png 2x`,
+/// into its image candidate URLs.
+fn srcset_candidates(srcset: Cow<'_, str>) -> Vec<Cow<'_, str>> {
+    fn urls(mut srcset: &str) -> Vec<&str> {
+        let mut urls = Vec::new();
+        loop {
+            srcset = srcset.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            if srcset.is_empty() {
+                break;
+            }
+            let end = srcset.find(char::is_whitespace).unwrap_or(srcset.len());
+            let (url, rest) = srcset.split_at(end);
+            // A trailing comma ends a candidate without descriptors.
+            let trimmed = url.trim_end_matches(',');
+            urls.push(trimmed);
+            srcset = if trimmed.len() < url.len() {
+                rest
+            } else {
+                // Skip the descriptors.
+                rest.find(',').map_or("", |n| &rest[n..])
+            };
+        }
+        urls
+    }
+
+    match srcset {
+        Cow::Borrowed(s) => urls(s).into_iter().map(Cow::Borrowed).collect(),
+        Cow::Owned(s) => urls(&s)
+            .into_iter()
+            .map(|url| Cow::Owned(url.to_string()))
+            .collect(),
+    }
 }
 
 #[cfg(test)]
@@ -223,6 +437,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_html_img2dest_nested() {
+        assert_eq!(
+            html_img2dest(
+                r#"<a href="dest"><span>a&amp;b</span> <img src="x.png" alt="X"> <b>c</b><img src="y.png"></a>abc"#
+            ),
+            Ok((
+                "abc",
+                (
+                    Cow::from("a&b "),
+                    Cow::from("X"),
+                    Cow::from("x.png"),
+                    Cow::from(" c"),
+                    Cow::from("dest"),
+                    Cow::from("")
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn test_srcset() {
+        assert_eq!(
+            tag_img(r#"<img srcset="a.png, b.png 2x" alt="A">abc"#),
+            Ok(("abc", (Cow::from("A"), Cow::from("a.png"))))
+        );
+        assert_eq!(
+            html_source_link(r#"<source src="movie.mp4" type="video/mp4">abc"#),
+            Ok(("abc", Link::Image(Cow::from(""), Cow::from("movie.mp4"))))
+        );
+        assert!(html_source(r#"<source type="video/mp4">abc"#).is_err());
+
+        assert_eq!(
+            srcset_candidates(Cow::from(" a.png 100w,b,c.png  ,d.png 1.5x , ")),
+            vec!["a.png", "b,c.png", "d.png"]
+        );
+        assert_eq!(
+            srcset_candidates(Cow::from("a.png,b.png")),
+            vec!["a.png,b.png"]
+        );
+        assert_eq!(
+            srcset_candidates(Cow::from("a.png, b.png")),
+            vec!["a.png", "b.png"]
+        );
+        assert_eq!(srcset_candidates(Cow::from("")), Vec::<Cow<str>>::new());
+
+        let (_, links) =
+            html_img_candidates(r#"<source srcset="a&amp;b.png 1x, c.png 2x">abc"#).unwrap();
+        assert_eq!(
+            links,
+            vec![
+                Link::Image(Cow::from(""), Cow::from("a&b.png")),
+                Link::Image(Cow::from(""), Cow::from("c.png")),
+            ]
+        );
+    }
+
     #[test]
     fn test_attribute_list() {
         let expected = (
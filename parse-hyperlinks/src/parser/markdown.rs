@@ -2,15 +2,23 @@
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::generic_error;
+use crate::parser::html::character_reference;
 use crate::parser::parse::LABEL_LEN_MAX;
 use crate::parser::percent_decode;
+use crate::parser::url_scheme;
 use crate::parser::Link;
+use crate::parser::LinkError;
 use crate::take_until_unbalanced;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::multispace1;
 use nom::combinator::*;
-use std::borrow::Cow;
+use nom::error::VerboseError;
 
 /// The following character are escapable in _link text_, _link label_, _link
 /// destination_ and _link title_.
@@ -18,8 +26,34 @@ const ESCAPABLE: &str = r###"!"#$%&'()*+,-./:;<=>?@[\]^_`{|}~"###;
 
 /// Wrapper around `md_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn md_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = md_text2dest(i)?;
+pub fn md_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_text2dest_link_generic(i)
+}
+
+/// Same as `md_text2dest_link()`, but with `nom::error::VerboseError` as error
+/// type. The error lists where the inner parsers failed, followed by the
+/// context `md_text2dest_link` at the link start.
+/// ```
+/// use nom::error::{ErrorKind, VerboseErrorKind};
+/// use parse_hyperlinks::parser::markdown::md_text2dest_link_verbose;
+///
+/// let err = md_text2dest_link_verbose("[text](<dest").unwrap_err();
+/// let nom::Err::Error(err) = err else { panic!() };
+/// assert_eq!(err.errors[0], ("<dest", VerboseErrorKind::Nom(ErrorKind::Tag)));
+/// assert_eq!(
+///     err.errors.last(),
+///     Some(&("[text](<dest", VerboseErrorKind::Context("md_text2dest_link")))
+/// );
+/// ```
+pub fn md_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_text2dest_link", md_text2dest_link_generic)(i)
+}
+
+/// Same as `md_text2dest_link()`, but generic over the error type.
+fn md_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = md_text2dest_generic(i)?;
     Ok((i, Link::Text2Dest(te, de, ti)))
 }
 
@@ -45,7 +79,14 @@ pub fn md_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from("foo@dest"), Cow::from("mailto:foo@dest"), Cow::from(""))))
 /// );
 /// ```
-pub fn md_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn md_text2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    md_text2dest_generic(i)
+}
+
+/// Same as `md_text2dest()`, but generic over the error type.
+fn md_text2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     alt((
         // Parse autolink.
         nom::sequence::delimited(
@@ -54,13 +95,19 @@ pub fn md_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>
                 nom::bytes::complete::take_till1(|c: char| {
                     c.is_ascii_whitespace() || c == '>' || c == '<'
                 }),
-                alt((md_absolute_uri, md_email_address)),
+                alt((
+                    generic_error(md_absolute_uri),
+                    generic_error(md_email_address),
+                )),
             ),
             tag(">"),
         ),
         // Parse inline link.
         map(
-            nom::sequence::tuple((md_link_text, md_link_destination_enclosed)),
+            nom::sequence::tuple((
+                generic_error(md_link_text),
+                generic_error(md_link_destination_enclosed),
+            )),
             |(a, (b, c))| (a, b, c),
         ),
     ))(i)
@@ -68,8 +115,21 @@ pub fn md_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>
 
 /// Wrapper around `md_label2dest()` that packs the result in
 /// `Link::Label2Dest`.
-pub fn md_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (l, d, t)) = md_label2dest(i)?;
+pub fn md_label2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_label2dest_link_generic(i)
+}
+
+/// Same as `md_label2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn md_label2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_label2dest_link", md_label2dest_link_generic)(i)
+}
+
+/// Same as `md_label2dest_link()`, but generic over the error type.
+fn md_label2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (l, d, t)) = md_label2dest_generic(i)?;
     Ok((i, Link::Label2Dest(l, d, t)))
 }
 
@@ -114,40 +174,80 @@ pub fn md_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
 /// document. [Link reference
 /// definitions](https://spec.commonmark.org/0.30/#link-reference-definition)
 /// can come either before or after the links that use them.
-pub fn md_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn md_label2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    md_label2dest_generic(i)
+}
+
+/// Same as `md_label2dest()`, but generic over the error type.
+fn md_label2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     // Consume up to three spaces.
     let (i, _) = nom::bytes::complete::take_while_m_n(0, 3, |c| c == ' ')(i)?;
     // Take label.
-    let (i, link_text) = md_link_label(i)?;
+    let (i, link_text) = generic_error(md_link_label)(i)?;
     let (i, _) = nom::character::complete::char(':')(i)?;
     // Take spaces.
-    let (i, _) = verify(nom::character::complete::multispace1, |s: &str| {
-        !s.contains("\n\n")
+    let (i, _) = verify(nom::character::complete::multispace0, |s: &str| {
+        s.matches('\n').count() <= 1
     })(i)?;
-    // Take destination.
-    let (i, link_destination) = md_link_destination(i)?;
-    // Try, but do not fail.
-    let (i, link_title) = alt((
-        // Take link title.
-        md_link_title,
-        nom::combinator::success(Cow::from("")),
-    ))(i)?;
+    // Take destination, it can be empty only when enclosed in `<>`.
+    let (i, link_destination) = map(
+        alt((
+            nom::sequence::delimited(
+                tag("<"),
+                generic_error(md_pointy_link_destination),
+                tag(">"),
+            ),
+            generic_error(md_raw_link_destination),
+        )),
+        |s| md_unescape(s, true),
+    )(i)?;
 
-    // Now consume as much whitespace as possible.
-    let (i, _) = nom::character::complete::space0(i)?;
+    // The title must be followed by the end of the line. If it is not, the
+    // definition ends before the title, but only if the title starts on the
+    // next line.
+    let with_title = match generic_error(md_link_title)(i) {
+        Ok((j, link_title)) => generic_error(md_line_end)(j).map(|(j, _)| (j, link_title)),
+        Err(e) => Err(e),
+    };
+    match with_title {
+        Ok((i, link_title)) => Ok((i, (link_text, link_destination, link_title))),
+        Err(e) => match generic_error(md_line_end)(i) {
+            Ok((i, _)) => Ok((i, (link_text, link_destination, Cow::from("")))),
+            Err(e2) if md_link_title(i).is_err() => Err(e2),
+            Err(_) => Err(e),
+        },
+    }
+}
 
-    // Check if there is newline coming. Do not consume.
-    if !i.is_empty() {
+/// Consumes spaces and tabs and checks, that the line ends here. The line
+/// ending is not consumed.
+fn md_line_end(i: &str) -> nom::IResult<&str, ()> {
+    let (i, _) = nom::character::complete::space0(i)?;
+    if !i.is_empty() && !i.starts_with("\r\n") {
         let _ = nom::character::complete::newline(i)?;
     }
-
-    Ok((i, (link_text, link_destination, link_title)))
+    Ok((i, ()))
 }
 
 /// Wrapper around `md_text2label()` that packs the result in
 /// `Link::Text2Label`.
-pub fn md_text2label_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (t, l)) = md_text2label(i)?;
+pub fn md_text2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_text2label_link_generic(i)
+}
+
+/// Same as `md_text2label_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn md_text2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_text2label_link", md_text2label_link_generic)(i)
+}
+
+/// Same as `md_text2label_link()`, but generic over the error type.
+fn md_text2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (t, l)) = md_text2label_generic(i)?;
     Ok((i, Link::Text2Label(t, l)))
 }
 
@@ -188,13 +288,21 @@ pub fn md_text2label_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from("link text"), Cow::from("link text"))))
 /// );
 /// ```
-pub fn md_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+pub fn md_text2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    md_text2label_generic(i)
+}
+
+/// Same as `md_text2label()`, but generic over the error type.
+pub(crate) fn md_text2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
     let (i, (link_text, link_label)) = alt((
-        nom::sequence::pair(md_link_text, md_link_label),
-        nom::combinator::map(nom::sequence::terminated(md_link_text, tag("[]")), |s| {
-            (s.clone(), s)
-        }),
-        nom::combinator::map(md_link_text, |s| (s.clone(), s)),
+        nom::sequence::pair(generic_error(md_link_text), generic_error(md_link_label)),
+        nom::combinator::map(
+            nom::sequence::terminated(generic_error(md_link_text), tag("[]")),
+            |s| (s.clone(), s),
+        ),
+        nom::combinator::map(generic_error(md_link_text), |s| (s.clone(), s)),
     ))(i)?;
 
     // Check that there is no `[` or `(` following. Do not consume.
@@ -205,6 +313,90 @@ pub fn md_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
     Ok((i, (link_text, link_label)))
 }
 
+/// Parses a [Pandoc attribute block](https://pandoc.org/MANUAL.html#extension-link_attributes),
+/// that follows a link or image, e.g. `{#id .class key="value"}`.
+///
+/// It returns the attributes as `(name, value)` pairs in order of
+/// appearance: `#id` becomes `("id", "id")`, all classes `.class` are joined
+/// into one `("class", "class1 class2")` pair. The block must not span more
+/// than one line. It fails, if a word is neither an identifier, a class nor
+/// a `key=value` pair, e.g. for `{text}`.
+///
+/// ```
+/// use parse_hyperlinks::parser::markdown::md_link_attributes;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   md_link_attributes(r#"{#top .ext .small target="_blank"}abc"#),
+///   Ok(("abc", vec![
+///     (Cow::from("id"), Cow::from("top")),
+///     (Cow::from("class"), Cow::from("ext small")),
+///     (Cow::from("target"), Cow::from("_blank")),
+///   ]))
+/// );
+/// assert!(md_link_attributes("{text}").is_err());
+/// ```
+pub fn md_link_attributes(i: &str) -> nom::IResult<&str, Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+    let err = || nom::Err::Error(nom::error::Error::new(i, nom::error::ErrorKind::Verify));
+    let (j, inner) =
+        nom::sequence::delimited(tag("{"), nom::bytes::complete::is_not("{}\n\r"), tag("}"))(i)?;
+
+    let mut attributes: Vec<(Cow<str>, Cow<str>)> = Vec::new();
+    // Index of the `class` attribute, if any.
+    let mut class: Option<usize> = None;
+    let mut rest = inner.trim_start();
+    while !rest.is_empty() {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let (name, value) = if let Some(id) = word.strip_prefix('#').filter(|w| !w.is_empty()) {
+            rest = &rest[word_end..];
+            ("id", id)
+        } else if let Some(c) = word.strip_prefix('.').filter(|w| !w.is_empty()) {
+            rest = &rest[word_end..];
+            ("class", c)
+        } else {
+            let (name, value) = rest.split_once('=').ok_or_else(err)?;
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(err());
+            }
+            let (value, r) = match value.strip_prefix('"') {
+                Some(quoted) => {
+                    let (value, r) = quoted.split_once('"').ok_or_else(err)?;
+                    if !r.is_empty() && !r.starts_with(char::is_whitespace) {
+                        return Err(err());
+                    }
+                    (value, r)
+                }
+                None => value.split_at(value.find(char::is_whitespace).unwrap_or(value.len())),
+            };
+            rest = r;
+            (name, value)
+        };
+        match (name, class) {
+            ("class", Some(n)) => {
+                let classes = attributes[n].1.to_mut();
+                classes.push(' ');
+                classes.push_str(value);
+            }
+            _ => {
+                if name == "class" {
+                    class = Some(attributes.len());
+                }
+                attributes.push((Cow::Borrowed(name), Cow::Borrowed(value)));
+            }
+        }
+        rest = rest.trim_start();
+    }
+    if attributes.is_empty() {
+        return Err(err());
+    }
+    Ok((j, attributes))
+}
+
 /// Parses _link text_.
 /// Brackets are allowed in the
 /// [link text](https://spec.commonmark.org/0.29/#link-text) only if (a) they are
@@ -212,7 +404,7 @@ pub fn md_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
 /// an open bracket `[`, a sequence of zero or more inlines, and a close
 /// bracket `]`.
 /// [CommonMark Spec](https://spec.commonmark.org/0.29/#link-text)
-pub(crate) fn md_link_text(i: &str) -> nom::IResult<&str, Cow<str>> {
+pub(crate) fn md_link_text(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::combinator::map_parser(
         nom::sequence::delimited(tag("["), take_until_unbalanced('[', ']'), tag("]")),
         md_escaped_str_transform,
@@ -225,9 +417,9 @@ pub(crate) fn md_link_text(i: &str) -> nom::IResult<&str, Cow<str>> {
 /// be at least one non-whitespace character. Unescaped square bracket characters
 /// are not allowed inside the opening and closing square brackets of link
 /// labels. A link label can have at most 999 characters inside the square
-/// brackets (TODO).
+/// brackets.
 /// [CommonMark Spec](https://spec.commonmark.org/0.29/#link-label)
-fn md_link_label(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn md_link_label(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::combinator::map_parser(
         nom::combinator::verify(
             nom::sequence::delimited(
@@ -239,55 +431,105 @@ fn md_link_label(i: &str) -> nom::IResult<&str, Cow<str>> {
                 ),
                 tag("]"),
             ),
-            |l: &str| l.len() <= LABEL_LEN_MAX,
+            |l: &str| l.chars().count() <= LABEL_LEN_MAX,
         ),
         md_escaped_str_transform,
     )(i)
 }
 
-/// This is a wrapper around `md_parse_link_destination()`. It takes its result
-/// and removes the `\` before the escaped characters `ESCAPABLE`.
-pub(crate) fn md_link_destination(i: &str) -> nom::IResult<&str, Cow<str>> {
-    nom::combinator::map_parser(md_parse_link_destination, md_escaped_str_transform)(i)
+/// This is a wrapper around `md_parse_link_destination()`. It takes its result,
+/// removes the `\\` before the escaped characters `ESCAPABLE` and decodes
+/// character references.
+pub(crate) fn md_link_destination(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
+    map(md_parse_link_destination, |s| md_unescape(s, true))(i)
 }
 
 /// A [link destination](https://spec.commonmark.org/0.30/#link-destination)
 /// consists of either
 ///
 /// * a sequence of zero or more characters between an opening `<` and a
-/// closing `>` that contains no line endings or unescaped `<` or `>`
-/// characters, or
+///   closing `>` that contains no line endings or unescaped `<` or `>`
+///   characters, or
 /// * a nonempty sequence of characters that does not start with `<`, does not
-/// include [ASCII control
-/// characters](https://spec.commonmark.org/0.30/#ascii-control-character) or
-/// [space](https://spec.commonmark.org/0.30/#space) character, and includes
-/// parentheses only if (a) they are backslash-escaped or (b) they are part of a
-/// balanced pair of unescaped parentheses. (Implementations may impose limits
-/// on parentheses nesting to avoid performance issues, but at least three
-/// levels of nesting should be supported.)
+///   include [ASCII control
+///   characters](https://spec.commonmark.org/0.30/#ascii-control-character) or
+///   [space](https://spec.commonmark.org/0.30/#space) character, and includes
+///   parentheses only if (a) they are backslash-escaped or (b) they are part of a
+///   balanced pair of unescaped parentheses. (Implementations may impose limits
+///   on parentheses nesting to avoid performance issues, but at least three
+///   levels of nesting should be supported.)
 fn md_parse_link_destination(i: &str) -> nom::IResult<&str, &str> {
     alt((
-        nom::sequence::delimited(
-            tag("<"),
-            nom::bytes::complete::escaped(
-                nom::character::complete::none_of(r#"\<>"#),
-                '\\',
-                nom::character::complete::one_of(ESCAPABLE),
-            ),
-            tag(">"),
-        ),
-        map(nom::bytes::complete::tag("<>"), |_| ""),
-        alt((
-            nom::bytes::complete::is_not(" \t\r\n"),
-            nom::combinator::success(""),
-        )),
+        nom::sequence::delimited(tag("<"), md_pointy_link_destination, tag(">")),
+        md_raw_link_destination,
+        nom::combinator::success(""),
     ))(i)
 }
 
-/// Matches `md_link_destination` in parenthesis.
-pub(crate) fn md_link_destination_enclosed(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
-    map_parser(
-        nom::sequence::delimited(tag("("), take_until_unbalanced('(', ')'), tag(")")),
+/// Takes the content of a link destination between `<` and `>`.
+fn md_pointy_link_destination(i: &str) -> nom::IResult<&str, &str> {
+    let mut it = i.char_indices();
+    while let Some((n, c)) = it.next() {
+        match c {
+            '\\' if i[n + 1..].starts_with(|c| ESCAPABLE.contains(c)) => {
+                // Skip the escaped char.
+                it.next();
+            }
+            '<' | '>' | '\n' | '\r' => return Ok((&i[n..], &i[..n])),
+            _ => {}
+        }
+    }
+    Ok(("", i))
+}
+
+/// Takes a link destination not enclosed in `<` and `>`. It ends before
+/// the first space, ASCII control character or unbalanced `)`.
+fn md_raw_link_destination(i: &str) -> nom::IResult<&str, &str> {
+    let mut depth = 0;
+    let mut end = i.len();
+    let mut it = i.char_indices();
+    while let Some((n, c)) = it.next() {
+        match c {
+            '<' if n == 0 => break,
+            '\\' if i[n + 1..].starts_with(|c| ESCAPABLE.contains(c)) => {
+                // Skip the escaped char.
+                it.next();
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                end = n;
+                break;
+            }
+            ')' => depth -= 1,
+            c if c == ' ' || c.is_ascii_control() => {
+                end = n;
+                break;
+            }
+            _ => {}
+        }
+    }
+    if i.starts_with('<') || end == 0 || depth != 0 {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            i,
+            nom::error::ErrorKind::IsNot,
+        )));
+    }
+    Ok((&i[end..], &i[..end]))
+}
+
+/// Matches `md_link_destination` and an optional `md_link_title` in
+/// parenthesis. Both can be surrounded by spaces, tabs and up to one line
+/// ending.
+pub(crate) fn md_link_destination_enclosed(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    let space = || {
+        verify(nom::character::complete::multispace0, |s: &str| {
+            s.matches('\n').count() <= 1
+        })
+    };
+    nom::sequence::delimited(
+        nom::sequence::pair(tag("("), space()),
         nom::sequence::tuple((
             md_link_destination,
             alt((
@@ -296,13 +538,15 @@ pub(crate) fn md_link_destination_enclosed(i: &str) -> nom::IResult<&str, (Cow<s
                 nom::combinator::success(Cow::from("")),
             )),
         )),
+        nom::sequence::pair(space(), tag(")")),
     )(i)
 }
 
-/// This is a wrapper around `md_parse_link_title()`. It takes its result
-/// and removes the `\` before the escaped characters `ESCAPABLE`.
-fn md_link_title(i: &str) -> nom::IResult<&str, Cow<str>> {
-    nom::combinator::map_parser(md_parse_link_title, md_escaped_str_transform)(i)
+/// This is a wrapper around `md_parse_link_title()`. It takes its result,
+/// removes the `\` before the escaped characters `ESCAPABLE` and decodes
+/// character references.
+fn md_link_title(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
+    map(md_parse_link_title, |s| md_unescape(s, true))(i)
 }
 
 /// A link title is always preceded one or more whitespace inluding
@@ -334,7 +578,7 @@ fn md_parse_link_title(i: &str) -> nom::IResult<&str, &str> {
                     nom::bytes::complete::escaped(
                         nom::character::complete::none_of(r#"\'"#),
                         '\\',
-                        nom::character::complete::one_of(ESCAPABLE),
+                        nom::character::complete::anychar,
                     ),
                     tag("'"),
                 ),
@@ -343,7 +587,7 @@ fn md_parse_link_title(i: &str) -> nom::IResult<&str, &str> {
                     nom::bytes::complete::escaped(
                         nom::character::complete::none_of(r#"\""#),
                         '\\',
-                        nom::character::complete::one_of(ESCAPABLE),
+                        nom::character::complete::anychar,
                     ),
                     tag("\""),
                 ),
@@ -354,15 +598,46 @@ fn md_parse_link_title(i: &str) -> nom::IResult<&str, &str> {
 }
 
 /// Remove the `\` before the escaped characters `ESCAPABLE`.
-fn md_escaped_str_transform(i: &str) -> nom::IResult<&str, Cow<str>> {
-    nom::combinator::map(
-        nom::bytes::complete::escaped_transform(
-            nom::bytes::complete::is_not("\\"),
-            '\\',
-            nom::character::complete::one_of(ESCAPABLE),
-        ),
-        |s| if s == i { Cow::from(i) } else { Cow::from(s) },
-    )(i)
+fn md_escaped_str_transform(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
+    Ok(("", md_unescape(i, false)))
+}
+
+/// Removes the `\` before the escaped characters `ESCAPABLE`. A `\` before
+/// other characters is literal. If `entities` is true, character references
+/// like `&amp;` are decoded too, except escaped ones like `\&amp;`.
+pub(crate) fn md_unescape(i: &str, entities: bool) -> Cow<'_, str> {
+    if !(i.contains('\\') || entities && i.contains('&')) {
+        return Cow::Borrowed(i);
+    }
+    let mut out = String::new();
+    let mut rest = i;
+    while let Some(c) = rest.chars().next() {
+        let escaped = rest[c.len_utf8()..]
+            .chars()
+            .next()
+            .filter(|e| c == '\\' && ESCAPABLE.contains(*e));
+        let reference = if entities && c == '&' {
+            character_reference(rest)
+        } else {
+            None
+        };
+        let len = match (escaped, reference) {
+            (Some(e), _) => {
+                out.push(e);
+                c.len_utf8() + e.len_utf8()
+            }
+            (None, Some((len, decoded))) => {
+                out.push_str(&decoded);
+                len
+            }
+            (None, None) => {
+                out.push(c);
+                c.len_utf8()
+            }
+        };
+        rest = &rest[len..];
+    }
+    Cow::Owned(out)
 }
 
 /// Parses an [absolute URI](https://spec.commonmark.org/0.30/#absolute-uri).
@@ -382,17 +657,11 @@ fn md_escaped_str_transform(i: &str) -> nom::IResult<&str, Cow<str>> {
 /// period (”.”), or hyphen (”-”).
 ///
 /// [CommonMark Spec](https://spec.commonmark.org/0.30/#autolinks)
-fn md_absolute_uri(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+fn md_absolute_uri(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
     let j = i;
     map(
         all_consuming(nom::sequence::separated_pair(
-            // Parse scheme.
-            verify(
-                nom::bytes::complete::take_till1(|c: char| {
-                    !(c.is_ascii_alphanumeric() || "+.-".contains(c))
-                }),
-                |s: &str| s.len() >= 2 && s.len() <= 32,
-            ),
+            url_scheme,
             tag(":"),
             // Parse domain.
             map_parser(
@@ -421,7 +690,7 @@ fn md_absolute_uri(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)
 /// The link’s label is the email address, and the
 /// URL is `mailto:` followed by the email address.
 ///
-fn md_email_address(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+fn md_email_address(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
     let j = i;
     map(
         all_consuming(nom::sequence::separated_pair(
@@ -433,16 +702,364 @@ fn md_email_address(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>
             // Parse domain.
             nom::bytes::complete::take_till1(|c: char| !(c.is_alphanumeric() || ".-".contains(c))),
         )),
-        |(_, _)| {
+        move |(_, _)| {
             (
                 Cow::Borrowed(j),
-                Cow::Owned(format!("mailto:{}", j.to_string())),
+                Cow::Owned(format!("mailto:{}", j)),
                 Cow::Borrowed(""),
             )
         },
     )(i)
 }
 
+/// Wrapper around `md_gfm_autolink()` that packs the result in
+/// `Link::Text2Dest`.
+pub fn md_gfm_autolink_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_gfm_autolink_link_generic(i)
+}
+
+/// Same as `md_gfm_autolink_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn md_gfm_autolink_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_gfm_autolink_link", md_gfm_autolink_link_generic)(i)
+}
+
+/// Same as `md_gfm_autolink_link()`, but generic over the error type.
+fn md_gfm_autolink_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = md_gfm_autolink_generic(i)?;
+    Ok((i, Link::Text2Dest(te, de, ti)))
+}
+
+/// Parses a GitHub Flavored Markdown
+/// [extended autolink](https://github.github.com/gfm/#autolinks-extension-):
+/// a link, that is recognized without any markup.
+///
+/// It returns either `Ok((i, (link_text, link_destination, Cow::from("")))`
+/// or some error. `link_text` is the autolink as written. The
+/// `link_destination` of
+/// * a _www autolink_, e.g. `www.example.com/path`, is prefixed with
+///   `http://`,
+/// * an _extended URL autolink_, e.g. `https://example.com/path`, is the
+///   link text,
+/// * an _extended email autolink_, e.g. `foo@example.com`, is prefixed with
+///   `mailto:`.
+///
+/// As on GitHub, trailing punctuation `?!.,:*_~` is not part of the
+/// autolink, neither is a trailing `)` without matching `(` or an entity
+/// reference like `&amp;` at the end.
+///
+/// This parser expects to start at the first letter of the autolink. The
+/// caller must guarantee, that it is at the beginning of a line, or after
+/// whitespace or one of the characters `*_~(`.
+/// ```
+/// use parse_hyperlinks::parser::markdown::md_gfm_autolink;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   md_gfm_autolink("www.commonmark.org/a.b. abc"),
+///   Ok((". abc", (
+///     Cow::from("www.commonmark.org/a.b"),
+///     Cow::from("http://www.commonmark.org/a.b"),
+///     Cow::from(""),
+///   )))
+/// );
+/// assert_eq!(
+///   md_gfm_autolink("https://example.com/(a)), abc").unwrap().0,
+///   "), abc"
+/// );
+/// assert_eq!(
+///   md_gfm_autolink("foo@bar.baz.").unwrap().1.1,
+///   Cow::from("mailto:foo@bar.baz")
+/// );
+/// ```
+pub fn md_gfm_autolink(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    md_gfm_autolink_generic(i)
+}
+
+/// Same as `md_gfm_autolink()`, but generic over the error type.
+fn md_gfm_autolink_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let err = || nom::Err::Error(E::from_error_kind(i, nom::error::ErrorKind::Verify));
+    let prefix = ["www.", "http://", "https://"]
+        .into_iter()
+        .find(|p| i.starts_with(p));
+    let Some(prefix) = prefix else {
+        return generic_error(md_gfm_email)(i);
+    };
+
+    let end = i
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(i.len());
+    let link = md_gfm_trim(&i[..end]);
+    if link.len() <= prefix.len() {
+        return Err(err());
+    }
+    // The domain of a www autolink includes `www.`.
+    let domain_start = if prefix == "www." { 0 } else { prefix.len() };
+    let domain = &link[domain_start..];
+    let domain = &domain[..domain
+        .find(|c: char| !(c.is_alphanumeric() || "._-".contains(c)))
+        .unwrap_or(domain.len())];
+    if !md_gfm_valid_domain(domain) {
+        return Err(err());
+    }
+
+    let dest = if prefix == "www." {
+        Cow::Owned(format!("http://{}", link))
+    } else {
+        Cow::Borrowed(link)
+    };
+    Ok((
+        &i[link.len()..],
+        (Cow::Borrowed(link), dest, Cow::Borrowed("")),
+    ))
+}
+
+/// Parses a GFM extended email autolink, see `md_gfm_autolink()`.
+fn md_gfm_email(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    let err = || nom::Err::Error(nom::error::Error::new(i, nom::error::ErrorKind::Verify));
+    let local = &i[..i
+        .find(|c: char| !(c.is_alphanumeric() || ".-_+".contains(c)))
+        .unwrap_or(i.len())];
+    if local.is_empty() || !i[local.len()..].starts_with('@') {
+        return Err(err());
+    }
+    let domain = &i[local.len() + 1..];
+    let domain = &domain[..domain
+        .find(|c: char| !(c.is_alphanumeric() || "._-".contains(c)))
+        .unwrap_or(domain.len())];
+    let domain = domain.trim_end_matches('.');
+    if !domain.contains('.')
+        || domain.ends_with(['-', '_'])
+        || domain.split('.').any(|segment| segment.is_empty())
+    {
+        return Err(err());
+    }
+    let link = &i[..local.len() + 1 + domain.len()];
+    Ok((
+        &i[link.len()..],
+        (
+            Cow::Borrowed(link),
+            Cow::Owned(format!("mailto:{}", link)),
+            Cow::Borrowed(""),
+        ),
+    ))
+}
+
+/// True if `domain` is a GFM valid domain: segments of alphanumeric
+/// characters, `_` and `-` separated by periods, without `_` in the last two
+/// segments.
+fn md_gfm_valid_domain(domain: &str) -> bool {
+    let segments: Vec<&str> = domain.split('.').collect();
+    !domain.is_empty()
+        && segments.iter().all(|s| !s.is_empty())
+        && segments.iter().rev().take(2).all(|s| !s.contains('_'))
+}
+
+/// Removes trailing punctuation, unmatched `)` and entity references from
+/// the extended autolink `link`, following the GFM rules.
+fn md_gfm_trim(mut link: &str) -> &str {
+    loop {
+        let trimmed = link.trim_end_matches(['?', '!', '.', ',', ':', '*', '_', '~']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(t) if trimmed.matches(')').count() > trimmed.matches('(').count() => t,
+            _ => trimmed,
+        };
+        let trimmed = match trimmed.strip_suffix(';').and_then(|t| t.rsplit_once('&')) {
+            Some((t, name))
+                if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) =>
+            {
+                t
+            }
+            _ => trimmed,
+        };
+        if trimmed.len() == link.len() {
+            return link;
+        }
+        link = trimmed;
+    }
+}
+
+/// Strips the inline markup of the Markdown fragment `i`, e.g. a _link
+/// text_, and returns its plain text: emphasis markers and the backticks of
+/// code spans are removed, images are replaced by their _image alt_ text and
+/// inline HTML tags are dropped. Backslash escapes and character references
+/// are decoded.
+///
+/// ```
+/// use parse_hyperlinks::parser::markdown::md_plain_text;
+///
+/// assert_eq!(md_plain_text("**bold** ![alt](img) text"), "bold alt text");
+/// assert_eq!(md_plain_text(r"`a*b` <b>c</b> \*d\* &amp; snake_case"), "a*b c *d* & snake_case");
+/// ```
+pub fn md_plain_text(i: &str) -> Cow<'_, str> {
+    if !i.contains(['\\', '`', '!', '*', '_', '<', '&']) {
+        return Cow::Borrowed(i);
+    }
+    let mut out = String::new();
+    let mut rest = i;
+    let mut prev = None;
+    while let Some(c) = rest.chars().next() {
+        let run = rest.len() - rest.trim_start_matches(c).len();
+        let len = match c {
+            '\\' => match rest[1..].chars().next() {
+                Some(e) if ESCAPABLE.contains(e) => {
+                    out.push(e);
+                    1 + e.len_utf8()
+                }
+                _ => {
+                    out.push(c);
+                    1
+                }
+            },
+            '`' => match md_code_span_end(&rest[run..], run) {
+                Some(end) => {
+                    let code = &rest[run..run + end];
+                    let stripped = code
+                        .strip_prefix(' ')
+                        .and_then(|s| s.strip_suffix(' '))
+                        .filter(|s| !s.trim().is_empty());
+                    out.push_str(stripped.unwrap_or(code));
+                    2 * run + end
+                }
+                None => {
+                    out.push_str(&rest[..run]);
+                    run
+                }
+            },
+            '*' | '_' => {
+                // Keep the run, if it can neither open nor close emphasis.
+                let next = rest[run..].chars().next();
+                let opens = next.is_some_and(|n| !n.is_whitespace());
+                let closes = prev.is_some_and(|p: char| !p.is_whitespace());
+                let intraword = c == '_'
+                    && prev.is_some_and(|p| p.is_alphanumeric())
+                    && next.is_some_and(|n| n.is_alphanumeric());
+                if !(opens || closes) || intraword {
+                    out.push_str(&rest[..run]);
+                }
+                run
+            }
+            '!' if rest.starts_with("![") => match md_image_alt(rest) {
+                Ok((j, alt)) => {
+                    out.push_str(&md_plain_text(alt));
+                    rest.len() - j.len()
+                }
+                Err(_) => {
+                    out.push(c);
+                    1
+                }
+            },
+            '<' => match md_inline_tag(rest) {
+                Ok((j, _)) => rest.len() - j.len(),
+                Err(_) => {
+                    out.push(c);
+                    1
+                }
+            },
+            '&' => match character_reference(rest) {
+                Some((len, decoded)) => {
+                    out.push_str(&decoded);
+                    len
+                }
+                None => {
+                    out.push(c);
+                    1
+                }
+            },
+            _ => {
+                out.push(c);
+                c.len_utf8()
+            }
+        };
+        prev = rest[..len].chars().next_back();
+        rest = &rest[len..];
+    }
+    if out == i {
+        Cow::Borrowed(i)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+/// Returns the length of the code span content in `i`, which is followed
+/// by a closing backtick string of exactly `run` backticks.
+fn md_code_span_end(i: &str, run: usize) -> Option<usize> {
+    let mut n = 0;
+    while let Some(start) = i[n..].find('`') {
+        let start = n + start;
+        let len = i[start..].len() - i[start..].trim_start_matches('`').len();
+        if len == run {
+            return Some(start);
+        }
+        n = start + len;
+    }
+    None
+}
+
+/// Parses an inline or reference image and returns its _image alt_ text
+/// without transformation.
+fn md_image_alt(i: &str) -> nom::IResult<&str, &str> {
+    let (i, alt_text) =
+        nom::sequence::delimited(tag("!["), take_until_unbalanced('[', ']'), tag("]"))(i)?;
+    let (i, _) = opt(alt((
+        map(md_link_destination_enclosed, |_| ()),
+        map(md_link_label, |_| ()),
+    )))(i)?;
+    Ok((i, alt_text))
+}
+
+/// Parses an inline HTML start or end tag, e.g. `<b>` or `</span>`.
+fn md_inline_tag(i: &str) -> nom::IResult<&str, &str> {
+    recognize(nom::sequence::tuple((
+        tag("<"),
+        opt(tag("/")),
+        nom::character::complete::alpha1,
+        nom::bytes::complete::take_till(|c| c == '>'),
+        tag(">"),
+    )))(i)
+}
+
+/// Replaces the _link text_ of the Markdown link `link` with its plain text
+/// as returned by `md_plain_text()`. The plain text is derived from the
+/// source `consumed`, the link was parsed from. Other links, e.g.
+/// autolinks, are returned unchanged.
+pub(crate) fn md_plain_link<'a>(consumed: &'a str, link: Link<'a>) -> Link<'a> {
+    let text = || {
+        nom::sequence::delimited(tag("["), take_until_unbalanced('[', ']'), tag("]"))(consumed)
+            .map(|(_, text)| text)
+    };
+    match link {
+        Link::Text2Dest(te, de, ti) => match text() {
+            Ok(text) => Link::Text2Dest(md_plain_text(text), de, ti),
+            Err(_) => Link::Text2Dest(te, de, ti),
+        },
+        Link::Text2Label(te, la) => match text() {
+            Ok(text) => Link::Text2Label(md_plain_text(text), la),
+            Err(_) => Link::Text2Label(te, la),
+        },
+        Link::Image2Dest(te1, alt, src, te2, de, ti) => {
+            let texts = text().and_then(|text| {
+                nom::sequence::tuple((
+                    nom::bytes::complete::take_until("!["),
+                    md_image_alt,
+                    nom::combinator::rest,
+                ))(text)
+            });
+            match texts {
+                Ok((_, (te1, _, te2))) => {
+                    Link::Image2Dest(md_plain_text(te1), alt, src, md_plain_text(te2), de, ti)
+                }
+                Err(_) => Link::Image2Dest(te1, alt, src, te2, de, ti),
+            }
+        }
+        link => link,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -569,6 +1186,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_md_link_attributes() {
+        assert_eq!(
+            md_link_attributes(r#"{ .a #b .c d="e f" g=h }"#),
+            Ok((
+                "",
+                vec![
+                    (Cow::from("class"), Cow::from("a c")),
+                    (Cow::from("id"), Cow::from("b")),
+                    (Cow::from("d"), Cow::from("e f")),
+                    (Cow::from("g"), Cow::from("h")),
+                ]
+            ))
+        );
+        assert_eq!(
+            md_link_attributes(r#"{a=""}"#),
+            Ok(("", vec![(Cow::from("a"), Cow::from(""))]))
+        );
+        assert!(md_link_attributes("{}").is_err());
+        assert!(md_link_attributes("{ }").is_err());
+        assert!(md_link_attributes("{#}").is_err());
+        assert!(md_link_attributes("{.a b}").is_err());
+        assert!(md_link_attributes("{a b=c}").is_err());
+        assert!(md_link_attributes(r#"{a="b}"#).is_err());
+        assert!(md_link_attributes(r#"{a="b"c}"#).is_err());
+        assert!(md_link_attributes("{.a\n.b}").is_err());
+        assert!(md_link_attributes("{a={b}}").is_err());
+    }
+
     #[test]
     fn test_md_text2label() {
         assert_eq!(
@@ -774,6 +1420,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_md_plain_text() {
+        assert!(matches!(md_plain_text("plain text"), Cow::Borrowed(_)));
+        assert!(matches!(md_plain_text("a * b"), Cow::Borrowed(_)));
+        assert_eq!(md_plain_text("*a* __b__ ***c***"), "a b c");
+        assert_eq!(md_plain_text("snake_case_name"), "snake_case_name");
+        assert_eq!(md_plain_text("``a ` b`` `` ` ``"), "a ` b `");
+        assert_eq!(md_plain_text("`unclosed"), "`unclosed");
+        assert_eq!(md_plain_text("![*a*](b \"c\") ![d][e] ![f]"), "a d f");
+        assert_eq!(
+            md_plain_text("a <span class=\"x\">b</span> 1 < 2"),
+            "a b 1 < 2"
+        );
+        assert_eq!(
+            md_plain_text("\\\\ \\a &#42;x&#42; &copy"),
+            "\\ \\a *x* &copy"
+        );
+    }
+
+    #[test]
+    fn test_md_label2dest_commonmark_0_30() {
+        // Examples 192 to 209 of the CommonMark Spec 0.30.
+        let ok = |i, rest, dest, title| {
+            assert_eq!(
+                md_label2dest(i),
+                Ok((rest, (Cow::from("foo"), Cow::from(dest), Cow::from(title)))),
+                "{i}"
+            )
+        };
+        ok("[foo]: /url \"title\"\n", "\n", "/url", "title");
+        ok(
+            "   [foo]: \n      /url  \n           'the title'  \n",
+            "\n",
+            "/url",
+            "the title",
+        );
+        ok(
+            "[foo]: /url '\ntitle\nline1\nline2\n'\n",
+            "\n",
+            "/url",
+            "\ntitle\nline1\nline2\n",
+        );
+        ok("[foo]: /url (title)\n", "\n", "/url", "title");
+        ok("[foo]:\n/url\n\n[foo]", "\n\n[foo]", "/url", "");
+        ok("[foo]: <>\n", "\n", "", "");
+        ok(
+            "[foo]: /url\\bar\\*baz \"foo\\\"bar\\baz\"\n",
+            "\n",
+            "/url\\bar*baz",
+            "foo\"bar\\baz",
+        );
+        ok("[foo]:/url\r\n", "\r\n", "/url", "");
+        ok(
+            "[foo]: /url\n\"title\" ok\n",
+            "\n\"title\" ok\n",
+            "/url",
+            "",
+        );
+        ok("[foo]: /f&ouml;&ouml;", "", "/föö", "");
+
+        for i in [
+            "[foo]: /url 'title\n\nwith blank line'\n",
+            "[foo]:\n\n[foo]",
+            "[foo]:",
+            "[foo]: <bar>(baz)\n",
+            "[foo]: /url \"title\" ok\n",
+        ] {
+            assert!(md_label2dest(i).is_err(), "{i}");
+        }
+    }
+
     #[test]
     fn test_md_link_text() {
         assert_eq!(
@@ -854,6 +1571,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_md_text2dest_commonmark_0_30() {
+        // Examples 482 to 509 of the CommonMark Spec 0.30.
+        let ok = |i, dest, title| {
+            assert_eq!(
+                md_text2dest(i),
+                Ok(("", (Cow::from("link"), Cow::from(dest), Cow::from(title)))),
+                "{i}"
+            )
+        };
+        ok("[link](/uri \"title\")", "/uri", "title");
+        ok("[link](/uri)", "/uri", "");
+        ok("[link]()", "", "");
+        ok("[link](<>)", "", "");
+        ok("[link](</my uri>)", "/my uri", "");
+        ok("[link](\\(foo\\))", "(foo)", "");
+        ok("[link](foo(and(bar)))", "foo(and(bar))", "");
+        ok("[link](foo\\(and\\(bar\\))", "foo(and(bar)", "");
+        ok("[link](<foo(and(bar)>)", "foo(and(bar)", "");
+        ok("[link](foo\\)\\:)", "foo):", "");
+        ok("[link](foo\\bar)", "foo\\bar", "");
+        ok("[link](foo%20b&auml;)", "foo%20bä", "");
+        ok("[link](\"title\")", "\"title\"", "");
+        ok("[link](/url 'title')", "/url", "title");
+        ok("[link](/url (title))", "/url", "title");
+        ok("[link](/url \"title \\\"&quot;\")", "/url", "title \"\"");
+        ok("[link](/url\u{a0}\"title\")", "/url\u{a0}\"title\"", "");
+        ok(
+            "[link](/url 'title \"and\" title')",
+            "/url",
+            "title \"and\" title",
+        );
+        ok("[link](   /uri\n  \"title\"  )", "/uri", "title");
+        assert_eq!(
+            md_text2dest("[a](<b)c>)"),
+            Ok(("", (Cow::from("a"), Cow::from("b)c"), Cow::from(""))))
+        );
+
+        for i in [
+            "[link](/my uri)",
+            "[link](foo\nbar)",
+            "[link](<foo\nbar>)",
+            "[link](<foo\\>)",
+            "[a](<b)c",
+            "[a](<b)c>",
+            "[a](<b>c)",
+            "[link](foo(and(bar))",
+            "[link](/url \"title \"and\" title\")",
+            "[link] (/uri)",
+            "[link](a\u{1}b)",
+        ] {
+            assert!(md_text2dest(i).is_err(), "{i}");
+        }
+    }
+
     #[test]
     fn test_md_parse_link_destination() {
         assert_eq!(md_parse_link_destination("<url>abc"), Ok(("abc", "url")));
@@ -1083,6 +1855,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_md_gfm_autolink() {
+        // Examples of the GFM specification.
+        let link = |i| md_gfm_autolink(i).map(|(rest, (te, de, _))| (rest, te, de));
+        assert_eq!(
+            link("www.commonmark.org/help for more"),
+            Ok((
+                " for more",
+                Cow::from("www.commonmark.org/help"),
+                Cow::from("http://www.commonmark.org/help")
+            ))
+        );
+        assert_eq!(
+            link("www.google.com/search?q=Markup+(business)))")
+                .unwrap()
+                .0,
+            "))"
+        );
+        assert_eq!(
+            link("www.google.com/search?q=(business))+ok").unwrap().0,
+            ""
+        );
+        assert_eq!(
+            link("www.google.com/search?q=commonmark&hl=en").unwrap().0,
+            ""
+        );
+        assert_eq!(
+            link("www.google.com/search?q=commonmark&hl;").unwrap().0,
+            "&hl;"
+        );
+        assert_eq!(link("www.commonmark.org/he<lp").unwrap().0, "<lp");
+        assert_eq!(
+            link("https://encrypted.google.com/search?q=Markup+(business))").unwrap(),
+            (
+                ")",
+                Cow::from("https://encrypted.google.com/search?q=Markup+(business)"),
+                Cow::from("https://encrypted.google.com/search?q=Markup+(business)")
+            )
+        );
+        assert_eq!(
+            link("hello+xyz@mail.example").unwrap(),
+            (
+                "",
+                Cow::from("hello+xyz@mail.example"),
+                Cow::from("mailto:hello+xyz@mail.example")
+            )
+        );
+        assert_eq!(link("a.b-c_d@a.b.").unwrap().0, ".");
+        assert!(link("hello@mail+xyz.example").is_err());
+        assert!(link("a.b-c_d@a.b-").is_err());
+        assert!(link("a.b-c_d@a.b_").is_err());
+
+        // Invalid domains.
+        assert!(link("www.").is_err());
+        assert!(link("www..").is_err());
+        assert!(link("https://").is_err());
+        assert!(link("https://a..b").is_err());
+        assert!(link("www.exa_mple.com").is_err());
+        assert!(link("www.a_b.example.com").is_ok());
+        assert!(link("http://localhost:8080/").is_ok());
+        assert!(link("ftp://example.com").is_err());
+        assert!(link("example.com").is_err());
+    }
+
     /*
     #[test]
     fn test_md_escaped() {
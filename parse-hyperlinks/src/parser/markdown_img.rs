@@ -1,20 +1,34 @@
 //! This module implements parsers for HTML image elements.
 #![allow(dead_code)]
 
-use super::markdown::md_link_destination;
+use crate::parser::generic_error;
 use crate::parser::markdown::md_link_destination_enclosed;
 use crate::parser::markdown::md_link_text;
+use crate::parser::markdown::md_text2label_generic;
+use crate::parser::markdown::md_unescape;
 use crate::parser::Link;
+use crate::parser::LinkError;
 use crate::take_until_unbalanced;
-use html_escape::decode_html_entities;
+use alloc::borrow::Cow;
 use nom::combinator::*;
+use nom::error::VerboseError;
 use nom::{bytes::complete::tag, sequence::tuple};
-use std::borrow::Cow;
 
 /// Wrapper around `md_img()` that packs the result in
 /// `Link::Image`.
-pub fn md_img_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (alt, src)) = md_img(i)?;
+pub fn md_img_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_img_link_generic(i)
+}
+
+/// Same as `md_img_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn md_img_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_img_link", md_img_link_generic)(i)
+}
+
+/// Same as `md_img_link()`, but generic over the error type.
+fn md_img_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src)) = md_img_generic(i)?;
     Ok((i, Link::Image(alt, src)))
 }
 
@@ -33,26 +47,107 @@ pub fn md_img_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("abc", (Cow::from("my Dog"), Cow::from("/images/my&dog.png"))))
 /// );
 /// ```
-pub fn md_img(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+pub fn md_img(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    md_img_generic(i)
+}
+
+/// Same as `md_img()`, but generic over the error type.
+fn md_img_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
     nom::sequence::preceded(
         tag("!"),
         // Parse inline link.
-        nom::sequence::tuple((md_link_text, md_img_link_destination_enclosed)),
+        nom::sequence::tuple((
+            generic_error(md_link_text),
+            generic_error(md_img_link_destination_enclosed),
+        )),
     )(i)
 }
 
+/// Wrapper around `md_img2label()` that packs the result in
+/// `Link::Image2Label`.
+pub fn md_img2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_img2label_link_generic(i)
+}
+
+/// Same as `md_img2label_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn md_img2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_img2label_link", md_img2label_link_generic)(i)
+}
+
+/// Same as `md_img2label_link()`, but generic over the error type.
+fn md_img2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, label)) = md_img2label_generic(i)?;
+    Ok((i, Link::Image2Label(alt, label)))
+}
+
+/// Parse a Markdown _reference image_.
+///
+/// It returns either `Ok((i, (img_alt, img_label)))` or some error.
+///
+/// Like _reference links_, reference images come in three kinds: full,
+/// collapsed and shortcut. In the latter two, the _image alt_ text serves as
+/// _link label_ (see `md_text2label()`). The image source is defined elsewhere
+/// in a _link reference definition_.
+///
+/// The parser expects to start at the link start (`!`) to succeed.
+/// It should always run at last position after all other parsers.
+/// ```
+/// use parse_hyperlinks;
+/// use parse_hyperlinks::parser::markdown_img::md_img2label;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   md_img2label("![my Dog][dog]abc"),
+///   Ok(("abc", (Cow::from("my Dog"), Cow::from("dog"))))
+/// );
+/// assert_eq!(
+///   md_img2label("![my Dog][]abc"),
+///   Ok(("abc", (Cow::from("my Dog"), Cow::from("my Dog"))))
+/// );
+/// assert_eq!(
+///   md_img2label("![my Dog]abc"),
+///   Ok(("abc", (Cow::from("my Dog"), Cow::from("my Dog"))))
+/// );
+/// ```
+pub fn md_img2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    md_img2label_generic(i)
+}
+
+/// Same as `md_img2label()`, but generic over the error type.
+fn md_img2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    nom::sequence::preceded(tag("!"), md_text2label_generic)(i)
+}
+
 /// Matches `md_link_destination` in parenthesis.
-fn md_img_link_destination_enclosed(i: &str) -> nom::IResult<&str, Cow<str>> {
-    map_parser(
-        nom::sequence::delimited(tag("("), take_until_unbalanced('(', ')'), tag(")")),
-        md_link_destination,
-    )(i)
+/// The link title is ignored.
+fn md_img_link_destination_enclosed(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
+    map(md_link_destination_enclosed, |(dest, _)| dest)(i)
 }
 
 /// Wrapper around `md_img()` that packs the result in
 /// `Link::Image`.
-pub fn md_img2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (text1, img_alt, img_src, text2, dest, title)) = md_img2dest(i)?;
+pub fn md_img2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    md_img2dest_link_generic(i)
+}
+
+/// Same as `md_img2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn md_img2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("md_img2dest_link", md_img2dest_link_generic)(i)
+}
+
+/// Same as `md_img2dest_link()`, but generic over the error type.
+fn md_img2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (text1, img_alt, img_src, text2, dest, title)) = md_img2dest_generic(i)?;
     Ok((
         i,
         Link::Image2Dest(text1, img_alt, img_src, text2, dest, title),
@@ -77,23 +172,61 @@ pub fn md_img2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///    (Cow::from("111"), Cow::from("my dog"), Cow::from("/my&dog.png"),
 ///     Cow::from("222"), Cow::from("http://page.com"), Cow::from("my title"),
 /// ))));
+/// assert_eq!(
+///   md_img2dest(r"[a\]![b](c)\[d](e)").unwrap().1 .0,
+///   Cow::from("a]")
+/// );
 /// ```
+#[allow(clippy::type_complexity)]
 pub fn md_img2dest(
     i: &str,
-) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>, Cow<str>, Cow<str>, Cow<str>)> {
+) -> nom::IResult<
+    &str,
+    (
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+        Cow<'_, str>,
+    ),
+> {
+    md_img2dest_generic(i)
+}
+
+/// Same as `md_img2dest()`, but generic over the error type.
+#[allow(clippy::type_complexity)]
+fn md_img2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<
+    &'a str,
+    (
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+        Cow<'a, str>,
+    ),
+    E,
+> {
     map(
         nom::sequence::tuple((
             map_parser(
-                nom::sequence::delimited(tag("["), take_until_unbalanced('[', ']'), tag("]")),
+                nom::sequence::delimited(
+                    tag("["),
+                    generic_error(take_until_unbalanced('[', ']')),
+                    tag("]"),
+                ),
                 tuple((
                     nom::bytes::complete::take_until("!["),
-                    md_img,
+                    md_img_generic,
                     nom::combinator::rest,
                 )),
             ),
-            md_link_destination_enclosed,
+            generic_error(md_link_destination_enclosed),
         )),
         // ((&str, (Cow<'_, str>, Cow<'_, str>), &str), (Cow<'_, str>, Cow<'_, str>)
-        |((a, (b, c), d), (e, f))| (decode_html_entities(a), b, c, decode_html_entities(d), e, f),
+        |((a, (b, c), d), (e, f))| (md_unescape(a, true), b, c, md_unescape(d, true), e, f),
     )(i)
 }
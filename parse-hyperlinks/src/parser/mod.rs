@@ -2,16 +2,31 @@
 //! definitions from text input.
 
 pub mod asciidoc;
+pub mod creole;
 pub mod html;
 pub mod html_img;
 pub mod markdown;
 pub mod markdown_img;
+pub mod org;
 pub mod parse;
 pub mod restructured_text;
 pub mod wikitext;
+#[cfg(feature = "std")]
+use crate::renderer::md_dest;
+#[cfg(feature = "std")]
+use crate::renderer::md_text;
+#[cfg(feature = "std")]
+use crate::rewrite::to_markup;
+use crate::url::url_kind;
+use crate::url::UrlKind;
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use core::fmt;
+use nom::error::ContextError;
 use nom::error::ErrorKind;
+use nom::error::ParseError;
+use nom::error::VerboseError;
 use percent_encoding::percent_decode_str;
-use std::borrow::Cow;
 
 /// A [hyperlink] with the following variants:
 /// * an [inline link] `Text2Dev`,
@@ -19,8 +34,11 @@ use std::borrow::Cow;
 /// * a [link reference definition] `Label2Dest`,
 /// * a [combined inline link / link reference definition] `TextLabel2Dest`,
 /// * a [reference alias] `Label2Label`,
-/// * an [inline image] `Image` or
-/// * an [inline link with embedded inline image] `Image2Dest`
+/// * an [inline image] `Image`,
+/// * a [reference image] `Image2Label`,
+/// * an [inline link with embedded inline image] `Image2Dest` or
+/// * a [Sphinx cross-reference] `Role2Target`
+///
 /// This is the main return type of this API.
 ///
 /// The _link title_ in Markdown is optional, when not given the string is set
@@ -33,8 +51,11 @@ use std::borrow::Cow;
 /// [combined inline link / link reference definition]: https://docutils.sourceforge.io/docs/ref/rst/restructuredtext.html#hyperlink-references
 /// [reference alias]: https://docutils.sourceforge.io/docs/ref/rst/restructuredtext.html#hyperlink-references
 /// [inline image]: https://spec.commonmark.org/0.30/#images
+/// [reference image]: https://spec.commonmark.org/0.30/#example-582
 /// [inline link with embedded inline image]: https://spec.commonmark.org/0.30/#example-519
+/// [Sphinx cross-reference]: https://www.sphinx-doc.org/en/master/usage/referencing.html
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum Link<'a> {
     /// An _inline link_ with the following tuple values:
@@ -160,6 +181,22 @@ pub enum Link<'a> {
     /// Note: this crate does not contain parsers for this variant.
     Image(Cow<'a, str>, Cow<'a, str>),
 
+    /// A _reference image_ with the following tuple values:
+    /// ```text
+    /// Image2Label(img_alt, link_label)
+    /// ```
+    /// In **reference images** the image source is defined elsewhere in
+    /// the document in some _link reference definition_. The iterators
+    /// resolve it into an `Image`.
+    /// * Markdown examples:
+    ///   ```md
+    ///   ![img_alt][link_label]
+    ///
+    ///   ![img_alt]
+    ///   ```
+    ///   When only _image alt_ is given, _link label_ is set to the same string.
+    Image2Label(Cow<'a, str>, Cow<'a, str>),
+
     /// An _inline link_ with embedded _inline image_ and the following
     /// tuple values.
     /// ```text
@@ -173,6 +210,321 @@ pub enum Link<'a> {
         Cow<'a, str>,
         Cow<'a, str>,
     ),
+
+    /// A Sphinx _cross-reference_ with the following tuple values:
+    /// ```text
+    /// Role2Target(role, link_text, link_target)
+    /// ```
+    /// A **cross-reference** is reStructuredText interpreted text with a
+    /// role prefix. Sphinx resolves `link_target` according to `role`,
+    /// e.g. to a document, a label or a Python object. When no explicit
+    /// title is given, `link_text` is empty and Sphinx derives it from the
+    /// target. This variant is only produced when
+    /// `ParserOptions::sphinx_roles` is set, e.g.
+    /// ```rst
+    /// :doc:`link_target`
+    ///
+    /// :ref:`link_text <link_target>`
+    ///
+    /// :external+proj:ref:`link_target`
+    /// ```
+    Role2Target(Cow<'a, str>, Cow<'a, str>, Cow<'a, str>),
+}
+
+impl Link<'_> {
+    /// Converts all borrowed strings into owned strings, so the link no
+    /// longer borrows from the input text. The returned link can outlive the
+    /// input buffer, e.g. to be stored in a cache or to be sent to another
+    /// thread.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::parser::parse::take_link;
+    /// use std::borrow::Cow;
+    ///
+    /// let link: Link<'static> = {
+    ///     let i = String::from("abc[text](dest)abc");
+    ///     let (_, (_, link)) = take_link(&i).unwrap();
+    ///     link.into_owned()
+    /// };
+    /// assert_eq!(link, Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("")));
+    /// assert!(!link.is_borrowed());
+    /// ```
+    pub fn into_owned(self) -> Link<'static> {
+        fn o(s: Cow<'_, str>) -> Cow<'static, str> {
+            Cow::Owned(s.into_owned())
+        }
+        match self {
+            Link::Text2Dest(a, b, c) => Link::Text2Dest(o(a), o(b), o(c)),
+            Link::Text2Label(a, b) => Link::Text2Label(o(a), o(b)),
+            Link::Label2Dest(a, b, c) => Link::Label2Dest(o(a), o(b), o(c)),
+            Link::TextLabel2Dest(a, b, c) => Link::TextLabel2Dest(o(a), o(b), o(c)),
+            Link::Label2Label(a, b) => Link::Label2Label(o(a), o(b)),
+            Link::Image(a, b) => Link::Image(o(a), o(b)),
+            Link::Image2Label(a, b) => Link::Image2Label(o(a), o(b)),
+            Link::Image2Dest(a, b, c, d, e, f) => {
+                Link::Image2Dest(o(a), o(b), o(c), o(d), o(e), o(f))
+            }
+            Link::Role2Target(a, b, c) => Link::Role2Target(o(a), o(b), o(c)),
+        }
+    }
+
+    /// True if all strings of the link borrow from the input text, i.e. the
+    /// parser did not allocate. See `parser::parse::needs_allocation()` for
+    /// when this is guaranteed.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use std::borrow::Cow;
+    ///
+    /// assert!(Link::Text2Dest(Cow::from("a"), Cow::from("b"), Cow::from("")).is_borrowed());
+    /// assert!(!Link::Image(Cow::from("a"), Cow::Owned("b".to_string())).is_borrowed());
+    /// ```
+    pub fn is_borrowed(&self) -> bool {
+        #[allow(clippy::ptr_arg)]
+        fn b(s: &Cow<'_, str>) -> bool {
+            matches!(s, Cow::Borrowed(_))
+        }
+        match self {
+            Link::Text2Dest(a, c, d)
+            | Link::Label2Dest(a, c, d)
+            | Link::TextLabel2Dest(a, c, d)
+            | Link::Role2Target(a, c, d) => b(a) && b(c) && b(d),
+            Link::Text2Label(a, c)
+            | Link::Label2Label(a, c)
+            | Link::Image(a, c)
+            | Link::Image2Label(a, c) => b(a) && b(c),
+            Link::Image2Dest(a, c, d, e, f, g) => b(a) && b(c) && b(d) && b(e) && b(f) && b(g),
+        }
+    }
+
+    /// The visible _link text_: the text of a link, the _image alt_ text
+    /// of an image or of a link with embedded image, or the text of a
+    /// Sphinx cross-reference.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use std::borrow::Cow;
+    ///
+    /// let link = Link::Text2Dest(Cow::from("text"), Cow::from("dest"), Cow::from("title"));
+    /// assert_eq!(link.text(), Some("text"));
+    /// assert_eq!(link.dest(), Some("dest"));
+    /// assert_eq!(link.title(), Some("title"));
+    /// assert_eq!(link.label(), None);
+    /// ```
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Link::Text2Dest(te, _, _)
+            | Link::Text2Label(te, _)
+            | Link::TextLabel2Dest(te, _, _)
+            | Link::Image(te, _)
+            | Link::Image2Label(te, _)
+            | Link::Image2Dest(_, te, _, _, _, _)
+            | Link::Role2Target(_, te, _) => Some(te),
+            Link::Label2Dest(_, _, _) | Link::Label2Label(_, _) => None,
+        }
+    }
+
+    /// The _link destination_, or the _image source_ of an image. Links
+    /// with embedded image return the destination of the link.
+    pub fn dest(&self) -> Option<&str> {
+        match self {
+            Link::Text2Dest(_, de, _)
+            | Link::Label2Dest(_, de, _)
+            | Link::TextLabel2Dest(_, de, _)
+            | Link::Image(_, de)
+            | Link::Image2Dest(_, _, _, _, de, _) => Some(de),
+            Link::Text2Label(_, _)
+            | Link::Label2Label(_, _)
+            | Link::Image2Label(_, _)
+            | Link::Role2Target(_, _, _) => None,
+        }
+    }
+
+    /// The kind of `dest()`, e.g. to spot `javascript:` links, see
+    /// `url::url_kind()`.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::take_link;
+    /// use parse_hyperlinks::url::UrlKind;
+    ///
+    /// let (_, (_, link)) = take_link("<a href=javascript:alert(1)>x</a>").unwrap();
+    /// assert_eq!(link.url_kind(), Some(UrlKind::Javascript));
+    /// ```
+    pub fn url_kind(&self) -> Option<UrlKind> {
+        self.dest().map(url_kind)
+    }
+
+    /// The _link title_, if the variant has one. Empty titles are returned
+    /// as `Some("")`.
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Link::Text2Dest(_, _, ti)
+            | Link::Label2Dest(_, _, ti)
+            | Link::TextLabel2Dest(_, _, ti)
+            | Link::Image2Dest(_, _, _, _, _, ti) => Some(ti),
+            Link::Text2Label(_, _)
+            | Link::Label2Label(_, _)
+            | Link::Image(_, _)
+            | Link::Image2Label(_, _)
+            | Link::Role2Target(_, _, _) => None,
+        }
+    }
+
+    /// The _link label_: the label a reference refers to, or the label a
+    /// definition defines. `Label2Label` returns the alias, not its target.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use std::borrow::Cow;
+    ///
+    /// let link = Link::Label2Dest(Cow::from("label"), Cow::from("dest"), Cow::from(""));
+    /// assert_eq!(link.text(), None);
+    /// assert_eq!(link.dest(), Some("dest"));
+    /// assert_eq!(link.title(), Some(""));
+    /// assert_eq!(link.label(), Some("label"));
+    /// ```
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Link::Text2Label(_, la)
+            | Link::Label2Dest(la, _, _)
+            | Link::TextLabel2Dest(la, _, _)
+            | Link::Label2Label(la, _)
+            | Link::Image2Label(_, la) => Some(la),
+            Link::Text2Dest(_, _, _)
+            | Link::Image(_, _)
+            | Link::Image2Dest(_, _, _, _, _, _)
+            | Link::Role2Target(_, _, _) => None,
+        }
+    }
+}
+
+/// Renders the link in Markdown syntax. Markdown has no reference aliases
+/// and no cross-references: `Label2Label(alias, label)` is rendered as
+/// reference link `[alias][label]`, `Role2Target(role, text, target)` as
+/// inline link `[text](target)`.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use std::borrow::Cow;
+///
+/// let link = Link::Text2Dest(Cow::from("te[x]t"), Cow::from("de st"), Cow::from("title"));
+/// assert_eq!(link.to_string(), r#"[te\[x\]t](<de st> "title")"#);
+/// let link = Link::Image2Label(Cow::from("alt"), Cow::from("label"));
+/// assert_eq!(link.to_string(), "![alt][label]");
+/// ```
+#[cfg(feature = "std")]
+impl fmt::Display for Link<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (to_markup(self, MarkupLang::Markdown), self) {
+            (Some(markup), _) => f.write_str(&markup),
+            (None, Link::Label2Label(alias, label)) => {
+                write!(f, "[{}][{}]", md_text(alias), md_text(label))
+            }
+            (None, Link::Role2Target(_, text, target)) => {
+                let text = if text.is_empty() { target } else { text };
+                write!(f, "[{}]({})", md_text(text), md_dest(target))
+            }
+            (None, l) => write!(f, "{:?}", l),
+        }
+    }
+}
+
+/// The markup languages, whose hyperlink syntax the parsers recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MarkupLang {
+    /// [CommonMark](https://spec.commonmark.org/0.30/).
+    Markdown,
+    /// [reStructuredText](https://docutils.sourceforge.io/docs/ref/rst/restructuredtext.html).
+    Rst,
+    /// [Asciidoc](https://asciidoctor.org/docs/user-manual/#url).
+    Adoc,
+    /// [HTML](https://www.w3.org/TR/html52/textlevel-semantics.html#the-a-element).
+    Html,
+    /// [Wikitext](https://www.mediawiki.org/wiki/Specs/wikitext/1.0.0).
+    Wikitext,
+    /// [Org-mode](https://orgmode.org/manual/Hyperlinks.html).
+    Org,
+}
+
+/// A set of markup languages, e.g. to restrict the parsers to some of them
+/// with `parse::ParserOptions::markup_langs`. The default set contains all
+/// markup languages, except Org-mode: its targets `<<target>>` would be
+/// mistaken for Asciidoc cross references and its links `[[target]]` for
+/// Markdown reference links.
+///
+/// ```
+/// use parse_hyperlinks::parser::{MarkupLang, MarkupLangs};
+///
+/// let langs: MarkupLangs = [MarkupLang::Markdown, MarkupLang::Html].into_iter().collect();
+/// assert!(langs.contains(MarkupLang::Html));
+/// assert!(!langs.contains(MarkupLang::Adoc));
+/// assert!(MarkupLangs::default().contains(MarkupLang::Adoc));
+/// assert!(!MarkupLangs::default().contains(MarkupLang::Org));
+/// assert!(MarkupLangs::ALL.contains(MarkupLang::Org));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MarkupLangs(u8);
+
+impl MarkupLangs {
+    /// The set of all markup languages.
+    pub const ALL: MarkupLangs = MarkupLangs(0b111111);
+
+    /// The empty set.
+    pub const fn empty() -> Self {
+        MarkupLangs(0)
+    }
+
+    /// Returns the set with `lang` added.
+    pub const fn with(self, lang: MarkupLang) -> Self {
+        MarkupLangs(self.0 | Self::bit(lang))
+    }
+
+    /// True if `lang` is in the set.
+    pub const fn contains(self, lang: MarkupLang) -> bool {
+        self.0 & Self::bit(lang) != 0
+    }
+
+    /// The bit representing `lang`.
+    const fn bit(lang: MarkupLang) -> u8 {
+        1 << lang as u8
+    }
+}
+
+impl Default for MarkupLangs {
+    fn default() -> Self {
+        MarkupLangs(Self::ALL.0 & !Self::bit(MarkupLang::Org))
+    }
+}
+
+impl FromIterator<MarkupLang> for MarkupLangs {
+    fn from_iter<T: IntoIterator<Item = MarkupLang>>(iter: T) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+/// The error types of the generic link parsers, e.g. `nom::error::Error`
+/// or `nom::error::VerboseError`. The public link parsers call these with
+/// the error type `nom::error::Error`, their `_verbose` variants with
+/// `VerboseError`.
+pub(crate) trait LinkError<'a>: ParseError<&'a str> + ContextError<&'a str> {}
+
+impl<'a, E: ParseError<&'a str> + ContextError<&'a str>> LinkError<'a> for E {}
+
+/// Converts the errors of `parser`, e.g. `url_scheme()` or
+/// `take_until_unbalanced()`, into the error type `E`. The position and
+/// `ErrorKind` are kept, but `parser` itself does not add to a
+/// `VerboseError`.
+pub(crate) fn generic_error<'a, O, E, F>(
+    mut parser: F,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, O, E>
+where
+    E: LinkError<'a>,
+    F: FnMut(&'a str) -> nom::IResult<&'a str, O>,
+{
+    move |i| parser(i).map_err(|e| e.map(|e| E::from_error_kind(e.input, e.code)))
 }
 
 /// A parser that decodes percent encoded URLS.
@@ -184,13 +536,149 @@ pub enum Link<'a> {
 /// let res = percent_decode("https://getreu.net/?q=%5Ba%20b%5D").unwrap();
 /// assert_eq!(res, ("", Cow::Owned("https://getreu.net/?q=[a b]".to_string())));
 ///```
-fn percent_decode(i: &str) -> nom::IResult<&str, Cow<str>> {
+fn percent_decode(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     let decoded = percent_decode_str(i)
         .decode_utf8()
         .map_err(|_| nom::Err::Error(nom::error::Error::new(i, ErrorKind::EscapedTransform)))?;
     Ok(("", decoded))
 }
 
+/// Parses the [scheme](https://spec.commonmark.org/0.30/#scheme) of an
+/// absolute URL, without the following `:`. As CommonMark defines it, the
+/// scheme consists of 2–32 characters beginning with an ASCII letter and
+/// followed by ASCII letters, digits, `+`, `.` or `-`. One letter schemes
+/// are excluded, because they are Windows drive letters, e.g. `C:`.
+///
+/// All markup languages share this parser. Which schemes are recognized in
+/// links, is decided by `parse::ParserOptions::url_schemes`.
+/// ```
+/// use parse_hyperlinks::parser::url_scheme;
+///
+/// assert_eq!(url_scheme("irc://chat"), Ok(("://chat", "irc")));
+/// assert!(url_scheme("C:\\dir").is_err());
+/// ```
+pub fn url_scheme(i: &str) -> nom::IResult<&str, &str> {
+    nom::combinator::verify(
+        nom::bytes::complete::take_while1(|c: char| c.is_ascii_alphanumeric() || "+.-".contains(c)),
+        |s: &str| s.starts_with(|c: char| c.is_ascii_alphabetic()) && (2..=32).contains(&s.len()),
+    )(i)
+}
+
+/// Parses the start of an absolute URL in continuous text: a scheme
+/// followed by `://`, or `mailto:`. Returns the scheme, the `:` is consumed.
+/// The Asciidoc and Wikitext parsers recognize URLs with this parser
+/// regardless of their scheme.
+pub(crate) fn url_start(i: &str) -> nom::IResult<&str, &str> {
+    let (j, scheme) = nom::sequence::terminated(url_scheme, nom::bytes::complete::tag(":"))(i)?;
+    if scheme.eq_ignore_ascii_case("mailto") || j.starts_with("//") {
+        Ok((j, scheme))
+    } else {
+        Err(nom::Err::Error(nom::error::Error::new(i, ErrorKind::Tag)))
+    }
+}
+
+/// Parses a bare URI in continuous text, whose scheme is one of `schemes`
+/// (compared ASCII case-insensitively), e.g. `tel:+123456789` or
+/// `geo:47.37,8.54`, into a `Link::Text2Dest`. Its link text and
+/// destination are the URI as written.
+///
+/// The URI ends at whitespace or at one of the characters `<>"`. Trailing
+/// punctuation `,;.:!?'` is not part of the URI, neither is a trailing `)`
+/// without matching `(`.
+///
+/// `parse::take_link_with()` uses this parser for the schemes in
+/// `ParserOptions::bare_uri_schemes`.
+/// ```
+/// use parse_hyperlinks::parser::{bare_uri_link, Link};
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     bare_uri_link("tel:+41-44-123-45-67. abc", &["tel", "sms"]),
+///     Ok((". abc", Link::Text2Dest(
+///         Cow::from("tel:+41-44-123-45-67"),
+///         Cow::from("tel:+41-44-123-45-67"),
+///         Cow::from(""),
+///     )))
+/// );
+/// assert!(bare_uri_link("geo:47.37,8.54", &["tel", "sms"]).is_err());
+/// ```
+pub fn bare_uri_link<'a>(i: &'a str, schemes: &[&str]) -> nom::IResult<&'a str, Link<'a>> {
+    bare_uri_link_generic(i, schemes)
+}
+
+/// Same as `bare_uri_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn bare_uri_link_verbose<'a>(
+    i: &'a str,
+    schemes: &[&str],
+) -> nom::IResult<&'a str, Link<'a>, VerboseError<&'a str>> {
+    nom::error::context("bare_uri_link", |i| bare_uri_link_generic(i, schemes))(i)
+}
+
+/// Same as `bare_uri_link()`, but generic over the error type.
+fn bare_uri_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+    schemes: &[&str],
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let err = || nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify));
+    let (j, scheme) =
+        nom::sequence::terminated(generic_error(url_scheme), nom::bytes::complete::tag(":"))(i)?;
+    if !schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+        return Err(err());
+    }
+    let end = j
+        .find(|c: char| c.is_whitespace() || "<>\"".contains(c))
+        .unwrap_or(j.len());
+    let mut rest = j[..end].trim_end_matches([',', ';', '.', ':', '!', '?', '\'']);
+    while let Some(r) = rest
+        .strip_suffix(')')
+        .filter(|_| rest.matches(')').count() > rest.matches('(').count())
+    {
+        rest = r.trim_end_matches([',', ';', '.', ':', '!', '?', '\'']);
+    }
+    if rest.is_empty() {
+        return Err(err());
+    }
+    let uri = &i[..i.len() - j.len() + rest.len()];
+    Ok((
+        &i[uri.len()..],
+        Link::Text2Dest(Cow::Borrowed(uri), Cow::Borrowed(uri), Cow::Borrowed("")),
+    ))
+}
+
+#[test]
+fn test_bare_uri_link() {
+    let schemes = ["tel", "sms", "geo"];
+    assert_eq!(
+        bare_uri_link("GEO:1,2;u=3 abc", &schemes),
+        Ok((
+            " abc",
+            Link::Text2Dest(
+                Cow::from("GEO:1,2;u=3"),
+                Cow::from("GEO:1,2;u=3"),
+                Cow::from("")
+            )
+        ))
+    );
+    assert_eq!(bare_uri_link("sms:+1(2)3),", &schemes).unwrap().0, "),");
+    assert_eq!(bare_uri_link("tel:1)", &schemes).unwrap().0, ")");
+    assert_eq!(bare_uri_link("tel:1<br>", &schemes).unwrap().0, "<br>");
+    assert!(bare_uri_link("tel:.", &schemes).is_err());
+    assert!(bare_uri_link("tel: 1", &schemes).is_err());
+    assert!(bare_uri_link("tel", &schemes).is_err());
+    assert!(bare_uri_link("fax:1", &schemes).is_err());
+    assert!(bare_uri_link("tel:1", &[]).is_err());
+}
+
+#[test]
+fn test_url_start() {
+    assert_eq!(url_start("HTTPS://a"), Ok(("//a", "HTTPS")));
+    assert_eq!(url_start("mailto:a@b"), Ok(("a@b", "mailto")));
+    assert!(url_start("http:/a").is_err());
+    assert!(url_start("note: a").is_err());
+    assert!(url_start("c://a").is_err());
+}
+
 #[test]
 fn test_percent_decode() {
     let res = percent_decode("percent%20encoded string").unwrap();
@@ -201,3 +689,22 @@ fn test_percent_decode() {
     assert!(matches!(res.1, Cow::Borrowed(..)));
     assert_eq!(res.1, Cow::from("nothing"));
 }
+
+#[test]
+fn test_link_accessors() {
+    let c = |s| Cow::Borrowed(s);
+    let link = Link::Image2Dest(c("a"), c("alt"), c("src"), c("b"), c("dest"), c("title"));
+    assert_eq!(link.text(), Some("alt"));
+    assert_eq!(link.dest(), Some("dest"));
+    assert_eq!(link.title(), Some("title"));
+    assert_eq!(link.label(), None);
+    assert_eq!(link.to_string(), r#"[a![alt](src)b](dest "title")"#);
+
+    let link = Link::Label2Label(c("alias"), c("label"));
+    assert_eq!((link.text(), link.label()), (None, Some("alias")));
+    assert_eq!(link.to_string(), "[alias][label]");
+
+    let link = Link::Role2Target(c("doc"), c(""), c("path/doc"));
+    assert_eq!((link.text(), link.dest()), (Some(""), None));
+    assert_eq!(link.to_string(), "[path/doc](path/doc)");
+}
@@ -0,0 +1,370 @@
+//! This module implements parsers for Org-mode hyperlinks.
+//!
+//! Org writes links as `[[link][description]]` or `[[link]]`. Links to other
+//! resources, e.g. `[[https://example.org][Example]]` or `[[file:a.org]]`,
+//! are inline links. Internal links, e.g. `[[target]]`, refer to a target
+//! `<<target>>`, a radio target `<<<target>>>` or a named element
+//! `#+NAME: target` in the same file: they are reference links and the
+//! targets are link reference definitions. `take_link_with()` uses these
+//! parsers, when `ParserOptions::markup_langs` contains `MarkupLang::Org`.
+#![allow(dead_code)]
+#![allow(clippy::type_complexity)]
+
+use crate::parser::url_scheme;
+use crate::parser::url_start;
+use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use nom::bytes::complete::tag;
+use nom::bytes::complete::tag_no_case;
+use nom::bytes::complete::take_till;
+use nom::bytes::complete::take_until;
+use nom::error::ErrorKind;
+use nom::error::VerboseError;
+
+/// The Org-mode link types, besides absolute URLs, that refer to other
+/// resources, see `org_text2dest_link()`. Compared ASCII case-insensitively.
+pub const LINK_TYPES: [&str; 9] = [
+    "file",
+    "attachment",
+    "id",
+    "doi",
+    "news",
+    "shell",
+    "elisp",
+    "help",
+    "info",
+];
+
+/// Parse an Org-mode link to another resource, e.g.
+/// `[[https://example.org][Example]]`, into a `Link::Text2Dest`.
+///
+/// The link must be an absolute URL, e.g. `https://example.org` or
+/// `mailto:a@example.org`, start with one of the `LINK_TYPES`, e.g.
+/// `file:`, or be a file path starting with `/`, `./`, `../` or `~/`.
+/// Without description, the link as written becomes the _link text_. The
+/// escapes `\[`, `\]` and `\\` in the link are decoded.
+///
+/// The parser expects to start at the link start (`[[`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::org::org_text2dest_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     org_text2dest_link("[[https://example.org][Example]]abc"),
+///     Ok(("abc", Link::Text2Dest(Cow::from("Example"), Cow::from("https://example.org"), Cow::from(""))))
+/// );
+/// assert_eq!(
+///     org_text2dest_link("[[file:notes.org]]abc"),
+///     Ok(("abc", Link::Text2Dest(Cow::from("file:notes.org"), Cow::from("file:notes.org"), Cow::from(""))))
+/// );
+/// assert!(org_text2dest_link("[[target]]").is_err());
+/// ```
+pub fn org_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    org_text2dest_link_generic(i)
+}
+
+/// Same as `org_text2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn org_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("org_text2dest_link", org_text2dest_link_generic)(i)
+}
+
+/// Same as `org_text2dest_link()`, but generic over the error type.
+fn org_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (j, (link, text)) = org_link(i)?;
+    if !is_external(&link) {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    let text = text.map_or_else(|| link.clone(), Cow::from);
+    Ok((j, Link::Text2Dest(text, link, Cow::from(""))))
+}
+
+/// Parse an Org-mode internal link, e.g. `[[target][text]]`, into a
+/// `Link::Text2Label`. Its _link label_ is the target. Without description,
+/// the target becomes the _link text_ too.
+///
+/// Links to headings `[[*heading]]` and custom IDs `[[#id]]` keep their
+/// prefix in the _link label_. Links with an URL scheme or a file path are
+/// recognized by `org_text2dest_link()`.
+///
+/// The parser expects to start at the link start (`[[`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::org::org_text2label_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     org_text2label_link("[[my target][see here]]abc"),
+///     Ok(("abc", Link::Text2Label(Cow::from("see here"), Cow::from("my target"))))
+/// );
+/// assert_eq!(
+///     org_text2label_link("[[*Intro]]abc"),
+///     Ok(("abc", Link::Text2Label(Cow::from("*Intro"), Cow::from("*Intro"))))
+/// );
+/// assert!(org_text2label_link("[[https://example.org]]").is_err());
+/// ```
+pub fn org_text2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    org_text2label_link_generic(i)
+}
+
+/// Same as `org_text2label_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn org_text2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("org_text2label_link", org_text2label_link_generic)(i)
+}
+
+/// Same as `org_text2label_link()`, but generic over the error type.
+fn org_text2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (j, (label, text)) = org_link(i)?;
+    if is_external(&label) {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    let text = text.map_or_else(|| label.clone(), Cow::from);
+    Ok((j, Link::Text2Label(text, label)))
+}
+
+/// Parse an Org-mode target `<<target>>` or radio target `<<<target>>>`
+/// into a `Link::Label2Dest`. Its _link destination_ is the fragment
+/// `#target`, the _link title_ is empty.
+///
+/// The target must neither start nor end with whitespace and must not
+/// contain `<`, `>` or line breaks. Occurrences of a radio target's text
+/// are not recognized as links.
+///
+/// The parser expects to start at the target start (`<<`) to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::org::org_target_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     org_target_link("<<my target>>abc"),
+///     Ok(("abc", Link::Label2Dest(Cow::from("my target"), Cow::from("#my target"), Cow::from(""))))
+/// );
+/// assert_eq!(
+///     org_target_link("<<<radio>>>abc"),
+///     Ok(("abc", Link::Label2Dest(Cow::from("radio"), Cow::from("#radio"), Cow::from(""))))
+/// );
+/// assert!(org_target_link("<< a >>").is_err());
+/// ```
+pub fn org_target_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    org_target_link_generic(i)
+}
+
+/// Same as `org_target_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn org_target_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("org_target_link", org_target_link_generic)(i)
+}
+
+/// Same as `org_target_link()`, but generic over the error type.
+fn org_target_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (j, radio) = nom::combinator::opt(tag("<<<"))(i)?;
+    let j = if radio.is_some() { j } else { tag("<<")(i)?.0 };
+    let (j, target) = take_till(|c| matches!(c, '<' | '>' | '\n' | '\r'))(j)?;
+    let (j, _) = tag(if radio.is_some() { ">>>" } else { ">>" })(j)?;
+    if target.is_empty() || target.trim() != target {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    Ok((j, target_link(target)))
+}
+
+/// Parse an Org-mode affiliated keyword `#+NAME: target`, naming the
+/// following element, into a `Link::Label2Dest`. The keyword is case
+/// insensitive. Its _link destination_ is the fragment `#target`, the
+/// _link title_ is empty. The parser consumes the line without the line
+/// break.
+///
+/// The parser expects to start at the beginning of the line to succeed.
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::org::org_name_link;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     org_name_link("#+name: fig:logo\n[[./logo.png]]"),
+///     Ok(("\n[[./logo.png]]", Link::Label2Dest(Cow::from("fig:logo"), Cow::from("#fig:logo"), Cow::from(""))))
+/// );
+/// ```
+pub fn org_name_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    org_name_link_generic(i)
+}
+
+/// Same as `org_name_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn org_name_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("org_name_link", org_name_link_generic)(i)
+}
+
+/// Same as `org_name_link()`, but generic over the error type.
+fn org_name_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (j, _) = tag_no_case("#+NAME:")(i)?;
+    let (j, name) = take_till(|c| c == '\n' || c == '\r')(j)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    Ok((j, target_link(name)))
+}
+
+/// Packs the target `target` in a `Link::Label2Dest`.
+fn target_link(target: &str) -> Link<'_> {
+    Link::Label2Dest(
+        Cow::from(target),
+        Cow::from(format!("#{}", target)),
+        Cow::from(""),
+    )
+}
+
+/// Parses `[[link][description]]` or `[[link]]` and returns the decoded
+/// link and the description, if any.
+fn org_link<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Option<&'a str>), E> {
+    let (j, _) = tag("[[")(i)?;
+    // The link ends at the first unescaped `]`. Brackets inside the link
+    // must be escaped.
+    let mut escaped = false;
+    let len = j
+        .find(|c| {
+            let end = (c == ']' || c == '[') && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })
+        .ok_or_else(|| nom::Err::Error(E::from_error_kind(i, ErrorKind::TakeUntil)))?;
+    let (link, j) = j.split_at(len);
+    if j.starts_with('[') {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    let (j, text) = if let Some(j) = j.strip_prefix("][") {
+        let (j, text) = take_until("]]")(j)?;
+        (j, Some(text.trim()))
+    } else {
+        (j, None)
+    };
+    let (j, _) = tag("]]")(j)?;
+
+    let link = link.trim();
+    if link.is_empty()
+        || link.contains(['\n', '\r'])
+        || text.is_some_and(|t| t.is_empty() || t.contains(['\n', '\r']) || t.contains("[["))
+    {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+    }
+    Ok((j, (unescape(link), text)))
+}
+
+/// Decodes the escapes `\[`, `\]` and `\\` of an Org-mode link.
+fn unescape(link: &str) -> Cow<'_, str> {
+    if !link.contains('\\') {
+        return Cow::Borrowed(link);
+    }
+    let mut out = String::with_capacity(link.len());
+    let mut chars = link.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(e @ ('[' | ']' | '\\'))) => {
+                out.push(e);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// True if `link` refers to another resource: it is an absolute URL, starts
+/// with one of the `LINK_TYPES` followed by `:` or is a file path.
+fn is_external(link: &str) -> bool {
+    url_start(link).is_ok()
+        || matches!(url_scheme(link), Ok((rest, scheme))
+            if rest.starts_with(':') && LINK_TYPES.iter().any(|t| t.eq_ignore_ascii_case(scheme)))
+        || ["/", "./", "../", "~/"].iter().any(|p| link.starts_with(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_org_link() {
+        assert_eq!(
+            org_text2label_link("[[a\\]b][c]]d"),
+            Ok(("d", Link::Text2Label(Cow::from("c"), Cow::from("a]b"))))
+        );
+        assert_eq!(
+            org_text2dest_link(r"[[https://e/\[1\]]]"),
+            Ok((
+                "",
+                Link::Text2Dest(
+                    Cow::from("https://e/[1]"),
+                    Cow::from("https://e/[1]"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert_eq!(
+            org_text2label_link(r"[[f\\]]"),
+            Ok(("", Link::Text2Label(Cow::from(r"f\"), Cow::from(r"f\"))))
+        );
+        // Unterminated links.
+        assert!(org_text2label_link("[[a").is_err());
+        assert!(org_text2label_link("[[a]").is_err());
+        assert!(org_text2label_link("[[a][b").is_err());
+        assert!(org_text2label_link("[[a][b]").is_err());
+        assert!(org_text2label_link(r"[[a\]]").is_err());
+        // Empty links and descriptions.
+        assert!(org_text2label_link("[[]]").is_err());
+        assert!(org_text2label_link("[[ ]]").is_err());
+        assert!(org_text2label_link("[[a][ ]]").is_err());
+        // Nested brackets.
+        assert!(org_text2label_link("[[[[a]]]]").is_err());
+        assert!(org_text2label_link("[[a[b]]").is_err());
+        assert!(org_text2label_link("[[a][b [[c]] d]]").is_err());
+        // Line breaks.
+        assert!(org_text2label_link("[[a\nb]]").is_err());
+        assert!(org_text2label_link("[[a][b\nc]]").is_err());
+    }
+
+    #[test]
+    fn test_org_target_link() {
+        assert_eq!(
+            org_target_link("<<<radio>>>abc"),
+            Ok((
+                "abc",
+                Link::Label2Dest(Cow::from("radio"), Cow::from("#radio"), Cow::from(""))
+            ))
+        );
+        assert!(org_target_link("<<>>").is_err());
+        assert!(org_target_link("<<<>>>").is_err());
+        assert!(org_target_link("<< >>").is_err());
+        assert!(org_target_link("<<a").is_err());
+        assert!(org_target_link("<<a>").is_err());
+        assert!(org_target_link("<<<a>>").is_err());
+        assert!(org_target_link("<<a<b>>").is_err());
+        assert!(org_target_link("<<a\nb>>").is_err());
+    }
+
+    #[test]
+    fn test_org_name_link() {
+        assert_eq!(
+            org_name_link("#+Name:  tab \r\n| a |"),
+            Ok((
+                "\r\n| a |",
+                Link::Label2Dest(Cow::from("tab"), Cow::from("#tab"), Cow::from(""))
+            ))
+        );
+        assert!(org_name_link("#+NAME:").is_err());
+        assert!(org_name_link("#+NAME:   \ntab").is_err());
+        assert!(org_name_link("#+NAMES: tab").is_err());
+        assert!(org_name_link(" #+NAME: tab").is_err());
+    }
+}
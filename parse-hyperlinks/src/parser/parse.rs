@@ -1,36 +1,290 @@
 //! This module implements parsers to extract hyperlinks and link reference
 //! definitions from text input. The parsers search for Markdown,
-//! ReStructuredText, Asciidoc, Wikitext and HTML formatted links.
+//! ReStructuredText, Asciidoc, Wikitext, HTML and, on request, Org-mode
+//! formatted links.
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::asciidoc::adoc_img_link;
 use crate::parser::asciidoc::adoc_label2dest_link;
 use crate::parser::asciidoc::adoc_text2dest_link;
 use crate::parser::asciidoc::adoc_text2label_link;
+use crate::parser::bare_uri_link;
+use crate::parser::creole::creole_img_link;
+use crate::parser::creole::creole_text2dest_link;
+use crate::parser::creole::dokuwiki_img_link;
+use crate::parser::creole::dokuwiki_text2dest_link;
+use crate::parser::html::html_area2dest_link;
+use crate::parser::html::html_rel2dest_link;
 use crate::parser::html::html_text2dest_link;
 use crate::parser::html_img::html_img2dest_link;
 use crate::parser::html_img::html_img_link;
+use crate::parser::html_img::html_source_link;
+use crate::parser::markdown::md_gfm_autolink_link;
 use crate::parser::markdown::md_label2dest_link;
+use crate::parser::markdown::md_link_attributes;
+use crate::parser::markdown::md_plain_link;
 use crate::parser::markdown::md_text2dest_link;
 use crate::parser::markdown::md_text2label_link;
 use crate::parser::markdown_img::md_img2dest_link;
+use crate::parser::markdown_img::md_img2label_link;
 use crate::parser::markdown_img::md_img_link;
+use crate::parser::org::org_name_link;
+use crate::parser::org::org_target_link;
+use crate::parser::org::org_text2dest_link;
+use crate::parser::org::org_text2label_link;
+use crate::parser::restructured_text::rst_image_link;
 use crate::parser::restructured_text::rst_label2dest_link;
 use crate::parser::restructured_text::rst_label2label_link;
+use crate::parser::restructured_text::rst_role2target_link;
+use crate::parser::restructured_text::rst_substitution2label_link;
+use crate::parser::restructured_text::rst_substitution_image_link;
 use crate::parser::restructured_text::rst_text2dest_link;
 use crate::parser::restructured_text::rst_text2label_link;
 use crate::parser::restructured_text::rst_text_label2dest_link;
+use crate::parser::url_scheme;
+use crate::parser::url_start;
+use crate::parser::wikitext::wikitext_free_url_link;
 use crate::parser::wikitext::wikitext_text2dest_link;
 use crate::parser::Link;
+use crate::parser::MarkupLang;
+use crate::parser::MarkupLangs;
+use alloc::borrow::Cow;
 use nom::branch::alt;
-use nom::bytes::complete::take_till;
 use nom::character::complete::anychar;
-use std::borrow::Cow;
 
 /// Link max label. This limits the damage of a forgotten closing brackets.
 /// [CommonMark Spec](https://spec.commonmark.org/0.30/#link-label)
 pub const LABEL_LEN_MAX: usize = 999;
 
+/// Options enabling link syntaxes, that `take_link()` does not recognize by
+/// default. Pass them to `take_link_with()`.
+///
+/// ```
+/// use parse_hyperlinks::parser::parse::ParserOptions;
+///
+/// let options = ParserOptions {
+///     sphinx_roles: true,
+///     ..ParserOptions::default()
+/// };
+/// assert_ne!(options, ParserOptions::default());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Recognize Sphinx cross-references like `` :doc:`path` `` or
+    /// `` :ref:`text <label>` `` as `Link::Role2Target`.
+    pub sphinx_roles: bool,
+    /// How the _link text_ of Markdown links is returned.
+    pub link_text: LinkText,
+    /// Only links of these markup languages are recognized, e.g. to avoid
+    /// that Asciidoc attributes in Markdown documents are mistaken for
+    /// links.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions};
+    /// use parse_hyperlinks::parser::{MarkupLang, MarkupLangs};
+    ///
+    /// let options = ParserOptions {
+    ///     markup_langs: MarkupLangs::empty().with(MarkupLang::Markdown),
+    ///     ..ParserOptions::default()
+    /// };
+    /// let i = "{attr} `a <b>`_ [text](dest)";
+    /// let (_, (skipped, _)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(skipped, "{attr} `a <b>`_ ");
+    /// ```
+    pub markup_langs: MarkupLangs,
+    /// The wiki syntax `MarkupLang::Wikitext` links are written in.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions, WikiDialect};
+    /// use std::borrow::Cow;
+    ///
+    /// let options = ParserOptions {
+    ///     wiki_dialect: WikiDialect::DokuWiki,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let i = "abc [[wiki:syntax|Syntax]] {{logo.png?50|Logo}}";
+    /// let (i, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link, Link::Text2Dest(Cow::from("Syntax"), Cow::from("wiki:syntax"), Cow::from("")));
+    /// let (_, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link, Link::Image(Cow::from("Logo"), Cow::from("logo.png")));
+    /// ```
+    pub wiki_dialect: WikiDialect,
+    /// The URL schemes of the links, that are recognized by their URL: bare
+    /// URLs and `link:` macros in Asciidoc, external links in Wikitext and
+    /// autolinks like `<irc://chat>` in Markdown. Links with explicit
+    /// markup, e.g. `[text](dest)`, are not concerned.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions, UrlSchemes};
+    ///
+    /// let i = "<irc://chat> https://a [ftp://c d]";
+    /// let options = ParserOptions::default();
+    /// let (i, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link.dest(), Some("irc://chat"));
+    ///
+    /// let options = ParserOptions {
+    ///     url_schemes: UrlSchemes::Only(&["ftp"]),
+    ///     ..ParserOptions::default()
+    /// };
+    /// let (_, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link.dest(), Some("ftp://c"));
+    /// ```
+    pub url_schemes: UrlSchemes,
+    /// Consume the Pandoc attribute block following a Markdown link or
+    /// image, e.g. `[text](dest){target=_blank}`, as part of the link.
+    /// Otherwise, the block is verbatim text or even mistaken for an
+    /// Asciidoc reference. `meta::LinkMeta` reads the attributes, see
+    /// `markdown::md_link_attributes()`.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     md_link_attributes: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let i = "[text](dest){.ext target=_blank}abc";
+    /// let (rest, _) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(rest, "abc");
+    /// ```
+    pub md_link_attributes: bool,
+    /// Recognize the GitHub Flavored Markdown extended autolinks, e.g.
+    /// `www.example.com`, `https://example.com` or `foo@example.com`, as
+    /// GitHub does, see `markdown::md_gfm_autolink()`.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::Link;
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions};
+    /// use std::borrow::Cow;
+    ///
+    /// let options = ParserOptions {
+    ///     gfm: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let i = "Visit www.example.com/a.";
+    /// let (rest, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(rest, ".");
+    /// assert_eq!(link, Link::Text2Dest(
+    ///     Cow::from("www.example.com/a"),
+    ///     Cow::from("http://www.example.com/a"),
+    ///     Cow::from(""),
+    /// ));
+    /// ```
+    pub gfm: bool,
+    /// Recognize bare URIs with these schemes in continuous text, e.g.
+    /// `tel:+123456789`, see `parser::bare_uri_link()`. They are reported
+    /// as Markdown links. `CONTACT_SCHEMES` lists the schemes of contact
+    /// pages. By default, no bare URIs are recognized this way.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::{take_link_with, ParserOptions, CONTACT_SCHEMES};
+    ///
+    /// let options = ParserOptions {
+    ///     bare_uri_schemes: &CONTACT_SCHEMES,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let i = "Call tel:+123456789, or text sms:+123456789.";
+    /// let (i, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link.dest(), Some("tel:+123456789"));
+    /// let (_, (_, link)) = take_link_with(i, &options).unwrap();
+    /// assert_eq!(link.dest(), Some("sms:+123456789"));
+    /// ```
+    pub bare_uri_schemes: &'static [&'static str],
+}
+
+/// The schemes of phone numbers, text messages and locations, see
+/// `ParserOptions::bare_uri_schemes`.
+pub const CONTACT_SCHEMES: [&str; 3] = ["tel", "sms", "geo"];
+
+/// The schemes Asciidoc and Wikitext links are recognized with by default,
+/// see `UrlSchemes::Default`.
+pub const URL_SCHEMES: [&str; 7] = ["http", "https", "ftp", "ftps", "irc", "ircs", "mailto"];
+
+/// The URL schemes, that `take_link_with()` recognizes, see
+/// `ParserOptions::url_schemes`. Schemes are compared ASCII
+/// case-insensitively.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UrlSchemes {
+    /// Markdown autolinks accept any scheme, as CommonMark specifies. The
+    /// other markup languages accept the schemes in `URL_SCHEMES`.
+    #[default]
+    Default,
+    /// All markup languages accept any scheme.
+    Any,
+    /// All markup languages accept only these schemes.
+    Only(&'static [&'static str]),
+}
+
+impl UrlSchemes {
+    /// True if links of `markup_lang` with the URL scheme `scheme` are
+    /// recognized.
+    ///
+    /// ```
+    /// use parse_hyperlinks::parser::parse::UrlSchemes;
+    /// use parse_hyperlinks::parser::MarkupLang;
+    ///
+    /// assert!(UrlSchemes::Default.allows(MarkupLang::Markdown, "irc"));
+    /// assert!(UrlSchemes::Default.allows(MarkupLang::Adoc, "HTTPS"));
+    /// assert!(!UrlSchemes::Default.allows(MarkupLang::Adoc, "file"));
+    /// assert!(!UrlSchemes::Only(&["https"]).allows(MarkupLang::Markdown, "irc"));
+    /// ```
+    pub fn allows(self, markup_lang: MarkupLang, scheme: &str) -> bool {
+        let schemes: &[&str] = match self {
+            UrlSchemes::Default if markup_lang == MarkupLang::Markdown => return true,
+            UrlSchemes::Default => &URL_SCHEMES,
+            UrlSchemes::Any => return true,
+            UrlSchemes::Only(schemes) => schemes,
+        };
+        schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+    }
+}
+
+/// The wiki syntax of `MarkupLang::Wikitext` links, see
+/// `ParserOptions::wiki_dialect`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WikiDialect {
+    /// MediaWiki external links `[dest text]`, see
+    /// `wikitext::wikitext_text2dest()`.
+    #[default]
+    MediaWiki,
+    /// Creole links `[[dest|text]]` and images `{{src|alt}}`, see
+    /// `creole::creole_text2dest_link()`.
+    Creole,
+    /// DokuWiki links `[[dest|text]]` and media `{{src?params|alt}}`, see
+    /// `creole::dokuwiki_text2dest_link()`.
+    DokuWiki,
+}
+
+/// Representation of the _link text_ of Markdown links, e.g. of
+/// `[**bold** ![alt](img) text](dest)`.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::parse::{take_link_with, LinkText, ParserOptions};
+/// use std::borrow::Cow;
+///
+/// let options = ParserOptions {
+///     link_text: LinkText::Plain,
+///     ..ParserOptions::default()
+/// };
+/// let i = "abc[*emphasized* `code`](dest)abc";
+/// let (_, (_, link)) = take_link_with(i, &options).unwrap();
+/// assert_eq!(link, Link::Text2Dest(Cow::from("emphasized code"), Cow::from("dest"), Cow::from("")));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LinkText {
+    /// The link text as written in the source, only backslash escapes are
+    /// decoded. Nested inline markup is preserved.
+    #[default]
+    Raw,
+    /// The plain text of the link text as returned by
+    /// `markdown::md_plain_text()`: inline markup is stripped, images are
+    /// replaced by their _image alt_ text.
+    Plain,
+}
+
 /// Consumes the input until it finds a Markdown, RestructuredText, Asciidoc or
 /// HTML formatted _inline link_ (`Text2Dest`) or _link reference definition_
 /// (`Label2Dest`).
@@ -103,7 +357,9 @@ pub const LABEL_LEN_MAX: usize = 999;
 ///
 /// Note: This function is depreciated and will be removed in some later release.
 /// Use `take_link()` instead.
-pub fn take_text2dest_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn take_text2dest_label2dest(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
     let mut j = i;
     loop {
         match take_link(j) {
@@ -195,6 +451,8 @@ pub fn take_text2dest_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
 /// abc{label2}[text2]abc
 /// abc{label3}abc
 /// :label4: https://destination4
+/// abc image:logo.png[alt5] abc
+/// image::diagram.svg[alt6,300]
 /// "#;
 ///
 /// let (i, r) = take_link(i).unwrap();
@@ -208,6 +466,10 @@ pub fn take_text2dest_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
 /// assert_eq!(r.1, Link::Text2Label(Cow::from(""), Cow::from("label3")));
 /// let (i, r) = take_link(i).unwrap();
 /// assert_eq!(r.1, Link::Label2Dest(Cow::from("label4"), Cow::from("https://destination4"), Cow::from("")));
+/// let (i, r) = take_link(i).unwrap();
+/// assert_eq!(r.1, Link::Image(Cow::from("alt5"), Cow::from("logo.png")));
+/// let (i, r) = take_link(i).unwrap();
+/// assert_eq!(r.1, Link::Image(Cow::from("alt6"), Cow::from("diagram.svg")));
 /// ```
 ///
 /// # HTML
@@ -228,7 +490,87 @@ pub fn take_text2dest_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
 /// assert_eq!(r.0, "abc\nabc");
 /// assert_eq!(r.1, Link::Text2Dest(Cow::from("text2"), Cow::from("destination2"), Cow::from("title2")));
 /// ```
-pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
+pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link<'_>)> {
+    take_link_with(i, &ParserOptions::default())
+}
+
+/// Same as `take_link()`, but additionally recognizes the link syntaxes
+/// enabled in `options`.
+///
+/// # Sphinx
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::parse::take_link_with;
+/// use parse_hyperlinks::parser::parse::ParserOptions;
+/// use std::borrow::Cow;
+///
+/// let options = ParserOptions {
+///     sphinx_roles: true,
+///     ..ParserOptions::default()
+/// };
+/// let i = r#"abc :doc:`path/doc` abc
+/// see (:ref:`text <label>`) and :external+proj:ref:`x`
+/// "#;
+///
+/// let (i, r) = take_link_with(i, &options).unwrap();
+/// assert_eq!(r.0, "abc ");
+/// assert_eq!(r.1, Link::Role2Target(Cow::from("doc"), Cow::from(""), Cow::from("path/doc")));
+/// let (i, r) = take_link_with(i, &options).unwrap();
+/// assert_eq!(r.0, " abc\nsee (");
+/// assert_eq!(r.1, Link::Role2Target(Cow::from("ref"), Cow::from("text"), Cow::from("label")));
+/// let (i, r) = take_link_with(i, &options).unwrap();
+/// assert_eq!(r.1, Link::Role2Target(Cow::from("external+proj:ref"), Cow::from(""), Cow::from("x")));
+/// ```
+pub fn take_link_with<'a>(
+    i: &'a str,
+    options: &ParserOptions,
+) -> nom::IResult<&'a str, (&'a str, Link<'a>)> {
+    take_link_lang_with(i, options).map(|(j, (skipped, link, _))| (j, (skipped, link)))
+}
+
+/// Same as `take_link()`, but additionally returns the markup language,
+/// whose grammar matched the link.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::MarkupLang;
+/// use parse_hyperlinks::parser::parse::take_link_lang;
+/// use std::borrow::Cow;
+///
+/// let i = "abc[text1](dest1)abc `text2 <dest2>`_ abc\n:label3: https://dest3\n";
+///
+/// let (i, r) = take_link_lang(i).unwrap();
+/// assert_eq!(r.1, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from("")));
+/// assert_eq!(r.2, MarkupLang::Markdown);
+/// let (i, r) = take_link_lang(i).unwrap();
+/// assert_eq!(r.2, MarkupLang::Rst);
+/// let (i, r) = take_link_lang(i).unwrap();
+/// assert_eq!(r.2, MarkupLang::Adoc);
+/// assert!(take_link_lang(i).is_err());
+/// ```
+pub fn take_link_lang(i: &str) -> nom::IResult<&str, (&str, Link<'_>, MarkupLang)> {
+    take_link_lang_with(i, &ParserOptions::default())
+}
+
+/// Same as `take_link_with()`, but additionally returns the markup language,
+/// whose grammar matched the link.
+pub fn take_link_lang_with<'a>(
+    i: &'a str,
+    options: &ParserOptions,
+) -> nom::IResult<&'a str, (&'a str, Link<'a>, MarkupLang)> {
+    use MarkupLang::*;
+    let langs = options.markup_langs;
+    let (wiki_link, wiki_img): (WikiParser<'a>, WikiParser<'a>) = match options.wiki_dialect {
+        WikiDialect::MediaWiki => (wikitext_text2dest_link, |i| {
+            Err(nom::Err::Error(nom::error::Error::new(
+                i,
+                nom::error::ErrorKind::Alt,
+            )))
+        }),
+        WikiDialect::Creole => (creole_text2dest_link, creole_img_link),
+        WikiDialect::DokuWiki => (dokuwiki_text2dest_link, dokuwiki_img_link),
+    };
     let mut j = i;
     let mut skip_count = 0;
     let mut input_start = true;
@@ -251,8 +593,10 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
             if let Ok((k, r)) = alt((
                 // Now we search for `label2*`.
                 // For both parser is the indent meaningful. We mustn't consume them.
-                rst_label2label_link,
-                rst_label2dest_link,
+                lang(langs, Rst, rst_label2label_link),
+                lang(langs, Rst, rst_label2dest_link),
+                lang(langs, Rst, rst_image_link),
+                lang(langs, Rst, rst_substitution_image_link),
             ))(j)
             {
                 break (k, r);
@@ -273,8 +617,9 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
                 // Now we search for `label2*`.
                 // These parsers do not care about the indent, as long it is
                 // only whitespace.
-                md_label2dest_link,
-                adoc_label2dest_link,
+                lang(langs, Markdown, md_label2dest_link),
+                url_schemes(options, lang(langs, Adoc, adoc_label2dest_link)),
+                lang(langs, Org, org_name_link),
             ))(j)
             {
                 break (k, r);
@@ -282,27 +627,60 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
         };
         // Start searching for links.
 
-        // Regular `text` links can start everywhere.
-        if let Ok((k, r)) = alt((
-            // Start with `text2dest`.
-            md_img_link,
-            md_img2dest_link,
-            md_text2dest_link,
-            // This should be first, because it is very specific.
-            wikitext_text2dest_link,
+        // Regular `text` links can start everywhere. Only try the parsers,
+        // that accept the first byte.
+        let first_byte = j.as_bytes().first().copied();
+        let res = match first_byte {
+            Some(b'!') => md_attributes(options, lang(langs, Markdown, md_img_link))(j),
+            Some(b'[') => alt((
+                md_attributes(options, lang(langs, Markdown, md_img2dest_link)),
+                md_attributes(options, lang(langs, Markdown, md_text2dest_link)),
+                // Wikitext must come after Markdown: in `[https://a](b)` it
+                // would take the Markdown link text as its destination.
+                url_schemes(options, lang(langs, Wikitext, wiki_link)),
+                lang(langs, Org, org_text2dest_link),
+                lang(langs, Org, org_text2label_link),
+            ))(j),
             // `rst_text2dest` must be always placed before `rst_text2label`.
-            rst_text2dest_link,
-            rst_text_label2dest_link,
-            adoc_text2label_link,
-            html_img_link,
-            html_img2dest_link,
-            html_text2dest_link,
-        ))(j)
-        {
+            Some(b'`') => alt((
+                lang(langs, Rst, rst_text2dest_link),
+                lang(langs, Rst, rst_text_label2dest_link),
+            ))(j),
+            Some(b'{') => alt((
+                lang(langs, Wikitext, wiki_img),
+                lang(langs, Adoc, adoc_text2label_link),
+            ))(j),
+            Some(b'<') => alt((
+                url_schemes(options, lang(langs, Markdown, md_text2dest_link)),
+                lang(langs, Html, html_img_link),
+                lang(langs, Html, html_source_link),
+                lang(langs, Html, html_img2dest_link),
+                lang(langs, Html, html_text2dest_link),
+                lang(langs, Html, html_area2dest_link),
+                lang(langs, Html, html_rel2dest_link),
+                lang(langs, Org, org_target_link),
+            ))(j),
+            _ => Err(nom::Err::Error(nom::error::Error::new(
+                j,
+                nom::error::ErrorKind::Alt,
+            ))),
+        };
+        if let Ok((k, r)) = res {
             break (k, r);
         };
 
         if whitespace || line_start || input_start {
+            if options.gfm {
+                // GFM autolinks may also follow these delimiters.
+                let k = j.trim_start_matches(['*', '_', '~', '(']);
+                if let Ok((l, r)) =
+                    url_schemes(options, lang(langs, Markdown, md_gfm_autolink_link))(k)
+                {
+                    skip_count += j.len() - k.len();
+                    break (l, r);
+                };
+            };
+
             // There must be at least one more byte. If it is one of `([<'"`, skip it.
             let k = if let (k, Some(_)) =
                 nom::combinator::opt(nom::character::complete::one_of("([<'\""))(j)?
@@ -314,19 +692,45 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
                 j
             };
 
+            if options.sphinx_roles {
+                if let Ok((l, r)) = lang(langs, Rst, rst_role2target_link)(k) {
+                    // If ever we have skipped a char, remember it now.
+                    skip_count += j.len() - k.len();
+                    break (l, r);
+                };
+            };
+
             // `rst_text2label` must be always placed after `rst_text2dest`.
             // `md_text2label` must be always placed after `adoc_text2label` and `adoc_text2dest`,
             // because the former consumes `[*]`.
-            if let Ok((l, r)) = alt((rst_text2label_link, adoc_text2dest_link))(k) {
+            // Asciidoc's bare URL comes before Wikitext's free URL, that
+            // differs only in the trailing punctuation.
+            if let Ok((l, r)) = alt((
+                lang(langs, Rst, rst_text2label_link),
+                lang(langs, Rst, rst_substitution2label_link),
+                url_schemes(options, lang(langs, Adoc, adoc_text2dest_link)),
+                lang(langs, Adoc, adoc_img_link),
+                url_schemes(options, lang(langs, Wikitext, wikitext_free_url_link)),
+                lang(langs, Markdown, |i| {
+                    bare_uri_link(i, options.bare_uri_schemes)
+                }),
+            ))(k)
+            {
                 // If ever we have skipped a char, remember it now.
                 skip_count += j.len() - k.len();
                 break (l, r);
             };
         };
 
-        // This parser is so unspecific, that it must be the last.
-        if let Ok((k, r)) = md_text2label_link(j) {
-            break (k, r);
+        // These parsers are so unspecific, that they must be the last.
+        if matches!(first_byte, Some(b'!' | b'[')) {
+            if let Ok((k, r)) = alt((
+                md_attributes(options, lang(langs, Markdown, md_img2label_link)),
+                md_attributes(options, lang(langs, Markdown, md_text2label_link)),
+            ))(j)
+            {
+                break (k, r);
+            };
         };
 
         // This makes sure that we advance.
@@ -334,25 +738,11 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
         skip_count += j.len() - k.len();
         j = k;
 
-        // This might not consume bytes and never fails.
-        let (k, _) = take_till(|c|
-            // After this, we should check for: `md_label2dest`, `rst_label2dest`, `rst_text2label`, `adoc_text2dest`.
-            c == '\n'
-            // After this, possible start for `adoc_text2dest` or `rst_text2label`:
-            || c == ' ' || c == '\t'
-            // These are candidates for `rst_text2label`, `rst_text_label2dest` `rst_text2dest`:
-            || c == '`'
-            // These could be the start of all `md_img` link types.
-            || c == '!'
-            // These could be the start of all `md_*` link types.
-            || c == '['
-            // These could be the start of the `adoc_text2label` link type.
-            || c == '{'
-            // And this could be an HTML hyperlink:
-            || c == '<')(j)?;
-
-        skip_count += j.len() - k.len();
-        j = k;
+        // Skip to the next byte, where a link might start. All these bytes are
+        // ASCII, so they can not be part of a multi-byte UTF-8 character.
+        let n = next_link_start(j.as_bytes());
+        skip_count += n;
+        j = &j[n..];
         input_start = false;
     };
 
@@ -366,25 +756,364 @@ pub fn take_link(i: &str) -> nom::IResult<&str, (&str, Link)> {
     // recognized in the middle of a line.
     // It is sufficient to do this check once, because both parser guarantee to
     // consume the whole line in case of success.
-    let (mut l, link) = res;
+    let (mut l, (link, markup_lang)) = res;
     match link {
         Link::Label2Dest(_, _, _) | Link::Label2Label(_, _) => {}
         _ => {
             // Just consume, the result does not matter.
-            let (m, _) = nom::combinator::opt(alt((rst_label2dest_link, md_label2dest_link)))(l)?;
+            let (m, _) = nom::combinator::opt(alt((
+                lang(langs, Rst, rst_label2dest_link),
+                lang(langs, Markdown, md_label2dest_link),
+            )))(l)?;
             l = m;
         }
     };
 
     let skipped_input = &i[0..skip_count];
+    let consumed = &i[skip_count..i.len() - l.len()];
+
+    let link = match (options.link_text, markup_lang) {
+        (LinkText::Plain, Markdown) => md_plain_link(consumed, link),
+        _ => link,
+    };
+
+    #[cfg(feature = "debug-allocations")]
+    assert!(
+        link.is_borrowed() || needs_allocation_with(consumed, options),
+        "unexpected allocation for {:?} in {:?}",
+        link,
+        consumed
+    );
+
+    Ok((l, (skipped_input, link, markup_lang)))
+}
+
+/// Wraps the link parser `parser`, so that it fails, if the link's
+/// destination is an URL with a scheme, that `options.url_schemes` does not
+/// allow. Destinations without scheme pass, e.g. relative URLs or DokuWiki
+/// page names like `wiki:syntax`.
+fn url_schemes<'a, F>(
+    options: &ParserOptions,
+    mut parser: F,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, (Link<'a>, MarkupLang)>
+where
+    F: FnMut(&'a str) -> nom::IResult<&'a str, (Link<'a>, MarkupLang)>,
+{
+    let schemes = options.url_schemes;
+    move |i| {
+        let (j, (link, markup_lang)) = parser(i)?;
+        let dest = link.dest().unwrap_or_default();
+        let scheme = match markup_lang {
+            // An autolink's destination is always an absolute URI.
+            MarkupLang::Markdown => url_scheme(dest).ok().map(|(_, s)| s),
+            _ => url_start(dest).ok().map(|(_, s)| s),
+        };
+        match scheme {
+            Some(scheme) if !schemes.allows(markup_lang, scheme) => Err(nom::Err::Error(
+                nom::error::Error::new(i, nom::error::ErrorKind::Verify),
+            )),
+            _ => Ok((j, (link, markup_lang))),
+        }
+    }
+}
+
+/// Wraps the Markdown link parser `parser`, so that it also consumes the
+/// attribute block following the link, if `options.md_link_attributes` is
+/// set.
+fn md_attributes<'a, F>(
+    options: &ParserOptions,
+    mut parser: F,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, (Link<'a>, MarkupLang)>
+where
+    F: FnMut(&'a str) -> nom::IResult<&'a str, (Link<'a>, MarkupLang)>,
+{
+    let enabled = options.md_link_attributes;
+    move |i| {
+        let (j, res) = parser(i)?;
+        match enabled.then(|| md_link_attributes(j)) {
+            Some(Ok((k, _))) => Ok((k, res)),
+            _ => Ok((j, res)),
+        }
+    }
+}
+
+/// A link parser of the wiki dialect `ParserOptions::wiki_dialect`.
+type WikiParser<'a> = fn(&'a str) -> nom::IResult<&'a str, Link<'a>>;
+
+/// Wraps the link parser `parser` of the markup language `markup_lang`, so
+/// that it returns `markup_lang` together with the link. The parser fails,
+/// if `markup_lang` is not in `markup_langs`.
+fn lang<'a, F>(
+    markup_langs: MarkupLangs,
+    markup_lang: MarkupLang,
+    mut parser: F,
+) -> impl FnMut(&'a str) -> nom::IResult<&'a str, (Link<'a>, MarkupLang)>
+where
+    F: FnMut(&'a str) -> nom::IResult<&'a str, Link<'a>>,
+{
+    move |i| {
+        if !markup_langs.contains(markup_lang) {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                i,
+                nom::error::ErrorKind::Alt,
+            )));
+        }
+        parser(i).map(|(j, link)| (j, (link, markup_lang)))
+    }
+}
+
+/// Guesses the markup language of `input`, e.g. of a pasted snippet. Every
+/// link found counts two points for its markup language, every line with a
+/// signature construct one point: `.. ` (reStructuredText), `:name:`
+/// attribute entries (Asciidoc), `<a href` or `<html` (HTML), `[[` (Wikitext),
+/// `#+` keywords (Org-mode), fenced code blocks (Markdown) and headings
+/// (`# ` Markdown, `= ` or `== ` Asciidoc, `== text ==` Wikitext). The
+/// language with the most points wins, on a tie the first in the order
+/// Markdown, reStructuredText, Asciidoc, HTML, Wikitext, Org-mode. Without
+/// any clue, the result is Markdown.
+///
+/// ```
+/// use parse_hyperlinks::parser::parse::detect_markup;
+/// use parse_hyperlinks::parser::MarkupLang;
+///
+/// assert_eq!(detect_markup("abc [text](dest) abc"), MarkupLang::Markdown);
+/// assert_eq!(detect_markup("abc `text`_ abc\n\n.. _text: dest\n"), MarkupLang::Rst);
+/// assert_eq!(detect_markup(":toc:\n\nabc https://dest[text] abc"), MarkupLang::Adoc);
+/// assert_eq!(detect_markup(r#"<p>abc <a href="dest">text</a></p>"#), MarkupLang::Html);
+/// assert_eq!(detect_markup("== Title ==\nabc [https://dest text] abc"), MarkupLang::Wikitext);
+/// assert_eq!(detect_markup("#+TITLE: Notes\n\nabc <<target>> abc"), MarkupLang::Org);
+/// assert_eq!(detect_markup("abc"), MarkupLang::Markdown);
+/// ```
+pub fn detect_markup(input: &str) -> MarkupLang {
+    use MarkupLang::*;
+    const LANGS: [MarkupLang; 6] = [Markdown, Rst, Adoc, Html, Wikitext, Org];
+    let mut points = [0usize; 6];
+
+    let mut i = input;
+    while let Ok((j, (_, _, lang))) = take_link_lang(i) {
+        points[lang as usize] += 2;
+        i = j;
+    }
 
-    Ok((l, (skipped_input, link)))
+    for line in input.lines() {
+        let lang = if line.starts_with(".. ") {
+            Rst
+        } else if line.starts_with("#+") {
+            Org
+        } else if line.starts_with("```") || line.starts_with("~~~") || line.starts_with("# ") {
+            Markdown
+        } else if line.starts_with('=') && line.trim_end().ends_with('=') {
+            Wikitext
+        } else if line.starts_with("= ")
+            || line.starts_with("== ")
+            || (line.starts_with(':')
+                && line[1..].split_once(':').is_some_and(|(name, _)| {
+                    !name.is_empty() && !name.contains(char::is_whitespace)
+                }))
+        {
+            Adoc
+        } else if line.contains("[[") {
+            Wikitext
+        } else if line.contains("<a href") || line.contains("<html") || line.contains("<A HREF") {
+            Html
+        } else {
+            continue;
+        };
+        points[lang as usize] += 1;
+    }
+
+    // On a tie, the first language wins.
+    let mut best = 0;
+    for n in 1..LANGS.len() {
+        if points[n] > points[best] {
+            best = n;
+        }
+    }
+    LANGS[best]
+}
+
+/// Zero-allocation contract: returns `false` if a link parsed from the source
+/// `consumed` is guaranteed to borrow all its strings from the input, i.e.
+/// `Link::is_borrowed()` is `true`. `consumed` is the part of the input
+/// `take_link()` consumed for the link, without the skipped bytes.
+///
+/// The parsers allocate only when the link's source contains:
+/// * escaped characters (`\`), HTML entities (`&`) or percent encoded
+///   characters (`%`),
+/// * line breaks,
+/// * an email address (`@`), which is prefixed with `mailto:`,
+/// * a GFM www autolink, which is prefixed with `http://`,
+/// * whitespace inside reStructuredText syntax, which is removed from the
+///   destination,
+/// * nested tags in the link text of HTML links, which are removed,
+/// * `-` or `_` in an Asciidoc image macro, which are replaced by spaces, when
+///   the default alt text is derived from the file name.
+///
+/// With options, see `needs_allocation_with()`.
+///
+/// With the cargo feature `debug-allocations`, `take_link()` and its
+/// siblings panic, when a link breaks this contract.
+///
+/// ```
+/// use parse_hyperlinks::parser::parse::{needs_allocation, take_link};
+///
+/// let i = "abc[text](https://getreu.net)abc";
+/// let (j, (skipped, link)) = take_link(i).unwrap();
+/// let consumed = &i[skipped.len()..i.len() - j.len()];
+/// assert!(!needs_allocation(consumed));
+/// assert!(link.is_borrowed());
+///
+/// assert!(needs_allocation(r"[te\_xt](dest)"));
+/// assert!(needs_allocation("<getreu@web.de>"));
+/// assert!(needs_allocation("`a\u{2028}b`_"));
+/// assert!(needs_allocation("image:my-logo.png[]"));
+/// assert!(needs_allocation("<a href=\"dest\"><b>text</b></a>"));
+/// ```
+pub fn needs_allocation(consumed: &str) -> bool {
+    needs_allocation_with(consumed, &ParserOptions::default())
+}
+
+/// Same as `needs_allocation()`, but for links parsed by `take_link_with()`
+/// with `options`. Additionally, the parsers allocate when the link's
+/// source contains:
+/// * inline markup (`` ` ``, `!`, `*`, `_` or `<`) with `LinkText::Plain`,
+///   which is stripped from the link text,
+/// * an Org-mode target `<<target>>` or keyword `#+NAME:`, whose
+///   destination `#target` is derived from its name.
+///
+/// ```
+/// use parse_hyperlinks::parser::parse::{needs_allocation_with, LinkText, ParserOptions};
+/// use parse_hyperlinks::parser::MarkupLangs;
+///
+/// let options = ParserOptions {
+///     link_text: LinkText::Plain,
+///     ..ParserOptions::default()
+/// };
+/// assert!(!needs_allocation_with("[text](dest)", &options));
+/// assert!(needs_allocation_with("[*text*](dest)", &options));
+/// assert!(needs_allocation_with("[x![a](b)]", &options));
+///
+/// let options = ParserOptions {
+///     markup_langs: MarkupLangs::ALL,
+///     ..ParserOptions::default()
+/// };
+/// assert!(needs_allocation_with("<<target>>", &options));
+/// assert!(!needs_allocation_with("[[target]]", &options));
+/// ```
+pub fn needs_allocation_with(consumed: &str, options: &ParserOptions) -> bool {
+    if consumed.contains(['\\', '&', '%', '\n', '\r', '@']) {
+        return true;
+    }
+    if options.link_text == LinkText::Plain && consumed.contains(['`', '!', '*', '_', '<']) {
+        return true;
+    }
+    let c = consumed.trim_start_matches([' ', '\t']);
+    if options.markup_langs.contains(MarkupLang::Org)
+        && (c.starts_with("<<")
+            || c.get(..7)
+                .is_some_and(|k| k.eq_ignore_ascii_case("#+name:")))
+    {
+        return true;
+    }
+    (c.starts_with("..") || c.starts_with("__") || c.contains('`'))
+        && c.contains(char::is_whitespace)
+        || c.starts_with('<') && c.matches('<').count() > 2
+        || c.starts_with("image:") && c.contains(['-', '_'])
+        || c.starts_with("www.")
+}
+
+/// Same as `take_link()`, but operates on bytes, that are not guaranteed to
+/// be valid UTF-8. Invalid UTF-8 byte sequences are skipped like any other
+/// text between links: they end up in the returned skipped bytes. A link
+/// never contains invalid UTF-8, so a link interrupted by an invalid byte is
+/// not recognized.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::parse::take_link_bytes;
+/// use std::borrow::Cow;
+///
+/// let i = b"abc\xff[text1](dest1)\xfe\xfeabc[text2](dest2)\xff";
+///
+/// let (i, r) = take_link_bytes(i).unwrap();
+/// assert_eq!(r.0, b"abc\xff");
+/// assert_eq!(r.1, Link::Text2Dest(Cow::from("text1"), Cow::from("dest1"), Cow::from("")));
+/// let (i, r) = take_link_bytes(i).unwrap();
+/// assert_eq!(r.0, b"\xfe\xfeabc");
+/// assert_eq!(r.1, Link::Text2Dest(Cow::from("text2"), Cow::from("dest2"), Cow::from("")));
+/// assert!(take_link_bytes(i).is_err());
+/// ```
+pub fn take_link_bytes(i: &[u8]) -> nom::IResult<&[u8], (&[u8], Link<'_>)> {
+    let mut j = i;
+    loop {
+        // Split `j` into the longest valid UTF-8 prefix and the rest.
+        let (valid, rest, invalid_len) = match core::str::from_utf8(j) {
+            Ok(valid) => (valid, &j[j.len()..], 0),
+            Err(e) => {
+                let (valid, rest) = j.split_at(e.valid_up_to());
+                // `error_len()` is `None` for an incomplete sequence at the end.
+                let invalid_len = e.error_len().unwrap_or(rest.len());
+                // Can not fail, `valid` was validated above.
+                (core::str::from_utf8(valid).unwrap(), rest, invalid_len)
+            }
+        };
+
+        if let Ok((k, (skipped, link))) = take_link(valid) {
+            let offset = i.len() - j.len();
+            let skipped_len = offset + skipped.len();
+            let consumed_len = offset + valid.len() - k.len();
+            return Ok((&i[consumed_len..], (&i[..skipped_len], link)));
+        }
+
+        if rest.is_empty() {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                rest,
+                nom::error::ErrorKind::Eof,
+            )));
+        }
+        j = &rest[invalid_len..];
+    }
+}
+
+/// Position of the first byte in `i`, where the search for the next link
+/// must resume, or `i.len()` if there is none. These are:
+/// * `\n`, after which we check for `md_label2dest`, `rst_label2dest`,
+///   `rst_text2label` and `adoc_text2dest`,
+/// * ` ` and `\t`, possible starts of `adoc_text2dest` or `rst_text2label`,
+/// * `` ` ``, a candidate for `rst_text2label`, `rst_text_label2dest` and
+///   `rst_text2dest`,
+/// * `[` and `!`, which could be the start of all `md_*` link types,
+/// * `{`, which could be the start of `adoc_text2label`,
+/// * `<`, which could be an HTML hyperlink.
+///
+/// Whitespace ends the search in most texts, so it is searched first with
+/// the vectorized `memchr3()`. The other bytes are searched only before it.
+/// `benches/take_link.rs` measures the effect.
+#[inline]
+fn next_link_start(i: &[u8]) -> usize {
+    let n = memchr::memchr3(b'\n', b' ', b'\t', i).unwrap_or(i.len());
+    i[..n]
+        .iter()
+        .position(|b| matches!(b, b'`' | b'[' | b'<' | b'!' | b'{'))
+        .unwrap_or(n)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_detect_markup() {
+        use MarkupLang::*;
+        let i = "# Title\n\n<a href=\"x\">y</a>\n[a](b) [c][d]\n\n[d]: e\n";
+        assert_eq!(detect_markup(i), Markdown);
+        let i = "Title\n=====\n\n.. image:: a.png\n\nSee `a <b>`_ and `c`_.\n";
+        assert_eq!(detect_markup(i), Rst);
+        let i = "= Title\n:author: me\n\n== Section\n\nlink:doc.html[Doc]\n";
+        assert_eq!(detect_markup(i), Adoc);
+        assert_eq!(detect_markup(""), Markdown);
+    }
+
     #[test]
     fn test_take_link() {
         let expected = nom::Err::Error(nom::error::Error::new("", nom::error::ErrorKind::Eof));
@@ -711,4 +1440,550 @@ ghi[http://getreu.net](<http://blog.getreu.net>)jkl"#;
         assert_eq!(skipped, "");
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_take_link_lang() {
+        let i = "[![alt1](src1)](dest1)\n.. _label2: dest2\n<a href=\"dest3\">text3</a>\
+                 [https://dest4 text4]{label5}[text5]`text6`_";
+        let langs: Vec<MarkupLang> =
+            std::iter::successors(take_link_lang(i).ok(), |(j, _)| take_link_lang(j).ok())
+                .map(|(_, (_, _, lang))| lang)
+                .collect();
+        assert_eq!(
+            langs,
+            [
+                MarkupLang::Markdown,
+                MarkupLang::Rst,
+                MarkupLang::Html,
+                MarkupLang::Wikitext,
+                MarkupLang::Adoc,
+                MarkupLang::Rst
+            ]
+        );
+
+        let options = ParserOptions {
+            sphinx_roles: true,
+            ..ParserOptions::default()
+        };
+        let (_, (_, _, lang)) = take_link_lang_with("abc :doc:`path`", &options).unwrap();
+        assert_eq!(lang, MarkupLang::Rst);
+    }
+
+    #[test]
+    fn test_take_link_org() {
+        let i = "#+TITLE: Notes\n  #+name: tab\nSee [[tab][the table]], <<a b>> and \
+                 [[https://c][d]] [[./e.org]] <<<f>>> [[*g]] [x](y)";
+        let links = |markup_langs| {
+            let options = ParserOptions {
+                markup_langs,
+                ..ParserOptions::default()
+            };
+            let mut links = Vec::new();
+            let mut i = i;
+            while let Ok((j, (_, link, lang))) = take_link_lang_with(i, &options) {
+                links.push((link, lang));
+                i = j;
+            }
+            links
+        };
+        let c = Cow::from;
+        let org = |link| (link, MarkupLang::Org);
+        assert_eq!(
+            links(MarkupLangs::ALL),
+            [
+                org(Link::Label2Dest(c("tab"), c("#tab"), c(""))),
+                org(Link::Text2Label(c("the table"), c("tab"))),
+                org(Link::Label2Dest(c("a b"), c("#a b"), c(""))),
+                org(Link::Text2Dest(c("d"), c("https://c"), c(""))),
+                org(Link::Text2Dest(c("./e.org"), c("./e.org"), c(""))),
+                org(Link::Label2Dest(c("f"), c("#f"), c(""))),
+                org(Link::Text2Label(c("*g"), c("*g"))),
+                (Link::Text2Dest(c("x"), c("y"), c("")), MarkupLang::Markdown),
+            ]
+        );
+        // Org-mode is not recognized by default.
+        assert!(links(MarkupLangs::default())
+            .iter()
+            .all(|(_, lang)| *lang != MarkupLang::Org));
+    }
+
+    #[test]
+    fn test_take_link_wiki_dialect() {
+        let i = "[[a|b]] {{c|d}} {e}[f] [https://g h]";
+        let links = |wiki_dialect| {
+            let options = ParserOptions {
+                wiki_dialect,
+                ..ParserOptions::default()
+            };
+            let mut j = i;
+            let mut links = Vec::new();
+            while let Ok((k, (_, link, lang))) = take_link_lang_with(j, &options) {
+                links.push((link, lang));
+                j = k;
+            }
+            links
+        };
+        assert_eq!(
+            links(WikiDialect::Creole),
+            [
+                (
+                    Link::Text2Dest(Cow::from("b"), Cow::from("a"), Cow::from("")),
+                    MarkupLang::Wikitext
+                ),
+                (
+                    Link::Image(Cow::from("d"), Cow::from("c")),
+                    MarkupLang::Wikitext
+                ),
+                (
+                    Link::Text2Label(Cow::from("f"), Cow::from("e")),
+                    MarkupLang::Adoc
+                ),
+                // Asciidoc's bare URL.
+                (
+                    Link::Text2Dest(
+                        Cow::from("https://g"),
+                        Cow::from("https://g"),
+                        Cow::from("")
+                    ),
+                    MarkupLang::Adoc
+                ),
+            ]
+        );
+        let links = links(WikiDialect::MediaWiki);
+        assert!(links
+            .iter()
+            .all(|(link, _)| !matches!(link, Link::Image(_, _))));
+        assert_eq!(
+            links.last().unwrap().0,
+            Link::Text2Dest(Cow::from("h"), Cow::from("https://g"), Cow::from(""))
+        );
+    }
+
+    #[test]
+    fn test_take_link_wikitext_free_url() {
+        let options = ParserOptions {
+            markup_langs: MarkupLangs::empty().with(MarkupLang::Wikitext),
+            ..ParserOptions::default()
+        };
+        let i = "See https://a.org/x_(y). and (https://b.org), [https://c d]";
+        let mut links = Vec::new();
+        let mut j = i;
+        while let Ok((k, (_, link, lang))) = take_link_lang_with(j, &options) {
+            assert_eq!(lang, MarkupLang::Wikitext);
+            links.push(link);
+            j = k;
+        }
+        let c = Cow::from;
+        assert_eq!(
+            links,
+            [
+                Link::Text2Dest(c("https://a.org/x_(y)"), c("https://a.org/x_(y)"), c("")),
+                Link::Text2Dest(c("https://b.org"), c("https://b.org"), c("")),
+                Link::Text2Dest(c("d"), c("https://c"), c("")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_take_link_url_schemes() {
+        let i = "file:///a[b] <file:///c> [file:///d e] <mailto:f@g> <h@i>";
+        let dests = |url_schemes| {
+            let options = ParserOptions {
+                url_schemes,
+                ..ParserOptions::default()
+            };
+            let mut j = i;
+            let mut dests = Vec::new();
+            while let Ok((k, (_, link))) = take_link_with(j, &options) {
+                // Unrecognized URLs leave Markdown reference links behind.
+                dests.extend(link.dest().map(str::to_string));
+                j = k;
+            }
+            dests
+        };
+        assert_eq!(
+            dests(UrlSchemes::Default),
+            ["file:///c", "mailto:f@g", "mailto:h@i"]
+        );
+        assert_eq!(
+            dests(UrlSchemes::Any),
+            [
+                "file:///a",
+                "file:///c",
+                "file:///d",
+                "mailto:f@g",
+                "mailto:h@i"
+            ]
+        );
+        assert_eq!(
+            dests(UrlSchemes::Only(&["FILE"])),
+            ["file:///a", "file:///c", "file:///d"]
+        );
+    }
+
+    #[test]
+    fn test_take_link_gfm() {
+        let i = "www.a.b (www.c.d) **https://e.f/g**, h@i.j.\n[k](www.l.m)";
+        let links = |gfm| {
+            let options = ParserOptions {
+                gfm,
+                markup_langs: MarkupLangs::empty().with(MarkupLang::Markdown),
+                ..ParserOptions::default()
+            };
+            let mut j = i;
+            let mut links = Vec::new();
+            while let Ok((k, (skipped, link))) = take_link_with(j, &options) {
+                links.push((skipped, link.dest().unwrap_or_default().to_string()));
+                j = k;
+            }
+            links
+        };
+        assert_eq!(
+            links(true),
+            [
+                ("", "http://www.a.b".to_string()),
+                (" (", "http://www.c.d".to_string()),
+                (") **", "https://e.f/g".to_string()),
+                ("**, ", "mailto:h@i.j".to_string()),
+                (".\n", "www.l.m".to_string()),
+            ]
+        );
+        assert_eq!(
+            links(false),
+            [(
+                "www.a.b (www.c.d) **https://e.f/g**, h@i.j.\n",
+                "www.l.m".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_take_link_bare_uri_schemes() {
+        let i = "(tel:1) <sms:2> <a href=\"geo:3,4\">x</a> [y](tel:5)\ngeo:6,7.";
+        let dests = |bare_uri_schemes| {
+            let options = ParserOptions {
+                bare_uri_schemes,
+                ..ParserOptions::default()
+            };
+            let mut j = i;
+            let mut dests = Vec::new();
+            while let Ok((k, (_, link))) = take_link_with(j, &options) {
+                dests.extend(link.dest().map(str::to_string));
+                j = k;
+            }
+            dests
+        };
+        assert_eq!(
+            dests(&CONTACT_SCHEMES),
+            ["tel:1", "sms:2", "geo:3,4", "tel:5", "geo:6,7"]
+        );
+        assert_eq!(dests(&[]), ["sms:2", "geo:3,4", "tel:5"]);
+    }
+
+    #[test]
+    fn test_take_link_md_link_attributes() {
+        let i = "[a](b){.c} ![d](e){#f} [g]{h=i} {j=k}";
+        let links = |md_link_attributes| {
+            let options = ParserOptions {
+                md_link_attributes,
+                ..ParserOptions::default()
+            };
+            let mut j = i;
+            let mut links = Vec::new();
+            while let Ok((k, (skipped, link))) = take_link_with(j, &options) {
+                links.push((skipped, &j[skipped.len()..j.len() - k.len()], link));
+                j = k;
+            }
+            links
+        };
+        let links_on = links(true);
+        assert_eq!(
+            links_on
+                .iter()
+                .map(|(s, c, _)| (*s, *c))
+                .collect::<Vec<_>>(),
+            [
+                ("", "[a](b){.c}"),
+                (" ", "![d](e){#f}"),
+                (" ", "[g]{h=i}"),
+                (" ", "{j=k}")
+            ]
+        );
+        assert_eq!(
+            links_on[0].2,
+            Link::Text2Dest(Cow::from("a"), Cow::from("b"), Cow::from(""))
+        );
+        // Without the option, the blocks are mistaken for Asciidoc references.
+        let options = ParserOptions {
+            md_link_attributes: false,
+            ..ParserOptions::default()
+        };
+        let (j, (_, link)) = take_link_with(i, &options).unwrap();
+        assert_eq!(
+            link,
+            Link::Text2Dest(Cow::from("a"), Cow::from("b"), Cow::from(""))
+        );
+        assert_eq!(j, "{.c} ![d](e){#f} [g]{h=i} {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from(""), Cow::from(".c")));
+        assert_eq!(j, " ![d](e){#f} [g]{h=i} {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Image(Cow::from("d"), Cow::from("e")));
+        assert_eq!(j, "{#f} [g]{h=i} {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from(""), Cow::from("#f")));
+        assert_eq!(j, " [g]{h=i} {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from("g"), Cow::from("g")));
+        assert_eq!(j, "{h=i} {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from(""), Cow::from("h=i")));
+        assert_eq!(j, " {j=k}");
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from(""), Cow::from("j=k")));
+        assert_eq!(j, "");
+    }
+
+    #[test]
+    fn test_take_link_plain_text() {
+        let options = ParserOptions {
+            link_text: LinkText::Plain,
+            ..ParserOptions::default()
+        };
+        let i = r"[**bold** ![alt](img) text](dest)[\*a\* _b_][c]<http://d>[<i>e</i>](f)";
+
+        let (j, (_, link)) = take_link(i).unwrap();
+        assert_eq!(
+            link,
+            Link::Image2Dest(
+                Cow::from("**bold** "),
+                Cow::from("alt"),
+                Cow::from("img"),
+                Cow::from(" text"),
+                Cow::from("dest"),
+                Cow::from("")
+            )
+        );
+        let (_, (_, link)) = take_link(j).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from("*a* _b_"), Cow::from("c")));
+
+        let (j, (_, link)) = take_link_with(i, &options).unwrap();
+        assert_eq!(
+            link,
+            Link::Image2Dest(
+                Cow::from("bold "),
+                Cow::from("alt"),
+                Cow::from("img"),
+                Cow::from(" text"),
+                Cow::from("dest"),
+                Cow::from("")
+            )
+        );
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(link, Link::Text2Label(Cow::from("*a* b"), Cow::from("c")));
+        let (j, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(
+            link,
+            Link::Text2Dest(Cow::from("http://d"), Cow::from("http://d"), Cow::from(""))
+        );
+        let (_, (_, link)) = take_link_with(j, &options).unwrap();
+        assert_eq!(
+            link,
+            Link::Text2Dest(Cow::from("e"), Cow::from("f"), Cow::from(""))
+        );
+    }
+
+    #[test]
+    fn test_take_link_bytes() {
+        // Valid input behaves like `take_link()`.
+        let i = "abc [a](b) éé `c <d>`_";
+        let (j, (skipped, link)) = take_link(i).unwrap();
+        let (k, (skipped_bytes, link_bytes)) = take_link_bytes(i.as_bytes()).unwrap();
+        assert_eq!(k, j.as_bytes());
+        assert_eq!(skipped_bytes, skipped.as_bytes());
+        assert_eq!(link_bytes, link);
+
+        // A link interrupted by an invalid byte is skipped.
+        let i = b"[a](\xffb) \xe2\x82 <c@d.e>\xe2\x82";
+        let (j, (skipped, link)) = take_link_bytes(i).unwrap();
+        assert_eq!(skipped, b"[a](\xffb) \xe2\x82 ");
+        assert_eq!(
+            link,
+            Link::Text2Dest(Cow::from("c@d.e"), Cow::from("mailto:c@d.e"), Cow::from(""))
+        );
+        assert_eq!(j, b"\xe2\x82");
+        assert!(take_link_bytes(j).is_err());
+        assert!(take_link_bytes(b"").is_err());
+    }
+
+    #[test]
+    fn test_needs_allocation() {
+        let borrowed = [
+            "[text](dest \"title\")",
+            "![alt](src)",
+            "[text][label]",
+            "[label]: dest",
+            "`text<dest>`__",
+            "https://dest[text]",
+            "<a href=\"dest\">text</a>",
+            "[https://dest text]",
+        ];
+        for i in borrowed {
+            assert!(!needs_allocation(i), "{}", i);
+            assert!(take_link(i).unwrap().1 .1.is_borrowed(), "{}", i);
+        }
+
+        let owned = [
+            "[te\\_xt](dest)",
+            "<a href=\"d&amp;est\">text</a>",
+            "link:https://d%20est[text]",
+            "<a@b.c>",
+            "`text <de st>`__",
+        ];
+        for i in owned {
+            assert!(needs_allocation(i), "{}", i);
+            assert!(!take_link(i).unwrap().1 .1.is_borrowed(), "{}", i);
+        }
+    }
+
+    /// Pieces of link syntax, the inputs of `proptest_needs_allocation` are
+    /// made of.
+    const SYNTAX: [&str; 51] = [
+        "[",
+        "]",
+        "(",
+        ")",
+        "<",
+        ">",
+        "`",
+        "_",
+        "__",
+        ":",
+        ".. ",
+        "![",
+        "image:",
+        "image::",
+        "link:",
+        "https://",
+        "www.",
+        "a",
+        "b-c",
+        "d_e",
+        " ",
+        "\n",
+        "\\",
+        "&amp;",
+        "%20",
+        "@",
+        "<a href=\"",
+        "\">",
+        "</a>",
+        "<b>",
+        "</b>",
+        "<img src=\"",
+        "{",
+        "}",
+        "|",
+        "\"",
+        "*",
+        "~",
+        "\u{2028}",
+        "é",
+        "tel:",
+        ".png",
+        "[[",
+        "]]",
+        "{{",
+        "}}",
+        "#",
+        "=",
+        "<<",
+        ">>",
+        "#+NAME: ",
+    ];
+
+    /// Strategy generating all combinations of `ParserOptions`.
+    fn parser_options() -> impl proptest::strategy::Strategy<Value = ParserOptions> {
+        use crate::parser::MarkupLang::*;
+        use proptest::prelude::*;
+        use proptest::sample::select;
+        (
+            any::<bool>(),
+            select(&[LinkText::Raw, LinkText::Plain][..]),
+            proptest::sample::subsequence(&[Markdown, Rst, Adoc, Html, Wikitext, Org][..], 1..=6),
+            select(
+                &[
+                    WikiDialect::MediaWiki,
+                    WikiDialect::Creole,
+                    WikiDialect::DokuWiki,
+                ][..],
+            ),
+            select(
+                &[
+                    UrlSchemes::Default,
+                    UrlSchemes::Any,
+                    UrlSchemes::Only(&["ftp", "tel"]),
+                ][..],
+            ),
+            any::<bool>(),
+            any::<bool>(),
+            select(vec![&[][..], &CONTACT_SCHEMES[..]]),
+        )
+            .prop_map(
+                |(
+                    sphinx_roles,
+                    link_text,
+                    markup_langs,
+                    wiki_dialect,
+                    url_schemes,
+                    md_link_attributes,
+                    gfm,
+                    bare_uri_schemes,
+                )| ParserOptions {
+                    sphinx_roles,
+                    link_text,
+                    markup_langs: markup_langs.into_iter().collect(),
+                    wiki_dialect,
+                    url_schemes,
+                    md_link_attributes,
+                    gfm,
+                    bare_uri_schemes,
+                },
+            )
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn proptest_needs_allocation(
+            pieces in proptest::collection::vec(proptest::sample::select(&SYNTAX[..]), 0..16),
+            options in parser_options(),
+        ) {
+            let input = pieces.concat();
+            let mut i = input.as_str();
+            while let Ok((j, (skipped, link))) = take_link_with(i, &options) {
+                let consumed = &i[skipped.len()..i.len() - j.len()];
+                proptest::prop_assert!(
+                    link.is_borrowed() || needs_allocation_with(consumed, &options),
+                    "unexpected allocation for {:?} in {:?} with {:?}",
+                    link,
+                    consumed,
+                    options
+                );
+                i = j;
+            }
+        }
+    }
+
+    #[test]
+    fn test_needs_allocation_plain() {
+        let options = ParserOptions {
+            link_text: LinkText::Plain,
+            ..ParserOptions::default()
+        };
+        for i in ["[x![a](b)]", "[*x*](d)", "[x *y*][l]"] {
+            let (_, (_, link)) = take_link_with(i, &options).unwrap();
+            assert!(!link.is_borrowed(), "{}", i);
+            assert!(needs_allocation_with(i, &options), "{}", i);
+        }
+    }
 }
@@ -2,13 +2,19 @@
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::generic_error;
 use crate::parser::parse::LABEL_LEN_MAX;
 use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::combinator::*;
+use nom::error::VerboseError;
 use nom::IResult;
-use std::borrow::Cow;
 
 /// Character that can be escaped with `\`.
 ///
@@ -18,8 +24,21 @@ const ESCAPABLE: &str = r#" `:<>_\"#;
 
 /// Wrapper around `rst_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn rst_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = rst_text2dest(i)?;
+pub fn rst_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_text2dest_link_generic(i)
+}
+
+/// Same as `rst_text2dest_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn rst_text2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_text2dest_link", rst_text2dest_link_generic)(i)
+}
+
+/// Same as `rst_text2dest_link()`, but generic over the error type.
+fn rst_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = rst_text2dest_generic(i)?;
     Ok((i, Link::Text2Dest(te, de, ti)))
 }
 
@@ -46,18 +65,42 @@ pub fn rst_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 /// ```
 /// The bracketed URI must be preceded by whitespace and be the last text
 /// before the end string.
-pub fn rst_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
-    let (i, (ln, ld)) = rst_parse_text2target(true, false)(i)?;
-    let ln = rst_escaped_link_text_transform(ln)?.1;
-    let ld = rst_escaped_link_destination_transform(ld)?.1;
+pub fn rst_text2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_text2dest_generic(i)
+}
+
+/// Same as `rst_text2dest()`, but generic over the error type.
+fn rst_text2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (ln, ld)) = generic_error(rst_parse_text2target(true, false))(i)?;
+    let ln = generic_error(rst_escaped_link_text_transform)(ln)?.1;
+    let ld = generic_error(rst_escaped_link_destination_transform)(ld)?.1;
+    // Without link text, the URI is shown.
+    let ln = if ln.is_empty() { ld.clone() } else { ln };
 
     Ok((i, (ln, ld, Cow::Borrowed(""))))
 }
 
 /// Wrapper around `rst_textlabel2dest()` that packs the result in
 /// `Link::TextLabel2Dest`.
-pub fn rst_text_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = rst_text_label2dest(i)?;
+pub fn rst_text_label2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_text_label2dest_link_generic(i)
+}
+
+/// Same as `rst_text_label2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_text_label2dest_link_verbose(
+    i: &str,
+) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_text_label2dest_link", rst_text_label2dest_link_generic)(i)
+}
+
+/// Same as `rst_text_label2dest_link()`, but generic over the error type.
+fn rst_text_label2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = rst_text_label2dest_generic(i)?;
     Ok((i, Link::TextLabel2Dest(te, de, ti)))
 }
 
@@ -84,10 +127,27 @@ pub fn rst_text_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
 /// ```
 /// The bracketed URI must be preceded by whitespace and be the last text
 /// before the end string.
-pub fn rst_text_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
-    let (i, (ln, ld)) = rst_parse_text2target(false, false)(i)?;
-    let ln = rst_escaped_link_text_transform(ln)?.1;
-    let ld = rst_escaped_link_destination_transform(ld)?.1;
+///
+/// As in docutils, whitespace in the URI, e.g. when it spans lines, is
+/// removed, an escaped space `\ ` is kept. A URI ending with `_` must escape
+/// it as `\_`, otherwise it is an alias, see `rst_text2label()`. Without
+/// link text, e.g. `` `<http://www.python.org>`_ ``, the link text is the
+/// URI.
+pub fn rst_text_label2dest(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_text_label2dest_generic(i)
+}
+
+/// Same as `rst_text_label2dest()`, but generic over the error type.
+fn rst_text_label2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (ln, ld)) = generic_error(rst_parse_text2target(false, false))(i)?;
+    let ln = generic_error(rst_escaped_link_text_transform)(ln)?.1;
+    let ld = generic_error(rst_escaped_link_destination_transform)(ld)?.1;
+    // Without link text, the URI is shown.
+    let ln = if ln.is_empty() { ld.clone() } else { ln };
 
     Ok((i, (ln, ld, Cow::Borrowed(""))))
 }
@@ -133,12 +193,17 @@ fn rst_parse_text2target(
         };
 
         // From here on, we only deal with the inner result of the above.
-        // Take everything until the first unescaped `<`
-        let (inner_rest, link_text): (&str, &str) = nom::bytes::complete::escaped(
-            nom::character::complete::none_of(r#"\<"#),
-            '\\',
-            nom::character::complete::one_of(ESCAPABLE),
-        )(inner)?;
+        // Take everything until the first unescaped `<`. The link text may
+        // be empty.
+        let (inner_rest, link_text): (&str, &str) = if inner.starts_with('<') {
+            (inner, "")
+        } else {
+            nom::bytes::complete::escaped(
+                nom::character::complete::none_of(r#"\<"#),
+                '\\',
+                nom::character::complete::one_of(ESCAPABLE),
+            )(inner)?
+        };
         // Trim trailing whitespace.
         let link_text = link_text.trim_end();
 
@@ -161,9 +226,10 @@ fn rst_parse_text2target(
         // Fail if `link_dest_label` is empty.
         let (_, _) = nom::combinator::not(nom::combinator::eof)(link_dest_label)?;
 
-        // Get last char.
-        let last_char_is_ = link_dest_label.is_char_boundary(link_dest_label.len() - 1)
-            && &link_dest_label[link_dest_label.len() - 1..] == "_";
+        // Is the last char an unescaped `_`? An escaped `\_` ends a URI.
+        let last_char_is_ = link_dest_label
+            .strip_suffix('_')
+            .is_some_and(|s| (s.len() - s.trim_end_matches('\\').len()) % 2 == 0);
         // If (`label==true`), we expect trailing `_`, fail otherwise.
         // If (`label==false`), we fail when there is a trailing `_`.
         if (label && !last_char_is_) || (!label && last_char_is_) {
@@ -174,7 +240,7 @@ fn rst_parse_text2target(
         };
         // When label, strip trailing `_`.
         if label {
-            link_dest_label = &link_dest_label[..link_dest_label.len() - 1];
+            link_dest_label = link_dest_label.strip_suffix('_').unwrap_or(link_dest_label);
         };
 
         Ok((i, (link_text, link_dest_label)))
@@ -183,8 +249,21 @@ fn rst_parse_text2target(
 
 /// Wrapper around `rst_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn rst_text2label_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, la)) = rst_text2label(i)?;
+pub fn rst_text2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_text2label_link_generic(i)
+}
+
+/// Same as `rst_text2label_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_text2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_text2label_link", rst_text2label_link_generic)(i)
+}
+
+/// Same as `rst_text2label_link()`, but generic over the error type.
+fn rst_text2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, la)) = rst_text2label_generic(i)?;
     Ok((i, Link::Text2Label(te, la)))
 }
 
@@ -217,10 +296,31 @@ pub fn rst_text2label_link(i: &str) -> nom::IResult<&str, Link> {
 /// );
 /// ```
 ///
-pub fn rst_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
-    let (i, (te, la)) = rst_parse_text2label(i)?;
-    let te = rst_escaped_link_text_transform(te)?.1;
-    let la = rst_escaped_link_text_transform(la)?.1;
+/// An embedded alias `` `link text <link label_>`_ `` or
+/// `` `link text <link label_>`__ `` is distinguished from an embedded URI by
+/// its trailing unescaped `_`. Whitespace in the alias, e.g. when it spans
+/// lines, is folded into single spaces. Without link text, the link text is
+/// the alias.
+///
+pub fn rst_text2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    rst_text2label_generic(i)
+}
+
+/// Same as `rst_text2label()`, but generic over the error type.
+fn rst_text2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (te, la)) = generic_error(rst_parse_text2label)(i)?;
+    let te = generic_error(rst_escaped_link_text_transform)(te)?.1;
+    let la = generic_error(rst_escaped_link_text_transform)(la)?.1;
+    // Labels spanning lines are whitespace normalized.
+    let la = if la.contains(|c: char| c.is_whitespace() && c != ' ') || la.contains("  ") {
+        Cow::Owned(la.split_whitespace().collect::<Vec<_>>().join(" "))
+    } else {
+        la
+    };
+    // Without link text, the alias is shown.
+    let te = if te.is_empty() { la.clone() } else { te };
 
     Ok((i, (te, la)))
 }
@@ -252,6 +352,11 @@ pub fn rst_text2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
 /// without consuming it.
 ///
 fn rst_parse_text2label(i: &str) -> nom::IResult<&str, (&str, &str)> {
+    // An anonymous reference with embedded alias refers to the alias.
+    if let Ok((i, r)) = rst_parse_text2target(true, true)(i) {
+        return Ok((i, r));
+    }
+
     let (mut i, (link_text, mut link_label)) = alt((
         rst_parse_text2target(false, true),
         nom::combinator::map(rst_parse_simple_label, |s| (s, s)),
@@ -266,10 +371,280 @@ fn rst_parse_text2label(i: &str) -> nom::IResult<&str, (&str, &str)> {
     Ok((i, (link_text, link_label)))
 }
 
+/// Wrapper around `rst_substitution2label()` that packs the result in
+/// `Link::Text2Label`.
+pub fn rst_substitution2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_substitution2label_link_generic(i)
+}
+
+/// Same as `rst_substitution2label_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_substitution2label_link_verbose(
+    i: &str,
+) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context(
+        "rst_substitution2label_link",
+        rst_substitution2label_link_generic,
+    )(i)
+}
+
+/// Same as `rst_substitution2label_link()`, but generic over the error type.
+fn rst_substitution2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, la)) = rst_substitution2label_generic(i)?;
+    Ok((i, Link::Text2Label(te, la)))
+}
+
+/// Parse a RestructuredText _substitution reference_ used as _reference
+/// link_: `|name|_`, or anonymous `|name|__`. Sphinx documents use them
+/// e.g. for badges linking to some destination.
+///
+/// The caller must guarantee the same as for `rst_text2label()`.
+///
+/// Returns `(link_text, link_label)`. `link_text` is the substitution name,
+/// the substituted content is not inserted. `link_label` is the name as well,
+/// or `_` for anonymous references.
+/// ```rust
+/// use parse_hyperlinks::parser::restructured_text::rst_substitution2label;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   rst_substitution2label("|build status|_ abc"),
+///   Ok((" abc", (Cow::from("build status"), Cow::from("build status"))))
+/// );
+/// assert_eq!(
+///   rst_substitution2label("|badge|__ abc"),
+///   Ok((" abc", (Cow::from("badge"), Cow::from("_"))))
+/// );
+/// ```
+pub fn rst_substitution2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    rst_substitution2label_generic(i)
+}
+
+/// Same as `rst_substitution2label()`, but generic over the error type.
+fn rst_substitution2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, name) = nom::sequence::delimited(
+        tag("|"),
+        generic_error(rst_parse_substitution_name),
+        tag("|_"),
+    )(i)?;
+    let (i, anonymous) = opt(nom::character::complete::char('_'))(i)?;
+    // The reference must not be followed by a word character.
+    let _ = not(nom::character::complete::satisfy(|c| {
+        c.is_alphanumeric() || c == '_'
+    }))(i)?;
+
+    let label = if anonymous.is_some() { "_" } else { name };
+    Ok((i, (Cow::Borrowed(name), Cow::Borrowed(label))))
+}
+
+/// Parses the name of a substitution between the enclosing `|`. The name
+/// must not start or end with whitespace.
+fn rst_parse_substitution_name(i: &str) -> nom::IResult<&str, &str> {
+    verify(nom::bytes::complete::is_not("|\n\r"), |s: &str| {
+        !s.starts_with(char::is_whitespace)
+            && !s.ends_with(char::is_whitespace)
+            && s.len() <= LABEL_LEN_MAX
+    })(i)
+}
+
+/// Sphinx roles producing inline formatting rather than cross-references.
+/// `rst_role2target()` does not recognize them as links.
+const SPHINX_NON_REFERENCE_ROLES: [&str; 30] = [
+    "ab",
+    "abbr",
+    "abbreviation",
+    "ac",
+    "acronym",
+    "code",
+    "command",
+    "dfn",
+    "emphasis",
+    "file",
+    "guilabel",
+    "kbd",
+    "literal",
+    "mailheader",
+    "makevar",
+    "math",
+    "menuselection",
+    "mimetype",
+    "newsgroup",
+    "program",
+    "raw",
+    "regexp",
+    "samp",
+    "strong",
+    "sub",
+    "subscript",
+    "sup",
+    "superscript",
+    "t",
+    "title-reference",
+];
+
+/// Wrapper around `rst_role2target()` that packs the result in
+/// `Link::Role2Target`.
+pub fn rst_role2target_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_role2target_link_generic(i)
+}
+
+/// Same as `rst_role2target_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_role2target_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_role2target_link", rst_role2target_link_generic)(i)
+}
+
+/// Same as `rst_role2target_link()`, but generic over the error type.
+fn rst_role2target_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (ro, te, ta)) = rst_role2target_generic(i)?;
+    Ok((i, Link::Role2Target(ro, te, ta)))
+}
+
+/// Parse a Sphinx _cross-reference_ written as reStructuredText
+/// interpreted text with a role prefix.
+///
+/// The parser returns `(role, link_text, link_target)`. When no explicit
+/// title is given, `link_text` is empty, indicating that Sphinx derives
+/// the visible text from the target.
+/// ```
+/// use parse_hyperlinks::parser::restructured_text::rst_role2target;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   rst_role2target(":doc:`path/to/doc`abc"),
+///   Ok(("abc", (Cow::from("doc"), Cow::from(""), Cow::from("path/to/doc"))))
+/// );
+/// assert_eq!(
+///   rst_role2target(":ref:`Link text <my-label>`abc"),
+///   Ok(("abc", (Cow::from("ref"), Cow::from("Link text"), Cow::from("my-label"))))
+/// );
+/// assert_eq!(
+///   rst_role2target(":external+proj:ref:`x`abc"),
+///   Ok(("abc", (Cow::from("external+proj:ref"), Cow::from(""), Cow::from("x"))))
+/// );
+/// ```
+/// Role names consist of alphanumerics plus isolated internal hyphens,
+/// underscores, plus signs, colons and periods. Roles that only format
+/// text, e.g. `` :math:`x` ``, are not recognized. Neither are targets
+/// prefixed with `!`, which Sphinx renders without creating a reference.
+/// A `~` prefix is stripped from the target.
+///
+/// The caller must guarantee, that either:
+/// * we are at the input start -or-
+/// * the byte just before was a whitespace (including newline)!
+pub fn rst_role2target(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_role2target_generic(i)
+}
+
+/// Same as `rst_role2target()`, but generic over the error type.
+fn rst_role2target_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (role, text, target)) = generic_error(rst_parse_role2target)(i)?;
+    let text = generic_error(rst_escaped_link_text_transform)(text)?.1;
+    let target = generic_error(rst_escaped_link_text_transform)(target)?.1;
+
+    Ok((i, (Cow::Borrowed(role), text, target)))
+}
+
+/// This parser recognizes interpreted text with a role prefix:
+///     :role:`target`
+/// or:
+///     :role:`text <target>`
+///
+/// Escape sequences are recognized and skipped, but not replaced here.
+fn rst_parse_role2target(i: &str) -> nom::IResult<&str, (&str, &str, &str)> {
+    let (i, role) = nom::sequence::delimited(
+        tag(":"),
+        nom::combinator::recognize(nom::multi::separated_list1(
+            nom::character::complete::one_of("-_+:."),
+            nom::character::complete::alphanumeric1,
+        )),
+        tag(":"),
+    )(i)?;
+
+    if SPHINX_NON_REFERENCE_ROLES.contains(&role) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            i,
+            nom::error::ErrorKind::Tag,
+        )));
+    };
+
+    let (i, inner) = nom::sequence::delimited(
+        tag("`"),
+        nom::bytes::complete::escaped(
+            nom::character::complete::none_of(r#"\`"#),
+            '\\',
+            nom::character::complete::one_of(ESCAPABLE),
+        ),
+        tag("`"),
+    )(i)?;
+
+    // Assure that the next char is not`_`: this would be a hyperlink reference.
+    if !i.is_empty() {
+        let _ = nom::combinator::not(nom::character::complete::char('_'))(i)?;
+    };
+
+    // `!` suppresses the reference.
+    let _ = nom::combinator::not(nom::character::complete::char('!'))(inner)?;
+
+    // Is there an explicit title: `text <target>`?
+    let explicit_title: IResult<&str, (&str, &str)> = nom::sequence::pair(
+        nom::bytes::complete::escaped(
+            nom::character::complete::none_of(r#"\<"#),
+            '\\',
+            nom::character::complete::one_of(ESCAPABLE),
+        ),
+        nom::sequence::terminated(
+            nom::sequence::delimited(
+                tag("<"),
+                nom::bytes::complete::escaped(
+                    nom::character::complete::none_of(r#"\<>"#),
+                    '\\',
+                    nom::character::complete::one_of(ESCAPABLE),
+                ),
+                tag(">"),
+            ),
+            nom::combinator::eof,
+        ),
+    )(inner);
+    let (text, target) = if let Ok((_, (text, target))) = explicit_title {
+        (text.trim_end(), target)
+    } else {
+        ("", inner)
+    };
+
+    let target = target.strip_prefix('~').unwrap_or(target);
+
+    // Fail if `target` is empty.
+    let _ = nom::combinator::not(nom::combinator::eof)(target)?;
+
+    Ok((i, (role, text, target)))
+}
+
 /// Wrapper around `rst_label2dest()` that packs the result in
 /// `Link::Label2Dest`.
-pub fn rst_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (l, d, t)) = rst_label2dest(i)?;
+pub fn rst_label2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_label2dest_link_generic(i)
+}
+
+/// Same as `rst_label2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_label2dest_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_label2dest_link", rst_label2dest_link_generic)(i)
+}
+
+/// Same as `rst_label2dest_link()`, but generic over the error type.
+fn rst_label2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (l, d, t)) = rst_label2dest_generic(i)?;
     Ok((i, Link::Label2Dest(l, d, t)))
 }
 
@@ -301,15 +676,35 @@ pub fn rst_label2dest_link(i: &str) -> nom::IResult<&str, Link> {
 /// .. _`Python: home page`: http://www.python.org
 /// ```
 /// See unit test `test_rst_label2dest()` for more examples.
-pub fn rst_label2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
-    let (i, (l, d)) = rst_label2target(false, i)?;
+pub fn rst_label2dest(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_label2dest_generic(i)
+}
+
+/// Same as `rst_label2dest()`, but generic over the error type.
+fn rst_label2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let (i, (l, d)) = generic_error(|i| rst_label2target(false, i))(i)?;
     Ok((i, (l, d, Cow::from(""))))
 }
 
 /// Wrapper around `rst_label2label()` that packs the result in
 /// `Link::Label2Label`.
-pub fn rst_label2label_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (l1, l2)) = rst_label2label(i)?;
+pub fn rst_label2label_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_label2label_link_generic(i)
+}
+
+/// Same as `rst_label2label_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_label2label_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_label2label_link", rst_label2label_link_generic)(i)
+}
+
+/// Same as `rst_label2label_link()`, but generic over the error type.
+fn rst_label2label_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (l1, l2)) = rst_label2label_generic(i)?;
     Ok((i, Link::Label2Label(l1, l2)))
 }
 
@@ -325,6 +720,10 @@ pub fn rst_label2label_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("\nabc", (Cow::from("alt label"), Cow::from("label"))))
 /// );
 /// assert_eq!(
+///   rst_label2label("   .. _alt: `other label`_\nabc"),
+///   Ok(("\nabc", (Cow::from("alt"), Cow::from("other label"))))
+/// );
+/// assert_eq!(
 ///   rst_label2label("   .. __: label_\nabc"),
 ///   Ok(("\nabc", (Cow::from("_"), Cow::from("label"))))
 /// );
@@ -333,14 +732,234 @@ pub fn rst_label2label_link(i: &str) -> nom::IResult<&str, Link> {
 ///   Ok(("\nabc", (Cow::from("_"), Cow::from("label"))))
 /// );
 /// ```
-pub fn rst_label2label(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
-    rst_label2target(true, i)
+pub fn rst_label2label(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
+    rst_label2label_generic(i)
+}
+
+/// Same as `rst_label2label()`, but generic over the error type.
+fn rst_label2label_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(|i| rst_label2target(true, i))(i)
+}
+
+/// Wrapper around `rst_image()` that packs the result in `Link::Image`, or
+/// in `Link::Image2Dest` if the directive has a `:target:` option.
+pub fn rst_image_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_image_link_generic(i)
+}
+
+/// Same as `rst_image_link()`, but with `nom::error::VerboseError` as error
+/// type.
+pub fn rst_image_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("rst_image_link", rst_image_link_generic)(i)
+}
+
+/// Same as `rst_image_link()`, but generic over the error type.
+fn rst_image_link_generic<'a, E: LinkError<'a>>(i: &'a str) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src, dest)) = rst_image_generic(i)?;
+    Ok((i, rst_image2link(alt, src, dest)))
+}
+
+/// Parse a reStructuredText `image` or `figure` directive:
+/// `.. image:: src` or `.. figure:: src`.
+///
+/// This parser expects to start at the beginning of the line and consumes
+/// the whole explicit markup block, including the directive's indented
+/// options. It returns `(image_alt, image_src, link_destination)`:
+/// * `image_alt` is the value of the `:alt:` option, or `image_src`, as
+///   docutils' HTML writer does, if there is none.
+/// * `link_destination` is the value of the `:target:` option, or empty.
+///   Targets referring to a label, e.g. `:target: label_`, are not resolved
+///   and return an empty `link_destination`, too.
+///
+/// The caption and legend of a figure are not consumed.
+/// ```
+/// use parse_hyperlinks::parser::restructured_text::rst_image;
+/// use std::borrow::Cow;
+///
+/// let i = ".. image:: pic.png\n   :alt: A picture\n   :target: https://dest\nabc";
+/// assert_eq!(
+///   rst_image(i),
+///   Ok(("\nabc", (Cow::from("A picture"), Cow::from("pic.png"), Cow::from("https://dest"))))
+/// );
+/// ```
+pub fn rst_image(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_image_generic(i)
+}
+
+/// Same as `rst_image()`, but generic over the error type.
+fn rst_image_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(|i| rst_image_block(false, i))(i)
+}
+
+/// Wrapper around `rst_substitution_image()` that packs the result in
+/// `Link::Image`, or in `Link::Image2Dest` if the directive has a `:target:`
+/// option.
+pub fn rst_substitution_image_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    rst_substitution_image_link_generic(i)
+}
+
+/// Same as `rst_substitution_image_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn rst_substitution_image_link_verbose(
+    i: &str,
+) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context(
+        "rst_substitution_image_link",
+        rst_substitution_image_link_generic,
+    )(i)
+}
+
+/// Same as `rst_substitution_image_link()`, but generic over the error type.
+fn rst_substitution_image_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (alt, src, dest)) = rst_substitution_image_generic(i)?;
+    Ok((i, rst_image2link(alt, src, dest)))
+}
+
+/// Parse a reStructuredText _substitution definition_ with an `image`
+/// directive: `.. |name| image:: src`. A substitution reference `|name|_`
+/// shows this image as link, see `rst_substitution2label()`.
+///
+/// Same as `rst_image()`, but as in docutils, the _image alt_ text defaults
+/// to the substitution name.
+/// ```
+/// use parse_hyperlinks::parser::restructured_text::rst_substitution_image;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///   rst_substitution_image(".. |badge| image:: https://ci/badge.svg\n   :height: 20\nabc"),
+///   Ok(("\nabc", (Cow::from("badge"), Cow::from("https://ci/badge.svg"), Cow::from(""))))
+/// );
+/// ```
+pub fn rst_substitution_image(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    rst_substitution_image_generic(i)
+}
+
+/// Same as `rst_substitution_image()`, but generic over the error type.
+fn rst_substitution_image_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    generic_error(|i| rst_image_block(true, i))(i)
+}
+
+/// Packs the result of `rst_image()` in `Link::Image` or `Link::Image2Dest`.
+fn rst_image2link<'a>(alt: Cow<'a, str>, src: Cow<'a, str>, dest: Cow<'a, str>) -> Link<'a> {
+    if dest.is_empty() {
+        Link::Image(alt, src)
+    } else {
+        Link::Image2Dest(
+            Cow::Borrowed(""),
+            alt,
+            src,
+            Cow::Borrowed(""),
+            dest,
+            Cow::Borrowed(""),
+        )
+    }
+}
+
+/// Parser for image directives:
+/// * `substitution==false`: `.. image:: src` or `.. figure:: src`,
+/// * `substitution==true`: `.. |name| image:: src`.
+fn rst_image_block(
+    substitution: bool,
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    let (i, block) = rst_explicit_markup_block(".. ")(i)?;
+    let res = match block {
+        Cow::Borrowed(s) => {
+            let (_, (alt, src, dest)) = rst_parse_image(substitution, s)?;
+            (Cow::Borrowed(alt), Cow::Borrowed(src), Cow::Borrowed(dest))
+        }
+        Cow::Owned(strg) => {
+            let (_, (alt, src, dest)) = rst_parse_image(substitution, &strg).map_err(|_| {
+                nom::Err::Error(nom::error::Error::new(i, nom::error::ErrorKind::Tag))
+            })?;
+            (
+                Cow::Owned(alt.to_string()),
+                Cow::Owned(src.to_string()),
+                Cow::Owned(dest.to_string()),
+            )
+        }
+    };
+    Ok((i, res))
+}
+
+/// Parses the content of an image directive block, whose lines are joined
+/// with spaces, and returns `(image_alt, image_src, link_destination)`.
+fn rst_parse_image(substitution: bool, i: &str) -> nom::IResult<&str, (&str, &str, &str)> {
+    let (i, name) = if substitution {
+        let (i, name) = nom::sequence::delimited(
+            tag("|"),
+            rst_parse_substitution_name,
+            nom::sequence::pair(tag("|"), nom::character::complete::space1),
+        )(i)?;
+        let (i, _) = tag("image::")(i)?;
+        (i, Some(name))
+    } else {
+        let (i, _) = alt((tag("image::"), tag("figure::")))(i)?;
+        (i, None)
+    };
+    let (i, src) = nom::sequence::preceded(
+        nom::character::complete::space1,
+        nom::bytes::complete::take_till1(|c: char| c.is_whitespace()),
+    )(i)?;
+
+    let mut image_alt = name.unwrap_or(src);
+    let mut dest = "";
+    for (option, value) in rst_directive_options(i) {
+        match option {
+            "alt" => image_alt = value,
+            "target" if !value.ends_with('_') => dest = value,
+            _ => {}
+        }
+    }
+    Ok(("", (image_alt, src, dest)))
+}
+
+/// Splits the options `:name: value :name2: value2` of a directive block,
+/// whose lines are joined with spaces, into `(name, value)` pairs.
+fn rst_directive_options(i: &str) -> Vec<(&str, &str)> {
+    // True if an option starts at the beginning of `s`.
+    fn is_option(s: &str) -> bool {
+        s.strip_prefix(':')
+            .and_then(|s| s.split_once(':'))
+            .is_some_and(|(name, rest)| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                    && (rest.is_empty() || rest.starts_with(' '))
+            })
+    }
+
+    let mut options = Vec::new();
+    let mut rest = i.trim_start();
+    while is_option(rest) {
+        let (name, value) = rest[1..].split_once(':').unwrap_or_default();
+        // The value ends where the next option starts.
+        let end = value
+            .match_indices(" :")
+            .map(|(n, _)| n)
+            .find(|&n| is_option(&value[n + 1..]))
+            .unwrap_or(value.len());
+        options.push((name, value[..end].trim()));
+        rest = value[end..].trim_start();
+    }
+    options
 }
 
 /// Parser for _link_reference_definitions_:
 /// * `label==false`:  the link is of type `Label2Dest`
 /// * `label==true`: the link is of type `Label2Label`
-fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
     let my_err = |_| {
         nom::Err::Error(nom::error::Error::new(
             i,
@@ -363,19 +982,30 @@ fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
                 rst_parse_label2target(label)(s)?
             } else if label {
                 // This is supposed to be a label.
-                ("", ("_", rst_parse_simple_label(s)?.1))
+                (
+                    "",
+                    (
+                        "_",
+                        nom::combinator::all_consuming(rst_parse_simple_label)(s)?.1,
+                    ),
+                )
             } else {
                 // This is supposed to be a destination (url).
                 ("", ("_", s))
             };
             // If the target is a destination (not a label), the last char must not be `_`.
             if !label {
-                let _ = nom::combinator::not(rst_parse_simple_label)(lt).map_err(my_err)?;
+                let _ = nom::combinator::not(nom::combinator::all_consuming(
+                    rst_parse_simple_label,
+                ))(lt)
+                .map_err(my_err)?;
             };
-            (
-                rst_escaped_link_text_transform(ls)?.1,
-                rst_escaped_link_destination_transform(lt)?.1,
-            )
+            let lt = if label {
+                rst_escaped_link_text_transform(lt)?.1
+            } else {
+                rst_escaped_link_destination_transform(lt)?.1
+            };
+            (rst_escaped_link_text_transform(ls)?.1, lt)
         }
 
         Cow::Owned(strg) => {
@@ -383,7 +1013,9 @@ fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
                 rst_parse_label2target(label)(&strg).map_err(my_err)?
             } else if label {
                 // This is supposed to be a label.
-                let s = rst_parse_simple_label(&strg).map_err(my_err)?.1;
+                let s = nom::combinator::all_consuming(rst_parse_simple_label)(&strg)
+                    .map_err(my_err)?
+                    .1;
                 ("", ("_", s))
             } else {
                 // This is supposed to be a destination (url).
@@ -391,7 +1023,10 @@ fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
             };
             // If the target is a destination (not a label), the last char must not be `_`.
             if !label {
-                let _ = nom::combinator::not(rst_parse_simple_label)(lt).map_err(my_err)?;
+                let _ = nom::combinator::not(nom::combinator::all_consuming(
+                    rst_parse_simple_label,
+                ))(lt)
+                .map_err(my_err)?;
             };
             let ls = Cow::Owned(
                 rst_escaped_link_text_transform(ls)
@@ -399,12 +1034,12 @@ fn rst_label2target(label: bool, i: &str) -> nom::IResult<&str, (Cow<str>, Cow<s
                     .1
                     .to_string(),
             );
-            let lt = Cow::Owned(
+            let lt = if label {
+                rst_escaped_link_text_transform(lt)
+            } else {
                 rst_escaped_link_destination_transform(lt)
-                    .map_err(my_err)?
-                    .1
-                    .to_string(),
-            );
+            };
+            let lt = Cow::Owned(lt.map_err(my_err)?.1.to_string());
             (ls, lt)
         }
     };
@@ -447,7 +1082,7 @@ fn rst_parse_label2target(label: bool) -> impl Fn(&str) -> IResult<&str, (&str,
 
         let link_target = if label {
             // The target is another label.
-            rst_parse_simple_label(i)?.1
+            nom::combinator::all_consuming(rst_parse_simple_label)(i)?.1
         } else {
             // The target is a destination.
             i
@@ -469,18 +1104,19 @@ fn rst_parse_simple_label(i: &str) -> nom::IResult<&str, &str> {
         let (k, mut r) = nom::bytes::complete::take_till1(|c: char| {
             !(c.is_alphanumeric() || c == '-' || c == '_')
         })(i)?;
-        // Is `r` ending with `__`? There should be at least 2 bytes: `"__".len()`
-        if r.len() >= 3 && r.is_char_boundary(r.len() - 2) && &r[r.len() - 2..] == "__" {
-            // Consume one `_`, but keep one `_` in remaining bytes.
-            i = &i[r.len() - 1..];
+        // Is `r` ending with `__`? The label before must not be empty.
+        if let Some(label) = r.strip_suffix("__").filter(|l| !l.is_empty()) {
+            // Consume one `_`, but keep one `_` in remaining input.
+            // `label.len() + 1` is a char boundary, because `_` is ASCII.
+            i = &i[label.len() + 1..];
             // Strip two `__` from result.
-            r = &r[..r.len() - 2];
-        // Is `r` ending with `_`? There should be at least 1 byte: `"_".len()`.
-        } else if !r.is_empty() && r.is_char_boundary(r.len() - 1) && &r[r.len() - 1..] == "_" {
-            // Remaining bytes.
+            r = label;
+        // Is `r` ending with `_`?
+        } else if let Some(label) = r.strip_suffix('_') {
+            // Remaining input.
             i = k;
             // Strip `_` from result.
-            r = &r[..r.len() - 1]
+            r = label;
         } else {
             return Err(nom::Err::Error(nom::error::Error::new(
                 k,
@@ -530,6 +1166,7 @@ fn rst_parse_simple_label(i: &str) -> nom::IResult<&str, &str> {
 /// * whose second and subsequent lines (if any) are indented relative to the
 ///   first, and
 /// * which ends before an unindented line
+///
 /// As with external hyperlink targets, the link block of an indirect
 /// hyperlink target may begin on the same line as the explicit markup start
 /// or the next line. It may also be split over multiple lines, in which case
@@ -582,7 +1219,7 @@ fn rst_explicit_markup_block<'a>(
 /// with:
 ///     \`:<>
 /// Preserves usual whitespace, but removes `\ `.
-fn rst_escaped_link_text_transform(i: &str) -> IResult<&str, Cow<str>> {
+fn rst_escaped_link_text_transform(i: &str) -> IResult<&str, Cow<'_, str>> {
     nom::combinator::map(
         nom::bytes::complete::escaped_transform(
             nom::bytes::complete::is_not("\\"),
@@ -603,7 +1240,7 @@ fn rst_escaped_link_text_transform(i: &str) -> IResult<&str, Cow<str>> {
 }
 
 /// Deletes all whitespace, but keeps one space for each `\ `.
-fn remove_whitespace(i: &str) -> IResult<&str, Cow<str>> {
+fn remove_whitespace(i: &str) -> IResult<&str, Cow<'_, str>> {
     let mut res = Cow::Borrowed("");
     let mut j = i;
     while !j.is_empty() {
@@ -611,7 +1248,7 @@ fn remove_whitespace(i: &str) -> IResult<&str, Cow<str>> {
         let (k, s) = nom::bytes::complete::escaped(
             nom::character::complete::none_of("\\\r\n \t"),
             '\\',
-            nom::character::complete::one_of(r#" :`<>\"#),
+            nom::character::complete::one_of(ESCAPABLE),
         )(k)?;
         res = match res {
             Cow::Borrowed("") => Cow::Borrowed(s),
@@ -635,7 +1272,7 @@ fn remove_whitespace(i: &str) -> IResult<&str, Cow<str>> {
 ///     \\\`\ \:\<\>
 /// with:
 ///     \` :<>
-fn rst_escaped_link_destination_transform(i: &str) -> IResult<&str, Cow<str>> {
+fn rst_escaped_link_destination_transform(i: &str) -> IResult<&str, Cow<'_, str>> {
     let my_err = |_| {
         nom::Err::Error(nom::error::Error::new(
             i,
@@ -666,6 +1303,60 @@ mod tests {
     use super::*;
     use nom::error::ErrorKind;
 
+    #[test]
+    fn test_rst_embedded_uris_and_aliases() {
+        // Escaped trailing underscore: URI, not alias.
+        assert_eq!(
+            rst_text_label2dest("`text <uri\\_>`_ abc"),
+            Ok((
+                " abc",
+                (Cow::from("text"), Cow::from("uri_"), Cow::from(""))
+            ))
+        );
+        // Escaped backslash before the underscore: alias.
+        assert_eq!(
+            rst_text2label("`text <ali\\\\_>`_ abc"),
+            Ok((" abc", (Cow::from("text"), Cow::from("ali\\"))))
+        );
+        // Anonymous reference with embedded alias.
+        assert_eq!(
+            rst_text2label("`text <alias_>`__ abc"),
+            Ok((" abc", (Cow::from("text"), Cow::from("alias"))))
+        );
+        // Multi-line targets.
+        assert_eq!(
+            rst_text2dest("`text <http://a.b/\n   c\\ d>`__ abc"),
+            Ok((
+                " abc",
+                (
+                    Cow::from("text"),
+                    Cow::from("http://a.b/c d"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert_eq!(
+            rst_text2label("`text <multi\n   word  alias_>`_ abc"),
+            Ok((" abc", (Cow::from("text"), Cow::from("multi word alias"))))
+        );
+        // No link text.
+        assert_eq!(
+            rst_text_label2dest("`<http://uri>`_ abc"),
+            Ok((
+                " abc",
+                (
+                    Cow::from("http://uri"),
+                    Cow::from("http://uri"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert_eq!(
+            rst_text2label("`<alias_>`_ abc"),
+            Ok((" abc", (Cow::from("alias"), Cow::from("alias"))))
+        );
+    }
+
     #[test]
     fn test_rst_text2dest() {
         let expected = (
@@ -836,6 +1527,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rst_multibyte_labels() {
+        assert_eq!(
+            rst_text2label("ссылка_ abc"),
+            Ok((" abc", (Cow::from("ссылка"), Cow::from("ссылка"))))
+        );
+        assert_eq!(
+            rst_text2label("链接__ abc"),
+            Ok((" abc", (Cow::from("链接"), Cow::from("_"))))
+        );
+        assert_eq!(
+            rst_text2label("`🦀 крабик`_ abc"),
+            Ok((" abc", (Cow::from("🦀 крабик"), Cow::from("🦀 крабик"))))
+        );
+        assert_eq!(
+            rst_text2dest("`链接 <https://例子.com/ü>`__ abc"),
+            Ok((
+                " abc",
+                (
+                    Cow::from("链接"),
+                    Cow::from("https://例子.com/ü"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert_eq!(
+            rst_text2label("`ссылка <метка_>`_ abc"),
+            Ok((" abc", (Cow::from("ссылка"), Cow::from("метка"))))
+        );
+        assert_eq!(rst_parse_simple_label("é__ abc"), Ok(("_ abc", "é")));
+        // Emoji are not alphanumeric: they need backquotes.
+        assert!(rst_parse_simple_label("🦀_ abc").is_err());
+        assert_eq!(rst_parse_simple_label("`🦀`_ abc"), Ok((" abc", "🦀")));
+    }
+
     #[test]
     fn test_rst_parse_text2label() {
         assert_eq!(
@@ -879,8 +1605,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rst_role2target() {
+        let expected = (
+            " abc",
+            (Cow::from("py:func"), Cow::from(""), Cow::from("a.b.c")),
+        );
+        assert_eq!(rst_role2target(":py:func:`~a.b.c` abc").unwrap(), expected);
+
+        let expected = ("", (Cow::from("ref"), Cow::from("a <b>"), Cow::from("c")));
+        assert_eq!(rst_role2target(r#":ref:`a \<b\> <c>`"#).unwrap(), expected);
+
+        assert_eq!(
+            rst_role2target(":math:`x^2`").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new("`x^2`", ErrorKind::Tag))
+        );
+        assert_eq!(
+            rst_role2target(":ref:`!label`").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new("!label", ErrorKind::Not))
+        );
+        assert_eq!(
+            rst_role2target(":ref:`label`_").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new("_", ErrorKind::Not))
+        );
+        assert_eq!(
+            rst_role2target(":ref:`~`").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new("", ErrorKind::Not))
+        );
+        assert_eq!(
+            rst_role2target("::`label`").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new(":`label`", ErrorKind::AlphaNumeric))
+        );
+        assert_eq!(
+            rst_role2target(":ref-:`label`").unwrap_err(),
+            nom::Err::Error(nom::error::Error::new("-:`label`", ErrorKind::Tag))
+        );
+    }
+
     #[test]
     fn test_rst_label2dest() {
+        // Not a label: the target does not end with `_`.
+        assert_eq!(
+            rst_label2dest(".. _a: b_.\nabc"),
+            Ok(("\nabc", (Cow::from("a"), Cow::from("b_."), Cow::from(""))))
+        );
+        assert!(rst_label2label(".. _a: b_.\nabc").is_err());
+
         let expected = (
             "\nabc",
             (
@@ -998,6 +1768,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rst_substitution2label() {
+        assert_eq!(
+            rst_substitution2label("|name|_"),
+            Ok(("", (Cow::from("name"), Cow::from("name"))))
+        );
+        assert_eq!(
+            rst_substitution2label("|name|__, abc"),
+            Ok((", abc", (Cow::from("name"), Cow::from("_"))))
+        );
+        assert!(rst_substitution2label("|name|").is_err());
+        assert!(rst_substitution2label("|name|_abc").is_err());
+        assert!(rst_substitution2label("| name|_").is_err());
+        assert!(rst_substitution2label("||_").is_err());
+        assert!(rst_substitution2label("|na\nme|_").is_err());
+    }
+
+    #[test]
+    fn test_rst_substitution_image() {
+        assert_eq!(
+            rst_substitution_image_link("  .. |a b| image::\n     img.png\n     :width: 3\nabc"),
+            Ok(("\nabc", Link::Image(Cow::from("a b"), Cow::from("img.png"))))
+        );
+        assert_eq!(
+            rst_substitution_image_link(".. |a| image:: img.png\n   :alt: x\n   :target: dest\n"),
+            Ok((
+                "\n",
+                Link::Image2Dest(
+                    Cow::from(""),
+                    Cow::from("x"),
+                    Cow::from("img.png"),
+                    Cow::from(""),
+                    Cow::from("dest"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert!(rst_substitution_image(".. |a| replace:: text\n").is_err());
+        assert!(rst_substitution_image(".. |a|image:: img.png\n").is_err());
+        assert!(rst_substitution_image(".. image:: img.png\n").is_err());
+    }
+
+    #[test]
+    fn test_rst_image() {
+        assert_eq!(
+            rst_image_link(".. image:: pic.png\n\nabc"),
+            Ok((
+                "\n\nabc",
+                Link::Image(Cow::from("pic.png"), Cow::from("pic.png"))
+            ))
+        );
+        assert_eq!(
+            rst_image(".. figure:: pic.png\n   :alt: a: b\n   :target: ref_\n\n   Caption\n"),
+            Ok((
+                "\n\n   Caption\n",
+                (Cow::from("a: b"), Cow::from("pic.png"), Cow::from(""))
+            ))
+        );
+        assert_eq!(
+            rst_image_link(".. image:: pic.png\n   :scale: 50 %\n   :target: https://x/y:z\n"),
+            Ok((
+                "\n",
+                Link::Image2Dest(
+                    Cow::from(""),
+                    Cow::from("pic.png"),
+                    Cow::from("pic.png"),
+                    Cow::from(""),
+                    Cow::from("https://x/y:z"),
+                    Cow::from("")
+                )
+            ))
+        );
+        assert!(rst_image(".. image::pic.png\n").is_err());
+        assert!(rst_image(".. |a| image:: pic.png\n").is_err());
+        assert!(rst_image(".. _label: pic.png\n").is_err());
+        assert_eq!(
+            rst_directive_options(" :alt: a b :c: :d: e:f"),
+            [("alt", "a b"), ("c", ""), ("d", "e:f")]
+        );
+    }
+
     #[test]
     fn test_rst_label2label() {
         assert_eq!(
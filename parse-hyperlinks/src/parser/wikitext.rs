@@ -2,17 +2,37 @@
 #![allow(dead_code)]
 #![allow(clippy::type_complexity)]
 
+use crate::parser::generic_error;
 use crate::parser::percent_decode;
+use crate::parser::url_start;
 use crate::parser::Link;
+use crate::parser::LinkError;
+use alloc::borrow::Cow;
+use alloc::format;
 use nom::branch::alt;
 use nom::bytes::complete::is_not;
 use nom::bytes::complete::tag;
-use std::borrow::Cow;
+use nom::error::VerboseError;
 
 /// Wrapper around `wikitext_text2dest()` that packs the result in
 /// `Link::Text2Dest`.
-pub fn wikitext_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
-    let (i, (te, de, ti)) = wikitext_text2dest(i)?;
+pub fn wikitext_text2dest_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wikitext_text2dest_link_generic(i)
+}
+
+/// Same as `wikitext_text2dest_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn wikitext_text2dest_link_verbose(
+    i: &str,
+) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("wikitext_text2dest_link", wikitext_text2dest_link_generic)(i)
+}
+
+/// Same as `wikitext_text2dest_link()`, but generic over the error type.
+fn wikitext_text2dest_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = wikitext_text2dest_generic(i)?;
     Ok((i, Link::Text2Dest(te, de, ti)))
 }
 
@@ -40,19 +60,28 @@ pub fn wikitext_text2dest_link(i: &str) -> nom::IResult<&str, Link> {
 ///     expected
 /// );
 /// ```
-pub fn wikitext_text2dest(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>, Cow<str>)> {
+pub fn wikitext_text2dest(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    wikitext_text2dest_generic(i)
+}
+
+/// Same as `wikitext_text2dest()`, but generic over the error type.
+fn wikitext_text2dest_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
     let (i, (link_text, link_destination)) = nom::sequence::delimited(
         // HTML is case insensitive. XHTML, that is being XML is case sensitive.
         // Here we deal with HTML.
         tag("["),
-        nom::combinator::map_parser(is_not("]\n\r"), parse_inner),
+        nom::combinator::map_parser(is_not("]\n\r"), generic_error(parse_inner)),
         tag("]"),
     )(i)?;
     Ok((i, (link_text, link_destination, Cow::from(""))))
 }
 
 /// Parse link destination and link text.
-fn parse_inner(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
+fn parse_inner(i: &str) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>)> {
     let (i, link_destination) = nom::sequence::terminated(
         nom::combinator::map_parser(
             nom::bytes::complete::take_till(|c| c == ' ' || c == '\t'),
@@ -64,14 +93,129 @@ fn parse_inner(i: &str) -> nom::IResult<&str, (Cow<str>, Cow<str>)> {
     Ok((i, (Cow::from(link_text), link_destination)))
 }
 
-/// Parse URL.
-fn parse_url(i: &str) -> nom::IResult<&str, Cow<str>> {
+/// Parse URL. Besides absolute URLs, see `parser::url_start()`, MediaWiki
+/// accepts protocol-relative URLs starting with `//`.
+fn parse_url(i: &str) -> nom::IResult<&str, Cow<'_, str>> {
     nom::sequence::preceded(
-        nom::combinator::peek(alt((tag("http:"), tag("https:"), tag("mailto:")))),
+        nom::combinator::peek(alt((url_start, tag("//")))),
         percent_decode,
     )(i)
 }
 
+/// MediaWiki numbers external links without link text, e.g.
+/// `[https://example.com]`, in order of appearance and shows the number in
+/// brackets as link text: `[1]`, `[2]`, ... If `link` is such a link, its
+/// empty link text is replaced with the next number. `counter` is the number
+/// of links numbered so far. Other links are returned unchanged.
+///
+/// `iterator::MarkupLink` numbers the Wikitext links it returns this way.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::wikitext::{wikitext_number_link, wikitext_text2dest_link};
+/// use std::borrow::Cow;
+///
+/// let mut counter = 0;
+/// let (_, link) = wikitext_text2dest_link("[https://example.com]").unwrap();
+/// assert_eq!(
+///     wikitext_number_link(link, &mut counter),
+///     Link::Text2Dest(Cow::from("[1]"), Cow::from("https://example.com"), Cow::from(""))
+/// );
+/// assert_eq!(counter, 1);
+/// ```
+pub fn wikitext_number_link<'a>(link: Link<'a>, counter: &mut usize) -> Link<'a> {
+    match link {
+        Link::Text2Dest(te, de, ti) if te.is_empty() => {
+            *counter += 1;
+            Link::Text2Dest(Cow::Owned(format!("[{}]", counter)), de, ti)
+        }
+        link => link,
+    }
+}
+
+/// Wrapper around `wikitext_free_url()` that packs the result in
+/// `Link::Text2Dest`.
+pub fn wikitext_free_url_link(i: &str) -> nom::IResult<&str, Link<'_>> {
+    wikitext_free_url_link_generic(i)
+}
+
+/// Same as `wikitext_free_url_link()`, but with `nom::error::VerboseError`
+/// as error type.
+pub fn wikitext_free_url_link_verbose(i: &str) -> nom::IResult<&str, Link<'_>, VerboseError<&str>> {
+    nom::error::context("wikitext_free_url_link", wikitext_free_url_link_generic)(i)
+}
+
+/// Same as `wikitext_free_url_link()`, but generic over the error type.
+fn wikitext_free_url_link_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, Link<'a>, E> {
+    let (i, (te, de, ti)) = wikitext_free_url_generic(i)?;
+    Ok((i, Link::Text2Dest(te, de, ti)))
+}
+
+/// Parse a Wikitext _free external link_: a bare URL in the text, that
+/// MediaWiki renders as hyperlink.
+///
+/// It returns either `Ok((i, (link_text, link_destination, Cow::from("")))`
+/// or some error. `link_text` is the URL as written, `link_destination` the
+/// percent decoded URL.
+///
+/// The URL ends at whitespace or at one of the characters `[]<>"`. As in
+/// MediaWiki, trailing punctuation `,;.:!?` is not part of the URL, neither
+/// is a trailing `)`, if the URL contains no `(`. Protocol-relative URLs are
+/// not recognized here, because `//` is too common in continuous text.
+///
+/// `parser::parse::take_link()` tries this parser at the start of a word,
+/// after Asciidoc's bare URL. It takes effect only, when
+/// `MarkupLang::Adoc` is disabled.
+/// ```
+/// use parse_hyperlinks::parser::wikitext::wikitext_free_url;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(
+///     wikitext_free_url("https://example.com/a_(b). abc").unwrap(),
+///     (
+///         ". abc",
+///         (
+///             Cow::from("https://example.com/a_(b)"),
+///             Cow::from("https://example.com/a_(b)"),
+///             Cow::from("")
+///         )
+///     )
+/// );
+/// assert_eq!(
+///     wikitext_free_url("https://example.com/a), abc").unwrap().0,
+///     "), abc"
+/// );
+/// ```
+pub fn wikitext_free_url(
+    i: &str,
+) -> nom::IResult<&str, (Cow<'_, str>, Cow<'_, str>, Cow<'_, str>)> {
+    wikitext_free_url_generic(i)
+}
+
+/// Same as `wikitext_free_url()`, but generic over the error type.
+fn wikitext_free_url_generic<'a, E: LinkError<'a>>(
+    i: &'a str,
+) -> nom::IResult<&'a str, (Cow<'a, str>, Cow<'a, str>, Cow<'a, str>), E> {
+    let (_, url) = nom::sequence::preceded(
+        nom::combinator::peek(generic_error(url_start)),
+        nom::bytes::complete::take_till1(|c: char| c.is_whitespace() || "[]<>\"".contains(c)),
+    )(i)?;
+    let mut url = url.trim_end_matches([',', ';', '.', ':', '!', '?']);
+    while let Some(u) = url.strip_suffix(')').filter(|_| !url.contains('(')) {
+        url = u.trim_end_matches([',', ';', '.', ':', '!', '?']);
+    }
+    if url_start(url).map_or(true, |(rest, _)| rest.trim_start_matches('/').is_empty()) {
+        return Err(nom::Err::Error(E::from_error_kind(
+            i,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    let (_, dest) = generic_error(percent_decode)(url)?;
+    Ok((&i[url.len()..], (Cow::from(url), dest, Cow::from(""))))
+}
+
 #[test]
 fn test_wikitext_text2dest() {
     let expected = (
@@ -151,11 +295,68 @@ fn test_wikitext_text2dest() {
         expected
     );
 
+    // Any scheme is accepted here, `take_link_with()` filters them.
+    assert_eq!(
+        wikitext_text2dest(r#"[httpx://www.w3schools.com/ W3Schools]abc"#)
+            .unwrap()
+            .1
+             .1,
+        Cow::from("httpx://www.w3schools.com/")
+    );
+    assert!(wikitext_text2dest(r#"[www.w3schools.com W3Schools]abc"#).is_err());
+}
+
+#[test]
+fn test_wikitext_protocol_relative() {
+    let expected = (
+        "abc",
+        (
+            Cow::from("Example"),
+            Cow::from("//example.com/a b"),
+            Cow::from(""),
+        ),
+    );
+    assert_eq!(
+        wikitext_text2dest("[//example.com/a%20b Example]abc").unwrap(),
+        expected
+    );
+    assert!(wikitext_text2dest("[/example.com Example]abc").is_err());
+}
+
+#[test]
+fn test_wikitext_number_link() {
+    let mut counter = 0;
+    let links = ["[https://a]", "[https://b label]", "[//c]"]
+        .map(|i| wikitext_number_link(wikitext_text2dest_link(i).unwrap().1, &mut counter));
+    assert_eq!(links[0].text(), Some("[1]"));
+    assert_eq!(links[1].text(), Some("label"));
+    assert_eq!(links[2].text(), Some("[2]"));
+    assert_eq!(counter, 2);
+}
+
+#[test]
+fn test_wikitext_free_url() {
+    let url = |i| wikitext_free_url(i).map(|(j, (te, _, _))| (te, j));
+    assert_eq!(
+        url("https://a.org/x?y=1.").unwrap(),
+        (Cow::from("https://a.org/x?y=1"), ".")
+    );
+    assert_eq!(url("https://a.org/b!?;").unwrap().1, "!?;");
+    assert_eq!(url("https://a.org/(b)).").unwrap().1, ".");
+    assert_eq!(url("https://a.org/b)).").unwrap().1, ")).");
+    assert_eq!(url("http://a.org<br>").unwrap().1, "<br>");
+    assert_eq!(url("mailto:a@b.org, x").unwrap().1, ", x");
+    assert_eq!(
+        wikitext_free_url("https://a.org/%C3%BC x").unwrap().1 .1,
+        Cow::from("https://a.org/ü")
+    );
     assert_eq!(
-        wikitext_text2dest(r#"[httpx://www.w3schools.com/ W3Schools]abc"#).unwrap_err(),
-        nom::Err::Error(nom::error::Error::new(
-            "httpx://www.w3schools.com/",
-            nom::error::ErrorKind::Tag
-        ))
+        url("https://例子.org/ссылка_(я)).").unwrap(),
+        (Cow::from("https://例子.org/ссылка_(я))"), ".")
     );
+    assert_eq!(url("https://a.org/🦀)! x").unwrap().1, ")! x");
+    assert!(url("http:").is_err());
+    assert!(url("https://.").is_err());
+    assert_eq!(url("ftp://a.org").unwrap().0, "ftp://a.org");
+    assert!(url("file:a.org").is_err());
 }
@@ -1,15 +1,437 @@
 //! A set of functions providing markup source code to HTML renderer, that make
-//! hyperlinks clickable.
+//! hyperlinks clickable. `report2html()` renders an `analysis::Report` about
+//! the hyperlinks of a set of files.
 
+use crate::analysis::link_id;
+use crate::analysis::Report;
 use crate::iterator::MarkupLink;
+use crate::iterator::Span;
+use crate::parser::parse::ParserOptions;
 use crate::parser::Link;
+use crate::url::browser_scheme;
+use crate::url::local_path;
 use html_escape::encode_double_quoted_attribute;
 use html_escape::encode_text;
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::fmt;
 use std::io;
+use std::io::BufRead;
 use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-fn render<'a, O, P, W>(
+/// Options controlling the HTML renderers, see e.g.
+/// `text_links2html_writer_with()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RendererOptions {
+    /// When set, local images, that do not exist, are rendered with this
+    /// image source instead. The original image source is shown in the
+    /// image's `title`. Images with a URL scheme are never replaced.
+    pub broken_image_placeholder: Option<String>,
+    /// Directory local image sources are resolved against. The empty path
+    /// denotes the current working directory.
+    pub base_dir: PathBuf,
+    /// When set, every generated `<a>` element gets this `rel` attribute,
+    /// e.g. `nofollow noopener`.
+    pub rel: Option<String>,
+    /// When set, every generated `<a>` element gets this `target`
+    /// attribute, e.g. `_blank`.
+    pub target: Option<String>,
+    /// When set, every generated `<a>` element gets this `class` attribute.
+    pub class: Option<String>,
+    /// When set, only destinations and image sources without URL scheme or
+    /// with one of these schemes (compared ASCII case-insensitively) are
+    /// rendered as links and images. Other links are neutralized: the source
+    /// code viewers show them as verbatim text, the hyperlink extractors
+    /// drop them. Use `SAFE_SCHEMES` for untrusted input. When set, the
+    /// renderers HTML-escape the link texts.
+    pub allowed_schemes: Option<Vec<String>>,
+    /// When set, only links and images, whose destination or image source
+    /// matches this filter, are rendered as links and images. An image
+    /// inside a link is kept, if one of both matches. Other links are
+    /// neutralized like those with a disallowed scheme. When set, the
+    /// renderers HTML-escape the link texts.
+    pub dest_filter: Option<DestFilter>,
+    /// How destinations, that are email addresses, are rendered. This
+    /// applies to Markdown email autolinks, e.g. `<a@b.c>`, and to links of
+    /// any markup language, whose destination is a bare email address, e.g.
+    /// `[a](a@b.c)`.
+    pub email_links: EmailLinks,
+    /// If true, the source code viewers insert an empty element
+    /// `<span id="L1"></span>` at the beginning of every input line, so that
+    /// the rendered page can be deep-linked per line, e.g. with `#L42`.
+    pub line_anchors: bool,
+    /// If true, the source code viewers render reference links and images,
+    /// whose label has no matching link reference definition, as
+    /// `<span class="broken-link">…</span>`. Otherwise, they are printed as
+    /// verbatim text.
+    pub highlight_broken_references: bool,
+    /// Options of the link parser, e.g. to restrict it to some markup
+    /// languages.
+    pub parser_options: ParserOptions,
+}
+
+/// A predicate on link destinations and image sources, see
+/// `RendererOptions::dest_filter`. Two filters are equal, if they share
+/// the same predicate.
+///
+/// ```
+/// use parse_hyperlinks::renderer::{links2html_writer_with, DestFilter, RendererOptions};
+///
+/// let options = RendererOptions {
+///     dest_filter: Some(DestFilter::new(|dest| dest.contains("example.com"))),
+///     ..RendererOptions::default()
+/// };
+/// let mut output = Vec::new();
+/// links2html_writer_with("[a](https://example.com) [b](b.md)", &options, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<a href=\"https://example.com\" title=\"\">a</a><br>\n"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct DestFilter(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl DestFilter {
+    /// Constructor. `predicate` returns true for destinations to keep.
+    pub fn new<F: Fn(&str) -> bool + Send + Sync + 'static>(predicate: F) -> Self {
+        Self(Arc::new(predicate))
+    }
+
+    /// True if `dest` passes the filter.
+    pub fn matches(&self, dest: &str) -> bool {
+        (self.0)(dest)
+    }
+}
+
+impl fmt::Debug for DestFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DestFilter(..)")
+    }
+}
+
+impl PartialEq for DestFilter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DestFilter {}
+
+/// The treatment of email destinations, see `RendererOptions::email_links`.
+///
+/// An email destination is a bare email address, e.g. `a@b.c`, or a
+/// `mailto:` URL, that the parser added to a Markdown email autolink.
+/// Other `mailto:` URLs are rendered as written.
+///
+/// ```
+/// use parse_hyperlinks::renderer::{links2html_writer_with, EmailLinks, RendererOptions};
+///
+/// let i = "<a@b.c> [Mail](a@b.c) [Web](https://b.c)";
+/// let mut output = Vec::new();
+/// links2html_writer_with(i, &RendererOptions::default(), &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<a href=\"mailto:a@b.c\" title=\"\">a@b.c</a><br>\n\
+///      <a href=\"mailto:a@b.c\" title=\"\">Mail</a><br>\n\
+///      <a href=\"https://b.c\" title=\"\">Web</a><br>\n"
+/// );
+///
+/// let options = RendererOptions {
+///     email_links: EmailLinks::custom(|address| format!("/contact?to={}", address)),
+///     ..RendererOptions::default()
+/// };
+/// let mut output = Vec::new();
+/// links2html_writer_with("<a@b.c>", &options, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<a href=\"/contact?to=a@b.c\" title=\"\">a@b.c</a><br>\n"
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub enum EmailLinks {
+    /// Email addresses are rendered as `mailto:` URLs.
+    #[default]
+    Mailto,
+    /// Email addresses are rendered as written, without `mailto:`.
+    Verbatim,
+    /// Email addresses are rendered as the string this function returns
+    /// for them.
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl EmailLinks {
+    /// Constructor of `EmailLinks::Custom`. `map` returns the destination
+    /// an email address is rendered with.
+    pub fn custom<F: Fn(&str) -> String + Send + Sync + 'static>(map: F) -> Self {
+        Self::Custom(Arc::new(map))
+    }
+
+    /// Renders the email `address`.
+    fn render(&self, address: &str) -> String {
+        match self {
+            Self::Mailto => format!("mailto:{}", address),
+            Self::Verbatim => address.to_string(),
+            Self::Custom(map) => map(address),
+        }
+    }
+}
+
+impl fmt::Debug for EmailLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mailto => f.write_str("Mailto"),
+            Self::Verbatim => f.write_str("Verbatim"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for EmailLinks {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Mailto, Self::Mailto) | (Self::Verbatim, Self::Verbatim) => true,
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for EmailLinks {}
+
+/// True if `dest` is a bare email address, e.g. `a@b.c`: a local part and
+/// a domain without URL scheme, path, query or whitespace.
+fn is_email_address(dest: &str) -> bool {
+    let Some((local, domain)) = dest.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || "!#$%&'*+-.=^_`{|}~".contains(c))
+        && domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+/// URL schemes, that are safe to render clickable, even for untrusted input,
+/// see `RendererOptions::allowed_schemes`. With this option set, the
+/// renderers escape the link texts, so that they can not inject HTML.
+pub const SAFE_SCHEMES: [&str; 7] = ["http", "https", "mailto", "ftp", "tel", "sms", "geo"];
+
+impl RendererOptions {
+    /// Returns the `(src, title)` attributes to render the image `src` with.
+    fn image_src<'a>(&'a self, src: &'a str) -> (&'a str, &'a str) {
+        match &self.broken_image_placeholder {
+            Some(placeholder)
+                if local_path(&self.base_dir, src).is_some_and(|path| !path.exists()) =>
+            {
+                (placeholder, src)
+            }
+            _ => (src, ""),
+        }
+    }
+
+    /// True if the link destinations and image sources of `link` are
+    /// allowed, see `allowed_schemes` and `dest_filter`.
+    fn is_allowed(&self, link: &Link) -> bool {
+        let dests = match link {
+            Link::Text2Dest(_, dest, _) | Link::Image(_, dest) => [Some(dest), None],
+            Link::Image2Dest(_, _, src, _, dest, _) => [Some(src), Some(dest)],
+            _ => [None, None],
+        };
+        if let Some(filter) = &self.dest_filter {
+            let mut dests = dests.iter().flatten().peekable();
+            if dests.peek().is_some() && !dests.any(|dest| filter.matches(dest)) {
+                return false;
+            }
+        }
+        let allowed_schemes = match &self.allowed_schemes {
+            Some(allowed_schemes) => allowed_schemes,
+            None => return true,
+        };
+        dests.into_iter().flatten().all(|dest| {
+            // Browsers strip some characters, e.g. from `java\tscript:`.
+            browser_scheme(dest).is_none_or(|scheme| {
+                allowed_schemes.iter().any(|allowed| {
+                    scheme
+                        .clone()
+                        .map(|c| c.to_ascii_lowercase())
+                        .eq(allowed.chars().map(|c| c.to_ascii_lowercase()))
+                })
+            })
+        })
+    }
+
+    /// Returns the link text `text` as it is rendered: HTML-escaped, when
+    /// `allowed_schemes` or `dest_filter` is set, because the input is
+    /// untrusted then. Otherwise, link texts may contain HTML, e.g. the
+    /// nested tags of HTML links.
+    fn link_text<'b>(&self, text: &'b str) -> Cow<'b, str> {
+        if self.allowed_schemes.is_some() || self.dest_filter.is_some() {
+            encode_text(text)
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Applies `email_links` to the destinations of `link`. `consumed` is
+    /// the link's source, as passed to the link renderer.
+    fn email_link<'b>(&self, consumed: &str, link: Link<'b>) -> Link<'b> {
+        // The parser adds `mailto:` to Markdown email autolinks, whose link
+        // text is the address.
+        let autolink = !consumed.to_ascii_lowercase().contains("mailto:");
+        let render = |text: &str, dest: Cow<'b, str>| {
+            let address = match dest.strip_prefix("mailto:") {
+                Some(address) if autolink && address == text => address,
+                _ if is_email_address(&dest) => &dest,
+                _ => return dest,
+            };
+            Cow::Owned(self.email_links.render(address))
+        };
+        match link {
+            Link::Text2Dest(text, dest, title) => {
+                let dest = render(&text, dest);
+                Link::Text2Dest(text, dest, title)
+            }
+            Link::Image2Dest(text1, alt, src, text2, dest, title) => {
+                let dest = render("", dest);
+                Link::Image2Dest(text1, alt, src, text2, dest, title)
+            }
+            l => l,
+        }
+    }
+
+    /// Renders the additional attributes of the `<a>` element, each with a
+    /// leading space.
+    fn a_attributes(&self) -> String {
+        let mut attributes = String::new();
+        for (name, value) in [
+            ("class", &self.class),
+            ("target", &self.target),
+            ("rel", &self.rel),
+        ] {
+            if let Some(value) = value {
+                attributes.push_str(&format!(
+                    r#" {}="{}""#,
+                    name,
+                    encode_double_quoted_attribute(value)
+                ));
+            }
+        }
+        attributes
+    }
+}
+
+/// Inserts the line anchors of `RendererOptions::line_anchors` into the
+/// rendered text.
+#[derive(Debug)]
+struct LineAnchors {
+    /// If false, the text is not changed.
+    enabled: bool,
+    /// Number of the next input line.
+    line: Cell<usize>,
+}
+
+impl LineAnchors {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            line: Cell::new(1),
+        }
+    }
+
+    /// The anchor of the next line.
+    fn next(&self) -> String {
+        let line = self.line.get();
+        self.line.set(line + 1);
+        format!(r#"<span id="L{}"></span>"#, line)
+    }
+
+    /// Renders `begin_doc` followed by the anchor of the first line.
+    fn begin<'b>(&self, begin_doc: &'b str) -> Cow<'b, str> {
+        if self.enabled {
+            Cow::Owned(format!("{}{}", begin_doc, self.next()))
+        } else {
+            Cow::Borrowed(begin_doc)
+        }
+    }
+
+    /// Inserts an anchor after every line break of `text`.
+    fn insert<'b>(&self, text: Cow<'b, str>) -> Cow<'b, str> {
+        if !self.enabled || !text.contains('\n') {
+            return text;
+        }
+        let mut res = String::with_capacity(text.len() + 32);
+        for (n, line) in text.split('\n').enumerate() {
+            if n > 0 {
+                res.push('\n');
+                res.push_str(&self.next());
+            }
+            res.push_str(line);
+        }
+        Cow::Owned(res)
+    }
+
+    /// Counts the line breaks of `text`, that is not rendered.
+    fn skip(&self, text: &str) {
+        self.line
+            .set(self.line.get() + text.bytes().filter(|b| *b == b'\n').count());
+    }
+}
+
+/// Renders the source of a reference, that can not be resolved.
+fn broken_reference(consumed: &str) -> String {
+    format!(r#"<span class="broken-link">{}</span>"#, consumed)
+}
+
+/// Renders the `title` attribute of an image, if any.
+fn image_title(title: &str) -> String {
+    if title.is_empty() {
+        String::new()
+    } else {
+        format!(r#" title="{}""#, encode_double_quoted_attribute(title))
+    }
+}
+
+/// # Custom renderer
+///
+/// The machinery behind all renderers of this module: `render()` writes
+/// `begin_doc`, then the input text piece by piece, then `end_doc`. The text
+/// between two links (_verbatim text_) is passed to `verb_renderer`, every
+/// hyperlink or image to `link_renderer`, together with its source. Both,
+/// the verbatim text and the link's source, are HTML escaped before;
+/// the link's components are not. If `render_label` is true, link
+/// reference definitions are passed to `link_renderer` too, see
+/// `iterator::MarkupLink::new()`.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::renderer::render;
+/// use html_escape::encode_double_quoted_attribute;
+///
+/// let i = "abc[text1](https://getreu.net)abc";
+///
+/// let verb_renderer = |verb| verb;
+/// let link_renderer = |(consumed, link)| match link {
+///     Link::Text2Dest(text, dest, _) => format!(
+///         r#"<a class="ext" href="{}" target="_blank" rel="noopener">{}</a>"#,
+///         encode_double_quoted_attribute(&dest),
+///         text
+///     ),
+///     _ => format!("{}", consumed),
+/// };
+///
+/// let mut output = Vec::new();
+/// render(i, "<p>", "</p>", verb_renderer, link_renderer, false, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     r#"<p>abc<a class="ext" href="https://getreu.net" target="_blank" rel="noopener">text1</a>abc</p>"#
+/// );
+/// ```
+pub fn render<'a, O, P, W>(
     input: &'a str,
     begin_doc: &str,
     end_doc: &str,
@@ -18,6 +440,37 @@ fn render<'a, O, P, W>(
     render_label: bool,
     output: &mut W,
 ) -> Result<(), io::Error>
+where
+    O: Fn(Cow<'a, str>) -> Cow<'a, str>,
+    P: Fn((Cow<'a, str>, Link<'a>)) -> String,
+    W: Write,
+{
+    render_links(
+        input,
+        MarkupLink::new(input, render_label),
+        begin_doc,
+        end_doc,
+        verb_renderer,
+        link_renderer,
+        output,
+        None,
+    )
+}
+
+/// Same as `render()`, but the links are taken from `links`, an iterator
+/// over `input`. If `source_map` is given, the rendered pieces are recorded
+/// there.
+#[allow(clippy::too_many_arguments)]
+fn render_links<'a, O, P, W>(
+    input: &'a str,
+    links: MarkupLink<'a>,
+    begin_doc: &str,
+    end_doc: &str,
+    verb_renderer: O,
+    link_renderer: P,
+    output: &mut W,
+    mut source_map: Option<&mut SourceMap>,
+) -> Result<(), io::Error>
 where
     O: Fn(Cow<'a, str>) -> Cow<'a, str>,
     P: Fn((Cow<'a, str>, Link<'a>)) -> String,
@@ -27,23 +480,70 @@ where
     // when there are no hyperlinks in the input. In this case we print the
     // input as a whole.
     let mut rest = Cow::Borrowed(input);
+    let mut rest_start = 0;
+    // Number of bytes written so far.
+    let mut written = 0;
+    let mut write = |rendered: &str, source: Span| -> Result<(), io::Error> {
+        output.write_all(rendered.as_bytes())?;
+        if let Some(map) = source_map.as_deref_mut() {
+            if !rendered.is_empty() && !source.is_empty() {
+                map.segments
+                    .push((written..written + rendered.len(), source));
+            }
+        }
+        written += rendered.len();
+        Ok(())
+    };
 
-    output.write_all(begin_doc.as_bytes())?;
-    for ((skipped2, consumed2, remaining2), link) in MarkupLink::new(input, render_label) {
+    write(begin_doc, 0..0)?;
+    for ((skipped2, consumed2, remaining2), link) in links {
+        let end = input.len() - remaining2.len();
+        let start = end - consumed2.len();
         // (text2, dest2, title2)
         let skipped = encode_text(skipped2);
         let consumed = encode_text(consumed2);
         let remaining = encode_text(remaining2);
-        output.write_all(verb_renderer(skipped).as_bytes())?;
-        let rendered_link = link_renderer((consumed, link));
-        output.write_all(rendered_link.as_bytes())?;
+        write(&verb_renderer(skipped), start - skipped2.len()..start)?;
+        write(&link_renderer((consumed, link)), start..end)?;
         rest = remaining;
+        rest_start = end;
     }
-    output.write_all(verb_renderer(rest).as_bytes())?;
-    output.write_all(end_doc.as_bytes())?;
+    write(&verb_renderer(rest), rest_start..input.len())?;
+    write(end_doc, 0..0)?;
     Ok(())
 }
 
+/// Maps the byte ranges of rendered HTML to the byte ranges of the input
+/// text they were rendered from, e.g. to implement click-to-source in a
+/// preview. See `text_links2html_source_map()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    /// `(output, input)` byte ranges in output order: every hyperlink and
+    /// every text between two hyperlinks is one segment. Markup added by
+    /// the renderer, e.g. `<pre>`, belongs to no segment.
+    pub segments: Vec<(Span, Span)>,
+}
+
+impl SourceMap {
+    /// The input byte range rendered at the byte `output_offset` of the
+    /// output, if any.
+    pub fn input_span(&self, output_offset: usize) -> Option<Span> {
+        self.segments
+            .iter()
+            .find(|(output, _)| output.contains(&output_offset))
+            .map(|(_, input)| input.clone())
+    }
+
+    /// The output byte range the byte `input_offset` of the input is
+    /// rendered into, if any.
+    pub fn output_span(&self, input_offset: usize) -> Option<Span> {
+        self.segments
+            .iter()
+            .find(|(_, input)| input.contains(&input_offset))
+            .map(|(output, _)| output.clone())
+    }
+}
+
 /// # Source code viewer with link renderer
 ///
 /// Text to HTML renderer that prints the input text “as it is”, but
@@ -268,47 +768,280 @@ pub fn text_links2html(input: &str) -> String {
 ///     Ok(())
 /// }
 /// ```
-pub fn text_links2html_writer<'a, W>(input: &'a str, output: &mut W) -> Result<(), io::Error>
+pub fn text_links2html_writer<W>(input: &str, output: &mut W) -> Result<(), io::Error>
 where
     W: Write,
 {
-    let verb_renderer = |verb: Cow<'a, str>| verb;
+    text_links2html_writer_with(input, &RendererOptions::default(), output)
+}
 
-    let link_renderer = |(_consumed, link)| match link {
+/// # Source code viewer with link renderer
+///
+/// Same as `text_links2html_writer()`, but renders according to `options`.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_links2html_writer_with;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let options = RendererOptions {
+///     broken_image_placeholder: Some("broken.svg".to_string()),
+///     ..RendererOptions::default()
+/// };
+/// let i = "abc![alt](missing.png)abc![logo](https://getreu.net/logo.png)";
+///
+/// let mut output = Vec::new();
+/// text_links2html_writer_with(i, &options, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<pre>abc<img src=\"broken.svg\" alt=\"alt\" title=\"missing.png\">abc\
+///      <img src=\"https://getreu.net/logo.png\" alt=\"logo\"></pre>"
+/// );
+/// ```
+pub fn text_links2html_writer_with<W>(
+    input: &str,
+    options: &RendererOptions,
+    output: &mut W,
+) -> Result<(), io::Error>
+where
+    W: Write,
+{
+    let line_anchors = LineAnchors::new(options.line_anchors);
+    text_links2html_render(
+        input,
+        markup_links(input, options),
+        options,
+        (&line_anchors.begin("<pre>"), "</pre>"),
+        &line_anchors,
+        output,
+        None,
+    )
+}
+
+/// # Source code viewer with link renderer and source map
+///
+/// Same as `text_links2html_writer_with()`, but additionally returns a
+/// `SourceMap` relating the output to the input.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_links2html_source_map;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let i = "abc[text](dest)a<b";
+///
+/// let mut output = Vec::new();
+/// let map = text_links2html_source_map(i, &RendererOptions::default(), &mut output).unwrap();
+/// let output = String::from_utf8(output).unwrap();
+/// assert_eq!(output, "<pre>abc<a href=\"dest\" title=\"\">text</a>a&lt;b</pre>");
+/// assert_eq!(map.segments, [(5..8, 0..3), (8..40, 3..15), (40..46, 15..18)]);
+///
+/// // Click on `text`.
+/// let clicked = output.find("text<").unwrap();
+/// assert_eq!(&i[map.input_span(clicked).unwrap()], "[text](dest)");
+/// assert_eq!(map.output_span(16), Some(40..46));
+/// ```
+pub fn text_links2html_source_map<W>(
+    input: &str,
+    options: &RendererOptions,
+    output: &mut W,
+) -> Result<SourceMap, io::Error>
+where
+    W: Write,
+{
+    let mut source_map = SourceMap::default();
+    let line_anchors = LineAnchors::new(options.line_anchors);
+    text_links2html_render(
+        input,
+        markup_links(input, options),
+        options,
+        (&line_anchors.begin("<pre>"), "</pre>"),
+        &line_anchors,
+        output,
+        Some(&mut source_map),
+    )?;
+    Ok(source_map)
+}
+
+/// # Source code viewer with link renderer, streaming
+///
+/// Same as `text_links2html_writer_with()`, but reads `input` in chunks of
+/// about `chunk_size` bytes. Every chunk is rendered and written to `output`,
+/// which is flushed afterwards, before the next chunk is read. Thus, memory
+/// usage stays bounded however long the input is.
+///
+/// A chunk ends with a line break and holds at least one whole line. As
+/// chunks are parsed independently, links spanning two chunks are not
+/// recognized and link references are resolved only within the same chunk.
+/// Front matter is skipped at the beginning of the input only.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_links2html_stream_with;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let options = RendererOptions {
+///     line_anchors: true,
+///     ..RendererOptions::default()
+/// };
+/// let i = "abc\n[text](dest)\nabc";
+///
+/// let mut output = Vec::new();
+/// text_links2html_stream_with(i.as_bytes(), &options, 4, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<pre><span id=\"L1\"></span>abc\n<span id=\"L2\"></span>\
+///      <a href=\"dest\" title=\"\">text</a>\n<span id=\"L3\"></span>abc</pre>"
+/// );
+/// ```
+pub fn text_links2html_stream_with<R, W>(
+    input: R,
+    options: &RendererOptions,
+    chunk_size: usize,
+    output: &mut W,
+) -> Result<(), io::Error>
+where
+    R: BufRead,
+    W: Write,
+{
+    let line_anchors = LineAnchors::new(options.line_anchors);
+    render_stream(input, chunk_size, output, |chunk, first, last, output| {
+        let begin_doc = if first {
+            line_anchors.begin("<pre>")
+        } else {
+            Cow::Borrowed("")
+        };
+        text_links2html_render(
+            chunk,
+            markup_links(chunk, options).skip_front_matter(first),
+            options,
+            (&begin_doc, if last { "</pre>" } else { "" }),
+            &line_anchors,
+            output,
+            None,
+        )
+    })
+}
+
+/// Implements `text_links2html_writer_with()`, `text_links2html_source_map()`
+/// and `text_links2html_stream_with()`: renders `links` in `input` between
+/// `doc`, a pair of `begin_doc` and `end_doc`.
+fn text_links2html_render<'a, W>(
+    input: &'a str,
+    links: MarkupLink<'a>,
+    options: &RendererOptions,
+    doc: (&str, &str),
+    line_anchors: &LineAnchors,
+    output: &mut W,
+    source_map: Option<&mut SourceMap>,
+) -> Result<(), io::Error>
+where
+    W: Write,
+{
+    let verb_renderer = |verb: Cow<'a, str>| line_anchors.insert(verb);
+    let a_attributes = options.a_attributes();
+
+    let render_link = |link: Link| match link {
         Link::Text2Dest(text, dest, title) => format!(
-            r#"<a href="{}" title="{}">{}</a>"#,
-            encode_double_quoted_attribute(dest.as_ref()),
-            encode_double_quoted_attribute(title.as_ref()),
-            text
-        ),
-        Link::Image2Dest(text1, alt, src, text2, dest, title) => format!(
-            r#"<a href="{}" title="{}">{}<img alt="{}" src="{}">{}</a>"#,
+            r#"<a href="{}" title="{}"{}>{}</a>"#,
             encode_double_quoted_attribute(dest.as_ref()),
             encode_double_quoted_attribute(title.as_ref()),
-            text1,
-            encode_double_quoted_attribute(alt.as_ref()),
-            encode_double_quoted_attribute(src.as_ref()),
-            text2,
-        ),
-        Link::Image(alt, src) => format!(
-            r#"<img src="{}" alt="{}">"#,
-            encode_double_quoted_attribute(src.as_ref()),
-            encode_double_quoted_attribute(alt.as_ref()),
+            a_attributes,
+            options.link_text(&text)
         ),
+        Link::Image2Dest(text1, alt, src, text2, dest, title) => {
+            let (src, img_title) = options.image_src(&src);
+            format!(
+                r#"<a href="{}" title="{}"{}>{}<img alt="{}" src="{}"{}>{}</a>"#,
+                encode_double_quoted_attribute(dest.as_ref()),
+                encode_double_quoted_attribute(title.as_ref()),
+                a_attributes,
+                options.link_text(&text1),
+                encode_double_quoted_attribute(alt.as_ref()),
+                encode_double_quoted_attribute(src),
+                image_title(img_title),
+                options.link_text(&text2),
+            )
+        }
+        Link::Image(alt, src) => {
+            let (src, img_title) = options.image_src(&src);
+            format!(
+                r#"<img src="{}" alt="{}"{}>"#,
+                encode_double_quoted_attribute(src),
+                encode_double_quoted_attribute(alt.as_ref()),
+                image_title(img_title),
+            )
+        }
         e => format!("<ERROR rendering: {:?}>", e),
     };
 
-    render(
+    let link_renderer =
+        |(consumed, link): (Cow<str>, Link)| match options.email_link(&consumed, link) {
+            l if !options.is_allowed(&l) => line_anchors.insert(consumed).into_owned(),
+            Link::Text2Label(_, _) | Link::Image2Label(_, _) => {
+                broken_reference(&line_anchors.insert(consumed))
+            }
+            l => {
+                line_anchors.skip(&consumed);
+                render_link(l)
+            }
+        };
+
+    render_links(
         input,
-        "<pre>",
-        "</pre>",
+        links,
+        doc.0,
+        doc.1,
         verb_renderer,
         link_renderer,
-        true,
         output,
+        source_map,
     )
 }
 
+/// The links `text_links2html_writer_with()` and
+/// `text_rawlinks2html_writer_with()` render in `input`.
+fn markup_links<'a>(input: &'a str, options: &RendererOptions) -> MarkupLink<'a> {
+    MarkupLink::new(input, true)
+        .unresolved_references(options.highlight_broken_references)
+        .parser_options(options.parser_options)
+}
+
+/// Reads `input` in chunks of at least `chunk_size` bytes, that end with a
+/// line break or the end of `input`, and passes each chunk to
+/// `render_chunk`, together with `output` and whether it is the first and
+/// the last chunk. `output` is flushed after every chunk. The last chunk
+/// may be empty.
+fn render_stream<R, W, F>(
+    mut input: R,
+    chunk_size: usize,
+    output: &mut W,
+    mut render_chunk: F,
+) -> Result<(), io::Error>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(&str, bool, bool, &mut W) -> Result<(), io::Error>,
+{
+    let mut buf = Vec::new();
+    let mut first = true;
+    loop {
+        buf.clear();
+        let mut last = false;
+        while buf.len() < chunk_size.max(1) {
+            if input.read_until(b'\n', &mut buf)? == 0 {
+                last = true;
+                break;
+            }
+        }
+        let chunk =
+            std::str::from_utf8(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        render_chunk(chunk, first, last, output)?;
+        output.flush()?;
+        if last {
+            return Ok(());
+        }
+        first = false;
+    }
+}
+
 /// # Markup source code viewer
 ///
 /// Markup source code viewer, that make hyperlinks
@@ -539,42 +1272,161 @@ pub fn text_rawlinks2html(input: &str) -> String {
 ///     Ok(())
 /// }
 /// ```
-pub fn text_rawlinks2html_writer<'a, W>(input: &'a str, output: &mut W) -> Result<(), io::Error>
+pub fn text_rawlinks2html_writer<W>(input: &str, output: &mut W) -> Result<(), io::Error>
 where
     W: Write,
 {
-    let verb_renderer = |verb: Cow<'a, str>| verb;
+    text_rawlinks2html_writer_with(input, &RendererOptions::default(), output)
+}
 
-    let link_renderer = |(consumed, link)| match link {
-        Link::Text2Dest(_text, dest, title) => format!(
-            r#"<a href="{}" title="{}">{}</a>"#,
-            encode_double_quoted_attribute(dest.as_ref()),
-            encode_double_quoted_attribute(title.as_ref()),
-            consumed
-        ),
-        Link::Image2Dest(_text1, _alt, _src, _text2, dest, title) => format!(
-            r#"<a href="{}" title="{}">{}</a>"#,
-            encode_double_quoted_attribute(dest.as_ref()),
-            encode_double_quoted_attribute(title.as_ref()),
-            consumed
-        ),
-        Link::Image(alt, src) => format!(
-            r#"<a href="{}" title="{}">{}</a>"#,
-            encode_double_quoted_attribute(src.as_ref()),
-            encode_double_quoted_attribute(alt.as_ref()),
-            consumed
-        ),
-        e => format!("<ERROR rendering: {:?}>", e),
+/// # Markup source code viewer
+///
+/// Same as `text_rawlinks2html_writer()`, but renders according to
+/// `options`. As links are shown with their source, the option
+/// `broken_image_placeholder` has no effect.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_rawlinks2html_writer_with;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let options = RendererOptions {
+///     line_anchors: true,
+///     ..RendererOptions::default()
+/// };
+/// let i = "abc\nabc[text1](dest1)abc";
+///
+/// let mut output = Vec::new();
+/// text_rawlinks2html_writer_with(i, &options, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<pre><span id=\"L1\"></span>abc\n<span id=\"L2\"></span>abc\
+///      <a href=\"dest1\" title=\"\">[text1](dest1)</a>abc</pre>"
+/// );
+/// ```
+pub fn text_rawlinks2html_writer_with<W>(
+    input: &str,
+    options: &RendererOptions,
+    output: &mut W,
+) -> Result<(), io::Error>
+where
+    W: Write,
+{
+    let line_anchors = LineAnchors::new(options.line_anchors);
+    text_rawlinks2html_render(
+        input,
+        markup_links(input, options),
+        options,
+        (&line_anchors.begin("<pre>"), "</pre>"),
+        &line_anchors,
+        output,
+    )
+}
+
+/// # Markup source code viewer, streaming
+///
+/// Same as `text_rawlinks2html_writer_with()`, but reads `input` in chunks,
+/// see `text_links2html_stream_with()`.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_rawlinks2html_stream_with;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let i = "[text1](dest1)\n[text2][label]\n\n[label]: dest2\n";
+///
+/// let mut output = Vec::new();
+/// text_rawlinks2html_stream_with(i.as_bytes(), &RendererOptions::default(), 1, &mut output)
+///     .unwrap();
+/// // The reference definition is in another chunk.
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<pre><a href=\"dest1\" title=\"\">[text1](dest1)</a>\n[text2][label]\n\n\
+///      <a href=\"dest2\" title=\"\">[label]: dest2</a>\n</pre>"
+/// );
+/// ```
+pub fn text_rawlinks2html_stream_with<R, W>(
+    input: R,
+    options: &RendererOptions,
+    chunk_size: usize,
+    output: &mut W,
+) -> Result<(), io::Error>
+where
+    R: BufRead,
+    W: Write,
+{
+    let line_anchors = LineAnchors::new(options.line_anchors);
+    render_stream(input, chunk_size, output, |chunk, first, last, output| {
+        let begin_doc = if first {
+            line_anchors.begin("<pre>")
+        } else {
+            Cow::Borrowed("")
+        };
+        text_rawlinks2html_render(
+            chunk,
+            markup_links(chunk, options).skip_front_matter(first),
+            options,
+            (&begin_doc, if last { "</pre>" } else { "" }),
+            &line_anchors,
+            output,
+        )
+    })
+}
+
+/// Implements `text_rawlinks2html_writer_with()` and
+/// `text_rawlinks2html_stream_with()`, see `text_links2html_render()`.
+fn text_rawlinks2html_render<'a, W>(
+    input: &'a str,
+    links: MarkupLink<'a>,
+    options: &RendererOptions,
+    doc: (&str, &str),
+    line_anchors: &LineAnchors,
+    output: &mut W,
+) -> Result<(), io::Error>
+where
+    W: Write,
+{
+    let verb_renderer = |verb: Cow<'a, str>| line_anchors.insert(verb);
+    let a_attributes = options.a_attributes();
+
+    let link_renderer = |(consumed, link): (Cow<str>, Link)| {
+        let link = options.email_link(&consumed, link);
+        let consumed = line_anchors.insert(consumed);
+        match link {
+            l if !options.is_allowed(&l) => consumed.into_owned(),
+            Link::Text2Label(_, _) | Link::Image2Label(_, _) => broken_reference(&consumed),
+            Link::Text2Dest(_text, dest, title) => format!(
+                r#"<a href="{}" title="{}"{}>{}</a>"#,
+                encode_double_quoted_attribute(dest.as_ref()),
+                encode_double_quoted_attribute(title.as_ref()),
+                a_attributes,
+                consumed
+            ),
+            Link::Image2Dest(_text1, _alt, _src, _text2, dest, title) => format!(
+                r#"<a href="{}" title="{}"{}>{}</a>"#,
+                encode_double_quoted_attribute(dest.as_ref()),
+                encode_double_quoted_attribute(title.as_ref()),
+                a_attributes,
+                consumed
+            ),
+            Link::Image(alt, src) => format!(
+                r#"<a href="{}" title="{}"{}>{}</a>"#,
+                encode_double_quoted_attribute(src.as_ref()),
+                encode_double_quoted_attribute(alt.as_ref()),
+                a_attributes,
+                consumed
+            ),
+            e => format!("<ERROR rendering: {:?}>", e),
+        }
     };
 
-    render(
+    render_links(
         input,
-        "<pre>",
-        "</pre>",
+        links,
+        doc.0,
+        doc.1,
         verb_renderer,
         link_renderer,
-        true,
         output,
+        None,
     )
 }
 
@@ -785,6 +1637,133 @@ pub fn links2html(input: &str) -> String {
 pub fn links2html_writer<'a, S: 'a + AsRef<str>, W: Write>(
     input: S,
     output: &mut W,
+) -> Result<(), io::Error> {
+    links2html_writer_with(input, &RendererOptions::default(), output)
+}
+
+/// # Hyperlink extractor
+///
+/// Same as `links2html_writer()`, but renders according to `options`.
+///
+/// ```
+/// use parse_hyperlinks::renderer::links2html_writer_with;
+/// use parse_hyperlinks::renderer::RendererOptions;
+///
+/// let options = RendererOptions {
+///     rel: Some("nofollow noopener".to_string()),
+///     target: Some("_blank".to_string()),
+///     class: Some("ext".to_string()),
+///     ..RendererOptions::default()
+/// };
+/// let i = "abc[text1](https://getreu.net)abc";
+///
+/// let mut output = Vec::new();
+/// links2html_writer_with(i, &options, &mut output).unwrap();
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<a href=\"https://getreu.net\" title=\"\" class=\"ext\" target=\"_blank\" \
+///      rel=\"nofollow noopener\">text1</a><br>\n"
+/// );
+/// ```
+pub fn links2html_writer_with<'a, S: 'a + AsRef<str>, W: Write>(
+    input: S,
+    options: &RendererOptions,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    let input = input.as_ref();
+
+    let verb_renderer = |_| Cow::Borrowed("");
+    let a_attributes = options.a_attributes();
+
+    let link_renderer =
+        |(consumed, link): (Cow<str>, Link)| match options.email_link(&consumed, link) {
+            l if !options.is_allowed(&l) => String::new(),
+            Link::Text2Dest(text, dest, title) => format!(
+                "<a href=\"{}\" title=\"{}\"{}>{}</a><br>\n",
+                encode_double_quoted_attribute(dest.as_ref()),
+                encode_double_quoted_attribute(title.as_ref()),
+                a_attributes,
+                options.link_text(&text)
+            ),
+            Link::Image2Dest(text1, alt, _src, text2, dest, title) => format!(
+                "<a href=\"{}\" title=\"{}\"{}>{}[{}]{}</a><br>\n",
+                encode_double_quoted_attribute(dest.as_ref()),
+                encode_double_quoted_attribute(title.as_ref()),
+                a_attributes,
+                options.link_text(&text1),
+                options.link_text(if !alt.is_empty() { &alt } else { &dest }),
+                options.link_text(&text2),
+            ),
+            Link::Image(alt, src) => format!(
+                "<a href=\"{}\"{}>[{}]</a><br>\n",
+                encode_double_quoted_attribute(src.as_ref()),
+                a_attributes,
+                options.link_text(if !alt.is_empty() { &alt } else { &src }),
+            ),
+            e => format!("<ERROR rendering: {:?}>", e),
+        };
+
+    render_links(
+        input,
+        MarkupLink::new(input, false).parser_options(options.parser_options),
+        "",
+        "",
+        verb_renderer,
+        link_renderer,
+        output,
+        None,
+    )
+}
+
+/// # Hyperlink extractor with Markdown output
+///
+/// Extracts the hyperlinks of any supported markup language and renders
+/// them as a Markdown bullet list, one link per line. This is the Markdown
+/// counterpart of `links2html()`.
+///
+/// ```
+/// use parse_hyperlinks::renderer::links2markdown;
+///
+/// let i = r#"abc `text1 <dest1>`_ abc
+/// abc <a href="dest 2" title="title &quot;2&quot;">text [2]</a>abc
+/// abc ![alt3](src3) abc [![alt4](src4)](dest4)
+/// "#;
+///
+/// let expected = r#"- [text1](dest1)
+/// - [text \[2\]](<dest 2> "title \"2\"")
+/// - ![alt3](src3)
+/// - [![alt4](src4)](dest4)
+/// "#;
+/// assert_eq!(links2markdown(i), expected);
+/// ```
+#[inline]
+pub fn links2markdown(input: &str) -> String {
+    let mut output = Vec::new();
+    links2markdown_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// # Hyperlink extractor with Markdown output
+///
+/// Same as `links2markdown()`, but it uses `Write` for output.
+///
+/// Usage example:
+/// ```no_run
+/// use parse_hyperlinks::renderer::links2markdown_writer;
+/// use std::io;
+/// use std::io::Read;
+/// fn main() -> Result<(), ::std::io::Error> {
+///     let mut stdin = String::new();
+///     Read::read_to_string(&mut io::stdin(), &mut stdin)?;
+///
+///     links2markdown_writer(&stdin, &mut io::stdout())?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn links2markdown_writer<'a, S: 'a + AsRef<str>, W: Write>(
+    input: S,
+    output: &mut W,
 ) -> Result<(), io::Error> {
     let input = input.as_ref();
 
@@ -792,30 +1771,514 @@ pub fn links2html_writer<'a, S: 'a + AsRef<str>, W: Write>(
 
     let link_renderer = |(_consumed, link)| match link {
         Link::Text2Dest(text, dest, title) => format!(
-            "<a href=\"{}\" title=\"{}\">{}</a><br>\n",
-            encode_double_quoted_attribute(dest.as_ref()),
-            encode_double_quoted_attribute(title.as_ref()),
-            text
-        ),
-        Link::Image2Dest(text1, alt, _src, text2, dest, title) => format!(
-            "<a href=\"{}\" title=\"{}\">{}[{}]{}</a><br>\n",
-            encode_double_quoted_attribute(dest.as_ref()),
-            encode_double_quoted_attribute(title.as_ref()),
-            text1,
-            if !alt.is_empty() { &alt } else { &dest },
-            text2,
+            "- [{}]({}{})\n",
+            md_text(&text),
+            md_dest(&dest),
+            md_title(&title)
         ),
-        Link::Image(alt, src) => format!(
-            "<a href=\"{}\">[{}]</a><br>\n",
-            encode_double_quoted_attribute(src.as_ref()),
-            if !alt.is_empty() { &alt } else { &src },
+        Link::Image2Dest(text1, alt, src, text2, dest, title) => format!(
+            "- [{}![{}]({}){}]({}{})\n",
+            md_text(&text1),
+            md_text(&alt),
+            md_dest(&src),
+            md_text(&text2),
+            md_dest(&dest),
+            md_title(&title)
         ),
+        Link::Image(alt, src) => format!("- ![{}]({})\n", md_text(&alt), md_dest(&src)),
         e => format!("<ERROR rendering: {:?}>", e),
     };
 
     render(input, "", "", verb_renderer, link_renderer, false, output)
 }
 
+/// Escapes the brackets of a Markdown _link text_.
+pub(crate) fn md_text(text: &str) -> Cow<'_, str> {
+    if text.contains(['[', ']']) {
+        Cow::Owned(text.replace('[', "\\[").replace(']', "\\]"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Encloses a Markdown _link destination_ with `<` and `>` if it contains
+/// whitespace or parentheses.
+pub(crate) fn md_dest(dest: &str) -> Cow<'_, str> {
+    if dest.is_empty() || dest.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        Cow::Owned(format!(
+            "<{}>",
+            dest.replace('<', "\\<").replace('>', "\\>")
+        ))
+    } else {
+        Cow::Borrowed(dest)
+    }
+}
+
+/// Renders a Markdown _link title_ with its leading space, if any.
+pub(crate) fn md_title(title: &str) -> String {
+    if title.is_empty() {
+        String::new()
+    } else {
+        format!(r#" "{}""#, title.replace('"', "\\\""))
+    }
+}
+
+/// The default pattern of `text_links2text_writer_with()`.
+pub const TEXT_LINK_PATTERN: &str = "{text} ({dest})";
+
+/// # Plain text renderer
+///
+/// Renders the input as plain text, with every hyperlink rewritten as
+/// `text (dest)`, e.g. to obtain an e-mail friendly version of a Markdown
+/// or reStructuredText document. Images are rendered as `alt (src)`.
+/// Autolinks, whose text is the destination already, are rendered as text
+/// only. Link reference definitions and unresolved references are printed
+/// verbatim.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_links2text;
+///
+/// let i = r#"abc[text1][label1]abc
+/// abc `text2 <dest2>`_ abc
+/// abc <https://getreu.net> abc ![alt3](src3)
+/// [label1]: dest1 "title1"
+/// "#;
+///
+/// let expected = r#"abctext1 (dest1)abc
+/// abc text2 (dest2) abc
+/// abc https://getreu.net abc alt3 (src3)
+/// [label1]: dest1 "title1"
+/// "#;
+/// assert_eq!(text_links2text(i), expected);
+/// ```
+#[inline]
+pub fn text_links2text(input: &str) -> String {
+    let mut output = Vec::new();
+    text_links2text_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// # Plain text renderer
+///
+/// Same as `text_links2text()`, but it uses `Write` for output.
+///
+/// Usage example:
+/// ```no_run
+/// use parse_hyperlinks::renderer::text_links2text_writer;
+/// use std::io;
+/// use std::io::Read;
+/// fn main() -> Result<(), ::std::io::Error> {
+///     let mut stdin = String::new();
+///     Read::read_to_string(&mut io::stdin(), &mut stdin)?;
+///
+///     text_links2text_writer(&stdin, &mut io::stdout())?;
+///
+///     Ok(())
+/// }
+/// ```
+#[inline]
+pub fn text_links2text_writer<W: Write>(input: &str, output: &mut W) -> Result<(), io::Error> {
+    text_links2text_writer_with(input, TEXT_LINK_PATTERN, output)
+}
+
+/// # Plain text renderer
+///
+/// Same as `text_links2text_writer()`, but every hyperlink is rewritten
+/// according to `pattern`. The placeholders `{text}`, `{dest}` and
+/// `{title}` are replaced with the link's text, destination and title.
+///
+/// ```
+/// use parse_hyperlinks::renderer::text_links2text_writer_with;
+///
+/// let i = r#"abc[text1](dest1 "title1")abc"#;
+///
+/// let mut output = Vec::new();
+/// text_links2text_writer_with(i, "{text} <{dest}> ({title})", &mut output).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "abctext1 <dest1> (title1)abc");
+/// ```
+pub fn text_links2text_writer_with<W: Write>(
+    input: &str,
+    pattern: &str,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    let verb_renderer = |verb| verb;
+
+    let link_renderer = |(consumed, link)| match link {
+        Link::Text2Dest(text, dest, title) => text_link(pattern, &text, &dest, &title),
+        Link::Image2Dest(text1, alt, _src, text2, dest, title) => {
+            text_link(pattern, &format!("{text1}{alt}{text2}"), &dest, &title)
+        }
+        Link::Image(alt, src) => text_link(pattern, &alt, &src, ""),
+        _ => format!("{}", consumed),
+    };
+
+    render(input, "", "", verb_renderer, link_renderer, false, output)
+}
+
+/// Fills in the placeholders of `pattern`, see
+/// `text_links2text_writer_with()`. When `text` is the destination
+/// already, only `text` is returned.
+fn text_link(pattern: &str, text: &str, dest: &str, title: &str) -> String {
+    if text == dest || dest.strip_prefix("mailto:") == Some(text) {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let (value, placeholder_len) = if rest.starts_with("{text}") {
+            (text, "{text}".len())
+        } else if rest.starts_with("{dest}") {
+            (dest, "{dest}".len())
+        } else if rest.starts_with("{title}") {
+            (title, "{title}".len())
+        } else {
+            ("{", 1)
+        };
+        out.push_str(value);
+        rest = &rest[placeholder_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// # Hyperlink extractor with CSV output
+///
+/// Extracts the hyperlinks of any supported markup language and renders
+/// them as a table in CSV format, as described in
+/// [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180). The first row holds
+/// the column names `text,dest,title,kind,line`. `kind` is `link` or
+/// `image`, `line` is the number of the line the link starts on, counted
+/// from 1. An image inside a link yields two rows: one for the image and
+/// one for the link.
+///
+/// ```
+/// use parse_hyperlinks::renderer::links2csv;
+///
+/// let i = r#"abc[text1](dest1 "title, 1")abc
+/// abc[![alt2](src2)](dest2)
+/// "#;
+///
+/// let expected = r#"text,dest,title,kind,line
+/// text1,dest1,"title, 1",link,1
+/// alt2,src2,,image,2
+/// alt2,dest2,,link,2
+/// "#;
+/// assert_eq!(links2csv(i), expected);
+/// ```
+#[inline]
+pub fn links2csv(input: &str) -> String {
+    let mut output = Vec::new();
+    links2csv_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// # Hyperlink extractor with CSV output
+///
+/// Same as `links2csv()`, but it uses `Write` for output.
+pub fn links2csv_writer<S: AsRef<str>, W: Write>(
+    input: S,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    links2table_writer(input.as_ref(), ',', output)
+}
+
+/// # Hyperlink extractor with TSV output
+///
+/// Same as `links2csv()`, but the columns are separated by tabs.
+///
+/// ```
+/// use parse_hyperlinks::renderer::links2tsv;
+///
+/// let i = "abc[text1](dest1)abc";
+/// assert_eq!(links2tsv(i), "text\tdest\ttitle\tkind\tline\ntext1\tdest1\t\tlink\t1\n");
+/// ```
+#[inline]
+pub fn links2tsv(input: &str) -> String {
+    let mut output = Vec::new();
+    links2tsv_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// # Hyperlink extractor with TSV output
+///
+/// Same as `links2tsv()`, but it uses `Write` for output.
+pub fn links2tsv_writer<S: AsRef<str>, W: Write>(
+    input: S,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    links2table_writer(input.as_ref(), '\t', output)
+}
+
+/// Writes the links of `input` as table with the columns separated by
+/// `separator`.
+fn links2table_writer<W: Write>(
+    input: &str,
+    separator: char,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    let mut write_row = |row: [&str; 5]| {
+        let row = row
+            .iter()
+            .map(|cell| table_cell(cell, separator))
+            .collect::<Vec<_>>();
+        writeln!(output, "{}", row.join(&separator.to_string()))
+    };
+    write_row(["text", "dest", "title", "kind", "line"])?;
+    for row in link_rows(input, &ParserOptions::default()) {
+        write_row([
+            &row.text,
+            &row.dest,
+            &row.title,
+            row.kind,
+            &row.line.to_string(),
+        ])?;
+    }
+    Ok(())
+}
+
+/// A row of the link table rendered by `links2csv()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRow<'a> {
+    /// The link text or the image's alternative text.
+    pub text: Cow<'a, str>,
+    /// The link destination or the image source.
+    pub dest: Cow<'a, str>,
+    /// The link title, always empty for images.
+    pub title: Cow<'a, str>,
+    /// `link` or `image`.
+    pub kind: &'static str,
+    /// The number of the line the link starts on, counted from 1.
+    pub line: usize,
+}
+
+/// Returns the rows of the link table of `input`, as rendered by
+/// `links2csv()`. Reference links are resolved, link reference definitions
+/// are omitted.
+///
+/// ```
+/// use parse_hyperlinks::parser::parse::ParserOptions;
+/// use parse_hyperlinks::renderer::link_rows;
+///
+/// let i = "abc\n[![alt](src)](dest \"title\")";
+/// let rows = link_rows(i, &ParserOptions::default());
+/// assert_eq!(rows.len(), 2);
+/// assert_eq!((&*rows[0].dest, rows[0].kind, rows[0].line), ("src", "image", 2));
+/// assert_eq!((&*rows[1].text, &*rows[1].title), ("alt", "title"));
+/// ```
+pub fn link_rows<'a>(input: &'a str, options: &ParserOptions) -> Vec<LinkRow<'a>> {
+    let mut rows = Vec::new();
+    // Line number of the last link and its byte offset in `input`.
+    let mut line = 1;
+    let mut line_offset = 0;
+    for ((_, consumed, remaining), link) in MarkupLink::new(input, false).parser_options(*options) {
+        let offset = input.len() - consumed.len() - remaining.len();
+        line += input[line_offset..offset].matches('\n').count();
+        line_offset = offset;
+
+        let image = |alt, src| LinkRow {
+            text: alt,
+            dest: src,
+            title: Cow::Borrowed(""),
+            kind: "image",
+            line,
+        };
+        match link {
+            Link::Text2Dest(text, dest, title) => rows.push(LinkRow {
+                text,
+                dest,
+                title,
+                kind: "link",
+                line,
+            }),
+            Link::Image(alt, src) => rows.push(image(alt, src)),
+            Link::Image2Dest(text1, alt, src, text2, dest, title) => {
+                let text = Cow::Owned(format!("{}{}{}", text1, alt, text2));
+                rows.push(image(alt, src));
+                rows.push(LinkRow {
+                    text,
+                    dest,
+                    title,
+                    kind: "link",
+                    line,
+                });
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Encloses `cell` in double quotes, if it contains `separator`, a double
+/// quote or a line break. Double quotes are doubled.
+///
+/// ```
+/// use parse_hyperlinks::renderer::table_cell;
+///
+/// assert_eq!(table_cell("a, \"b\"", ','), r#""a, ""b""""#);
+/// assert_eq!(table_cell("a, b", '\t'), "a, b");
+/// ```
+pub fn table_cell(cell: &str, separator: char) -> Cow<'_, str> {
+    if cell.contains([separator, '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", cell.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
+
+/// # Hyperlink report
+///
+/// Renders the `analysis::Report` `report` as a self-contained HTML page:
+/// statistics, broken references, broken links, dangling fragments, unused
+/// link reference definitions and the link graph. Every finding shows its
+/// stable identifier, see `analysis::link_id()`.
+///
+/// ```
+/// use parse_hyperlinks::analysis::Report;
+/// use parse_hyperlinks::renderer::report2html;
+///
+/// let report = Report::new(&[("a.md", "[b](b.md) [c][x]")]);
+/// let html = report2html(&report);
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains("<h2>Broken links (1)</h2>"));
+/// assert!(html.contains("<td>b.md</td>"));
+/// ```
+#[inline]
+pub fn report2html(report: &Report) -> String {
+    let mut output = Vec::new();
+    report2html_writer(report, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// # Hyperlink report
+///
+/// Same as `report2html()`, but it uses `Write` for output.
+///
+/// Usage example:
+/// ```no_run
+/// use parse_hyperlinks::analysis::Report;
+/// use parse_hyperlinks::renderer::report2html_writer;
+/// use std::fs::File;
+/// fn main() -> Result<(), ::std::io::Error> {
+///     let report = Report::read(&["README.md", "doc/a.md"])?;
+///     report2html_writer(&report, &mut File::create("report.html")?)?;
+///     Ok(())
+/// }
+/// ```
+pub fn report2html_writer<W: Write>(report: &Report, output: &mut W) -> Result<(), io::Error> {
+    output.write_all(
+        br#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Hyperlink report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+th { background: #eee; }
+td.id { font-family: monospace; color: #666; }
+</style>
+</head>
+<body>
+<h1>Hyperlink report</h1>
+"#,
+    )?;
+
+    write_table(
+        output,
+        "Statistics",
+        &["", ""],
+        [
+            ("Files", report.files.len()),
+            ("Hyperlinks", report.links),
+            ("Images", report.images),
+            ("External destinations", report.external),
+            ("Broken references", report.broken_references.len()),
+            ("Broken links", report.broken_links.len()),
+            ("Dangling fragments", report.dangling_fragments.len()),
+            ("Unused definitions", report.unused_definitions.len()),
+        ]
+        .iter()
+        .map(|(name, n)| vec![name.to_string(), n.to_string()]),
+    )?;
+
+    for (heading, column, findings) in [
+        ("Broken references", "Label", &report.broken_references),
+        ("Broken links", "Destination", &report.broken_links),
+        (
+            "Dangling fragments",
+            "Destination",
+            &report.dangling_fragments,
+        ),
+        ("Unused definitions", "Label", &report.unused_definitions),
+    ] {
+        write_table(
+            output,
+            &format!("{} ({})", heading, findings.len()),
+            &["File", "Span", column, "Id"],
+            findings.iter().map(|(path, s, span)| {
+                vec![
+                    path.display().to_string(),
+                    format!("{}..{}", span.start, span.end),
+                    s.to_string(),
+                    format!("{:016x}", link_id(path, span, s)),
+                ]
+            }),
+        )?;
+    }
+
+    write_table(
+        output,
+        &format!("Link graph ({})", report.graph.len()),
+        &["File", "Links to"],
+        report
+            .graph
+            .iter()
+            .map(|(from, to)| vec![from.display().to_string(), to.display().to_string()]),
+    )?;
+
+    output.write_all(b"</body>\n</html>\n")
+}
+
+/// Writes a section with the heading `heading` and a table with the
+/// header `columns` and the `rows`. Cells are HTML escaped. An empty
+/// header is omitted. Cells of the column `Id` are set in monospace.
+fn write_table<W: Write>(
+    output: &mut W,
+    heading: &str,
+    columns: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> Result<(), io::Error> {
+    writeln!(output, "<h2>{}</h2>", encode_text(heading))?;
+    let mut rows = rows.peekable();
+    if rows.peek().is_none() {
+        return output.write_all(b"<p>None.</p>\n");
+    }
+
+    output.write_all(b"<table>\n")?;
+    if columns.iter().any(|c| !c.is_empty()) {
+        output.write_all(b"<tr>")?;
+        for column in columns {
+            write!(output, "<th>{}</th>", encode_text(column))?;
+        }
+        output.write_all(b"</tr>\n")?;
+    }
+    for row in rows {
+        output.write_all(b"<tr>")?;
+        for (n, cell) in row.iter().enumerate() {
+            if columns[n] == "Id" {
+                write!(output, r#"<td class="id">{}</td>"#, encode_text(cell))?;
+            } else {
+                write!(output, "<td>{}</td>", encode_text(cell))?;
+            }
+        }
+        output.write_all(b"</tr>\n")?;
+    }
+    output.write_all(b"</table>\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -936,4 +2399,395 @@ abc[label3]abc[label4]abc
         //eprintln!("{}", res);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_links2markdown() {
+        let i = r#"abc[text1][label1]abc
+abc [text2](destination2 "title2")
+  [label1]: destination1 "title1"
+abc[label3]abc
+![](<src (4)>)
+"#;
+
+        let expected = r#"- [text1](destination1 "title1")
+- [text2](destination2 "title2")
+- ![](<src (4)>)
+"#;
+        assert_eq!(links2markdown(i), expected);
+        assert_eq!(links2markdown("abc"), "");
+
+        assert_eq!(md_text("a[b]"), r"a\[b\]");
+        assert_eq!(md_dest(""), "<>");
+        assert_eq!(md_dest("a<b> c"), r"<a\<b\> c>");
+        assert_eq!(md_title(""), "");
+    }
+
+    #[test]
+    fn test_text_links2text() {
+        let i = "abc[text1](<dest 1>)abc\n[![alt2](src2)](dest2)\nabc[text3][label3]abc";
+        assert_eq!(
+            text_links2text(i),
+            "abctext1 (dest 1)abc\nalt2 (dest2)\nabc[text3][label3]abc"
+        );
+        assert_eq!(text_links2text("abc"), "abc");
+
+        assert_eq!(text_link("{dest}{ {text}", "{dest}", "d", ""), "d{ {dest}");
+        assert_eq!(text_link("[{title}]", "t", "d", "ti"), "[ti]");
+        assert_eq!(
+            text_link("{text} ({dest})", "a@b.c", "mailto:a@b.c", ""),
+            "a@b.c"
+        );
+    }
+
+    #[test]
+    fn test_links2csv() {
+        let i = "abc[text1][label1]abc\n\n[label1]: dest1 (title \"1\")\nabc\n![alt\n2](src2)";
+        let expected = "text,dest,title,kind,line\n\
+            text1,dest1,\"title \"\"1\"\"\",link,1\n\
+            \"alt\n2\",src2,,image,5\n";
+        assert_eq!(links2csv(i), expected);
+        assert_eq!(links2csv(""), "text,dest,title,kind,line\n");
+
+        assert_eq!(table_cell("a,b", '\t'), "a,b");
+        assert_eq!(table_cell("a\tb", '\t'), "\"a\tb\"");
+    }
+
+    #[test]
+    fn test_text_links2html_writer_with() {
+        let dir = std::env::temp_dir().join(format!("parse-hyperlinks-r-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a b.png"), "").unwrap();
+
+        let i = r#"![alt1](a%20b.png) [![alt2](c"d.png)](dest2)"#;
+        let render = |options: &RendererOptions| {
+            let mut output = Vec::new();
+            text_links2html_writer_with(i, options, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        // Without placeholder, nothing is checked.
+        assert_eq!(render(&RendererOptions::default()), text_links2html(i));
+
+        let options = RendererOptions {
+            broken_image_placeholder: Some("broken.svg".to_string()),
+            base_dir: dir.clone(),
+            ..RendererOptions::default()
+        };
+        assert_eq!(
+            render(&options),
+            r#"<pre><img src="a%20b.png" alt="alt1"> <a href="dest2" title=""><img alt="alt2" src="broken.svg" title="c&quot;d.png"></a></pre>"#
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let options = RendererOptions {
+            target: Some("_blank".to_string()),
+            class: Some(r#"a"b"#.to_string()),
+            ..RendererOptions::default()
+        };
+        assert_eq!(
+            render(&options),
+            r#"<pre><img src="a%20b.png" alt="alt1"> <a href="dest2" title="" class="a&quot;b" target="_blank"><img alt="alt2" src="c&quot;d.png"></a></pre>"#
+        );
+    }
+
+    #[test]
+    fn test_allowed_schemes() {
+        let options = RendererOptions {
+            allowed_schemes: Some(SAFE_SCHEMES.iter().map(|s| s.to_string()).collect()),
+            ..RendererOptions::default()
+        };
+        let i = r#"[a](JavaScript:alert(1)) <a href="jav&#x09;ascript:alert(2)">b</a> [c](HTTPS://c) [d](d.md) ![e](data:image/png;base64,AA)"#;
+
+        let mut output = Vec::new();
+        text_links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"<pre>[a](JavaScript:alert(1)) &lt;a href="jav&amp;#x09;ascript:alert(2)"&gt;b&lt;/a&gt; <a href="HTTPS://c" title="">c</a> <a href="d.md" title="">d</a> ![e](data:image/png;base64,AA)</pre>"#
+        );
+
+        let mut output = Vec::new();
+        links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<a href=\"HTTPS://c\" title=\"\">c</a><br>\n<a href=\"d.md\" title=\"\">d</a><br>\n"
+        );
+
+        // Without allow-list, everything is rendered.
+        assert_eq!(links2html(i).matches("<br>").count(), 5);
+    }
+
+    #[test]
+    fn test_allowed_schemes_link_text() {
+        let options = RendererOptions {
+            allowed_schemes: Some(SAFE_SCHEMES.iter().map(|s| s.to_string()).collect()),
+            ..RendererOptions::default()
+        };
+        let i = "[<img src=x onerror=alert(1)>](https://a) [<b>c</b>![d](e)<i>f</i>](https://g)";
+
+        let mut output = Vec::new();
+        text_links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<pre><a href=\"https://a\" title=\"\">&lt;img src=x onerror=alert(1)&gt;</a> \
+             <a href=\"https://g\" title=\"\">&lt;b&gt;c&lt;/b&gt;<img alt=\"d\" src=\"e\">\
+             &lt;i&gt;f&lt;/i&gt;</a></pre>"
+        );
+
+        let mut output = Vec::new();
+        links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<a href=\"https://a\" title=\"\">&lt;img src=x onerror=alert(1)&gt;</a><br>\n\
+             <a href=\"https://g\" title=\"\">&lt;b&gt;c&lt;/b&gt;[d]&lt;i&gt;f&lt;/i&gt;</a><br>\n"
+        );
+
+        // Without allow-list, the link text is rendered as is.
+        assert!(links2html(i).contains("<img src=x onerror=alert(1)>"));
+    }
+
+    #[test]
+    fn test_dest_filter() {
+        let options = RendererOptions {
+            dest_filter: Some(DestFilter::new(|dest| dest.ends_with(".md"))),
+            ..RendererOptions::default()
+        };
+        let i = "[a](a.md) [b](b.html) [![c](c.png)](c.md) ![d](d.png) [e][x]";
+
+        let mut output = Vec::new();
+        text_rawlinks2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<pre><a href=\"a.md\" title=\"\">[a](a.md)</a> [b](b.html) \
+             <a href=\"c.md\" title=\"\">[![c](c.png)](c.md)</a> ![d](d.png) [e][x]</pre>"
+        );
+        assert_eq!(options, options.clone());
+        assert_ne!(
+            options,
+            RendererOptions {
+                dest_filter: Some(DestFilter::new(|_| true)),
+                ..RendererOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let stream = |i: &[u8], chunk_size: usize, raw: bool| {
+            let options = RendererOptions {
+                line_anchors: true,
+                ..RendererOptions::default()
+            };
+            let mut output = Vec::new();
+            if raw {
+                text_rawlinks2html_stream_with(i, &options, chunk_size, &mut output)?;
+            } else {
+                text_links2html_stream_with(i, &options, chunk_size, &mut output)?;
+            }
+            Ok::<_, io::Error>(String::from_utf8(output).unwrap())
+        };
+        let with = |i: &str, raw: bool| {
+            let options = RendererOptions {
+                line_anchors: true,
+                ..RendererOptions::default()
+            };
+            let mut output = Vec::new();
+            if raw {
+                text_rawlinks2html_writer_with(i, &options, &mut output).unwrap();
+            } else {
+                text_links2html_writer_with(i, &options, &mut output).unwrap();
+            }
+            String::from_utf8(output).unwrap()
+        };
+
+        let i = "---\ntitle: a\n---\n[b](c)\n---\nd: [e]\n\n[e]: f\n";
+        for raw in [false, true] {
+            // One chunk renders like the input as a whole.
+            assert_eq!(stream(i.as_bytes(), 1000, raw).unwrap(), with(i, raw));
+            // A chunk size of 0 is treated like 1.
+            assert_eq!(
+                stream(i.as_bytes(), 0, raw).unwrap(),
+                stream(i.as_bytes(), 1, raw).unwrap()
+            );
+            assert_eq!(
+                stream(b"", 10, raw).unwrap(),
+                "<pre><span id=\"L1\"></span></pre>"
+            );
+            assert_eq!(
+                stream(b"a\n\xff", 1, raw).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+        // Only the first chunk's front matter is skipped and references
+        // resolve within a chunk.
+        let output = stream(i.as_bytes(), 1, false).unwrap();
+        assert!(output.contains("<a href=\"c\" title=\"\">b</a>"));
+        assert!(output.contains("---\n<span id=\"L6\"></span>d: [e]"));
+        assert!(output.contains("<span id=\"L9\"></span></pre>"));
+        // The chunk ends after the line exceeding the chunk size.
+        assert_eq!(
+            stream(b"[a](b)\n[c](d)", 3, false).unwrap(),
+            with("[a](b)\n[c](d)", false)
+        );
+    }
+
+    #[test]
+    fn test_source_map() {
+        let render = |i: &str, options: &RendererOptions| {
+            let mut output = Vec::new();
+            let map = text_links2html_source_map(i, options, &mut output).unwrap();
+            (String::from_utf8(output).unwrap(), map)
+        };
+
+        let (output, map) = render("", &RendererOptions::default());
+        assert_eq!(output, "<pre></pre>");
+        assert!(map.segments.is_empty());
+        assert_eq!(map.input_span(0), None);
+
+        let i = "---\ntitle: a\n---\n[b](c)\nd";
+        let options = RendererOptions {
+            line_anchors: true,
+            ..RendererOptions::default()
+        };
+        let (output, map) = render(i, &options);
+        assert_eq!(map.segments.len(), 3);
+        for (out, inp) in &map.segments {
+            assert_eq!(
+                output[out.clone()].contains("href"),
+                i[inp.clone()].contains("](")
+            );
+        }
+        assert_eq!(map.segments[0].1, 0..17);
+        assert_eq!(map.segments[2].1, 23..i.len());
+        assert!(output[map.segments[2].0.clone()].contains(r#"<span id="L5"></span>d"#));
+    }
+
+    #[test]
+    fn test_email_links() {
+        let i = "<a@b.c> [d](mailto:d@e.f) [g](g@h.i) `j <j@k.l>`_ [m](m@n.png/x) <o@p>";
+        let render = |options: &RendererOptions| {
+            let mut output = Vec::new();
+            links2html_writer_with(i, options, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(
+            render(&RendererOptions::default()),
+            "<a href=\"mailto:a@b.c\" title=\"\">a@b.c</a><br>\n\
+             <a href=\"mailto:d@e.f\" title=\"\">d</a><br>\n\
+             <a href=\"mailto:g@h.i\" title=\"\">g</a><br>\n\
+             <a href=\"mailto:j@k.l\" title=\"\">j</a><br>\n\
+             <a href=\"m@n.png/x\" title=\"\">m</a><br>\n\
+             <a href=\"mailto:o@p\" title=\"\">o@p</a><br>\n"
+        );
+        let options = RendererOptions {
+            email_links: EmailLinks::Verbatim,
+            ..RendererOptions::default()
+        };
+        assert_eq!(
+            render(&options),
+            "<a href=\"a@b.c\" title=\"\">a@b.c</a><br>\n\
+             <a href=\"mailto:d@e.f\" title=\"\">d</a><br>\n\
+             <a href=\"g@h.i\" title=\"\">g</a><br>\n\
+             <a href=\"j@k.l\" title=\"\">j</a><br>\n\
+             <a href=\"m@n.png/x\" title=\"\">m</a><br>\n\
+             <a href=\"o@p\" title=\"\">o@p</a><br>\n"
+        );
+
+        // The scheme filter sees the rendered destination.
+        let options = RendererOptions {
+            allowed_schemes: Some(vec!["https".to_string()]),
+            ..RendererOptions::default()
+        };
+        let mut output = Vec::new();
+        text_rawlinks2html_writer_with("[g](g@h.i)", &options, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "<pre>[g](g@h.i)</pre>");
+
+        assert!(is_email_address("a.b+c@d-e.f"));
+        assert!(!is_email_address("a@b..c"));
+        assert!(!is_email_address("a/b@c"));
+        assert!(!is_email_address("@b"));
+        assert!(!is_email_address("a@"));
+        assert_eq!(EmailLinks::default(), EmailLinks::Mailto);
+        let custom = EmailLinks::custom(|a| a.to_uppercase());
+        assert_eq!(custom, custom.clone());
+        assert_ne!(custom, EmailLinks::custom(|a| a.to_uppercase()));
+        assert_eq!(format!("{:?}", custom), "Custom(..)");
+    }
+
+    #[test]
+    fn test_line_anchors() {
+        let options = RendererOptions {
+            line_anchors: true,
+            ..RendererOptions::default()
+        };
+        let i = "abc[text1](dest1\n\"title1\")abc\n[text2\ntext2](dest2)\nabc";
+
+        let mut output = Vec::new();
+        text_links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<pre><span id=\"L1\"></span>abc<a href=\"dest1\" title=\"title1\">text1</a>abc\n\
+             <span id=\"L3\"></span><a href=\"dest2\" title=\"\">text2\ntext2</a>\n\
+             <span id=\"L5\"></span>abc</pre>"
+        );
+
+        let mut output = Vec::new();
+        text_rawlinks2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<pre><span id=\"L1\"></span>abc<a href=\"dest1\" title=\"title1\">[text1](dest1\n\
+             <span id=\"L2\"></span>\"title1\")</a>abc\n\
+             <span id=\"L3\"></span><a href=\"dest2\" title=\"\">[text2\n\
+             <span id=\"L4\"></span>text2](dest2)</a>\n\
+             <span id=\"L5\"></span>abc</pre>"
+        );
+
+        // Without line anchors.
+        let mut output = Vec::new();
+        text_rawlinks2html_writer_with(i, &RendererOptions::default(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), text_rawlinks2html(i));
+    }
+
+    #[test]
+    fn test_highlight_broken_references() {
+        let options = RendererOptions {
+            highlight_broken_references: true,
+            ..RendererOptions::default()
+        };
+        let i = "abc[text1][label1]abc[text2][label2]abc![alt3][label3]\n[label1]: dest1\n";
+
+        let mut output = Vec::new();
+        text_links2html_writer_with(i, &options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<pre>abc<a href=\"dest1\" title=\"\">text1</a>abc\
+             <span class=\"broken-link\">[text2][label2]</span>abc\
+             <span class=\"broken-link\">![alt3][label3]</span>\n\
+             <a href=\"dest1\" title=\"\">[label1]: dest1</a>\n</pre>"
+        );
+
+        let mut output = Vec::new();
+        text_rawlinks2html_writer_with(i, &options, &mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains(r#"abc<span class="broken-link">[text2][label2]</span>abc"#));
+
+        // By default, broken references are verbatim text.
+        assert!(text_links2html(i).contains("abc[text2][label2]abc"));
+    }
+
+    #[test]
+    fn test_report2html() {
+        let report = Report::new(&[("a<b.md", "[x](m&n.md) [y][y]\n")]);
+        let html = report2html(&report);
+        assert!(html.contains("<tr><td>Files</td><td>1</td></tr>"));
+        assert!(html.contains("<h2>Broken links (1)</h2>"));
+        assert!(html.contains(&format!(
+            r#"<tr><td>a&lt;b.md</td><td>0..11</td><td>m&amp;n.md</td><td class="id">{:016x}</td></tr>"#,
+            link_id(std::path::Path::new("a<b.md"), &(0..11), "m&n.md")
+        )));
+        assert!(html.contains("<h2>Broken references (1)</h2>"));
+        assert!(html.contains("<h2>Dangling fragments (0)</h2>\n<p>None.</p>"));
+        assert!(html.ends_with("</html>\n"));
+    }
 }
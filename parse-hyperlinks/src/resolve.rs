@@ -0,0 +1,199 @@
+//! Module resolving relative link destinations against a base.
+//!
+//! The base is the location of the document containing the links: either
+//! an absolute URL (`https://getreu.net/doc/a.html`) or a file system path
+//! (`doc/a.md`). In both cases, relative destinations are joined with the
+//! base's directory, i.e. everything up to its last `/`, and `.` and `..`
+//! segments are removed as described in
+//! [RFC 3986, section 5.2](https://www.rfc-editor.org/rfc/rfc3986#section-5.2).
+
+use crate::parser::Link;
+use crate::url::authority;
+use crate::url::scheme;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// Resolves the link destination `dest` found in the document located at
+/// `base`. Destinations with a URL scheme are returned unchanged, so are
+/// network-path references (`//host/path`) when `base` is not a URL.
+///
+/// ```
+/// use parse_hyperlinks::resolve::resolve;
+/// use std::borrow::Cow;
+///
+/// let base = "https://getreu.net/doc/a.html";
+/// assert_eq!(resolve(base, Cow::from("../img/a.png")), "https://getreu.net/img/a.png");
+/// assert_eq!(resolve(base, Cow::from("#sec")), "https://getreu.net/doc/a.html#sec");
+/// assert_eq!(resolve("doc/a.md", Cow::from("./b.md#sec")), "doc/b.md#sec");
+/// assert_eq!(resolve("doc/a.md", Cow::from("../../b.md")), "../b.md");
+/// assert_eq!(resolve("doc/a.md", Cow::from("mailto:a@b.c")), "mailto:a@b.c");
+/// ```
+pub fn resolve<'a>(base: &str, dest: Cow<'a, str>) -> Cow<'a, str> {
+    match join(base, &dest) {
+        Some(dest) => Cow::Owned(dest),
+        None => dest,
+    }
+}
+
+/// Resolves the link destinations and image sources of `link` with
+/// `resolve()`. Other link types are returned unchanged. Map the items of
+/// `iterator::MarkupLink` with this function to obtain absolute
+/// destinations.
+///
+/// ```
+/// use parse_hyperlinks::iterator::MarkupLink;
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::resolve::resolve_link;
+/// use std::borrow::Cow;
+///
+/// let i = "abc[text](../b.md)abc![alt](img/c.png)";
+/// let mut iter = MarkupLink::new(i, false).map(|(_, l)| resolve_link("doc/a/a.md", l));
+/// assert_eq!(iter.next(), Some(Link::Text2Dest(Cow::from("text"), Cow::from("doc/b.md"), Cow::from(""))));
+/// assert_eq!(iter.next(), Some(Link::Image(Cow::from("alt"), Cow::from("doc/a/img/c.png"))));
+/// ```
+pub fn resolve_link<'a>(base: &str, link: Link<'a>) -> Link<'a> {
+    match link {
+        Link::Text2Dest(te, de, ti) => Link::Text2Dest(te, resolve(base, de), ti),
+        Link::Label2Dest(la, de, ti) => Link::Label2Dest(la, resolve(base, de), ti),
+        Link::TextLabel2Dest(tl, de, ti) => Link::TextLabel2Dest(tl, resolve(base, de), ti),
+        Link::Image(alt, src) => Link::Image(alt, resolve(base, src)),
+        Link::Image2Dest(te1, alt, src, te2, de, ti) => {
+            Link::Image2Dest(te1, alt, resolve(base, src), te2, resolve(base, de), ti)
+        }
+        l => l,
+    }
+}
+
+/// Joins the link destination `dest` with `base`. Returns `None` if `dest`
+/// is already absolute: it has a URL scheme, or it is a network-path
+/// reference and `base` is not a URL.
+pub(crate) fn join(base: &str, dest: &str) -> Option<String> {
+    if scheme(dest).is_some() {
+        return None;
+    }
+    // `base` without fragment.
+    let base = base.split('#').next().unwrap_or_default();
+    // Split `base` into `(scheme://authority, path)`.
+    let (prefix, base_path) = match authority(base) {
+        Some(authority) => {
+            let prefix_len = base.find("//").unwrap_or_default() + "//".len() + authority.len();
+            base.split_at(prefix_len)
+        }
+        None => ("", base),
+    };
+
+    if let Some(dest) = dest.strip_prefix("//") {
+        return match scheme(base) {
+            Some(scheme) if !prefix.is_empty() => Some(format!("{scheme}://{dest}")),
+            _ => None,
+        };
+    }
+    if dest.is_empty() || dest.starts_with('#') {
+        return Some(format!("{base}{dest}"));
+    }
+    if dest.starts_with('?') {
+        let base = base.split('?').next().unwrap_or_default();
+        return Some(format!("{base}{dest}"));
+    }
+
+    let base_path = base_path.split('?').next().unwrap_or_default();
+    let path = if dest.starts_with('/') {
+        dest.to_string()
+    } else {
+        match base_path.rfind('/') {
+            Some(n) => format!("{}{dest}", &base_path[..=n]),
+            // The path of a URL is absolute.
+            None if !prefix.is_empty() => format!("/{dest}"),
+            None => dest.to_string(),
+        }
+    };
+
+    let (path, query) = match path.find(['?', '#']) {
+        Some(n) => path.split_at(n),
+        None => (path.as_str(), ""),
+    };
+    Some(format!("{prefix}{}{query}", remove_dot_segments(path)))
+}
+
+/// Removes `.` and `..` segments from `path`. In absolute paths, `..`
+/// segments above the root are dropped, in relative paths they are kept.
+fn remove_dot_segments(path: &str) -> String {
+    let is_absolute = path.starts_with('/');
+    let mut segments: Vec<&str> = Vec::new();
+    let mut it = path.split('/').skip(is_absolute as usize).peekable();
+    while let Some(segment) = it.next() {
+        match segment {
+            "." => {}
+            ".." if segments.last().is_some_and(|s| *s != "..") => {
+                segments.pop();
+            }
+            ".." if is_absolute => {}
+            s => {
+                segments.push(s);
+                continue;
+            }
+        }
+        // A path ending with a dot segment denotes a directory.
+        if it.peek().is_none() {
+            segments.push("");
+        }
+    }
+
+    let path = segments.join("/");
+    match (is_absolute, path.is_empty()) {
+        (true, _) => format!("/{path}"),
+        (false, true) => "./".to_string(),
+        (false, false) => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let base = "http://a.b/c/d.html?q#f";
+        assert_eq!(join(base, "e.html").unwrap(), "http://a.b/c/e.html");
+        assert_eq!(join(base, "./e.html").unwrap(), "http://a.b/c/e.html");
+        assert_eq!(join(base, "../e.html#g").unwrap(), "http://a.b/e.html#g");
+        assert_eq!(join(base, "../../e.html").unwrap(), "http://a.b/e.html");
+        assert_eq!(join(base, "..").unwrap(), "http://a.b/");
+        assert_eq!(join(base, "/x/./y/").unwrap(), "http://a.b/x/y/");
+        assert_eq!(join(base, "#g").unwrap(), "http://a.b/c/d.html?q#g");
+        assert_eq!(join(base, "?r").unwrap(), "http://a.b/c/d.html?r");
+        assert_eq!(join(base, "//x.y/z").unwrap(), "http://x.y/z");
+        assert_eq!(join(base, "mailto:a@b.c"), None);
+        assert_eq!(join("http://a.b", "c").unwrap(), "http://a.b/c");
+        assert_eq!(join("http://a.b?q", "c").unwrap(), "http://a.b/c");
+    }
+
+    #[test]
+    fn test_join_path() {
+        let base = "doc/a.md";
+        assert_eq!(join(base, "b.md").unwrap(), "doc/b.md");
+        assert_eq!(join(base, "./img/../b.md#sec").unwrap(), "doc/b.md#sec");
+        assert_eq!(join(base, "../../b.md").unwrap(), "../b.md");
+        assert_eq!(join(base, "..").unwrap(), "./");
+        assert_eq!(join(base, "/b.md").unwrap(), "/b.md");
+        assert_eq!(join(base, "#sec").unwrap(), "doc/a.md#sec");
+        assert_eq!(join(base, "?q").unwrap(), "doc/a.md?q");
+        assert_eq!(join(base, "//x.y/z"), None);
+        assert_eq!(join("a.md", "b/c.md").unwrap(), "b/c.md");
+        assert_eq!(join("doc/", "b.md").unwrap(), "doc/b.md");
+        assert_eq!(join("/a/a.md", "../../b.md").unwrap(), "/b.md");
+    }
+
+    #[test]
+    fn test_remove_dot_segments() {
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6");
+        assert_eq!(remove_dot_segments("../a/../../b"), "../../b");
+        assert_eq!(remove_dot_segments("a/."), "a/");
+        assert_eq!(remove_dot_segments("."), "./");
+        assert_eq!(remove_dot_segments("/.."), "/");
+    }
+}
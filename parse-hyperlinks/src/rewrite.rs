@@ -0,0 +1,1166 @@
+//! Module rewriting the link destinations of a document in place.
+//!
+//! The input is scanned with `parser::parse::take_link()`. Every link
+//! destination and image source is passed to a callback, which may replace
+//! it, e.g. to swap domains, to add query parameters or to convert `.md`
+//! into `.html` destinations. The document is written back in its original
+//! markup: apart from the replaced destinations, the output is identical to
+//! the input. `convert_links()` translates the link syntax of one markup
+//! language into another in the same way. `inline2reference()` turns the
+//! inline links of a Markdown document into reference links,
+//! `reference2inline()` does the reverse. `rename_label()` renames a link
+//! label at its definition and all its references.
+
+use crate::iterator::normalize_label;
+use crate::iterator::LabelMap;
+use crate::parser::asciidoc::adoc_img_link;
+use crate::parser::asciidoc::adoc_label2dest_link;
+use crate::parser::asciidoc::adoc_text2dest_link;
+use crate::parser::asciidoc::adoc_text2label_link;
+use crate::parser::html::html_text2dest_link;
+use crate::parser::html_img::html_img2dest_link;
+use crate::parser::html_img::html_img_link;
+use crate::parser::markdown::md_label2dest_link;
+use crate::parser::markdown::md_link_destination_enclosed;
+use crate::parser::markdown::md_text2dest_link;
+use crate::parser::markdown::md_text2label_link;
+use crate::parser::markdown_img::md_img2dest_link;
+use crate::parser::markdown_img::md_img2label_link;
+use crate::parser::markdown_img::md_img_link;
+use crate::parser::org::org_name_link;
+use crate::parser::org::org_target_link;
+use crate::parser::org::org_text2dest_link;
+use crate::parser::org::org_text2label_link;
+use crate::parser::parse::take_link;
+use crate::parser::parse::take_link_lang;
+use crate::parser::restructured_text::rst_label2dest_link;
+use crate::parser::restructured_text::rst_label2label_link;
+use crate::parser::restructured_text::rst_text2dest_link;
+use crate::parser::restructured_text::rst_text2label_link;
+use crate::parser::restructured_text::rst_text_label2dest_link;
+use crate::parser::wikitext::wikitext_text2dest_link;
+use crate::parser::Link;
+use crate::parser::MarkupLang;
+use crate::renderer::md_dest;
+use crate::renderer::md_text;
+use crate::renderer::md_title;
+use crate::take_until_unbalanced;
+use html_escape::encode_double_quoted_attribute;
+use html_escape::encode_text;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use thiserror::Error;
+
+/// Rewrites the link destinations and image sources of `input` with
+/// `rewriter`. When `rewriter` returns `None`, the destination is kept,
+/// otherwise it is replaced verbatim by the returned string, which must be
+/// valid in the markup at hand, i.e. escaped and quoted as needed.
+///
+/// Only destinations appearing literally in the input are passed to
+/// `rewriter`. Destinations the parser had to decode (e.g. containing escaped
+/// characters or email addresses, which are prefixed with `mailto:`), empty
+/// destinations and the targets of reference links are left unchanged.
+///
+/// ```
+/// use parse_hyperlinks::rewrite::rewrite;
+///
+/// let i = r#"abc[text1](doc/a.md "title1")abc
+/// abc<a href="https://old.net/b.md">text2</a>abc
+/// abc![alt3](img/c.png)
+/// [label4]: d.md
+/// "#;
+///
+/// let expected = r#"abc[text1](doc/a.html "title1")abc
+/// abc<a href="https://new.net/b.html">text2</a>abc
+/// abc![alt3](img/c.png)
+/// [label4]: d.html
+/// "#;
+///
+/// let res = rewrite(i, |dest| {
+///     let dest = dest.replace("https://old.net/", "https://new.net/");
+///     dest.strip_suffix(".md").map(|d| format!("{d}.html"))
+/// });
+/// assert_eq!(res, expected);
+/// ```
+#[inline]
+pub fn rewrite<F>(input: &str, rewriter: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = Vec::new();
+    rewrite_writer(input, rewriter, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// Same as `rewrite()`, but it uses `Write` for output.
+///
+/// Usage example:
+/// ```no_run
+/// use parse_hyperlinks::rewrite::rewrite_writer;
+/// use std::io;
+/// use std::io::Read;
+/// fn main() -> Result<(), ::std::io::Error> {
+///     let mut stdin = String::new();
+///     Read::read_to_string(&mut io::stdin(), &mut stdin)?;
+///
+///     rewrite_writer(
+///         &stdin,
+///         |dest| dest.strip_suffix(".md").map(|d| format!("{d}.html")),
+///         &mut io::stdout(),
+///     )?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn rewrite_writer<F, W>(input: &str, mut rewriter: F, output: &mut W) -> Result<(), io::Error>
+where
+    F: FnMut(&str) -> Option<String>,
+    W: Write,
+{
+    // Byte offset of the input not written yet.
+    let mut pos = 0;
+    let mut i = input;
+    while let Ok((j, (skipped, link))) = take_link(i) {
+        let consumed_start = input.len() - i.len() + skipped.len();
+        let consumed_end = input.len() - j.len();
+
+        // The destinations borrowing from the consumed input, in input order.
+        let mut dests: Vec<(usize, &str)> = destinations(&link)
+            .into_iter()
+            .filter_map(|dest| match dest {
+                Cow::Borrowed(dest) if !dest.is_empty() => {
+                    let offset = (dest.as_ptr() as usize).checked_sub(input.as_ptr() as usize)?;
+                    (offset >= consumed_start && offset + dest.len() <= consumed_end)
+                        .then_some((offset, *dest))
+                }
+                _ => None,
+            })
+            .collect();
+        dests.sort_unstable();
+
+        for (offset, dest) in dests {
+            if offset < pos {
+                continue;
+            }
+            if let Some(new_dest) = rewriter(dest) {
+                output.write_all(&input.as_bytes()[pos..offset])?;
+                output.write_all(new_dest.as_bytes())?;
+                pos = offset + dest.len();
+            }
+        }
+        i = j;
+    }
+    output.write_all(&input.as_bytes()[pos..])?;
+    Ok(())
+}
+
+/// Rewrites the hyperlinks of `input` written in the markup language `from`
+/// into the syntax of the markup language `to`. The text around the links
+/// is left untouched, so are links of other markup languages and links
+/// without equivalent in `to`, e.g. reference aliases in Markdown or
+/// images in reStructuredText. When converted to a markup language
+/// without link titles, the title is dropped. A reStructuredText
+/// link defining a label at the same time (`Link::TextLabel2Dest`) is
+/// converted into an inline link, references to its label are not resolved.
+///
+/// ```
+/// use parse_hyperlinks::parser::MarkupLang;
+/// use parse_hyperlinks::rewrite::convert_links;
+///
+/// let i = r#"abc `text1 <dest1>`__ abc
+/// abc `text2`_ abc `text3 <dest3>`_ abc
+///
+/// .. _text2: dest2
+/// "#;
+///
+/// let expected = r#"abc [text1](dest1) abc
+/// abc [text2] abc [text3](dest3) abc
+///
+/// [text2]: dest2
+/// "#;
+/// assert_eq!(convert_links(i, MarkupLang::Rst, MarkupLang::Markdown), expected);
+/// ```
+#[inline]
+pub fn convert_links(input: &str, from: MarkupLang, to: MarkupLang) -> String {
+    let mut output = Vec::new();
+    convert_links_writer(input, from, to, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// Same as `convert_links()`, but it uses `Write` for output.
+///
+/// Usage example:
+/// ```no_run
+/// use parse_hyperlinks::parser::MarkupLang;
+/// use parse_hyperlinks::rewrite::convert_links_writer;
+/// use std::io;
+/// use std::io::Read;
+/// fn main() -> Result<(), ::std::io::Error> {
+///     let mut stdin = String::new();
+///     Read::read_to_string(&mut io::stdin(), &mut stdin)?;
+///
+///     convert_links_writer(&stdin, MarkupLang::Rst, MarkupLang::Markdown, &mut io::stdout())?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn convert_links_writer<W: Write>(
+    input: &str,
+    from: MarkupLang,
+    to: MarkupLang,
+    output: &mut W,
+) -> Result<(), io::Error> {
+    let mut i = input;
+    while let Ok((j, (skipped, link, lang))) = take_link_lang(i) {
+        output.write_all(skipped.as_bytes())?;
+        let consumed = &i[skipped.len()..i.len() - j.len()];
+        // Label definitions may be indented, keep the indent.
+        let source = consumed.trim_start_matches([' ', '\t']);
+        let indent = &consumed[..consumed.len() - source.len()];
+
+        // `take_link_lang()` may consume more than the link itself, so we
+        // parse the link again to find its end.
+        let converted = match parse_link_as(from, source) {
+            Ok((rest, l)) if lang == from && l == link => {
+                to_markup(&link, to).map(|markup| (markup, rest))
+            }
+            _ => None,
+        };
+        match converted {
+            Some((markup, rest)) => {
+                output.write_all(indent.as_bytes())?;
+                output.write_all(markup.as_bytes())?;
+                output.write_all(rest.as_bytes())?;
+            }
+            None => output.write_all(consumed.as_bytes())?,
+        }
+        i = j;
+    }
+    output.write_all(i.as_bytes())?;
+    Ok(())
+}
+
+/// Rewrites the Markdown inline links and inline images of `input` into
+/// reference links and reference images. The link reference definitions are
+/// appended to the end of the document. Links with the same destination and
+/// title share one definition. When `input` already defines a label for a
+/// destination and title, it is reused, otherwise labels are numbered
+/// `1`, `2`, ... skipping the labels in use. Autolinks and links of other
+/// markup languages are left untouched.
+///
+/// ```
+/// use parse_hyperlinks::rewrite::inline2reference;
+///
+/// let i = r#"abc [*text1*](dest1 "title1") abc ![alt2](dest2) [text3](dest1 "title1")
+/// abc [text4](dest4) abc
+///
+/// [1]: dest0
+/// [label4]: dest4
+/// "#;
+///
+/// let expected = r#"abc [*text1*][2] abc ![alt2][3] [text3][2]
+/// abc [text4][label4] abc
+///
+/// [1]: dest0
+/// [label4]: dest4
+///
+/// [2]: dest1 "title1"
+/// [3]: dest2
+/// "#;
+/// assert_eq!(inline2reference(i), expected);
+/// ```
+#[inline]
+pub fn inline2reference(input: &str) -> String {
+    let mut output = Vec::new();
+    inline2reference_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// Same as `inline2reference()`, but it uses `Write` for output.
+pub fn inline2reference_writer<W: Write>(input: &str, output: &mut W) -> Result<(), io::Error> {
+    // Existing definitions: key: `(link_destination, link_title)`, value: label.
+    let mut labels: HashMap<(String, String), String> = HashMap::new();
+    let mut i = input;
+    while let Ok((j, (_, link, lang))) = take_link_lang(i) {
+        if let (MarkupLang::Markdown, Link::Label2Dest(la, de, ti)) = (lang, link) {
+            labels
+                .entry((de.to_string(), ti.to_string()))
+                .or_insert_with(|| la.to_string());
+        }
+        i = j;
+    }
+    let label_map = LabelMap::from(input);
+    let mut new_definitions = Vec::new();
+    let mut counter = 0;
+    let mut label = |dest: &str, title: &str| {
+        labels
+            .entry((dest.to_string(), title.to_string()))
+            .or_insert_with(|| {
+                let label = loop {
+                    counter += 1;
+                    let label = counter.to_string();
+                    if label_map.get(&label).is_none() {
+                        break label;
+                    }
+                };
+                new_definitions.push(format!(
+                    "[{}]: {}{}\n",
+                    label,
+                    md_dest(dest),
+                    md_title(title)
+                ));
+                label
+            })
+            .clone()
+    };
+
+    let mut i = input;
+    while let Ok((j, (skipped, link, lang))) = take_link_lang(i) {
+        output.write_all(skipped.as_bytes())?;
+        let consumed = &i[skipped.len()..i.len() - j.len()];
+        let converted = match parse_link_as(MarkupLang::Markdown, consumed) {
+            Ok((rest, l)) if lang == MarkupLang::Markdown && l == link => {
+                let source = &consumed[..consumed.len() - rest.len()];
+                match &link {
+                    Link::Text2Dest(_, de, ti) | Link::Image2Dest(_, _, _, _, de, ti) => {
+                        md_link_text_source(source).map(|text| {
+                            let text = md_img2reference(text, &mut label);
+                            format!("[{}][{}]{}", text, label(de, ti), rest)
+                        })
+                    }
+                    Link::Image(_, _) => {
+                        Some(format!("{}{}", md_img2reference(source, &mut label), rest))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        output.write_all(converted.as_deref().unwrap_or(consumed).as_bytes())?;
+        i = j;
+    }
+    output.write_all(i.as_bytes())?;
+
+    if !new_definitions.is_empty() {
+        if !input.is_empty() && !input.ends_with('\n') {
+            output.write_all(b"\n")?;
+        }
+        if !input.is_empty() {
+            output.write_all(b"\n")?;
+        }
+        for definition in new_definitions {
+            output.write_all(definition.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the Markdown reference links and reference images of `input`,
+/// that can be resolved, into inline links and inline images. The link
+/// reference definitions, that were referred to, are removed. Other
+/// definitions and unresolvable references are left untouched.
+///
+/// ```
+/// use parse_hyperlinks::rewrite::reference2inline;
+///
+/// let i = r#"abc [*text1*][Label1] abc ![alt2] [text3][]
+///
+/// [label1]: dest1 "title1"
+/// [alt2]: dest2
+/// [unused]: dest4
+/// "#;
+///
+/// let expected = r#"abc [*text1*](dest1 "title1") abc ![alt2](dest2) [text3][]
+///
+/// [unused]: dest4
+/// "#;
+/// assert_eq!(reference2inline(i), expected);
+/// ```
+#[inline]
+pub fn reference2inline(input: &str) -> String {
+    let mut output = Vec::new();
+    reference2inline_writer(input, &mut output).unwrap_or_default();
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// Same as `reference2inline()`, but it uses `Write` for output.
+pub fn reference2inline_writer<W: Write>(input: &str, output: &mut W) -> Result<(), io::Error> {
+    let label_map = LabelMap::from(input);
+    let resolve = |link: &Link| match link {
+        Link::Text2Label(_, la) | Link::Image2Label(_, la) => label_map.get(la),
+        _ => None,
+    };
+
+    // The normalized labels, that are referred to.
+    let mut used = HashSet::new();
+    let mut i = input;
+    while let Ok((j, (_, link, lang))) = take_link_lang(i) {
+        if let (MarkupLang::Markdown, Some(la)) = (lang, link.label()) {
+            if resolve(&link).is_some() {
+                used.extend(normalize_label(Cow::Borrowed(la)).map(|la| la.into_owned()));
+            }
+        }
+        i = j;
+    }
+
+    let mut i = input;
+    while let Ok((j, (mut skipped, link, lang))) = take_link_lang(i) {
+        let consumed = &i[skipped.len()..i.len() - j.len()];
+        let mut next = j;
+        let converted = match parse_link_as(MarkupLang::Markdown, consumed) {
+            Ok((rest, l)) if lang == MarkupLang::Markdown && l == link => match &link {
+                Link::Text2Label(_, _) => resolve(&link).and_then(|(de, ti)| {
+                    let text = md_link_text_source(consumed)?;
+                    Some(format!(
+                        "[{}]({}{}){}",
+                        text,
+                        md_dest(de),
+                        md_title(ti),
+                        rest
+                    ))
+                }),
+                Link::Image2Label(_, _) => resolve(&link).and_then(|(de, ti)| {
+                    let alt = md_link_text_source(&consumed[1..])?;
+                    Some(format!(
+                        "![{}]({}{}){}",
+                        alt,
+                        md_dest(de),
+                        md_title(ti),
+                        rest
+                    ))
+                }),
+                Link::Label2Dest(la, _, _)
+                    if normalize_label(Cow::Borrowed(la)).is_some_and(|la| used.contains(&*la)) =>
+                {
+                    // Remove the definition with its indent and line break.
+                    skipped = skipped.trim_end_matches([' ', '\t']);
+                    next = j
+                        .strip_prefix("\r\n")
+                        .or_else(|| j.strip_prefix('\n'))
+                        .unwrap_or(j);
+                    Some(rest.to_string())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        output.write_all(skipped.as_bytes())?;
+        output.write_all(converted.as_deref().unwrap_or(consumed).as_bytes())?;
+        i = next;
+    }
+    output.write_all(i.as_bytes())?;
+    Ok(())
+}
+
+/// Renames the _link label_ `old` to `new` in the Markdown and
+/// reStructuredText links of `input`: in the link reference definitions,
+/// aliases included, and in the reference links and images referring to
+/// `old`. Labels match as described in the CommonMark Spec, see
+/// `link_index::LinkIndex::references_to()`. Apart from the renamed labels,
+/// the output is identical to the input.
+///
+/// References, whose _link text_ doubles as label, e.g. `[old]` in Markdown
+/// or `` `old`_ `` in reStructuredText, keep their text and refer to `new`
+/// explicitly, e.g. `[old][new]` and `` `old <new_>`__ ``.
+///
+/// The renaming must not change where links point to: it fails with
+/// `RenameError::Invalid`, if `new` is not a valid label, i.e. it is empty
+/// or spans several lines, and with `RenameError::Exists`, if `new` matches
+/// the label of another link reference definition or alias in `input`.
+///
+/// ```
+/// use parse_hyperlinks::rewrite::rename_label;
+///
+/// let i = r#"abc [text1][Old Label] abc [old label] abc ![alt2][old label]
+///
+/// [old label]: dest1 "title1"
+///
+/// abc text3_ abc `text4 <old label_>`__
+///
+/// .. _text3: `Old label`_
+/// .. _`old label`: dest1
+/// "#;
+///
+/// let expected = r#"abc [text1][new] abc [old label][new] abc ![alt2][new]
+///
+/// [new]: dest1 "title1"
+///
+/// abc text3_ abc `text4 <new_>`__
+///
+/// .. _text3: new_
+/// .. _new: dest1
+/// "#;
+/// assert_eq!(rename_label(i, "old label", "new").unwrap(), expected);
+/// assert!(rename_label(i, "old label", "Text3").is_err());
+/// ```
+#[inline]
+pub fn rename_label(input: &str, old: &str, new: &str) -> Result<String, RenameError> {
+    let mut output = Vec::new();
+    rename_label_writer(input, old, new, &mut output)?;
+    Ok(String::from_utf8(output).unwrap_or_default())
+}
+
+/// Errors occurring while renaming a link label, see `rename_label()`.
+#[derive(Debug, Error)]
+pub enum RenameError {
+    /// The new label is not a valid label.
+    #[error("invalid label `{0}`")]
+    Invalid(String),
+    /// The new label is already defined in the input.
+    #[error("label `{0}` is already defined")]
+    Exists(String),
+    /// The output could not be written.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Same as `rename_label()`, but it uses `Write` for output.
+pub fn rename_label_writer<W: Write>(
+    input: &str,
+    old: &str,
+    new: &str,
+    output: &mut W,
+) -> Result<(), RenameError> {
+    let old = normalize_label(Cow::Borrowed(old));
+    let new = new.trim();
+    let new_normalized = normalize_label(Cow::Borrowed(new));
+    if new.is_empty() || new.contains(['\n', '\r']) || new_normalized.is_none() {
+        return Err(RenameError::Invalid(new.to_string()));
+    }
+    let is_old =
+        |label: &str| old.is_some() && label != "_" && normalize_label(Cow::Borrowed(label)) == old;
+
+    // Renaming onto another definition would redirect its references.
+    let mut i = input;
+    while let Ok((j, (_, link))) = take_link(i) {
+        let label = match &link {
+            Link::Label2Dest(la, _, _) | Link::Label2Label(la, _) => Some(la),
+            Link::TextLabel2Dest(la, _, _) => Some(la),
+            _ => None,
+        };
+        if let Some(label) = label {
+            if !is_old(label) && normalize_label(Cow::Borrowed(label)) == new_normalized {
+                return Err(RenameError::Exists(new.to_string()));
+            }
+        }
+        i = j;
+    }
+
+    let mut i = input;
+    while let Ok((j, (skipped, link, lang))) = take_link_lang(i) {
+        output.write_all(skipped.as_bytes())?;
+        let consumed = &i[skipped.len()..i.len() - j.len()];
+        let renamed = match (lang, &link) {
+            (MarkupLang::Markdown, Link::Text2Label(_, la)) if is_old(la) => {
+                md_rename_reference(consumed, 0, new)
+            }
+            (MarkupLang::Markdown, Link::Image2Label(_, la)) if is_old(la) => {
+                md_rename_reference(consumed, 1, new)
+            }
+            (MarkupLang::Markdown, Link::Label2Dest(la, _, _)) if is_old(la) => {
+                md_link_text_source(consumed)
+                    .map(|la| format!("[{}]{}", md_text(new), &consumed[la.len() + 2..]))
+            }
+            (MarkupLang::Rst, Link::Text2Label(_, la)) if is_old(la) => {
+                rst_rename_reference(consumed, new)
+            }
+            (MarkupLang::Rst, Link::Label2Dest(la, _, _)) if is_old(la) => {
+                rst_rename_definition(consumed, new)
+            }
+            (MarkupLang::Rst, Link::Label2Label(from, to)) if is_old(from) || is_old(to) => {
+                let renamed = if is_old(from) {
+                    rst_rename_definition(consumed, new)
+                } else {
+                    Some(consumed.to_string())
+                };
+                match renamed {
+                    Some(alias) if is_old(to) => rst_rename_alias_target(&alias, new),
+                    alias => alias,
+                }
+            }
+            _ => None,
+        };
+        output.write_all(renamed.as_deref().unwrap_or(consumed).as_bytes())?;
+        i = j;
+    }
+    output.write_all(i.as_bytes())?;
+    Ok(())
+}
+
+/// Renames the label of the Markdown reference link or, if `offset` is 1,
+/// reference image `source`. The _link text_ starts at byte `offset`.
+fn md_rename_reference(source: &str, offset: usize, new: &str) -> Option<String> {
+    let text = md_link_text_source(source.get(offset..)?)?;
+    let end = offset + text.len() + 2;
+    Some(format!("{}[{}]", &source[..end], md_text(new)))
+}
+
+/// Renames the label of the reStructuredText reference `source`.
+fn rst_rename_reference(source: &str, new: &str) -> Option<String> {
+    // `text <label_>`_ or `text <label_>`__
+    if let Some(rest) = source
+        .strip_suffix("_>`_")
+        .or_else(|| source.strip_suffix("_>`__"))
+    {
+        let start = rest.rfind('<')? + 1;
+        return Some(format!(
+            "{}{}{}",
+            &source[..start],
+            new,
+            &source[rest.len()..]
+        ));
+    }
+    // `text`_ or text_
+    let text = source
+        .strip_prefix('`')
+        .and_then(|s| s.strip_suffix("`_"))
+        .or_else(|| source.strip_suffix('_'))?;
+    Some(format!("`{} <{}_>`__", text, new))
+}
+
+/// Renames the label of the reStructuredText link reference definition or
+/// alias `source`, e.g. `.. _label: dest`.
+fn rst_rename_definition(source: &str, new: &str) -> Option<String> {
+    let rest = source.strip_prefix(".. _")?;
+    let len = if let Some(quoted) = rest.strip_prefix('`') {
+        // `label`:
+        let mut escaped = false;
+        quoted.find(|c| {
+            let end = c == '`' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })? + 2
+    } else {
+        // label: with `\:` escaping a colon.
+        let mut escaped = false;
+        rest.find(|c| {
+            let end = c == ':' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })?
+    };
+    Some(format!(".. _{}{}", rst_label(new), &rest[len..]))
+}
+
+/// Renames the target of the reStructuredText alias `source`, e.g.
+/// `.. _alias: label_`.
+fn rst_rename_alias_target(source: &str, new: &str) -> Option<String> {
+    let rest = source.trim_end().strip_suffix('_')?;
+    let start = match rest.strip_suffix('`') {
+        Some(quoted) => quoted.rfind('`')?,
+        None => rest.rfind(char::is_whitespace)? + 1,
+    };
+    Some(format!(
+        "{}{}_{}",
+        &source[..start],
+        rst_label(new),
+        &source[rest.len() + 1..]
+    ))
+}
+
+/// Returns the unparsed _link text_ of the Markdown link `source`, starting
+/// with `[`.
+fn md_link_text_source(source: &str) -> Option<&str> {
+    nom::sequence::delimited(tag("["), take_until_unbalanced('[', ']'), tag("]"))(source)
+        .ok()
+        .map(|(_, text)| text)
+}
+
+/// Rewrites the first inline image of the Markdown `source` into a
+/// reference image. `label` returns the label of a destination and title.
+fn md_img2reference<'a>(
+    source: &'a str,
+    label: &mut impl FnMut(&str, &str) -> String,
+) -> Cow<'a, str> {
+    let Some(n) = source.find("![") else {
+        return Cow::Borrowed(source);
+    };
+    let Some(alt) = md_link_text_source(&source[n + 1..]) else {
+        return Cow::Borrowed(source);
+    };
+    match md_link_destination_enclosed(&source[n + alt.len() + 3..]) {
+        Ok((rest, (src, title))) => Cow::Owned(format!(
+            "{}![{}][{}]{}",
+            &source[..n],
+            alt,
+            label(&src, &title),
+            rest
+        )),
+        Err(_) => Cow::Borrowed(source),
+    }
+}
+
+/// Parses a link at the beginning of `i` with the parsers of the markup
+/// language `lang` only.
+fn parse_link_as(lang: MarkupLang, i: &str) -> nom::IResult<&str, Link<'_>> {
+    match lang {
+        MarkupLang::Markdown => alt((
+            md_img2dest_link,
+            md_img_link,
+            md_text2dest_link,
+            md_label2dest_link,
+            md_img2label_link,
+            md_text2label_link,
+        ))(i),
+        MarkupLang::Rst => alt((
+            rst_label2label_link,
+            rst_label2dest_link,
+            rst_text2dest_link,
+            rst_text_label2dest_link,
+            rst_text2label_link,
+        ))(i),
+        MarkupLang::Adoc => alt((
+            adoc_label2dest_link,
+            adoc_text2label_link,
+            adoc_text2dest_link,
+            adoc_img_link,
+        ))(i),
+        MarkupLang::Html => alt((html_img_link, html_img2dest_link, html_text2dest_link))(i),
+        MarkupLang::Wikitext => wikitext_text2dest_link(i),
+        MarkupLang::Org => alt((
+            org_name_link,
+            org_target_link,
+            org_text2dest_link,
+            org_text2label_link,
+        ))(i),
+    }
+}
+
+/// Renders `link` in the syntax of the markup language `lang`, e.g. to
+/// generate links programmatically. Returns `None` if `lang` has no
+/// equivalent construct, e.g. for images in Wikitext.
+///
+/// Parsing the result with the parsers of `lang` gives back `link`, as long
+/// as its strings can be expressed in `lang`: e.g. reStructuredText and
+/// Wikitext have no _link title_, the Asciidoc and Wikitext parsers
+/// recognize absolute URLs only, and a `Link::TextLabel2Dest` is rendered
+/// as inline link in all languages, but reStructuredText.
+///
+/// ```
+/// use parse_hyperlinks::parser::Link;
+/// use parse_hyperlinks::parser::MarkupLang;
+/// use parse_hyperlinks::rewrite::to_markup;
+/// use std::borrow::Cow;
+///
+/// let link = Link::Text2Dest(Cow::from("a [b]"), Cow::from("c d"), Cow::from("e"));
+/// assert_eq!(
+///     to_markup(&link, MarkupLang::Markdown).unwrap(),
+///     r#"[a \[b\]](<c d> "e")"#
+/// );
+/// assert_eq!(
+///     to_markup(&link, MarkupLang::Rst).unwrap(),
+///     "`a [b] <c d>`__"
+/// );
+/// let image = Link::Image(Cow::from("alt"), Cow::from("src"));
+/// assert_eq!(to_markup(&image, MarkupLang::Wikitext), None);
+/// ```
+pub fn to_markup(link: &Link, lang: MarkupLang) -> Option<String> {
+    let s = match (lang, link) {
+        (MarkupLang::Markdown, Link::Text2Dest(te, de, ti))
+        | (MarkupLang::Markdown, Link::TextLabel2Dest(te, de, ti)) => {
+            format!("[{}]({}{})", md_text(te), md_dest(de), md_title(ti))
+        }
+        (MarkupLang::Markdown, Link::Text2Label(te, la)) if te == la => {
+            format!("[{}]", md_text(te))
+        }
+        (MarkupLang::Markdown, Link::Text2Label(te, la)) => {
+            format!("[{}][{}]", md_text(te), md_text(la))
+        }
+        (MarkupLang::Markdown, Link::Label2Dest(la, de, ti)) => {
+            format!("[{}]: {}{}", md_text(la), md_dest(de), md_title(ti))
+        }
+        (MarkupLang::Markdown, Link::Image(alt, src)) => {
+            format!("![{}]({})", md_text(alt), md_dest(src))
+        }
+        (MarkupLang::Markdown, Link::Image2Label(alt, la)) if alt == la => {
+            format!("![{}]", md_text(alt))
+        }
+        (MarkupLang::Markdown, Link::Image2Label(alt, la)) => {
+            format!("![{}][{}]", md_text(alt), md_text(la))
+        }
+        (MarkupLang::Markdown, Link::Image2Dest(te1, alt, src, te2, de, ti)) => format!(
+            "[{}![{}]({}){}]({}{})",
+            md_text(te1),
+            md_text(alt),
+            md_dest(src),
+            md_text(te2),
+            md_dest(de),
+            md_title(ti)
+        ),
+
+        (MarkupLang::Rst, Link::Text2Dest(te, de, _)) => {
+            format!("`{} <{}>`__", rst_text(te), rst_dest(de))
+        }
+        (MarkupLang::Rst, Link::Text2Label(te, la)) if te == la => {
+            format!("`{}`_", rst_text(te))
+        }
+        (MarkupLang::Rst, Link::Text2Label(te, la)) => {
+            format!("`{} <{}_>`__", rst_text(te), la)
+        }
+        (MarkupLang::Rst, Link::TextLabel2Dest(te, de, _)) => {
+            format!("`{} <{}>`_", rst_text(te), rst_dest(de))
+        }
+        (MarkupLang::Rst, Link::Label2Dest(la, de, _)) => {
+            format!(".. _{}: {}", rst_label(la), rst_dest(de))
+        }
+        (MarkupLang::Rst, Link::Label2Label(from, to)) => {
+            format!(".. _{}: {}_", rst_label(from), rst_label(to))
+        }
+
+        (MarkupLang::Adoc, Link::Text2Dest(te, de, _))
+        | (MarkupLang::Adoc, Link::TextLabel2Dest(te, de, _)) => {
+            format!("link:{}[{}]", de, adoc_text(te))
+        }
+        // Asciidoc attribute names contain no whitespace.
+        (MarkupLang::Adoc, Link::Text2Label(te, la)) if adoc_name(la) => {
+            format!("{{{}}}[{}]", la, adoc_text(te))
+        }
+        (MarkupLang::Adoc, Link::Label2Dest(la, de, _)) if adoc_name(la) => {
+            format!(":{}: {}", la, de)
+        }
+        (MarkupLang::Adoc, Link::Image(alt, src)) => format!("image:{}[{}]", src, adoc_alt(alt)),
+
+        (MarkupLang::Html, Link::Text2Dest(te, de, ti))
+        | (MarkupLang::Html, Link::TextLabel2Dest(te, de, ti)) => format!(
+            r#"<a href="{}" title="{}">{}</a>"#,
+            encode_double_quoted_attribute(de),
+            encode_double_quoted_attribute(ti),
+            encode_text(te)
+        ),
+        (MarkupLang::Html, Link::Image(alt, src)) => format!(
+            r#"<img src="{}" alt="{}">"#,
+            encode_double_quoted_attribute(src),
+            encode_double_quoted_attribute(alt)
+        ),
+        (MarkupLang::Html, Link::Image2Dest(te1, alt, src, te2, de, ti)) => format!(
+            r#"<a href="{}" title="{}">{}<img src="{}" alt="{}">{}</a>"#,
+            encode_double_quoted_attribute(de),
+            encode_double_quoted_attribute(ti),
+            encode_text(te1),
+            encode_double_quoted_attribute(src),
+            encode_double_quoted_attribute(alt),
+            encode_text(te2)
+        ),
+
+        // Wikitext has no escape for `]`.
+        (MarkupLang::Wikitext, Link::Text2Dest(te, de, _))
+        | (MarkupLang::Wikitext, Link::TextLabel2Dest(te, de, _))
+            if !te.contains(']') =>
+        {
+            format!("[{} {}]", de, te)
+        }
+
+        _ => return None,
+    };
+    Some(s)
+}
+
+/// Escapes the characters of a reStructuredText _link text_, that would
+/// end the text.
+fn rst_text(text: &str) -> Cow<'_, str> {
+    if text.contains(['`', '<']) {
+        Cow::Owned(text.replace('`', "\\`").replace('<', "\\<"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Encloses a reStructuredText _link label_ with backticks, unless it is a
+/// simple reference name: a word of alphanumerics, `-` and `_`.
+fn rst_label(label: &str) -> Cow<'_, str> {
+    if label
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        && !label.ends_with('_')
+    {
+        Cow::Borrowed(label)
+    } else {
+        Cow::Owned(format!("`{}`", label.replace('`', "\\`")))
+    }
+}
+
+/// Escapes a trailing `_` of a reStructuredText _link destination_, that
+/// would turn it into a label.
+fn rst_dest(dest: &str) -> Cow<'_, str> {
+    match dest.strip_suffix('_') {
+        Some(d) => Cow::Owned(format!("{}\\_", d)),
+        None => Cow::Borrowed(dest),
+    }
+}
+
+/// True, if `label` can be used as Asciidoc attribute name.
+fn adoc_name(label: &str) -> bool {
+    !label.is_empty() && !label.contains(|c: char| c.is_whitespace() || c == ':' || c == '}')
+}
+
+/// Quotes an Asciidoc image _alt text_, that contains a `,` or `=`, which
+/// would start the next attribute.
+fn adoc_alt(alt: &str) -> Cow<'_, str> {
+    if alt.contains([',', '=']) || alt.starts_with('"') {
+        Cow::Owned(format!("\"{}\"", adoc_text(alt)))
+    } else {
+        adoc_text(alt)
+    }
+}
+
+/// Escapes the closing bracket of an Asciidoc _link text_.
+fn adoc_text(text: &str) -> Cow<'_, str> {
+    if text.contains(']') {
+        Cow::Owned(text.replace(']', "\\]"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// The link destinations and image sources of `link`.
+fn destinations<'a, 'b>(link: &'b Link<'a>) -> Vec<&'b Cow<'a, str>> {
+    match link {
+        Link::Text2Dest(_, dest, _)
+        | Link::Label2Dest(_, dest, _)
+        | Link::TextLabel2Dest(_, dest, _)
+        | Link::Image(_, dest) => vec![dest],
+        Link::Image2Dest(_, _, src, _, dest, _) => vec![src, dest],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_rewrite() {
+        let to_html = |dest: &str| dest.strip_suffix(".md").map(|d| format!("{d}.html"));
+
+        // The image source precedes the link destination.
+        let i = "abc[![a.md](a.md)](b.md)abc";
+        assert_eq!(rewrite(i, to_html), "abc[![a.md](a.html)](b.html)abc");
+
+        // The link destination precedes the image source.
+        let i = r#"abc<a href="b.md"><img src="a.md" alt="a.md"></a>abc"#;
+        assert_eq!(
+            rewrite(i, to_html),
+            r#"abc<a href="b.html"><img src="a.html" alt="a.md"></a>abc"#
+        );
+
+        // Decoded destinations and reference links are kept.
+        let i = "abc[a](a\\_b.md)abc<c@d.md>abc[e][f]\n`g <g.md>`__\n\n[f]: f.md\n";
+        assert_eq!(
+            rewrite(i, to_html),
+            "abc[a](a\\_b.md)abc<c@d.md>abc[e][f]\n`g <g.html>`__\n\n[f]: f.html\n"
+        );
+
+        assert_eq!(rewrite("", to_html), "");
+        assert_eq!(rewrite("abc", |_| Some("x".to_string())), "abc");
+    }
+
+    #[test]
+    fn test_inline2reference() {
+        let i = "[![alt](src \"t\")](<my dest>) <http://auto> `a <b>`__ [x](1)\n[1]: y";
+        let expected = "[![alt][2]][3] <http://auto> `a <b>`__ [x][4]\n[1]: y\n\n\
+                        [2]: src \"t\"\n[3]: <my dest>\n[4]: 1\n";
+        assert_eq!(inline2reference(i), expected);
+        assert_eq!(inline2reference("abc"), "abc");
+        assert_eq!(inline2reference("[a](b)"), "[a][1]\n\n[1]: b\n");
+
+        // Round trip.
+        let i = "abc [a](b \"c\") ![d](e) [a](b \"c\")\n";
+        assert_eq!(reference2inline(&inline2reference(i)), format!("{i}\n"));
+    }
+
+    #[test]
+    fn test_rename_label() {
+        // Markdown
+        let i = "[a\\]b][x] [X] ![x][] [y]\r\n   [x]: <d e> \"t\"\r\n[y]: f\n";
+        assert_eq!(
+            rename_label(i, "x", "n[1]").unwrap(),
+            "[a\\]b][n\\[1\\]] [X][n\\[1\\]] ![x][n\\[1\\]] [y]\r\n   \
+             [n\\[1\\]]: <d e> \"t\"\r\n[y]: f\n"
+        );
+        // reStructuredText
+        let i = "a_ `b c`_ `d <a_>`_ e__\n\n.. _a: f\n.. _`b c`: a_\n.. _a\\: x: `a`_\n.. __: g\n";
+        assert_eq!(
+            rename_label(i, "a", "n m").unwrap(),
+            "`a <n m_>`__ `b c`_ `d <n m_>`_ e__\n\n.. _`n m`: f\n.. _`b c`: `n m`_\n\
+             .. _a\\: x: `n m`_\n.. __: g\n"
+        );
+        assert_eq!(
+            rename_label(i, "B  C", "n").unwrap(),
+            "a_ `b c <n_>`__ `d <a_>`_ e__\n\n.. _a: f\n.. _n: a_\n.. _a\\: x: `a`_\n.. __: g\n"
+        );
+        // Alias of itself.
+        assert_eq!(rename_label(".. _a: a_", "a", "b").unwrap(), ".. _b: b_");
+        // Anonymous labels are not renamed.
+        assert_eq!(rename_label(i, "_", "n").unwrap(), i);
+        // Invalid new labels.
+        for new in ["", " ", "a\nb"] {
+            assert!(matches!(
+                rename_label(i, "a", new),
+                Err(RenameError::Invalid(_))
+            ));
+        }
+        assert_eq!(rename_label("", "a", "b").unwrap(), "");
+        // Labels, that are already defined.
+        let i = "[a][foo] [b][bar]\n\n[foo]: x\n[bar]: y\n";
+        assert!(matches!(
+            rename_label(i, "foo", "BAR"),
+            Err(RenameError::Exists(_))
+        ));
+        assert_eq!(
+            rename_label(i, "foo", "Foo").unwrap(),
+            "[a][Foo] [b][bar]\n\n[Foo]: x\n[bar]: y\n"
+        );
+        for new in ["x: y", "b c", "z"] {
+            let i = ".. _a: f\n.. _x\\: y: f\n`b c <g>`_\n.. _z: a_\n";
+            assert!(matches!(
+                rename_label(i, "a", new),
+                Err(RenameError::Exists(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_reference2inline() {
+        let i = "[a] [b][] [c][x] ![d][x]\n  [A]: 1\n[x]: <2 3> 't'\n[u]: 4";
+        let expected = "[a](1) [b][] [c](<2 3> \"t\") ![d](<2 3> \"t\")\n[u]: 4";
+        assert_eq!(reference2inline(i), expected);
+    }
+
+    #[test]
+    fn test_convert_links() {
+        let i =
+            "abc[text1](dest1 \"title1\")abc\n[text2][label2] ![alt3](src3)\n   [label2]: dest2\n\
+            abc `text4 <dest4>`__ abc\n";
+        assert_eq!(
+            convert_links(i, MarkupLang::Markdown, MarkupLang::Rst),
+            "abc`text1 <dest1>`__abc\n`text2 <label2_>`__ ![alt3](src3)\n   .. _label2: dest2\n\
+            abc `text4 <dest4>`__ abc\n"
+        );
+        assert_eq!(
+            convert_links(i, MarkupLang::Markdown, MarkupLang::Adoc),
+            "abclink:dest1[text1]abc\n{label2}[text2] image:src3[alt3]\n   :label2: dest2\n\
+            abc `text4 <dest4>`__ abc\n"
+        );
+
+        let i = "abc https://dest1[text1] abc {label2}[text2]\n:label2: https://dest2\n";
+        assert_eq!(
+            convert_links(i, MarkupLang::Adoc, MarkupLang::Markdown),
+            "abc [text1](https://dest1) abc [text2][label2]\n[label2]: https://dest2\n"
+        );
+        assert_eq!(
+            convert_links(
+                "abc image::a.svg[A,300]\n",
+                MarkupLang::Adoc,
+                MarkupLang::Markdown
+            ),
+            "abc ![A](a.svg)\n"
+        );
+        assert_eq!(
+            convert_links(i, MarkupLang::Adoc, MarkupLang::Html),
+            "abc <a href=\"https://dest1\" title=\"\">text1</a> abc {label2}[text2]\n\
+            :label2: https://dest2\n"
+        );
+
+        assert_eq!(rst_text("a`b<c"), "a\\`b\\<c");
+        assert_eq!(rst_label("a:b"), "`a:b`");
+        assert_eq!(adoc_text("a]"), "a\\]");
+    }
+
+    /// Serializes `link`, parses it again and compares.
+    fn round_trip(link: Link, lang: MarkupLang) -> Result<(), TestCaseError> {
+        let markup = to_markup(&link, lang).unwrap();
+        prop_assert_eq!(parse_link_as(lang, &markup), Ok(("", link)), "{}", markup);
+        Ok(())
+    }
+
+    /// Link texts with characters, that need escaping in some languages.
+    const TEXT: &str = "[a-zA-Z0-9äü链🦀][a-zA-Z0-9äü链🦀 .,!?`<>\\[\\]-]{0,10}[a-zA-Z0-9äü链🦀]";
+    /// Link labels. reStructuredText normalizes whitespace in labels.
+    const LABEL: &str = "[a-z0-9]{1,4}( [a-z0-9-]{0,3}[a-z0-9])?";
+    /// Absolute and relative link destinations.
+    const DEST: &str = "(https://[a-z]{1,8}\\.org/)?[a-zA-Z0-9_./-]{1,10}";
+    /// Link titles.
+    const TITLE: &str = "([a-zA-Z0-9\"][a-zA-Z0-9 \"]{0,6}[a-zA-Z0-9\"])?";
+
+    proptest! {
+        #[test]
+        fn proptest_markdown(te in TEXT, la in LABEL, de in DEST, ti in TITLE, src in DEST) {
+            use Link::*;
+            let c = |s: &String| Cow::from(s.clone());
+            for link in [
+                Text2Dest(c(&te), c(&de), c(&ti)),
+                Text2Label(c(&te), c(&la)),
+                Text2Label(c(&la), c(&la)),
+                Label2Dest(c(&la), c(&de), c(&ti)),
+                Image(c(&te), c(&src)),
+                Image2Label(c(&te), c(&la)),
+                Image2Dest(c(&te), c(&la), c(&src), c(&te), c(&de), c(&ti)),
+            ] {
+                round_trip(link, MarkupLang::Markdown)?;
+            }
+        }
+
+        #[test]
+        fn proptest_rst(te in TEXT, la in LABEL, de in DEST) {
+            use Link::*;
+            let c = |s: &String| Cow::from(s.clone());
+            let e = Cow::from("");
+            for link in [
+                Text2Dest(c(&te), c(&de), e.clone()),
+                TextLabel2Dest(c(&te), c(&de), e.clone()),
+                Text2Label(c(&te), c(&la)),
+                Text2Label(c(&la), c(&la)),
+                Label2Dest(c(&la), c(&de), e.clone()),
+                Label2Label(c(&la), c(&te)),
+            ] {
+                round_trip(link, MarkupLang::Rst)?;
+            }
+        }
+
+        #[test]
+        fn proptest_adoc(te in TEXT, la in "[a-z0-9][a-z0-9_-]{0,6}", de in DEST, src in DEST) {
+            use Link::*;
+            let c = |s: &String| Cow::from(s.clone());
+            let e = Cow::from("");
+            // Only absolute URLs are recognized as Asciidoc link destinations.
+            let de = Cow::from(format!("https://{de}"));
+            for link in [
+                Text2Dest(c(&te), de.clone(), e.clone()),
+                Text2Label(c(&te), c(&la)),
+                Label2Dest(c(&la), de.clone(), e.clone()),
+                Image(c(&te), c(&src)),
+            ] {
+                round_trip(link, MarkupLang::Adoc)?;
+            }
+        }
+
+        #[test]
+        fn proptest_html(te in TEXT, de in DEST, ti in TITLE, src in DEST) {
+            use Link::*;
+            let c = |s: &String| Cow::from(s.clone());
+            for link in [
+                Text2Dest(c(&te), c(&de), c(&ti)),
+                Image(c(&te), c(&src)),
+                Image2Dest(c(&te), c(&te), c(&src), c(&te), c(&de), c(&ti)),
+            ] {
+                round_trip(link, MarkupLang::Html)?;
+            }
+        }
+
+        #[test]
+        fn proptest_wikitext(te in TEXT, de in DEST) {
+            let link = Link::Text2Dest(Cow::from(te), Cow::from(format!("https://{de}")), Cow::from(""));
+            if link.text().unwrap().contains(']') {
+                prop_assert_eq!(to_markup(&link, MarkupLang::Wikitext), None);
+            } else {
+                round_trip(link, MarkupLang::Wikitext)?;
+            }
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! Module providing small helpers to inspect link destinations.
+//!
+//! All functions compare ASCII case-insensitively, independent of the
+//! locale, and never allocate: they return slices of their input. This
+//! makes them cheap enough for filters running over every link of large
+//! inputs. The only exception is `url_normalize()`, which allocates when
+//! its input is not normalized already.
+
+use crate::parser::Link;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use percent_encoding::percent_decode_str;
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+/// Returns the scheme of `dest` as defined in
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.1),
+/// without the trailing `:`. Returns `None` for relative destinations.
+///
+/// ```
+/// use parse_hyperlinks::url::scheme;
+///
+/// assert_eq!(scheme("HTTPS://getreu.net"), Some("HTTPS"));
+/// assert_eq!(scheme("mailto:a@b.c"), Some("mailto"));
+/// assert_eq!(scheme("doc/a.md#sec"), None);
+/// assert_eq!(scheme("./a:b"), None);
+/// ```
+pub fn scheme(dest: &str) -> Option<&str> {
+    let (scheme, _) = dest.split_once(':')?;
+    if scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'.' | b'-'))
+    {
+        Some(scheme)
+    } else {
+        None
+    }
+}
+
+/// True if the scheme of `dest` is `scheme`, compared ASCII
+/// case-insensitively.
+///
+/// ```
+/// use parse_hyperlinks::url::has_scheme;
+///
+/// assert!(has_scheme("MailTo:a@b.c", "mailto"));
+/// assert!(!has_scheme("mailto2:a@b.c", "mailto"));
+/// assert!(!has_scheme("a@b.c", "mailto"));
+/// ```
+pub fn has_scheme(dest: &str, scheme: &str) -> bool {
+    self::scheme(dest).is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// True if `dest` is an `http:` or `https:` URL.
+///
+/// ```
+/// use parse_hyperlinks::url::is_http;
+///
+/// assert!(is_http("HTTP://getreu.net"));
+/// assert!(is_http("https://getreu.net"));
+/// assert!(!is_http("ftp://getreu.net"));
+/// ```
+pub fn is_http(dest: &str) -> bool {
+    has_scheme(dest, "http") || has_scheme(dest, "https")
+}
+
+/// The kind of a link destination or image source, see `url_kind()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UrlKind {
+    /// A URL with a scheme not listed below, e.g. `https://getreu.net`, or
+    /// a protocol-relative URL like `//getreu.net`.
+    Absolute,
+    /// A relative reference, e.g. `doc/a.md#sec`.
+    Relative,
+    /// A reference to a fragment of the same document, e.g. `#sec`.
+    Fragment,
+    /// An email address, e.g. `mailto:a@b.c`.
+    Mailto,
+    /// A phone number, e.g. `tel:+1-201-555-0123`.
+    Tel,
+    /// A text message, e.g. `sms:+1-201-555-0123`.
+    Sms,
+    /// A location, e.g. `geo:47.37,8.54`.
+    Geo,
+    /// Script code, e.g. `javascript:alert(1)`.
+    Javascript,
+    /// Inline content, e.g. `data:image/png;base64,...`.
+    Data,
+}
+
+impl UrlKind {
+    /// True for the kinds, that execute or embed content when followed,
+    /// and are therefore unsafe in untrusted input: `Javascript` and `Data`.
+    pub fn is_dangerous(self) -> bool {
+        matches!(self, UrlKind::Javascript | UrlKind::Data)
+    }
+}
+
+/// Classifies the link destination or image source `dest`.
+///
+/// The scheme is read as browsers do: leading whitespace and control
+/// characters are skipped, tabs and line breaks are ignored. Hence,
+/// `" java\tscript:"` is `UrlKind::Javascript`, too.
+///
+/// ```
+/// use parse_hyperlinks::url::{url_kind, UrlKind};
+///
+/// assert_eq!(url_kind("https://getreu.net"), UrlKind::Absolute);
+/// assert_eq!(url_kind("doc/a.md#sec"), UrlKind::Relative);
+/// assert_eq!(url_kind("#sec"), UrlKind::Fragment);
+/// assert_eq!(url_kind("MailTo:a@b.c"), UrlKind::Mailto);
+/// assert_eq!(url_kind("tel:+1-201-555-0123"), UrlKind::Tel);
+/// assert_eq!(url_kind(" java\tscript:alert(1)"), UrlKind::Javascript);
+/// assert_eq!(url_kind("data:text/html,<p>"), UrlKind::Data);
+/// assert!(url_kind("data:text/html,<p>").is_dangerous());
+/// ```
+pub fn url_kind(dest: &str) -> UrlKind {
+    let Some(scheme) = browser_scheme(dest) else {
+        return if dest.starts_with('#') {
+            UrlKind::Fragment
+        } else if dest.starts_with("//") {
+            UrlKind::Absolute
+        } else {
+            UrlKind::Relative
+        };
+    };
+    let is = |name: &str| {
+        scheme
+            .clone()
+            .map(|c| c.to_ascii_lowercase())
+            .eq(name.chars())
+    };
+    if is("mailto") {
+        UrlKind::Mailto
+    } else if is("tel") {
+        UrlKind::Tel
+    } else if is("sms") {
+        UrlKind::Sms
+    } else if is("geo") {
+        UrlKind::Geo
+    } else if is("javascript") {
+        UrlKind::Javascript
+    } else if is("data") {
+        UrlKind::Data
+    } else {
+        UrlKind::Absolute
+    }
+}
+
+/// Returns the characters of the scheme of `dest`, read as browsers do, see
+/// `url_kind()`. Returns `None` for relative destinations.
+pub(crate) fn browser_scheme(dest: &str) -> Option<impl Iterator<Item = char> + Clone + '_> {
+    fn is_kept(c: &char) -> bool {
+        !matches!(c, '\t' | '\n' | '\r')
+    }
+    let dest = dest.trim_start_matches(|c: char| c <= ' ');
+    let chars = dest.chars().filter(is_kept as fn(&char) -> bool);
+    let mut len = 0;
+    for c in chars.clone() {
+        match c {
+            ':' if len > 0 => return Some(chars.take(len)),
+            c if c.is_ascii_alphabetic()
+                || len > 0 && (c.is_ascii_digit() || matches!(c, '+' | '.' | '-')) =>
+            {
+                len += 1
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Returns the authority of `url`, i.e. `userinfo@host:port`, when `url`
+/// has a scheme followed by `//`.
+///
+/// ```
+/// use parse_hyperlinks::url::authority;
+///
+/// assert_eq!(authority("https://me@getreu.net:8080/a?b#c"), Some("me@getreu.net:8080"));
+/// assert_eq!(authority("file:///a/b"), Some(""));
+/// assert_eq!(authority("mailto:a@b.c"), None);
+/// assert_eq!(authority("//getreu.net/a"), None);
+/// ```
+pub fn authority(url: &str) -> Option<&str> {
+    let scheme = scheme(url)?;
+    let rest = url[scheme.len() + ':'.len_utf8()..].strip_prefix("//")?;
+    rest.split(['/', '?', '#']).next()
+}
+
+/// Returns the host of `url`: its authority without user information and
+/// port.
+///
+/// ```
+/// use parse_hyperlinks::url::host;
+///
+/// assert_eq!(host("https://me@getreu.net:8080/a"), Some("getreu.net"));
+/// assert_eq!(host("http://[::1]:80/"), Some("[::1]"));
+/// assert_eq!(host("doc/a.md"), None);
+/// ```
+pub fn host(url: &str) -> Option<&str> {
+    let authority = authority(url)?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    match host.rsplit_once(':') {
+        // An IPv6 address contains colons too.
+        Some((h, port))
+            if port.bytes().all(|c| c.is_ascii_digit())
+                && (!h.starts_with('[') || h.ends_with(']')) =>
+        {
+            Some(h)
+        }
+        _ => Some(host),
+    }
+}
+
+/// True if both URLs have a host and the hosts are equal, compared ASCII
+/// case-insensitively.
+///
+/// ```
+/// use parse_hyperlinks::url::same_host;
+///
+/// assert!(same_host("https://GETREU.net/a", "http://getreu.net:8080/b"));
+/// assert!(!same_host("https://getreu.net/a", "https://blog.getreu.net/"));
+/// assert!(!same_host("a.md", "a.md"));
+/// ```
+pub fn same_host(a: &str, b: &str) -> bool {
+    match (host(a), host(b)) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => false,
+    }
+}
+
+/// Normalizes `url`, so that equivalent URLs compare equal, see
+/// [RFC 3986, section 6.2.2](https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2):
+/// * the scheme and the host are lower cased,
+/// * the default port of the scheme is removed,
+/// * an empty path of `http:` and `https:` URLs becomes `/`,
+/// * internationalized domain names (IDN) are encoded with
+///   [Punycode](https://www.rfc-editor.org/rfc/rfc3492).
+///
+/// Destinations without scheme are returned unchanged. `url` is returned
+/// borrowed when it is normalized already.
+///
+/// ```
+/// use parse_hyperlinks::url::url_normalize;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(url_normalize(Cow::from("HTTPS://GetReu.NET:443")), "https://getreu.net/");
+/// assert_eq!(url_normalize(Cow::from("http://Bücher.example:8080/A?b#C")),
+///            "http://xn--bcher-kva.example:8080/A?b#C");
+/// assert_eq!(url_normalize(Cow::from("MAILTO:A@B.c")), "mailto:A@B.c");
+/// assert!(matches!(url_normalize(Cow::from("https://getreu.net/")), Cow::Borrowed(_)));
+/// ```
+pub fn url_normalize(url: Cow<'_, str>) -> Cow<'_, str> {
+    let scheme = match scheme(&url) {
+        Some(scheme) => scheme,
+        None => return url,
+    };
+    let rest = &url[scheme.len() + ':'.len_utf8()..];
+    let mut normalized = scheme.to_ascii_lowercase();
+    normalized.push(':');
+
+    match authority(&url) {
+        Some(authority) => {
+            let path = &rest["//".len() + authority.len()..];
+            let (userinfo, host_port) = match authority.rsplit_once('@') {
+                Some((userinfo, host_port)) => (Some(userinfo), host_port),
+                None => (None, authority),
+            };
+            let host = host(&url).unwrap_or_default();
+            let port = host_port[host.len()..].trim_start_matches(':');
+
+            normalized.push_str("//");
+            if let Some(userinfo) = userinfo {
+                normalized.push_str(userinfo);
+                normalized.push('@');
+            }
+            normalized.push_str(&host_normalize(host));
+            if !port.is_empty() && default_port(&normalized) != Some(port) {
+                normalized.push(':');
+                normalized.push_str(port);
+            }
+            if (path.is_empty() || path.starts_with(['?', '#'])) && is_http(&url) {
+                normalized.push('/');
+            }
+            normalized.push_str(path);
+        }
+        None => normalized.push_str(rest),
+    }
+
+    if normalized == *url {
+        url
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+/// Normalizes the link destinations and image sources of `link` with
+/// `url_normalize()`. Other link types are returned unchanged.
+pub fn normalize_link(link: Link<'_>) -> Link<'_> {
+    match link {
+        Link::Text2Dest(te, de, ti) => Link::Text2Dest(te, url_normalize(de), ti),
+        Link::Label2Dest(la, de, ti) => Link::Label2Dest(la, url_normalize(de), ti),
+        Link::TextLabel2Dest(tl, de, ti) => Link::TextLabel2Dest(tl, url_normalize(de), ti),
+        Link::Image(alt, src) => Link::Image(alt, url_normalize(src)),
+        Link::Image2Dest(te1, alt, src, te2, de, ti) => {
+            Link::Image2Dest(te1, alt, url_normalize(src), te2, url_normalize(de), ti)
+        }
+        l => l,
+    }
+}
+
+/// Returns the default port of the scheme of `url`, if known.
+fn default_port(url: &str) -> Option<&'static str> {
+    match scheme(url)?.to_ascii_lowercase().as_str() {
+        "http" | "ws" => Some("80"),
+        "https" | "wss" => Some("443"),
+        "ftp" => Some("21"),
+        _ => None,
+    }
+}
+
+/// Lower cases `host` and encodes its non-ASCII labels with Punycode.
+fn host_normalize(host: &str) -> Cow<'_, str> {
+    if host.is_ascii() {
+        return if host.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(host.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(host)
+        };
+    }
+    let labels = host
+        .to_lowercase()
+        .split('.')
+        .map(|label| match punycode(label) {
+            Some(encoded) if !label.is_ascii() => format!("xn--{encoded}"),
+            _ => label.to_string(),
+        })
+        .collect::<Vec<_>>();
+    Cow::Owned(labels.join("."))
+}
+
+/// Encodes `input` with the Punycode algorithm described in
+/// [RFC 3492](https://www.rfc-editor.org/rfc/rfc3492#section-6.3).
+/// Returns `None` on overflow.
+fn punycode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const TMIN: u32 = 1;
+    const TMAX: u32 = 26;
+    const INITIAL_BIAS: u32 = 72;
+    const INITIAL_N: u32 = 128;
+
+    /// Bias adaptation function.
+    fn adapt(delta: u32, numpoints: u32, first_time: bool) -> u32 {
+        let mut delta = if first_time { delta / 700 } else { delta / 2 };
+        delta += delta / numpoints;
+        let mut k = 0;
+        while delta > ((BASE - TMIN) * TMAX) / 2 {
+            delta /= BASE - TMIN;
+            k += BASE;
+        }
+        k + (BASE - TMIN + 1) * delta / (delta + 38)
+    }
+
+    /// Maps a digit value to its basic code point.
+    fn digit(d: u32) -> char {
+        match d {
+            0..=25 => (b'a' + d as u8) as char,
+            _ => (b'0' + (d - 26) as u8) as char,
+        }
+    }
+
+    let code_points = input.chars().map(|c| c as u32).collect::<Vec<_>>();
+    let mut output = input.chars().filter(|c| c.is_ascii()).collect::<String>();
+    let basic_len = output.len() as u32;
+    if basic_len > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic_len;
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        TMIN
+                    } else if k >= bias + TMAX {
+                        TMAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_len);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(output)
+}
+
+/// Resolves the local link destination `dest` relative to the directory
+/// `dir`: query and fragment are removed and percent encoded characters
+/// decoded. Returns `None` if `dest` is empty or has a URL scheme.
+#[cfg(feature = "std")]
+pub(crate) fn local_path(dir: &Path, dest: &str) -> Option<PathBuf> {
+    if dest.is_empty() || scheme(dest).is_some() {
+        return None;
+    }
+    let dest = dest.split(['?', '#']).next().unwrap_or_default();
+    Some(dir.join(&*percent_decode_str(dest).decode_utf8_lossy()))
+}
+
+/// True if `path` has the extension `.html` or `.htm`, compared ASCII
+/// case-insensitively.
+#[cfg(feature = "std")]
+pub(crate) fn is_html_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"))
+}
+
+/// True if `path` has the extension of a Markdown, reStructuredText or
+/// Asciidoc file, compared ASCII case-insensitively.
+#[cfg(feature = "std")]
+pub(crate) fn is_markup_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| {
+        ["md", "markdown", "rst", "rest", "adoc", "asciidoc"]
+            .iter()
+            .any(|m| e.eq_ignore_ascii_case(m))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme() {
+        assert_eq!(scheme("a+b.c-d:x"), Some("a+b.c-d"));
+        assert_eq!(scheme("1a:x"), None);
+        assert_eq!(scheme(":x"), None);
+        assert_eq!(scheme("a b:x"), None);
+        assert_eq!(scheme("ä:x"), None);
+        assert_eq!(scheme("abc"), None);
+    }
+
+    #[test]
+    fn test_url_kind() {
+        assert_eq!(url_kind(""), UrlKind::Relative);
+        assert_eq!(url_kind("./a:b"), UrlKind::Relative);
+        assert_eq!(url_kind("//a.b/c"), UrlKind::Absolute);
+        assert_eq!(url_kind("a+b:c"), UrlKind::Absolute);
+        assert_eq!(url_kind("\u{1}\nJava\rScript\t:x"), UrlKind::Javascript);
+        assert_eq!(url_kind("javascripts:x"), UrlKind::Absolute);
+        assert_eq!(url_kind("DATA:x"), UrlKind::Data);
+        assert_eq!(url_kind("tel:1"), UrlKind::Tel);
+        assert_eq!(url_kind("Sms:1"), UrlKind::Sms);
+        assert_eq!(url_kind("geo:1,2"), UrlKind::Geo);
+        assert!(!UrlKind::Mailto.is_dangerous());
+
+        assert_eq!(browser_scheme("1a:x").map(|s| s.count()), None);
+        assert_eq!(browser_scheme(":x").map(|s| s.count()), None);
+        assert_eq!(
+            browser_scheme("a\tb:x").map(|s| s.collect::<String>()),
+            Some("ab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("http://a.b"), Some("a.b"));
+        assert_eq!(host("http://a.b:"), Some("a.b"));
+        assert_eq!(host("http://u:p@a.b:1/"), Some("a.b"));
+        assert_eq!(host("http://[::1]/"), Some("[::1]"));
+        assert_eq!(host("http://a.b?c"), Some("a.b"));
+        assert_eq!(host("mailto:a@b.c"), None);
+    }
+
+    #[test]
+    fn test_local_path() {
+        let dir = Path::new("doc");
+        assert_eq!(
+            local_path(dir, "img/a%20b.png?x#y"),
+            Some(PathBuf::from("doc/img/a b.png"))
+        );
+        assert_eq!(local_path(dir, "data:image/png;base64,AAAA"), None);
+        assert_eq!(local_path(dir, ""), None);
+    }
+
+    #[test]
+    fn test_is_html_file() {
+        assert!(is_html_file(Path::new("doc/a.HTML")));
+        assert!(is_html_file(Path::new("a.htm")));
+        assert!(!is_html_file(Path::new("a.md")));
+        assert!(!is_html_file(Path::new("html")));
+    }
+
+    #[test]
+    fn test_is_markup_file() {
+        assert!(is_markup_file(Path::new("doc/a.MD")));
+        assert!(is_markup_file(Path::new("a.rst")));
+        assert!(is_markup_file(Path::new("a.adoc")));
+        assert!(!is_markup_file(Path::new("a.html")));
+        assert!(!is_markup_file(Path::new("md")));
+    }
+
+    #[test]
+    fn test_url_normalize() {
+        let n = |url: &'static str| url_normalize(Cow::from(url));
+        assert_eq!(n("HTTP://a.B:80/C"), "http://a.b/C");
+        assert_eq!(n("https://a.b:8443"), "https://a.b:8443/");
+        assert_eq!(n("https://a.b:?q"), "https://a.b/?q");
+        assert_eq!(n("https://Me@a.b#F"), "https://Me@a.b/#F");
+        assert_eq!(n("http://[::ABC]:80/"), "http://[::abc]/");
+        assert_eq!(n("ftp://a.b:21"), "ftp://a.b");
+        assert_eq!(n("file:///A/B"), "file:///A/B");
+        assert_eq!(n("WSS://a.b:443/x"), "wss://a.b/x");
+        assert_eq!(n("http://MÜNCHEN.de/"), "http://xn--mnchen-3ya.de/");
+        assert_eq!(n("doc/A.md"), "doc/A.md");
+    }
+
+    #[test]
+    fn test_normalize_link() {
+        assert_eq!(
+            normalize_link(Link::Image2Dest(
+                Cow::from(""),
+                Cow::from("alt"),
+                Cow::from("HTTP://A.b/i.png"),
+                Cow::from(""),
+                Cow::from("https://a.b:443"),
+                Cow::from("")
+            )),
+            Link::Image2Dest(
+                Cow::from(""),
+                Cow::from("alt"),
+                Cow::from("http://a.b/i.png"),
+                Cow::from(""),
+                Cow::from("https://a.b/"),
+                Cow::from("")
+            )
+        );
+    }
+
+    #[test]
+    fn test_punycode() {
+        // Examples from RFC 3492, section 7.1.
+        assert_eq!(
+            punycode("他们为什么不说中文").unwrap(),
+            "ihqwcrb4cv8a8dqg056pqjye"
+        );
+        assert_eq!(
+            punycode("3年B組金八先生").unwrap(),
+            "3B-ww4c5e180e575a65lsy2b"
+        );
+        assert_eq!(punycode("münchen").unwrap(), "mnchen-3ya");
+        assert_eq!(punycode("abc").unwrap(), "abc-");
+    }
+}
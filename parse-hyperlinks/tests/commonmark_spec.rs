@@ -0,0 +1,22 @@
+//! Runs the link related examples of the CommonMark Spec 0.31.2
+//! (`tests/data/spec.json`, from <https://spec.commonmark.org/0.31.2/spec.json>).
+#![cfg(feature = "serde")]
+
+use parse_hyperlinks::commonmark::{run_spec, spec_examples, LINK_SECTIONS};
+
+/// The current pass rate, 131 of 158 examples. It must not drop. Raise it,
+/// when the parsers improve.
+const MIN_PASS_RATE: f64 = 0.829;
+
+#[test]
+fn test_commonmark_spec() {
+    let examples = spec_examples(include_str!("data/spec.json")).unwrap();
+    let report = run_spec(&examples);
+    let expected = examples
+        .iter()
+        .filter(|e| LINK_SECTIONS.contains(&e.section.as_str()))
+        .count();
+    assert_eq!(report.results.len(), expected);
+    println!("{report}");
+    assert!(report.pass_rate() >= MIN_PASS_RATE, "{report}");
+}